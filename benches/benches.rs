@@ -1,11 +1,35 @@
-use chrono::{DateTime, FixedOffset, TimeZone};
+use chrono::{DateTime, Duration, FixedOffset, TimeZone};
 use criterion::{criterion_group, criterion_main, Criterion};
 use heliocron::{report, structs};
 
-fn run_report(date: DateTime<FixedOffset>, coordinates: structs::Coordinates) -> () {
+fn run_report(date: DateTime<FixedOffset>, coordinates: structs::Coordinates) {
     report::SolarReport::new(date, coordinates);
 }
 
+fn run_reports_one_at_a_time(
+    start: DateTime<FixedOffset>,
+    days: i64,
+    coordinates: structs::Coordinates,
+) -> Vec<report::SolarReport> {
+    (0..days)
+        .map(|day| report::SolarReport::new(start + Duration::days(day), coordinates))
+        .collect()
+}
+
+fn run_reports_in_bulk(
+    start: DateTime<FixedOffset>,
+    days: i64,
+    coordinates: structs::Coordinates,
+) -> Vec<report::SolarReport> {
+    report::solar_events_range(
+        coordinates,
+        structs::TwilightAngles::default(),
+        start,
+        start + Duration::days(days - 1),
+        Duration::days(1),
+    )
+}
+
 fn criterion_benchmark(c: &mut Criterion) {
     // set up parameters
     let date = FixedOffset::east(0).ymd(2020, 2, 25).and_hms(12, 0, 0);
@@ -16,6 +40,15 @@ fn criterion_benchmark(c: &mut Criterion) {
     };
 
     c.bench_function("run_report", |b| b.iter(|| run_report(date, coordinates)));
+
+    // a year of reports, as for a date range or the longest and shortest days
+    let start = FixedOffset::east(0).ymd(2020, 1, 1).and_hms(12, 0, 0);
+    c.bench_function("run_year_of_reports_one_at_a_time", |b| {
+        b.iter(|| run_reports_one_at_a_time(start, 366, coordinates))
+    });
+    c.bench_function("run_year_of_reports_in_bulk", |b| {
+        b.iter(|| run_reports_in_bulk(start, 366, coordinates))
+    });
 }
 
 criterion_group!(benches, criterion_benchmark);
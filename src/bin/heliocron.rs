@@ -1,19 +1,567 @@
 use std::process;
 
-use heliocron::{config, errors, report, subcommands};
+use chrono::{Datelike, Duration, TimeZone};
+
+use heliocron::{config, enums, errors, report, solar, structs, subcommands, utils};
 
 fn run_heliocron() -> Result<(), errors::HeliocronError> {
-    let config = config::get_config()?;
+    let mut config = config::get_config()?;
 
-    let report = report::SolarReport::new(config.date, config.coordinates);
+    if let Some(ref validation) = config.validation {
+        if !subcommands::display_validation(validation) {
+            process::exit(1);
+        }
+        return Ok(());
+    }
+
+    if config.dump_defaults {
+        subcommands::dump_defaults(&config::Config::defaults(config.now)?);
+        return Ok(());
+    }
+
+    if config.check_date {
+        subcommands::display_date_check(config.date, config.end_date);
+        return Ok(());
+    }
+
+    if config.verbose {
+        eprintln!("{}", config.describe());
+    }
+
+    if let Some(warning) = config.time_zone_warning() {
+        utils::warn(&warning, config.warnings_json);
+    }
+    if let Some(warning) = config.bounding_box_warning() {
+        utils::warn(&warning, config.warnings_json);
+    }
+
+    // a guard which fails stops here, silently, with its own exit code
+    if let Some(guard) = config.guard {
+        if !subcommands::check_guard(
+            &guard,
+            config.moment(),
+            config.coordinates,
+            &config.twilight_angles,
+        ) {
+            process::exit(2);
+        }
+    }
+
+    // taken out of the config, so that the subcommand's arguments can be moved whilst the rest of the config
+    // is still borrowed
+    let subcommand = config.subcommand.take();
+    let display = |mut report: report::SolarReport| {
+        report.hide_location = config.hide_location;
+        report.decimal_hours = config.decimal_hours;
+        report.day_fraction = config.day_fraction;
+        report.solar_time = config.solar_time;
+        report.relative_to_noon = config.relative_to_noon;
+        report.time_only = config.time_only;
+        report.with_accuracy = config.with_accuracy;
+        report.locale = config.locale;
+        report.sections = config.report_sections;
+        if let Some(ref profile) = config.horizon {
+            report.apply_horizon(profile);
+        }
+        report
+    };
+    let new_report = |date| {
+        display(report::SolarReport::with_twilight_angles(
+            date,
+            config.coordinates,
+            config.twilight_angles,
+        ))
+    };
+    // several days at once share the calculations which only depend on the location
+    let new_reports = |dates: &[_]| -> Vec<report::SolarReport> {
+        report::solar_events_for_dates(dates, config.coordinates, config.twilight_angles)
+            .into_iter()
+            .map(display)
+            .collect()
+    };
+    let report = new_report(config.date);
 
-    match config.subcommand {
-        Some(config::Subcommand::Report {}) => subcommands::display_report(report),
-        Some(config::Subcommand::Wait { offset, event }) => {
-            subcommands::wait(offset?, report, event?)?
+    match subcommand {
+        Some(config::Subcommand::Report {
+            json,
+            output_format,
+            tsv,
+            template_file,
+            offset_file,
+            depression,
+            illuminance,
+            shell_export,
+            chart_data,
+            civil_day,
+            direction,
+            astro,
+            shadow,
+            night,
+            photo,
+            event,
+            events,
+            between,
+            offsets,
+            fallback_event,
+            expect_sunrise,
+            expect_sunset,
+            tolerance,
+            min_day_length,
+            max_day_length,
+            filters,
+            count_only,
+            occurs,
+            upcoming,
+            around_now,
+            diff,
+        }) => {
+            // only the subcommands which show each day of the range calculate it
+            let reports = new_reports(&config.dates()?);
+            let min_day_length = min_day_length.transpose()?;
+            let max_day_length = max_day_length.transpose()?;
+            let filters = filters
+                .into_iter()
+                .map(|filter| config.standard_event(filter, "in a filter"))
+                .collect::<Result<Vec<_>, _>>()?;
+            let fallback_event = fallback_event
+                .map(|event| config.standard_event(event, "as a fallback event"))
+                .transpose()?;
+            // roll on to tomorrow once today's sunset is over, judged against right now unless a date or time
+            // was chosen
+            let now = config.moment();
+            let reports = match reports.first() {
+                Some(today)
+                    if upcoming
+                        && config.end_date.is_none()
+                        && today.sunset.datetime.map_or(false, |sunset| now > sunset) =>
+                {
+                    new_reports(&[config.following_date(config.date)?])
+                }
+                _ => reports,
+            };
+            // an event from the config file is a standard event or an altitude, plus an offset
+            let event = event.map(|event| config.resolve_event(event)).transpose()?;
+            let reports: Vec<report::SolarReport> = reports
+                .into_iter()
+                .map(|mut report| {
+                    report.include_astro = astro;
+                    report
+                })
+                .filter(|report| {
+                    min_day_length.map_or(true, |min| report.day_length >= min)
+                        && max_day_length.map_or(true, |max| report.day_length <= max)
+                        && filters
+                            .iter()
+                            .all(|filter| filter.matches(report.event_time(&filter.event)))
+                })
+                .collect();
+
+            // JSON times for an event have to be asked for on the command line, so that a format set in the config
+            // file doesn't change the output of --event
+            let event_json = match output_format {
+                Some(Ok(enums::OutputFormat::Json)) => true,
+                _ => json,
+            };
+            let format = match output_format {
+                _ if json => enums::OutputFormat::Json,
+                _ if tsv => enums::OutputFormat::Tsv,
+                Some(format) => format?,
+                // the extra sections only exist in the plain report, so asking for one of them overrides the
+                // format set in the config file
+                None if astro || shadow || night || photo => enums::OutputFormat::Plain,
+                None => config.output_format,
+            };
+
+            match event {
+                _ if around_now => {
+                    if config.end_date.is_some() {
+                        Err(errors::HeliocronError::Config(
+                            errors::ConfigErrorKind::InvalidDateRange(
+                                "Only a single day can be used with --around-now.",
+                            ),
+                        ))?
+                    }
+                    subcommands::display_around_now(
+                        &new_reports(&[
+                            config.preceding_date(config.date)?,
+                            config.date,
+                            config.following_date(config.date)?,
+                        ]),
+                        now,
+                    )
+                }
+                _ if diff.is_some() => {
+                    if config.end_date.is_some() {
+                        Err(errors::HeliocronError::Config(
+                            errors::ConfigErrorKind::InvalidDateRange(
+                                "Only a single day can be used with --diff.",
+                            ),
+                        ))?
+                    }
+                    let other = config.date_at_noon(diff.unwrap()?)?;
+                    if !solar::is_supported_date(&other) {
+                        Err(errors::HeliocronError::Config(
+                            errors::ConfigErrorKind::UnsupportedDate,
+                        ))?
+                    }
+                    subcommands::display_difference(&report, &new_report(other))
+                }
+                _ if count_only => subcommands::display_count(&reports),
+                _ if offset_file.is_some() => {
+                    subcommands::display_event_offsets(&reports, offset_file.as_ref().unwrap())?
+                }
+                _ if template_file.is_some() => {
+                    subcommands::display_template(&reports, template_file.as_ref().unwrap())?
+                }
+                _ if shell_export => subcommands::display_shell_export(&reports)?,
+                _ if chart_data => subcommands::display_chart_data(&reports),
+                _ if civil_day => subcommands::display_civil_day(&reports),
+                // a window of time on its own picks from every event
+                _ if !events.is_empty() || !between.is_empty() => subcommands::display_events(
+                    &reports,
+                    if events.is_empty() {
+                        enums::Event::all()
+                            .iter()
+                            .map(|event| (enums::EventBase::Standard(*event), Duration::zero()))
+                            .collect()
+                    } else {
+                        events
+                            .into_iter()
+                            .map(|event| config.resolve_event(event))
+                            .collect::<Result<_, _>>()?
+                    },
+                    match between
+                        .into_iter()
+                        .collect::<Result<Vec<_>, _>>()?
+                        .as_slice()
+                    {
+                        [start, end] => Some((*start, *end)),
+                        _ => None,
+                    },
+                ),
+                // an illuminance is parsed into the depression at which twilight gives it
+                _ if depression.is_some() || illuminance.is_some() => {
+                    subcommands::display_crossings(
+                        &reports,
+                        depression.or(illuminance).unwrap()?,
+                        direction.transpose()?,
+                    )
+                }
+                Some((event, _)) if occurs => {
+                    subcommands::display_occurrence(&reports, event, config.end_date.is_some())?
+                }
+                _ if format == enums::OutputFormat::Ics => subcommands::display_ics(
+                    &reports,
+                    &match event {
+                        Some((enums::EventBase::Standard(event), offset)) if offset.is_zero() => {
+                            vec![event]
+                        }
+                        Some(_) => Err(errors::HeliocronError::Config(
+                            errors::ConfigErrorKind::UnsupportedCustomEvent("in an iCalendar file"),
+                        ))?,
+                        None => vec![enums::Event::Sunrise, enums::Event::Sunset],
+                    },
+                    config.now,
+                ),
+                Some((event, event_offset)) if event_json => subcommands::display_event_times_json(
+                    &reports,
+                    event,
+                    event_offset,
+                    &offsets.into_iter().collect::<Result<Vec<_>, _>>()?,
+                    fallback_event,
+                    config.compact_json,
+                ),
+                Some((event, event_offset)) => subcommands::display_event_times(
+                    &reports,
+                    event,
+                    event_offset,
+                    offsets
+                        .into_iter()
+                        .map(|offset| offset.map(|(_, offset)| offset))
+                        .collect::<Result<_, _>>()?,
+                    fallback_event,
+                ),
+                None => subcommands::display_report(
+                    &reports,
+                    config.end_date.is_some(),
+                    format,
+                    config.compact_json,
+                    structs::ExtraSections {
+                        astro,
+                        shadow,
+                        night,
+                        photo,
+                    },
+                ),
+            }
+
+            let mut expected_times = vec![];
+            if let Some(time) = expect_sunrise {
+                expected_times.push((enums::Event::Sunrise, time?));
+            }
+            if let Some(time) = expect_sunset {
+                expected_times.push((enums::Event::Sunset, time?));
+            }
+            if !expected_times.is_empty() {
+                subcommands::compare_expected_times(
+                    &reports,
+                    expected_times,
+                    Duration::seconds(tolerance as i64),
+                )?
+            }
+        }
+        Some(config::Subcommand::Wait {
+            offset,
+            event,
+            at,
+            grace,
+            clamp_after,
+            clamp_before,
+            fallback_event,
+            align_minute,
+            round_mode,
+            poll_interval,
+            quiet,
+            event_arg,
+            offset_arg,
+        }) => {
+            // the positional event and offset stand in for --event and --offset
+            let event = event.or(event_arg);
+            let offset = offset_arg.unwrap_or(offset);
+            let sleep = structs::SleepOptions {
+                grace: Duration::zero(),
+                // rounding up by default, so that an aligned wait never ends before the event
+                align_minute: if align_minute {
+                    Some(round_mode.transpose()?.unwrap_or(enums::RoundMode::Up))
+                } else {
+                    None
+                },
+                poll_interval: std::time::Duration::from_secs(poll_interval.get()),
+                quiet,
+            };
+            match (event, at) {
+                (Some(event), None) => {
+                    let (event, event_offset) = config.resolve_event(event)?;
+                    subcommands::wait(
+                        offset? + event_offset,
+                        report,
+                        event,
+                        structs::WaitBounds {
+                            clamp_after: clamp_after
+                                .map(|mark| config.standard_event(mark, "as a clamp"))
+                                .transpose()?,
+                            clamp_before: clamp_before
+                                .map(|mark| config.standard_event(mark, "as a clamp"))
+                                .transpose()?,
+                            fallback: fallback_event
+                                .map(|mark| config.standard_event(mark, "as a fallback event"))
+                                .transpose()?,
+                        },
+                        structs::SleepOptions {
+                            grace: grace.transpose()?.unwrap_or_else(Duration::zero),
+                            ..sleep
+                        },
+                        config.warnings_json,
+                        config.now,
+                    )?
+                }
+                (None, Some(at)) => {
+                    let time_zone = match config.time_zone_source {
+                        config::ConfigSource::Default if !config.fixed_offset => None,
+                        _ => Some(*config.date.offset()),
+                    };
+                    subcommands::wait_at(offset?, at?, time_zone, sleep, config.now)?
+                }
+                // will never match as StructOpt requires exactly one of --event or --at
+                _ => println!("No event or time provided!"),
+            }
+        }
+        Some(config::Subcommand::Schedule {
+            event,
+            offset,
+            run,
+            margin,
+        }) => {
+            // the event by the name it was given, so that one from the config file is still looked up there
+            let name = match event {
+                Ok(ref event) => event.to_string(),
+                Err(errors::HeliocronError::Config(errors::ConfigErrorKind::UnknownEvent(
+                    ref name,
+                    _,
+                ))) => name.trim().to_lowercase(),
+                Err(_) => String::new(),
+            };
+            let (event, event_offset) = config.resolve_event(event)?;
+            let offset = offset?;
+            let reports = new_reports(&config.dates_from_date(report::NEXT_OCCURRENCE_DAYS)?);
+            let schedule =
+                report::Schedule::from_reports(&reports, &event, offset + event_offset, margin?);
+
+            // the wait is for the same place, and in the same time zone if one was chosen, as the line's time,
+            // and with everything else which changed it. Cron doesn't run in the current directory, so files are
+            // given by their full paths
+            let (latitude, longitude) = config.coordinates.to_decimal_degrees();
+            let mut wait_args = vec![
+                format!("--latitude {}", latitude),
+                format!("--longitude {}", longitude),
+            ];
+            match config.time_zone_source {
+                config::ConfigSource::Default if !config.fixed_offset => {}
+                _ => wait_args.push(format!("--time-zone {}", config.date.format("%:z"))),
+            }
+            let file = |path: &std::path::Path| {
+                let path = std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+                utils::shell_quote(&path.display().to_string())
+            };
+            for path in config.config_files.iter() {
+                wait_args.push(format!("--config {}", file(path)));
+            }
+            if let Some(pressure) = config.pressure {
+                wait_args.push(format!("--pressure {}", pressure));
+            }
+            if let Some(temperature) = config.temperature {
+                wait_args.push(format!("--temperature {}", temperature));
+            }
+            if let Some(ref path) = config.horizon_file {
+                wait_args.push(format!("--horizon-file {}", file(path)));
+            }
+            wait_args.push(format!("wait --event {}", name));
+            if offset != Duration::zero() {
+                wait_args.push(format!("--offset {}", utils::format_offset(offset)));
+            }
+            subcommands::display_schedule(&schedule, &wait_args, &run, config.warnings_json)?
+        }
+        Some(config::Subcommand::Season { day_number }) => {
+            subcommands::display_season(config.date, day_number)
+        }
+        Some(config::Subcommand::Schema {}) => subcommands::display_schema(config.compact_json),
+        Some(config::Subcommand::SunPath {
+            interval,
+            full_day,
+            json,
+        }) => subcommands::display_sun_path(
+            &report,
+            Duration::minutes(interval.get() as i64),
+            full_day,
+            json,
+            config.compact_json,
+        ),
+        Some(config::Subcommand::MoonPhases { year, json }) => subcommands::display_moon_phases(
+            year.unwrap_or_else(|| config.date.year()),
+            config.date.offset(),
+            json,
+            config.compact_json,
+        )?,
+        Some(config::Subcommand::DayExtremes { year, json }) => {
+            let reports =
+                new_reports(&config.dates_in_year(year.unwrap_or_else(|| config.date.year()))?);
+            subcommands::display_day_extremes(&reports, json, config.compact_json)
+        }
+        Some(config::Subcommand::SeasonBounds { year, json }) => {
+            let year = year.unwrap_or_else(|| config.date.year());
+            subcommands::display_season_bounds(
+                &report::SeasonBounds::from_reports(
+                    year,
+                    &new_reports(&config.dates_in_year(year)?),
+                ),
+                json,
+                config.compact_json,
+            )
+        }
+        Some(config::Subcommand::NextOccurrence { event, json }) => {
+            let reports = new_reports(&config.dates_from_date(report::NEXT_OCCURRENCE_DAYS)?);
+            subcommands::display_next_occurrence(
+                &report::NextOccurrence::from_reports(
+                    &reports,
+                    config.standard_event(event, "with next-occurrence")?,
+                ),
+                json,
+                config.compact_json,
+            )
+        }
+        Some(config::Subcommand::Night { twilight, json }) => {
+            let twilight = twilight?;
+            let nights = new_reports(&config.dates()?)
+                .iter()
+                .map(|evening| {
+                    let morning = new_report(config.following_date(evening.date)?);
+                    Ok(report::NightReport::new(evening, &morning, twilight))
+                })
+                .collect::<Result<Vec<_>, errors::HeliocronError>>()?;
+            subcommands::display_nights(&nights, json, config.compact_json)
+        }
+        Some(config::Subcommand::Darkness { json }) => {
+            let darkness = new_reports(&config.dates()?)
+                .iter()
+                .map(|evening| {
+                    let morning = new_report(config.following_date(evening.date)?);
+                    Ok(report::DarknessReport::new(evening, &morning))
+                })
+                .collect::<Result<Vec<_>, errors::HeliocronError>>()?;
+            subcommands::display_darkness(&darkness, json, config.compact_json)
+        }
+        Some(config::Subcommand::Phase { exit_code }) => {
+            // right now, unless a date or time was chosen
+            let phase = subcommands::display_phase(
+                config.moment(),
+                config.coordinates,
+                config.twilight_angles,
+            );
+            if exit_code {
+                process::exit(phase.exit_code());
+            }
+        }
+        Some(config::Subcommand::Compare { locations }) => {
+            // the chosen date at each location, at noon in its own time zone
+            let date = config.date.naive_local().date().and_hms(12, 0, 0);
+            let reports = locations
+                .into_iter()
+                .map(|location| {
+                    let location = location?;
+                    let time_zone = location.time_zone.unwrap_or(*config.date.offset());
+                    Ok(display(report::SolarReport::with_twilight_angles(
+                        time_zone.from_local_datetime(&date).unwrap(),
+                        location.coordinates,
+                        config.twilight_angles,
+                    )))
+                })
+                .collect::<Result<Vec<_>, errors::HeliocronError>>()?;
+            subcommands::display_comparison(&reports)
+        }
+        Some(config::Subcommand::Flight {
+            from,
+            to,
+            departure,
+            arrival,
+            step,
+            json,
+        }) => {
+            let (departure, arrival) = (departure?, arrival?);
+            if arrival <= departure || arrival - departure > Duration::hours(48) {
+                Err(errors::HeliocronError::Config(
+                    errors::ConfigErrorKind::InvalidFlight(
+                        "The arrival must be after the departure, and no more than 48 hours later.",
+                    ),
+                ))?
+            }
+            subcommands::display_flight(
+                &report::FlightReport::new(
+                    from?,
+                    to?,
+                    departure,
+                    arrival,
+                    config.twilight_angles.sunrise,
+                    Duration::minutes(step.get() as i64),
+                ),
+                json,
+                config.compact_json,
+            )
+        }
+        Some(config::Subcommand::DumpConfig { output }) => {
+            subcommands::dump_config(&config, output.as_deref())?
         }
-        // will never match None as this is caught earlier by StructOpt
-        None => println!("No subcommand provided!"),
+        // only a guard on its own, which has already passed
+        None => (),
     }
     Ok(())
 }
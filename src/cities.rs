@@ -0,0 +1,115 @@
+use std::result;
+
+use super::{
+    errors::{ConfigErrorKind, HeliocronError},
+    structs,
+};
+
+type Result<T> = result::Result<T, HeliocronError>;
+
+// a small database of cities, in the format name,country,latitude,longitude, where the country is an ISO 3166-1
+// alpha-2 code and the coordinates are in signed decimal degrees. There are no time zones, as the offset can't be
+// worked out from a zone name without the time zone database
+const CITIES: &str = include_str!("../data/cities.csv");
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct City {
+    pub name: &'static str,
+    pub country: &'static str,
+    pub latitude: f64,
+    pub longitude: f64,
+}
+
+impl City {
+    pub fn coordinates(&self) -> Result<structs::Coordinates> {
+        structs::Coordinates::from_signed_decimal_degrees(self.latitude, self.longitude)
+    }
+}
+
+fn cities() -> impl Iterator<Item = City> {
+    CITIES
+        .lines()
+        .filter(|line| !line.starts_with('#') && !line.trim().is_empty())
+        .map(|line| {
+            let fields: Vec<&'static str> = line.split(',').collect();
+            // the data is bundled with the program, so it is always well formed
+            City {
+                name: fields[0],
+                country: fields[1],
+                latitude: fields[2].parse().unwrap(),
+                longitude: fields[3].parse().unwrap(),
+            }
+        })
+}
+
+pub fn find_city(query: &str) -> Result<City> {
+    // the query is a city name, optionally followed by a comma and a country code e.g. "London,GB". Both are
+    // case insensitive
+    let (name, country) = match query.rfind(',') {
+        Some(i) => (query[..i].trim(), Some(query[i + 1..].trim())),
+        None => (query.trim(), None),
+    };
+
+    let mut matches: Vec<City> = cities()
+        .filter(|city| city.name.eq_ignore_ascii_case(name))
+        .filter(|city| country.map_or(true, |country| city.country.eq_ignore_ascii_case(country)))
+        .collect();
+
+    match matches.len() {
+        0 => Err(HeliocronError::Config(ConfigErrorKind::UnknownCity)),
+        1 => Ok(matches.remove(0)),
+        _ => Err(HeliocronError::Config(ConfigErrorKind::AmbiguousCity(
+            matches
+                .iter()
+                .map(|city| format!("{},{}", city.name, city.country))
+                .collect::<Vec<String>>()
+                .join(", "),
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cities_are_valid() {
+        // every city must have valid coordinates, and be uniquely identified by its name and country
+        let cities: Vec<City> = cities().collect();
+        assert!(cities.len() > 100);
+
+        for city in cities.iter() {
+            assert!(city.coordinates().is_ok(), "{:?}", city);
+            let query = format!("{},{}", city.name, city.country);
+            assert_eq!(*city, find_city(&query).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_find_city() {
+        let city = find_city("berlin").unwrap();
+        assert_eq!("Berlin", city.name);
+        assert_eq!("DE", city.country);
+
+        let coordinates = find_city("Sydney").unwrap().coordinates().unwrap();
+        assert_eq!(-33.8688, coordinates.latitude.value);
+        assert_eq!(151.2093, coordinates.longitude.value);
+
+        let city = find_city("Springfield, us").unwrap();
+        assert_eq!("US", city.country);
+    }
+
+    #[test]
+    fn test_find_city_fails() {
+        match find_city("Springfield") {
+            Err(HeliocronError::Config(ConfigErrorKind::AmbiguousCity(candidates))) => {
+                assert_eq!("Springfield,NZ, Springfield,US", candidates)
+            }
+            _ => panic!("Springfield should be ambiguous"),
+        }
+
+        assert!(find_city("Atlantis").is_err());
+        assert!(find_city("Berlin,FR").is_err());
+        assert!(find_city("").is_err());
+    }
+}
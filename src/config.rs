@@ -1,14 +1,17 @@
-use std::{fs, path::Path, result};
+use std::{
+    collections::BTreeMap, env, fmt, fs, io, num::NonZeroU32, num::NonZeroU64, path::Path,
+    path::PathBuf, result,
+};
 
-use chrono::{DateTime, Duration, FixedOffset, Local, TimeZone};
+use chrono::{DateTime, Datelike, Duration, FixedOffset, Local, NaiveDate, NaiveTime, TimeZone};
 use dirs;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use structopt::StructOpt;
 
 use super::{
-    enums,
+    cities, enums,
     errors::{ConfigErrorKind, HeliocronError},
-    parsers, structs,
+    parsers, solar, structs, utils,
 };
 
 type Result<T> = result::Result<T, HeliocronError>;
@@ -21,11 +24,13 @@ type Result<T> = result::Result<T, HeliocronError>;
              For example, to execute a script 'turn-on-lights.sh' at sunrise, make a Crontab entry to trigger \
              at a time that will always be before the chosen event (say, 2am) and use heliocron to calculate \
              and perform the appropriate delay:\n\n\
-             \t0 2 * * * heliocron --latitude 51.47N --longitude 3.1W wait --event sunrise && turn-on-lights.sh"
+             \t0 2 * * * heliocron --latitude 51.47N --longitude 3.1W wait --event sunrise && turn-on-lights.sh",
+    group = structopt::clap::ArgGroup::with_name("guard")
 )]
 struct Cli {
+    // only optional when there is a guard, which can be used on its own (see get_config)
     #[structopt(subcommand)]
-    subcommand: Subcommand,
+    subcommand: Option<Subcommand>,
 
     #[structopt(flatten)]
     date_args: DateArgs,
@@ -47,32 +52,812 @@ struct Cli {
         requires = "latitude"
     )]
     longitude: Option<String>,
+
+    #[structopt(
+        long = "city",
+        help = "Set the location to that of a city, e.g. \"Berlin\", from a small built-in list of major cities. Add a country code to choose between cities with the same name, e.g. \"London,CA\".",
+        conflicts_with_all = &["latitude", "longitude"]
+    )]
+    city: Option<String>,
+
+    #[structopt(
+        long = "coords",
+        help = "Set the latitude and longitude together, in signed decimal degrees with the latitude first, e.g. \"51.4769, -0.0005\".",
+        conflicts_with_all = &["latitude", "longitude", "city"],
+        allow_hyphen_values = true
+    )]
+    coords: Option<String>,
+
+    #[structopt(
+        long = "bbox",
+        help = "Set the location to the centre of a region, given as a bounding box of min latitude, min longitude, max latitude and max longitude in signed decimal degrees, e.g. \"51.28, -0.51, 51.69, 0.33\". The times are for the centre, so are only approximate elsewhere in the region, which is given as a warning.",
+        conflicts_with_all = &["latitude", "longitude", "city", "coords"],
+        allow_hyphen_values = true
+    )]
+    bbox: Option<String>,
+
+    #[structopt(
+        long = "config",
+        help = "Read configuration from this TOML file, instead of ~/.config/heliocron.toml. May be given more than once, in which case the files are layered in order, each overriding the settings of those before it.",
+        parse(from_os_str),
+        number_of_values = 1
+    )]
+    config: Vec<PathBuf>,
+
+    #[structopt(
+        long = "input-json",
+        help = "Read the location, and optionally the date and time zone, from this JSON file, or from stdin if it is '-', e.g. {\"latitude\": 51.4769, \"longitude\": -0.0005, \"date\": \"2020-06-21\", \"time_zone\": \"+01:00\"}. The latitude and longitude are in signed decimal degrees. These override any config file, and are overridden in turn by the command line.",
+        parse(from_os_str)
+    )]
+    input_json: Option<PathBuf>,
+
+    #[structopt(
+        long = "strict",
+        help = "Treat anything which would otherwise silently fall back to a default as an error, such as an invalid config file or an unset location."
+    )]
+    strict: bool,
+
+    #[structopt(
+        long = "verbose",
+        help = "Before doing anything else, print the resolved configuration to stderr, along with where each value came from."
+    )]
+    verbose: bool,
+
+    #[structopt(
+        long = "no-location",
+        help = "Leave the latitude and longitude out of the output, e.g. to share it without revealing where you are. They are still used for the calculations."
+    )]
+    no_location: bool,
+
+    #[structopt(
+        long = "decimal-hours",
+        help = "Show the times of events as a decimal number of hours past local midnight on the chosen day, e.g. 6.0769 rather than 06:04:37. Doesn't affect JSON output."
+    )]
+    decimal_hours: bool,
+
+    #[structopt(
+        long = "day-fraction",
+        help = "Show the times of events as a fraction of the day past local midnight on the chosen day, e.g. 0.25330 rather than 06:04:45, as some data pipelines expect. Times of day can be given in the same way, e.g. --at 0.25. Doesn't affect JSON output.",
+        conflicts_with = "decimal-hours"
+    )]
+    day_fraction: bool,
+
+    #[structopt(
+        long = "solar-time",
+        help = "Show the times of events in local apparent solar time, in which the Sun is due south (or north) at exactly 12:00:00, rather than in the chosen time zone. Doesn't affect JSON output."
+    )]
+    solar_time: bool,
+
+    #[structopt(
+        long = "relative-to-noon",
+        help = "Show the times of events as a signed offset from solar noon, e.g. -06:53:12 for a sunrise 6 hours 53 minutes and 12 seconds before noon, which makes the symmetry of the day easy to see. Combined with --decimal-hours, the offset is a signed decimal number of hours, or with --day-fraction, a signed fraction of a day. Doesn't affect JSON output."
+    )]
+    relative_to_noon: bool,
+
+    #[structopt(
+        long = "time-only",
+        help = "Show the times of events as the time of day on its own, e.g. 06:04:37, without the date and time zone offset. Combined with --solar-time, the time of day in solar time. Doesn't affect JSON output.",
+        conflicts_with_all = &["decimal-hours", "day-fraction", "relative-to-noon"]
+    )]
+    time_only: bool,
+
+    #[structopt(
+        long = "with-accuracy",
+        help = "Follow the time of each event with an estimate of its uncertainty, e.g. (±2 min), from how slowly the Sun crosses the event's altitude and how much the refraction near the horizon can vary. The estimate grows far from the equator, and is largest when the Sun barely reaches the altitude. Doesn't affect JSON or other machine-readable output."
+    )]
+    with_accuracy: bool,
+
+    #[structopt(
+        long = "locale",
+        help = "Write out the date in the plain report with the names of the weekday and month in the given language, e.g. 'fr' or 'de_DE.UTF-8'. Only the language is used, from: en, fr, de, es, it, nl, pt. The numeric dates and times, and machine-readable output, are unchanged.",
+        parse(from_str=parsers::parse_locale)
+    )]
+    locale: Option<Result<enums::Locale>>,
+
+    #[structopt(
+        long = "compact-json",
+        help = "Print JSON output on a single line, rather than indented over several lines, e.g. for piping into other programs."
+    )]
+    compact_json: bool,
+
+    #[structopt(
+        long = "tz-warning",
+        help = "Warn when the chosen time zone differs from the nominal time zone at the chosen location by more than an hour. Defaults to the tz_warning set in the config file, or off.",
+        conflicts_with = "no-tz-warning"
+    )]
+    tz_warning: bool,
+
+    #[structopt(
+        long = "no-tz-warning",
+        help = "Don't warn about the time zone, even if tz_warning is set in the config file."
+    )]
+    no_tz_warning: bool,
+
+    #[structopt(
+        long = "pressure",
+        help = "Set the atmospheric pressure in hPa (millibars), which together with --temperature sets the refraction at the horizon, and so the times of sunrise and sunset. [default: 1010]",
+        parse(from_str=parsers::parse_pressure),
+        allow_hyphen_values = true
+    )]
+    pressure: Option<Result<f64>>,
+
+    #[structopt(
+        long = "temperature",
+        help = "Set the air temperature in °C, which together with --pressure sets the refraction at the horizon, and so the times of sunrise and sunset. [default: 10]",
+        parse(from_str=parsers::parse_temperature),
+        allow_hyphen_values = true
+    )]
+    temperature: Option<Result<f64>>,
+
+    #[structopt(
+        long = "fail-if-dark",
+        help = "Exit with code 2, printing nothing, unless it is light, i.e. the centre of the Sun is at or above the altitude of sunrise and sunset (or the twilight chosen with --guard-twilight). Judged at the current time, or the time given with --datetime. May be used without a subcommand, e.g. heliocron --fail-if-dark && run.sh, or with one, which then only runs if it is light.",
+        group = "guard"
+    )]
+    fail_if_dark: bool,
+
+    #[structopt(
+        long = "fail-if-light",
+        help = "Exit with code 2, printing nothing, unless it is dark. The opposite of --fail-if-dark.",
+        group = "guard"
+    )]
+    fail_if_light: bool,
+
+    #[structopt(
+        long = "guard-twilight",
+        help = "Judge --fail-if-dark and --fail-if-light by the end of this kind of twilight, rather than by sunrise and sunset.",
+        parse(from_str=parsers::parse_twilight_type),
+        possible_values = enums::TWILIGHT_NAMES,
+        case_insensitive = true,
+        requires = "guard"
+    )]
+    guard_twilight: Option<Result<enums::TwilightType>>,
+
+    #[structopt(
+        long = "now",
+        help = "Treat this RFC 3339 date and time, e.g. 2020-06-21T12:00:00+01:00, as the current time rather than reading the system clock, e.g. to test cron jobs. This sets today's date, and the time that wait, phase and report --upcoming work from. If not given, the HELIOCRON_NOW environment variable is used, if set."
+    )]
+    now: Option<String>,
+
+    #[structopt(
+        long = "warnings-json",
+        help = "Print any warnings to stderr as JSON objects, one per line, each with a fixed code and a message, rather than as text. The output on stdout is unchanged."
+    )]
+    warnings_json: bool,
+
+    #[structopt(
+        long = "horizon-file",
+        help = "Compute sunrise and sunset over the local skyline rather than a level horizon, from this file of points around it, one per line, each an azimuth clockwise from north and the altitude of the skyline in that direction, both in degrees, e.g. 135 4.5. Between the points the altitude is interpolated linearly. Twilight is unaffected.",
+        parse(from_os_str)
+    )]
+    horizon_file: Option<PathBuf>,
+
+    #[structopt(
+        long = "validate",
+        help = "Check the config files, every setting in each, and list any problems found, without running anything. Exits with code 1 if there are any. No subcommand is needed."
+    )]
+    validate: bool,
+
+    #[structopt(
+        long = "dump-defaults",
+        help = "Print the built-in defaults, which apply when neither a config file nor the command line sets otherwise, and exit. No subcommand is needed."
+    )]
+    dump_defaults: bool,
+
+    #[structopt(
+        long = "check-date",
+        help = "Only parse the date, with the chosen date format and time zone (see --date, --date-format, --datetime and --time-zone), print the date and time it gives, and exit, e.g. to debug a --date-format. No subcommand is needed."
+    )]
+    check_date: bool,
 }
 
+// the built-in defaults, used when neither a config file nor the command line sets otherwise. The Royal
+// Observatory, Greenwich
+pub const DEFAULT_LATITUDE: &str = "51.4769N";
+pub const DEFAULT_LONGITUDE: &str = "0.0005W";
+// the delay from the event in wait
+pub const DEFAULT_OFFSET: &str = "00:00:00";
+// how often a wait checks the clock, in seconds, so that it still ends on time after the clock changes
+pub const DEFAULT_POLL_INTERVAL: &str = "60";
+// how long before the earliest the event happens a crontab line from schedule fires
+pub const DEFAULT_SCHEDULE_MARGIN: &str = "01:00:00";
+// the most days a range of dates may cover, about ten years
+pub const DEFAULT_MAX_RANGE_DAYS: &str = "3660";
+
 #[derive(Debug, StructOpt)]
 pub enum Subcommand {
-    Report {},
+    // either angle may be given for --direction, but not both
+    #[structopt(group = structopt::clap::ArgGroup::with_name("crossing_angle"))]
+    Report {
+        #[structopt(
+            long = "astro",
+            help = "Also show the astronomical quantities used in the calculations, along with the time scales involved."
+        )]
+        astro: bool,
+
+        #[structopt(
+            long = "shadow",
+            help = "Also show the direction and relative length of shadows cast at the chosen date and time."
+        )]
+        shadow: bool,
+
+        #[structopt(
+            long = "night",
+            help = "Also show how long the Sun spends above and below the angle of each kind of twilight over the day."
+        )]
+        night: bool,
+
+        #[structopt(
+            long = "photo",
+            help = "Also show the golden and blue hours of the morning and evening, and how long they last. The golden hour is when the centre of the Sun is between 6° above and 4° below the horizon, and the blue hour when it is between 4° and 6° below."
+        )]
+        photo: bool,
+
+        #[structopt(
+            long = "json",
+            help = "Show the report as JSON. Times are in RFC 3339 format, or null if the event doesn't occur, and the day length is in seconds. With --astro, the astronomical data is added as an object of numbers. With --event, an array of the event's times instead, one for each day and offset (see --offsets).",
+            conflicts_with_all = &["shadow", "night", "photo"],
+        )]
+        json: bool,
+
+        #[structopt(
+            help = "Choose how to show the report: as plain text, as JSON (the same as --json), as CSV, with one row per day, as TSV (the same as --tsv), or as an iCalendar file of the sunrises and sunsets (or of the event chosen with --event). Defaults to the output_format set in the config file, or plain.",
+            long = "output-format",
+            parse(from_str=parsers::parse_output_format),
+            possible_values = enums::OUTPUT_FORMAT_NAMES,
+            case_insensitive = true,
+            conflicts_with_all = &["json", "astro", "shadow", "night", "photo"],
+        )]
+        output_format: Option<Result<enums::OutputFormat>>,
+
+        #[structopt(
+            long = "tsv",
+            help = "Show the report as TSV, with the same header and columns as the CSV output but separated by tabs, for tools which prefer them. The same as --output-format tsv.",
+            conflicts_with_all = &["json", "output-format", "astro", "shadow", "night", "photo"],
+        )]
+        tsv: bool,
+
+        #[structopt(
+            help = "Instead of the full report, show each day's report using the template in this file. Placeholders are names in braces, such as {sunrise} or {day_length}, which are replaced with the same values as in the plain report; use {{ and }} for literal braces.",
+            long = "template-file",
+            parse(from_os_str),
+            conflicts_with_all = &["json", "output-format", "tsv", "event", "astro", "shadow", "night", "photo", "count-only"],
+        )]
+        template_file: Option<PathBuf>,
+
+        #[structopt(
+            help = "Instead of the full report, show the time of every event shifted by its offset from this file, a TOML table of events and offsets in the same formats as the offset for wait, e.g. sunset = \"-30m\". Events which aren't in the file aren't shifted.",
+            long = "offset-file",
+            parse(from_os_str),
+            conflicts_with_all = &["json", "output-format", "tsv", "template-file", "event", "astro", "shadow", "night", "photo", "count-only"],
+        )]
+        offset_file: Option<PathBuf>,
+
+        #[structopt(
+            help = "Instead of the full report, show when the geometric centre of the Sun is this many degrees below the horizon, in the same way as the definitions of twilight, e.g. 9 for a darkness between civil and nautical twilight. Both the morning and the evening crossings are shown, unless one is chosen with --direction.",
+            long = "depression",
+            parse(from_str=parsers::parse_depression),
+            allow_hyphen_values = true,
+            group = "crossing_angle",
+            conflicts_with_all = &["json", "output-format", "tsv", "template-file", "offset-file", "event", "astro", "shadow", "night", "photo", "count-only"],
+        )]
+        depression: Option<Result<f64>>,
+
+        #[structopt(
+            help = "Instead of the full report, show when twilight gives approximately this illuminance in lux, from 0.0007 in the darkest night to 400 at sunset, as the crossings of the corresponding depression angle (see --depression). The model assumes a clear sky with no Moon or artificial light, which can each change the real illuminance tenfold or more.",
+            long = "illuminance",
+            parse(from_str=parsers::parse_illuminance),
+            group = "crossing_angle",
+            conflicts_with_all = &["json", "output-format", "tsv", "template-file", "offset-file", "shell-export", "event", "astro", "shadow", "night", "photo", "count-only"],
+        )]
+        illuminance: Option<Result<f64>>,
+
+        #[structopt(
+            help = "Instead of the full report, output shell variable assignments such as HELIOCRON_SUNRISE='2020-03-08T06:28:26+00:00', ready for eval. Times are in the same formats as the CSV output.",
+            long = "shell-export",
+            conflicts_with_all = &["json", "output-format", "tsv", "template-file", "offset-file", "depression", "event", "astro", "shadow", "night", "photo", "count-only"],
+        )]
+        shell_export: bool,
+
+        #[structopt(
+            help = "Instead of the full report, output CSV with the date and the times of sunrise and sunset in minutes past local midnight, ready to plot the sunrise and sunset curves over a range of dates. The times are empty on days when the Sun doesn't rise or set.",
+            long = "chart-data",
+            conflicts_with_all = &["json", "output-format", "tsv", "template-file", "offset-file", "depression", "illuminance", "shell-export", "event", "events", "astro", "shadow", "night", "photo", "count-only", "around-now"],
+        )]
+        chart_data: bool,
+
+        #[structopt(
+            help = "Instead of the full report, show only the civil day, from civil dawn to civil dusk, and how long it lasts: the time when there is usually enough light to work outdoors without artificial lighting.",
+            long = "civil-day",
+            conflicts_with_all = &["json", "output-format", "tsv", "template-file", "offset-file", "depression", "illuminance", "shell-export", "chart-data", "event", "events", "astro", "shadow", "night", "photo", "count-only", "around-now"],
+        )]
+        civil_day: bool,
+
+        #[structopt(
+            help = "Only show the crossing of the depression angle (see --depression and --illuminance) as the Sun is rising, in the morning, or as it is setting, in the evening.",
+            long = "direction",
+            parse(from_str=parsers::parse_direction),
+            possible_values = enums::DIRECTION_NAMES,
+            case_insensitive = true,
+            requires = "crossing_angle",
+        )]
+        direction: Option<Result<enums::Direction>>,
+
+        #[structopt(
+            help = "Instead of the full report, only show the time of this event (see --offsets). One of sunrise, sunset, civil_dawn, civil_dusk, nautical_dawn, nautical_dusk, astronomical_dawn or astronomical_dusk, in any case, or an event defined in the config file.",
+            short = "e",
+            long = "event",
+            parse(from_str=parsers::parse_event),
+        )]
+        event: Option<Result<enums::Event>>,
+
+        #[structopt(
+            help = "Instead of the full report, only show the times of these comma-separated events, in the same formats as --event. Each event is shown once, in order of time, with any which happen at the same moment in the order sunrise, sunset, civil_dawn, civil_dusk, nautical_dawn, nautical_dusk, astronomical_dawn, astronomical_dusk, and any which don't occur last.",
+            long = "events",
+            parse(from_str=parsers::parse_event),
+            require_delimiter = true,
+            conflicts_with_all = &["json", "output-format", "tsv", "template-file", "offset-file", "depression", "shell-export", "event", "astro", "shadow", "night", "photo", "count-only"],
+        )]
+        events: Vec<Result<enums::Event>>,
+
+        #[structopt(
+            help = "Instead of the full report, only show the events whose times of day fall between these two times, inclusive, in the formats {HH:MM:SS | HH:MM} or as a fraction of the day (see --day-fraction), e.g. --between 17:00 22:00. Shown in the same way as --events, and only from those events if --events is also given. A window which ends before it starts runs on past midnight.",
+            long = "between",
+            parse(from_str=parsers::parse_time),
+            number_of_values = 2,
+            value_names = &["start", "end"],
+            conflicts_with_all = &["json", "output-format", "tsv", "template-file", "offset-file", "depression", "illuminance", "shell-export", "chart-data", "civil-day", "event", "astro", "shadow", "night", "photo", "count-only", "around-now"],
+        )]
+        between: Vec<Result<NaiveTime>>,
+
+        #[structopt(
+            help = "Show the time of the chosen event (see --event) shifted by each of these comma-separated offsets, in the same formats as the offset for wait. With --json, each is shown as it was given, along with its number of seconds.",
+            long = "offsets",
+            parse(from_str=parsers::parse_labelled_offset),
+            allow_hyphen_values = true,
+            require_delimiter = true,
+            requires = "event",
+        )]
+        offsets: Vec<Result<(String, Duration)>>,
+
+        #[structopt(
+            help = "On the days when the chosen event (see --event) doesn't occur, show this moment of the day instead, shifted in the same way and marked as such: solar_noon, solar_midnight, or any event which can be given to --event.",
+            long = "fallback-event",
+            parse(from_str=parsers::parse_day_mark),
+            requires = "event",
+            conflicts_with = "occurs",
+        )]
+        fallback_event: Option<Result<enums::DayMark>>,
+
+        #[structopt(
+            help = "Compare the calculated sunrise with this time, e.g. from a published almanac, in one of the following formats: {HH:MM:SS | HH:MM}, or as a fraction of the day (see --day-fraction). Exits with an error if they differ by more than the tolerance (see --tolerance).",
+            long = "expect-sunrise",
+            parse(from_str=parsers::parse_time),
+            conflicts_with = "json",
+        )]
+        expect_sunrise: Option<Result<NaiveTime>>,
+
+        #[structopt(
+            help = "Compare the calculated sunset with this time, in the same way as --expect-sunrise.",
+            long = "expect-sunset",
+            parse(from_str=parsers::parse_time),
+            conflicts_with = "json",
+        )]
+        expect_sunset: Option<Result<NaiveTime>>,
+
+        #[structopt(
+            help = "The largest difference, in seconds, allowed between an expected and a calculated time.",
+            long = "tolerance",
+            default_value = "60"
+        )]
+        tolerance: u32,
+
+        #[structopt(
+            help = "Only show the days on which the day length is at least this long, in the same formats as the offset for wait, e.g. 12:00 or 12h.",
+            long = "min-day-length",
+            parse(from_str=parsers::parse_duration),
+        )]
+        min_day_length: Option<Result<Duration>>,
+
+        #[structopt(
+            help = "Only show the days on which the day length is at most this long, in the same formats as --min-day-length.",
+            long = "max-day-length",
+            parse(from_str=parsers::parse_duration),
+        )]
+        max_day_length: Option<Result<Duration>>,
+
+        #[structopt(
+            help = "Only show the days on which an event happens before, at or after a time of day, e.g. 'sunset>=20:00'. The comparison can be any of <, <=, =, !=, >= or >. May be given more than once, in which case every filter must match.",
+            long = "filter",
+            parse(from_str=parsers::parse_filter),
+            number_of_values = 1,
+        )]
+        filters: Vec<Result<structs::EventFilter>>,
+
+        #[structopt(
+            help = "Instead of the reports themselves, only show the number of days in the date range which meet the conditions (see --min-day-length, --max-day-length and --filter).",
+            long = "count-only",
+            conflicts_with_all = &["json", "event", "astro", "shadow", "night", "photo"],
+        )]
+        count_only: bool,
+
+        #[structopt(
+            help = "Instead of its time, only show whether the chosen event (see --event) occurs on the day, as true or false, and exit with an error if it doesn't. With a range of dates, each day is shown on its own line and the error is given if the event doesn't occur on any one of them.",
+            long = "occurs",
+            requires = "event",
+            conflicts_with_all = &["offsets", "json", "output-format", "tsv"],
+        )]
+        occurs: bool,
+
+        #[structopt(
+            help = "Once today's sunset has passed, show tomorrow's report instead, so that the report always looks ahead. Compared with the current time, or the time given with --datetime. Has no effect on a range of dates, or on a day without a sunset.",
+            long = "upcoming"
+        )]
+        upcoming: bool,
+
+        #[structopt(
+            help = "Instead of the full report, only show the most recent event and the next one, with how long ago and how long until they are. Looks into the days either side as needed, e.g. for the next event late at night. Compared with the current time, or the time given with --datetime.",
+            long = "around-now",
+            conflicts_with_all = &["json", "output-format", "tsv", "template-file", "offset-file", "depression", "illuminance", "shell-export", "event", "events", "astro", "shadow", "night", "photo", "count-only", "upcoming"],
+        )]
+        around_now: bool,
+
+        #[structopt(
+            help = "Instead of the full report, show how much earlier or later each event is than on this other date, in the format %Y-%m-%d, e.g. a month ago. A positive difference means that the event is later on the chosen date, by the clock. An event which happens on only one of the two dates is shown as such.",
+            long = "diff",
+            parse(from_str=parsers::parse_day),
+            conflicts_with_all = &["json", "output-format", "tsv", "template-file", "offset-file", "depression", "illuminance", "shell-export", "chart-data", "civil-day", "event", "events", "between", "astro", "shadow", "night", "photo", "count-only", "upcoming", "around-now"],
+        )]
+        diff: Option<Result<NaiveDate>>,
+    },
 
     Wait {
         #[structopt(
-            help = "Choose a delay from your chosen event (see --event) in one of the following formats: {HH:MM:SS | HH:MM}. You may prepend the delay with '-' to make it negative. A negative offset will set the delay to be before the event, whilst a positive offset will set the delay to be after the event.",
+            help = "Choose a delay from your chosen event (see --event) in one of the following formats: {HH:MM:SS | HH:MM}, or as hours, minutes and/or seconds such as '1h30m'. You may prepend the delay with '-', or follow it with 'before', to make it negative. A negative offset will set the delay to be before the event, whilst a positive offset (optionally prepended with '+' or followed by 'after') will set the delay to be after the event.",
             short = "o",
             long = "offset",
-            default_value = "00:00:00",
+            default_value = DEFAULT_OFFSET,
             parse(from_str=parsers::parse_offset),
             allow_hyphen_values = true,
         )]
         offset: Result<Duration>,
 
         #[structopt(
-            help = "Choose an event from which to base your delay.", 
-            short = "e", 
-            long = "event", 
+            help = "Choose an event from which to base your delay. One of sunrise, sunset, civil_dawn, civil_dusk, nautical_dawn, nautical_dusk, astronomical_dawn or astronomical_dusk, in any case, or an event defined in the config file.",
+            short = "e",
+            long = "event",
+            parse(from_str=parsers::parse_event),
+            required_unless_one = &["at", "EVENT"],
+        )]
+        event: Option<Result<enums::Event>>,
+
+        #[structopt(
+            help = "Instead of an event, wait until a fixed time of day in one of the following formats: {HH:MM:SS | HH:MM}, or as a fraction of the day, e.g. 0.25 for 06:00. If the time has already passed today, wait until that time tomorrow.",
+            long = "at",
+            parse(from_str=parsers::parse_time),
+            conflicts_with = "event",
+        )]
+        at: Option<Result<NaiveTime>>,
+
+        #[structopt(
+            help = "If the chosen event [+ offset] has already passed, but by no more than this, return immediately instead of failing. In the same formats as the offset, e.g. 00:05 or 5m.",
+            long = "grace",
+            parse(from_str=parsers::parse_duration),
+        )]
+        grace: Option<Result<Duration>>,
+
+        #[structopt(
+            help = "Never wait until earlier than this moment of the day: solar_noon, solar_midnight, or any event which can be given to --event, e.g. wait -e sunset -o -02:00 --clamp-after solar_noon on a winter's day with an early sunset. Has no effect if the moment doesn't occur on the day.",
+            long = "clamp-after",
+            parse(from_str=parsers::parse_day_mark),
+            conflicts_with = "at",
+        )]
+        clamp_after: Option<Result<enums::DayMark>>,
+
+        #[structopt(
+            help = "Never wait until later than this moment of the day, in the same format as --clamp-after. If both are given and cross over, this one wins.",
+            long = "clamp-before",
+            parse(from_str=parsers::parse_day_mark),
+            conflicts_with = "at",
+        )]
+        clamp_before: Option<Result<enums::DayMark>>,
+
+        #[structopt(
+            help = "If the chosen event doesn't occur on the day, e.g. civil_dusk in a summer far from the equator, wait for this moment of the day instead, [+ offset] in the same way, with a warning saying so: solar_noon, solar_midnight, or any event which can be given to --event. Solar midnight is the one after the day's solar noon. If this doesn't occur either, the wait fails as it would have without it.",
+            long = "fallback-event",
+            parse(from_str=parsers::parse_day_mark),
+            conflicts_with = "at",
+        )]
+        fallback_event: Option<Result<enums::DayMark>>,
+
+        #[structopt(
+            help = "Round the time waited until up to the next whole minute, e.g. 19:47:23 becomes 19:48:00, so that anything run afterwards lines up with cron's minutes. The times shown by report are unchanged.",
+            long = "align-minute"
+        )]
+        align_minute: bool,
+
+        #[structopt(
+            help = "Which way --align-minute rounds the time waited until: up to the next whole minute, which never ends the wait before the event, down to the start of the minute, or to the nearest minute. [default: up]",
+            long = "round-mode",
+            parse(from_str=parsers::parse_round_mode),
+            possible_values = enums::ROUND_MODE_NAMES,
+            case_insensitive = true,
+            requires = "align-minute",
+        )]
+        round_mode: Option<Result<enums::RoundMode>>,
+
+        #[structopt(
+            help = "The longest time, in seconds, to sleep for at a time before checking the clock again. A longer interval wakes the machine less often, e.g. to save power on a battery-powered device, but is slower to notice a change to the system clock or a suspend; a very long one is almost a single sleep. A shorter one follows the clock more closely.",
+            long = "poll-interval",
+            default_value = DEFAULT_POLL_INTERVAL,
+        )]
+        poll_interval: NonZeroU64,
+
+        #[structopt(
+            help = "Print nothing on success, e.g. when run from a systemd unit's ExecStartPre, so that the journal stays clean. Errors are still written to stderr, and the exit code still shows whether the wait succeeded.",
+            long = "quiet",
+            short = "q"
+        )]
+        quiet: bool,
+
+        #[structopt(
+            help = "The event to wait for, in the same way as --event, for a shorter command, e.g. 'wait sunset \"30m before\"'.",
+            name = "EVENT",
             parse(from_str=parsers::parse_event),
-            possible_values = &["sunrise", "sunset", "civil_dawn", "civil_dusk", "nautical_dawn", "nautical_dusk", "astronomical_dawn", "astronomical_dusk"]
+            conflicts_with_all = &["event", "at"],
+        )]
+        event_arg: Option<Result<enums::Event>>,
+
+        #[structopt(
+            help = "The delay from the event given before it, in the same formats as --offset, which it can't be given with. A negative delay starting with '-' must come after '--', e.g. 'wait sunset -- -00:30:00'.",
+            name = "OFFSET",
+            parse(from_str=parsers::parse_offset),
+            allow_hyphen_values = true,
+            conflicts_with = "offset",
+        )]
+        offset_arg: Option<Result<Duration>>,
+    },
+
+    Schedule {
+        #[structopt(
+            help = "The event for the crontab line to wait for, in the same way as wait's --event.",
+            short = "e",
+            long = "event",
+            parse(from_str=parsers::parse_event),
+        )]
+        event: Result<enums::Event>,
+
+        #[structopt(
+            help = "The delay from the event, in the same formats as wait's --offset.",
+            short = "o",
+            long = "offset",
+            default_value = DEFAULT_OFFSET,
+            parse(from_str=parsers::parse_offset),
+            allow_hyphen_values = true,
+        )]
+        offset: Result<Duration>,
+
+        #[structopt(
+            help = "The command for the crontab line to run once the wait has finished, e.g. 'turn-on-lights.sh'.",
+            long = "run"
+        )]
+        run: String,
+
+        #[structopt(
+            help = "How long before the earliest the event [+ offset] happens over the next year the crontab line fires, in the same formats as the offset, e.g. 01:00 or 1h. It never fires before midnight, however early the event is.",
+            long = "margin",
+            default_value = DEFAULT_SCHEDULE_MARGIN,
+            parse(from_str=parsers::parse_duration),
+        )]
+        margin: Result<Duration>,
+    },
+
+    Season {
+        #[structopt(
+            help = "Instead of the next solstice or equinox, output the number of days since the previous one and until the next, counted by date in the chosen time zone. One on the chosen date is counted as the previous one, 0 days ago.",
+            long = "day-number"
+        )]
+        day_number: bool,
+    },
+
+    Schema {},
+
+    SunPath {
+        #[structopt(
+            help = "The number of minutes between each sample.",
+            long = "interval",
+            default_value = "10"
+        )]
+        interval: NonZeroU32,
+
+        #[structopt(
+            help = "Sample the whole day, from midnight to midnight, rather than just from sunrise to sunset.",
+            long = "full-day"
+        )]
+        full_day: bool,
+
+        #[structopt(long = "json", help = "Output the samples as JSON rather than CSV.")]
+        json: bool,
+    },
+
+    MoonPhases {
+        #[structopt(
+            help = "The year to list the new and full moons of. Defaults to the year of the chosen date.",
+            long = "year"
+        )]
+        year: Option<i32>,
+
+        #[structopt(long = "json", help = "Output the phases as JSON.")]
+        json: bool,
+    },
+
+    DayExtremes {
+        #[structopt(
+            help = "The year to find the longest and shortest days of. Defaults to the year of the chosen date.",
+            long = "year"
+        )]
+        year: Option<i32>,
+
+        #[structopt(
+            long = "json",
+            help = "Output the longest and shortest days as JSON. Day lengths are in seconds."
+        )]
+        json: bool,
+    },
+
+    SeasonBounds {
+        #[structopt(
+            help = "The year to search for the nights of each kind of twilight. Defaults to the year of the chosen date.",
+            long = "year"
+        )]
+        year: Option<i32>,
+
+        #[structopt(
+            long = "json",
+            help = "Output the periods with each kind of night as JSON, with their start and end dates."
+        )]
+        json: bool,
+    },
+
+    NextOccurrence {
+        #[structopt(
+            help = "The event to look for. One of sunrise, sunset, civil_dawn, civil_dusk, nautical_dawn, nautical_dusk, astronomical_dawn or astronomical_dusk, in any case.",
+            short = "e",
+            long = "event",
+            default_value = "sunrise",
+            parse(from_str=parsers::parse_event)
         )]
         event: Result<enums::Event>,
+
+        #[structopt(
+            long = "json",
+            help = "Output the next occurrence as JSON. The time is in RFC 3339 format, or null if the event doesn't occur within a year."
+        )]
+        json: bool,
+    },
+
+    Night {
+        #[structopt(
+            help = "The kind of twilight which marks the start and end of the night.",
+            long = "twilight",
+            default_value = "astronomical",
+            parse(from_str=parsers::parse_twilight_type),
+            possible_values = enums::TWILIGHT_NAMES,
+            case_insensitive = true,
+        )]
+        twilight: Result<enums::TwilightType>,
+
+        #[structopt(
+            long = "json",
+            help = "Output the night as JSON. Times are in RFC 3339 format, or null if the event doesn't occur, and the length is in seconds, or null if the night doesn't both begin and end."
+        )]
+        json: bool,
+    },
+
+    Darkness {
+        #[structopt(
+            long = "json",
+            help = "Output the darkness as JSON. Times are in RFC 3339 format, or null if the event doesn't occur, and lengths are in seconds, or null if the night doesn't both begin and end."
+        )]
+        json: bool,
+    },
+
+    Phase {
+        #[structopt(
+            help = "Also exit with a code for the phase: 0 for day, 2 for civil twilight, 3 for nautical twilight, 4 for astronomical twilight and 5 for night. 1 is still used for errors.",
+            long = "exit-code"
+        )]
+        exit_code: bool,
+    },
+
+    Compare {
+        #[structopt(
+            help = "A location to compare, as a latitude and a longitude in signed decimal degrees, optionally followed by the UTC offset of its time zone, separated by commas, e.g. '40.6413,-73.7781,-04:00'. Without an offset, its times are given in the chosen time zone (see --time-zone). Give this once for each location, each of which is shown in its own column.",
+            long = "location",
+            parse(from_str=parsers::parse_location),
+            number_of_values = 1,
+            required = true,
+            allow_hyphen_values = true,
+        )]
+        locations: Vec<Result<structs::Location>>,
     },
+
+    Flight {
+        #[structopt(
+            help = "Where the flight departs from, as a latitude and a longitude in signed decimal degrees, separated by a comma, e.g. '51.4700,-0.4543'.",
+            long = "from",
+            parse(from_str=parsers::parse_coordinates),
+            allow_hyphen_values = true
+        )]
+        from: Result<structs::Coordinates>,
+
+        #[structopt(
+            help = "Where the flight arrives, in the same format as --from.",
+            long = "to",
+            parse(from_str=parsers::parse_coordinates),
+            allow_hyphen_values = true
+        )]
+        to: Result<structs::Coordinates>,
+
+        #[structopt(
+            help = "When the flight departs, in RFC 3339 format, e.g. 2020-06-21T18:00:00+01:00. Times are given in this time zone.",
+            long = "departure",
+            parse(from_str=parsers::parse_flight_time)
+        )]
+        departure: Result<DateTime<FixedOffset>>,
+
+        #[structopt(
+            help = "When the flight arrives, in RFC 3339 format. This must be after the departure, and no more than 48 hours later.",
+            long = "arrival",
+            parse(from_str=parsers::parse_flight_time)
+        )]
+        arrival: Result<DateTime<FixedOffset>>,
+
+        #[structopt(
+            help = "The number of minutes between each check of the Sun along the route. Each sunrise or sunset found is then given to the second.",
+            long = "step",
+            default_value = "1"
+        )]
+        step: NonZeroU32,
+
+        #[structopt(
+            long = "json",
+            help = "Output the flight as JSON. Times are in RFC 3339 format."
+        )]
+        json: bool,
+    },
+
+    DumpConfig {
+        #[structopt(
+            help = "Write the config to this file, rather than printing it.",
+            long = "output",
+            parse(from_os_str)
+        )]
+        output: Option<PathBuf>,
+    },
+}
+
+impl Subcommand {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Subcommand::Report { .. } => "report",
+            Subcommand::Wait { .. } => "wait",
+            Subcommand::Schedule { .. } => "schedule",
+            Subcommand::Season { .. } => "season",
+            Subcommand::Schema {} => "schema",
+            Subcommand::SunPath { .. } => "sun-path",
+            Subcommand::MoonPhases { .. } => "moon-phases",
+            Subcommand::DayExtremes { .. } => "day-extremes",
+            Subcommand::SeasonBounds { .. } => "season-bounds",
+            Subcommand::NextOccurrence { .. } => "next-occurrence",
+            Subcommand::Night { .. } => "night",
+            Subcommand::Darkness { .. } => "darkness",
+            Subcommand::Phase { .. } => "phase",
+            Subcommand::Compare { .. } => "compare",
+            Subcommand::Flight { .. } => "flight",
+            Subcommand::DumpConfig { .. } => "dump-config",
+        }
+    }
 }
 
 #[derive(Debug, StructOpt)]
@@ -80,17 +865,218 @@ struct DateArgs {
     #[structopt(short = "d", long = "date")]
     date: Option<String>,
 
+    #[structopt(
+        long = "datetime",
+        help = "Set an exact date and time, in the format %Y-%m-%dT%H:%M:%S or %Y-%m-%dT%H:%M, rather than a date. The position of the Sun is given at this time, whilst events are still given for the whole day.",
+        conflicts_with_all = &["date", "end-date", "this-week", "this-month"]
+    )]
+    datetime: Option<String>,
+
+    #[structopt(
+        long = "date-only",
+        help = "Discard the time of day given with --datetime, keeping only its date, which is then taken to be at midday as with --date. Everything, including the position of the Sun and the current time used by --upcoming, --around-now and phase, is then the same whatever the time given."
+    )]
+    date_only: bool,
+
     #[structopt(short = "f", long = "date-format", default_value = "%Y-%m-%d")]
     date_format: String,
 
     #[structopt(short = "t", long = "time-zone", allow_hyphen_values = true)]
     time_zone: Option<String>,
+
+    #[structopt(
+        long = "fixed-offset",
+        help = "Keep the UTC offset in force in the local time zone on the chosen date for all times, rather than following daylight saving changes, e.g. over a range of dates or for wait --at. A time zone set with --time-zone is always fixed."
+    )]
+    fixed_offset: bool,
+
+    #[structopt(
+        long = "end-date",
+        help = "Show the report for every day from the chosen date up to and including this date, in the same format as --date."
+    )]
+    end_date: Option<String>,
+
+    #[structopt(
+        long = "this-week",
+        help = "Show the report for every day of the week containing the chosen date (see --week-start).",
+        conflicts_with_all = &["end-date", "this-month"]
+    )]
+    this_week: bool,
+
+    #[structopt(
+        long = "this-month",
+        help = "Show the report for every day of the month containing the chosen date.",
+        conflicts_with_all = &["end-date", "this-week"]
+    )]
+    this_month: bool,
+
+    #[structopt(
+        long = "week-start",
+        help = "Choose the day on which weeks start for --this-week.",
+        default_value = "monday",
+        possible_values = &["monday", "sunday"]
+    )]
+    week_start: String,
+
+    #[structopt(
+        long = "max-range-days",
+        help = "Refuse a range of dates covering more than this many days, to guard against a mistyped end date producing thousands of reports.",
+        default_value = DEFAULT_MAX_RANGE_DAYS
+    )]
+    max_range_days: NonZeroU32,
 }
 
-#[derive(Debug, Deserialize)]
+// the twilight table comes last, as TOML requires a table's values to come before any tables within it. A
+// setting which isn't one of these, such as a misspelt one, is kept aside so that the rest of the file still
+// applies, and is only reported by --validate and --strict
+#[derive(Debug, Deserialize, Serialize)]
 struct TomlConfig {
     latitude: Option<String>,
     longitude: Option<String>,
+    time_zone: Option<String>,
+    output_format: Option<String>,
+    show_sunrise_sunset: Option<bool>,
+    show_civil: Option<bool>,
+    show_nautical: Option<bool>,
+    show_astronomical: Option<bool>,
+    tz_warning: Option<bool>,
+    twilight: Option<TomlTwilight>,
+    events: Option<BTreeMap<String, TomlEvent>>,
+    #[serde(flatten)]
+    unknown: BTreeMap<String, toml::Value>,
+}
+
+// an event defined in terms of one of the standard events, e.g. 20 minutes after sunrise, or as the Sun rising or
+// setting through an altitude, e.g. 3° below the horizon as it sets
+#[derive(Debug, Deserialize, Serialize)]
+struct TomlEvent {
+    event: Option<String>,
+    elevation: Option<f64>,
+    direction: Option<String>,
+    offset: Option<String>,
+    #[serde(flatten)]
+    unknown: BTreeMap<String, toml::Value>,
+}
+
+impl TomlEvent {
+    fn base(&self, name: &str) -> Result<enums::EventBase> {
+        // either a standard event, or an altitude and a direction, but not both
+        match (&self.event, self.elevation, &self.direction) {
+            (Some(event), None, None) => {
+                Ok(enums::EventBase::Standard(parsers::parse_event(event)?))
+            }
+            (None, Some(elevation), Some(_)) if !(-90.0..=90.0).contains(&elevation) => {
+                Err(HeliocronError::Config(ConfigErrorKind::InvalidAltitude))
+            }
+            (None, Some(elevation), Some(direction)) => Ok(enums::EventBase::Elevation(
+                name.to_string(),
+                elevation,
+                enums::Direction::new(direction)?,
+            )),
+            _ => Err(HeliocronError::Config(ConfigErrorKind::InvalidCustomEvent)),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+struct TomlTwilight {
+    civil: Option<f64>,
+    nautical: Option<f64>,
+    astronomical: Option<f64>,
+    #[serde(flatten)]
+    unknown: BTreeMap<String, toml::Value>,
+}
+
+// the input of --input-json, which is checked field by field rather than deserialised directly, so that an
+// error can say which field is wrong
+#[derive(Debug, PartialEq)]
+struct JsonInput {
+    latitude: f64,
+    longitude: f64,
+    date: Option<String>,
+    time_zone: Option<String>,
+}
+
+impl JsonInput {
+    fn from_json(json: &str) -> Result<JsonInput> {
+        let invalid = |msg: String| HeliocronError::Config(ConfigErrorKind::InvalidInputJson(msg));
+        let value: serde_json::Value = serde_json::from_str(json)
+            .map_err(|err| invalid(format!("not valid JSON ({}).", err)))?;
+        let fields = value.as_object().ok_or_else(|| {
+            invalid(
+                "expected an object, with the fields latitude, longitude, date and time_zone."
+                    .to_string(),
+            )
+        })?;
+
+        if let Some(field) = fields
+            .keys()
+            .find(|field| !["latitude", "longitude", "date", "time_zone"].contains(&field.as_str()))
+        {
+            return Err(invalid(format!(
+                "unknown field '{}'. Choose from: latitude, longitude, date, time_zone.",
+                field
+            )));
+        }
+
+        let number = |field: &str| match fields.get(field) {
+            Some(value) => value.as_f64().ok_or_else(|| {
+                invalid(format!(
+                    "'{}' must be a number of signed decimal degrees.",
+                    field
+                ))
+            }),
+            None => Err(invalid(format!("missing field '{}'.", field))),
+        };
+        let string = |field: &str, expected: &str| match fields.get(field) {
+            None | Some(serde_json::Value::Null) => Ok(None),
+            Some(value) => value
+                .as_str()
+                .map(|value| Some(value.to_string()))
+                .ok_or_else(|| invalid(format!("'{}' must be a string {}.", field, expected))),
+        };
+
+        Ok(JsonInput {
+            latitude: number("latitude")?,
+            longitude: number("longitude")?,
+            date: string("date", "in the format YYYY-MM-DD, e.g. \"2020-06-21\"")?,
+            time_zone: string("time_zone", "holding a UTC offset, e.g. \"+01:00\"")?,
+        })
+    }
+}
+
+fn parse_time_zone(time_zone: &str) -> Result<FixedOffset> {
+    // a UTC offset in the same format as --time-zone, e.g. "+01:00"
+    Ok(*DateTime::parse_from_str(
+        &format!("2000-01-01T00:00:00{}", time_zone),
+        "%Y-%m-%dT%H:%M:%S%:z",
+    )?
+    .offset())
+}
+
+fn validate_toml(contents: &str, path: &Path, mut config: Config) -> Vec<String> {
+    // every problem with a config file, for --validate. The file is merged in the same way as when it is used,
+    // except that every problem is listed rather than stopping at the first. A setting which is of the wrong
+    // type stops the rest from being read
+    let toml_config: TomlConfig = match toml::from_str(contents) {
+        Ok(toml_config) => toml_config,
+        Err(err) => return vec![format!("not a valid config file ({})", err)],
+    };
+    config
+        .apply_toml(
+            toml_config,
+            ConfigSource::ConfigFile(path.to_path_buf()),
+            true,
+        )
+        .into_iter()
+        .map(|(key, err)| {
+            format!(
+                "{}: {}",
+                key,
+                err.to_string().trim_start_matches("Config error: ")
+            )
+        })
+        .collect()
 }
 
 impl TomlConfig {
@@ -98,13 +1084,31 @@ impl TomlConfig {
         TomlConfig {
             latitude: None,
             longitude: None,
+            time_zone: None,
+            twilight: None,
+            output_format: None,
+            show_sunrise_sunset: None,
+            show_civil: None,
+            show_nautical: None,
+            show_astronomical: None,
+            tz_warning: None,
+            events: None,
+            unknown: BTreeMap::new(),
         }
     }
 
-    fn from_toml(config: result::Result<TomlConfig, toml::de::Error>) -> TomlConfig {
+    fn from_toml(
+        config: result::Result<TomlConfig, toml::de::Error>,
+        path: &Path,
+        strict: bool,
+    ) -> Result<TomlConfig> {
         match config {
-            Ok(conf) => conf,
-            _ => TomlConfig::new(),
+            Ok(conf) => Ok(conf),
+            // a file which isn't valid TOML is skipped, unless we are being strict
+            Err(_) if strict => Err(HeliocronError::Config(ConfigErrorKind::InvalidTomlFile(
+                path.display().to_string(),
+            ))),
+            Err(_) => Ok(TomlConfig::new()),
         }
     }
 }
@@ -113,14 +1117,590 @@ impl TomlConfig {
 pub struct Config {
     pub coordinates: structs::Coordinates,
     pub date: DateTime<FixedOffset>,
+    pub end_date: Option<DateTime<FixedOffset>>,
+    pub time_zone: Option<String>,
+    pub fixed_offset: bool,
     pub subcommand: Option<Subcommand>,
+    pub guard: Option<structs::LightGuard>,
     pub event: Option<enums::Event>,
+    pub tz_warning: bool,
+    pub warnings_json: bool,
+    pub verbose: bool,
+    pub hide_location: bool,
+    pub decimal_hours: bool,
+    pub day_fraction: bool,
+    pub solar_time: bool,
+    pub relative_to_noon: bool,
+    pub time_only: bool,
+    pub with_accuracy: bool,
+    pub locale: Option<enums::Locale>,
+    pub compact_json: bool,
+    pub output_format: enums::OutputFormat,
+    pub report_sections: structs::ReportSections,
+    pub horizon: Option<structs::HorizonProfile>,
+    // the options given on the command line which change the times of events, so that schedule can pass them on
+    // to the wait in its crontab line
+    pub config_files: Vec<PathBuf>,
+    pub pressure: Option<f64>,
+    pub temperature: Option<f64>,
+    pub horizon_file: Option<PathBuf>,
+    pub coordinates_source: ConfigSource,
+    // whether the coordinates are the centre of a bounding box, rather than the place itself
+    pub from_bounding_box: bool,
+    pub date_source: ConfigSource,
+    pub time_zone_source: ConfigSource,
+    pub twilight_angles: structs::TwilightAngles,
+    pub twilight_angles_source: ConfigSource,
+    pub custom_events: BTreeMap<String, (enums::EventBase, Duration)>,
+    pub now: DateTime<FixedOffset>,
+    pub dump_defaults: bool,
+    pub check_date: bool,
+    // the problems found in each config file, if they were only to be checked, with --validate
+    pub validation: Option<Vec<(PathBuf, Vec<String>)>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConfigSource {
+    Default,
+    ConfigFile(PathBuf),
+    InputJson(PathBuf),
+    CommandLine,
+}
+
+impl fmt::Display for ConfigSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigSource::Default => write!(f, "default"),
+            ConfigSource::ConfigFile(path) => write!(f, "config file {}", path.display()),
+            ConfigSource::CommandLine => write!(f, "command line"),
+            ConfigSource::InputJson(path) => write!(f, "JSON input {}", path.display()),
+        }
+    }
 }
 
 impl Config {
-    fn merge_toml(mut self, toml_config: TomlConfig) -> Result<Config> {
-        if let (Some(latitude), Some(longitude)) = (toml_config.latitude, toml_config.longitude) {
-            self.coordinates = structs::Coordinates::from_decimal_degrees(&latitude, &longitude)?
+    pub fn defaults(now: DateTime<FixedOffset>) -> Result<Config> {
+        // the configuration before any config file or the command line is applied, which is also what
+        // --dump-defaults prints
+        Ok(Config {
+            coordinates: structs::Coordinates::from_decimal_degrees(
+                DEFAULT_LATITUDE,
+                DEFAULT_LONGITUDE,
+            )?,
+            // midday today, with the offset in force at midday rather than right now, which differs on the day
+            // that the clocks change
+            date: {
+                let midday = now.with_timezone(&Local).date().and_hms(12, 0, 0);
+                midday.with_timezone(midday.offset())
+            },
+            end_date: None,
+            time_zone: None,
+            fixed_offset: false,
+            subcommand: None,
+            guard: None,
+            event: None,
+            tz_warning: false,
+            warnings_json: false,
+            verbose: false,
+            hide_location: false,
+            decimal_hours: false,
+            day_fraction: false,
+            solar_time: false,
+            relative_to_noon: false,
+            time_only: false,
+            with_accuracy: false,
+            locale: None,
+            compact_json: false,
+            output_format: enums::OutputFormat::Plain,
+            report_sections: structs::ReportSections::default(),
+            horizon: None,
+            config_files: vec![],
+            pressure: None,
+            temperature: None,
+            horizon_file: None,
+            coordinates_source: ConfigSource::Default,
+            from_bounding_box: false,
+            date_source: ConfigSource::Default,
+            time_zone_source: ConfigSource::Default,
+            twilight_angles: structs::TwilightAngles::default(),
+            twilight_angles_source: ConfigSource::Default,
+            custom_events: BTreeMap::new(),
+            now,
+            dump_defaults: false,
+            check_date: false,
+            validation: None,
+        })
+    }
+
+    pub fn describe(&self) -> String {
+        // the resolved configuration, for --verbose
+        let time_zone_source = match self.time_zone_source {
+            ConfigSource::Default => "default, the local time zone".to_string(),
+            ref source => source.to_string(),
+        };
+        let mut description = format!(
+            "CONFIGURATION\n\
+            -------------\n\
+            {}, {} ({})\n\
+            Date and time: {} ({})\n\
+            Time zone: {} ({})\n",
+            self.coordinates.latitude,
+            self.coordinates.longitude,
+            self.coordinates_source,
+            self.date.format("%Y-%m-%d %H:%M:%S"),
+            self.date_source,
+            self.date.offset(),
+            time_zone_source,
+        );
+        description.push_str(&format!(
+            "Twilight angles: {} ({})\n",
+            self.twilight_angles, self.twilight_angles_source
+        ));
+        if let Some(end_date) = self.end_date {
+            description.push_str(&format!(
+                "End date: {} ({})\n",
+                end_date.format("%Y-%m-%d"),
+                self.date_source
+            ));
+        }
+        if let Some(subcommand) = &self.subcommand {
+            description.push_str(&format!("Subcommand: {}\n", subcommand.name()));
+        }
+        description
+    }
+
+    pub fn to_toml(&self) -> String {
+        // the settings which can be made in a config file, as they have been resolved from the defaults, any
+        // config files and the command line, ready to be saved as heliocron.toml
+        let (latitude, longitude) = self.coordinates.to_decimal_degrees();
+        let toml_config = TomlConfig {
+            latitude: Some(latitude),
+            longitude: Some(longitude),
+            // only a time zone which has been chosen, as the local one is otherwise followed
+            time_zone: self.time_zone.clone(),
+            output_format: Some(self.output_format.to_string()),
+            // only the pairs of events which have been hidden, as they are otherwise shown
+            show_sunrise_sunset: if self.report_sections.sunrise_sunset {
+                None
+            } else {
+                Some(false)
+            },
+            show_civil: if self.report_sections.civil {
+                None
+            } else {
+                Some(false)
+            },
+            show_nautical: if self.report_sections.nautical {
+                None
+            } else {
+                Some(false)
+            },
+            show_astronomical: if self.report_sections.astronomical {
+                None
+            } else {
+                Some(false)
+            },
+            // only a warning which has been turned on, as it is otherwise off
+            tz_warning: if self.tz_warning { Some(true) } else { None },
+            twilight: Some(TomlTwilight {
+                civil: Some(self.twilight_angles.civil),
+                nautical: Some(self.twilight_angles.nautical),
+                astronomical: Some(self.twilight_angles.astronomical),
+                unknown: BTreeMap::new(),
+            }),
+            events: if self.custom_events.is_empty() {
+                None
+            } else {
+                Some(
+                    self.custom_events
+                        .iter()
+                        .map(|(name, (base, offset))| {
+                            let (event, elevation, direction) = match base {
+                                enums::EventBase::Standard(event) => {
+                                    (Some(event.to_string()), None, None)
+                                }
+                                enums::EventBase::Elevation(_, elevation, direction) => {
+                                    (None, Some(*elevation), Some(direction.to_string()))
+                                }
+                            };
+                            (
+                                name.clone(),
+                                TomlEvent {
+                                    event,
+                                    elevation,
+                                    direction,
+                                    offset: Some(utils::format_offset(*offset)),
+                                    unknown: BTreeMap::new(),
+                                },
+                            )
+                        })
+                        .collect(),
+                )
+            },
+            unknown: BTreeMap::new(),
+        };
+        // strings and numbers can always be written as TOML
+        toml::to_string(&toml_config).unwrap()
+    }
+
+    pub fn standard_event<T>(&self, parsed: Result<T>, usage: &'static str) -> Result<T> {
+        // for the options which only take the standard events, naming an event from the config file says so,
+        // rather than that it is unknown
+        match parsed {
+            Err(HeliocronError::Config(ConfigErrorKind::UnknownEvent(ref name, _)))
+                if self.custom_events.contains_key(&name.trim().to_lowercase()) =>
+            {
+                Err(HeliocronError::Config(
+                    ConfigErrorKind::UnsupportedCustomEvent(usage),
+                ))
+            }
+            parsed => parsed,
+        }
+    }
+
+    pub fn resolve_event(
+        &self,
+        event: Result<enums::Event>,
+    ) -> Result<(enums::EventBase, Duration)> {
+        // a standard event as it is, or an event from the config file as what it is based on and its offset. An
+        // unknown name is still an error
+        match event {
+            Ok(event) => Ok((enums::EventBase::Standard(event), Duration::zero())),
+            Err(HeliocronError::Config(ConfigErrorKind::UnknownEvent(name, suggestion))) => self
+                .custom_events
+                .get(&name.trim().to_lowercase())
+                .cloned()
+                .ok_or(HeliocronError::Config(ConfigErrorKind::UnknownEvent(
+                    name, suggestion,
+                ))),
+            Err(err) => Err(err),
+        }
+    }
+
+    pub fn dates(&self) -> Result<Vec<DateTime<FixedOffset>>> {
+        // every date to report on, which is just the chosen date unless a date range was given
+        let end_date = match self.end_date {
+            Some(end_date) => end_date,
+            None => return Ok(vec![self.date]),
+        };
+
+        self.dates_between(
+            self.date.naive_local().date(),
+            end_date.naive_local().date(),
+        )
+    }
+
+    pub fn dates_in_year(&self, year: i32) -> Result<Vec<DateTime<FixedOffset>>> {
+        if !(solar::EARLIEST_YEAR..=solar::LATEST_YEAR).contains(&year) {
+            return Err(HeliocronError::Config(ConfigErrorKind::UnsupportedDate));
+        }
+        self.dates_between(
+            NaiveDate::from_ymd(year, 1, 1),
+            NaiveDate::from_ymd(year, 12, 31),
+        )
+    }
+
+    pub fn dates_from_date(&self, days: u32) -> Result<Vec<DateTime<FixedOffset>>> {
+        // this many days, starting with the chosen date
+        let start = self.date.naive_local().date();
+        self.dates_between(start, start + Duration::days(days as i64 - 1))
+    }
+
+    fn dates_between(
+        &self,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> Result<Vec<DateTime<FixedOffset>>> {
+        let mut dates = vec![];
+        let mut date = start;
+        while date <= end {
+            dates.push(self.date_at_noon(date)?);
+            date = date.succ();
+        }
+        Ok(dates)
+    }
+
+    pub fn following_date(&self, date: DateTime<FixedOffset>) -> Result<DateTime<FixedOffset>> {
+        // the day after one of the dates, with its own offset in the same way as the dates themselves
+        self.date_at_noon(date.naive_local().date().succ())
+    }
+
+    pub fn moment(&self) -> DateTime<FixedOffset> {
+        // the date and time chosen on the command line or in the JSON input, otherwise right now, in the chosen
+        // time zone
+        match self.date_source {
+            ConfigSource::CommandLine | ConfigSource::InputJson(_) => self.date,
+            _ => self.now.with_timezone(self.date.offset()),
+        }
+    }
+
+    pub fn preceding_date(&self, date: DateTime<FixedOffset>) -> Result<DateTime<FixedOffset>> {
+        self.date_at_noon(date.naive_local().date().pred())
+    }
+
+    pub fn date_at_noon(&self, date: NaiveDate) -> Result<DateTime<FixedOffset>> {
+        // the offset is worked out afresh for each date, so that a local time zone follows daylight saving,
+        // unless it has been fixed to that of the chosen date
+        if self.fixed_offset {
+            return Ok(self
+                .date
+                .offset()
+                .from_local_datetime(&date.and_hms(12, 0, 0))
+                .unwrap());
+        }
+        parsers::parse_date(
+            &date.format("%Y-%m-%d").to_string(),
+            "%Y-%m-%d",
+            self.time_zone.as_deref(),
+        )
+    }
+
+    pub fn time_zone_warning(&self) -> Option<structs::Warning> {
+        // without a time zone database, the best guess at the location's own time zone is the nautical time
+        // zone for its longitude. A chosen offset more than an hour away from that is a likely mistake, such as
+        // calculating events for Tokyo whilst leaving the time zone as the local one
+        if !self.tz_warning {
+            return None;
+        }
+
+        let chosen = *self.date.offset();
+        let nominal = self.coordinates.longitude.nominal_offset();
+
+        if (chosen.local_minus_utc() - nominal.local_minus_utc()).abs() > 3600 {
+            Some(structs::Warning {
+                code: "time_zone_mismatch",
+                message: format!(
+                    "the chosen time zone ({}) differs from the nominal time zone at this location ({}) \
+                     by more than an hour.",
+                    chosen, nominal
+                ),
+            })
+        } else {
+            None
+        }
+    }
+
+    pub fn bounding_box_warning(&self) -> Option<structs::Warning> {
+        // the events move across a region, by around four minutes for each degree of longitude, so the centre's
+        // times are only a guide to the rest of it
+        if !self.from_bounding_box {
+            return None;
+        }
+
+        let (latitude, longitude) = self.coordinates.to_decimal_degrees();
+        Some(structs::Warning {
+            code: "bounding_box_centre",
+            message: format!(
+                "the times are for the centre of the bounding box ({} {}), so are only approximate \
+                 for the rest of it.",
+                latitude, longitude
+            ),
+        })
+    }
+
+    fn merge_toml(
+        mut self,
+        toml_config: TomlConfig,
+        source: ConfigSource,
+        strict: bool,
+    ) -> Result<Config> {
+        let path = match source {
+            ConfigSource::ConfigFile(ref path) => path.display().to_string(),
+            _ => String::new(),
+        };
+        match self
+            .apply_toml(toml_config, source, strict)
+            .into_iter()
+            .next()
+        {
+            None => Ok(self),
+            // half a location says so, and any other problem is reported against the file as a whole
+            Some((_, err @ HeliocronError::Config(ConfigErrorKind::Strict(_)))) => Err(err),
+            Some(_) => Err(HeliocronError::Config(ConfigErrorKind::InvalidTomlFile(
+                path,
+            ))),
+        }
+    }
+
+    fn apply_toml(
+        &mut self,
+        toml_config: TomlConfig,
+        source: ConfigSource,
+        strict: bool,
+    ) -> Vec<(String, HeliocronError)> {
+        // each setting which is valid is merged in, and the rest are returned along with the key they are
+        // under, so that --validate can list them all
+        let mut problems = vec![];
+
+        // a setting which isn't known, such as a misspelt one, is ignored, unless we are being strict
+        let mut unknown = |prefix: &str, settings: &BTreeMap<String, toml::Value>| {
+            if strict {
+                for key in settings.keys() {
+                    problems.push((
+                        format!("{}{}", prefix, key),
+                        HeliocronError::Config(ConfigErrorKind::UnknownSetting),
+                    ));
+                }
+            }
+        };
+        unknown("", &toml_config.unknown);
+        if let Some(ref twilight) = toml_config.twilight {
+            unknown("twilight.", &twilight.unknown);
+        }
+        if let Some(ref events) = toml_config.events {
+            for (key, event) in events {
+                unknown(&format!("events.{}.", key), &event.unknown);
+            }
+        }
+
+        match (toml_config.latitude, toml_config.longitude) {
+            (Some(latitude), Some(longitude)) => {
+                match structs::Coordinates::from_decimal_degrees(&latitude, &longitude) {
+                    Ok(coordinates) => {
+                        self.coordinates = coordinates;
+                        self.coordinates_source = source.clone();
+                    }
+                    Err(err) => problems.push(("latitude, longitude".to_string(), err)),
+                }
+            }
+            (None, None) => (),
+            // half a location is ignored, unless we are being strict
+            _ if strict => problems.push((
+                "latitude, longitude".to_string(),
+                HeliocronError::Config(ConfigErrorKind::Strict(
+                    "both latitude and longitude must be set in the config file, or neither",
+                )),
+            )),
+            _ => (),
+        }
+
+        // as with --time-zone, a time zone on its own applies to today's date, and is overridden in turn by
+        // the JSON input and the command line
+        if let Some(time_zone) = toml_config.time_zone {
+            let date = parse_time_zone(&time_zone)
+                .map_err(|_| HeliocronError::Config(ConfigErrorKind::InvalidTimeZone))
+                .and_then(|_| {
+                    parsers::parse_date(
+                        &self.date.format("%Y-%m-%d").to_string(),
+                        "%Y-%m-%d",
+                        Some(&time_zone),
+                    )
+                });
+            match date {
+                Ok(date) => {
+                    self.date = date;
+                    self.time_zone = Some(time_zone);
+                    self.time_zone_source = source.clone();
+                }
+                Err(err) => problems.push(("time_zone".to_string(), err)),
+            }
+        }
+
+        // any of the twilight angles may be overridden, but they must be altitudes
+        if let Some(twilight) = toml_config.twilight {
+            let defaults = self.twilight_angles;
+            let mut angle = |name: &str, angle: Option<f64>, default: f64| match angle {
+                Some(angle) if !(-90.0..=90.0).contains(&angle) => {
+                    problems.push((
+                        format!("twilight.{}", name),
+                        HeliocronError::Config(ConfigErrorKind::InvalidAltitude),
+                    ));
+                    default
+                }
+                angle => angle.unwrap_or(default),
+            };
+            self.twilight_angles = structs::TwilightAngles {
+                civil: angle("civil", twilight.civil, defaults.civil),
+                nautical: angle("nautical", twilight.nautical, defaults.nautical),
+                astronomical: angle("astronomical", twilight.astronomical, defaults.astronomical),
+                ..defaults
+            };
+            self.twilight_angles_source = source.clone();
+        }
+
+        if let Some(format) = toml_config.output_format {
+            match enums::OutputFormat::new(&format) {
+                Ok(format) => self.output_format = format,
+                Err(err) => problems.push(("output_format".to_string(), err)),
+            }
+        }
+
+        // the pairs of events in the plain report, each of which a later file can turn back on
+        let sections = &mut self.report_sections;
+        sections.sunrise_sunset = toml_config
+            .show_sunrise_sunset
+            .unwrap_or(sections.sunrise_sunset);
+        sections.civil = toml_config.show_civil.unwrap_or(sections.civil);
+        sections.nautical = toml_config.show_nautical.unwrap_or(sections.nautical);
+        sections.astronomical = toml_config
+            .show_astronomical
+            .unwrap_or(sections.astronomical);
+
+        // the time zone warning is a guess, so it is off unless asked for
+        self.tz_warning = toml_config.tz_warning.unwrap_or(self.tz_warning);
+
+        // named events, which must be based on one of the standard events or an altitude, and can't take the
+        // standard events' names. An event defined again in a later file replaces the earlier definition
+        for (key, event) in toml_config.events.unwrap_or_default() {
+            let name = key.trim().to_lowercase();
+            let key = format!("events.{}", key);
+            if enums::EVENT_NAMES.contains(&name.as_str()) {
+                problems.push((
+                    key,
+                    HeliocronError::Config(ConfigErrorKind::ReservedEventName(name)),
+                ));
+                continue;
+            }
+            let base = event
+                .base(&name)
+                .map_err(|err| problems.push((key.clone(), err)));
+            let offset = match event.offset {
+                Some(ref offset) => parsers::parse_offset(offset)
+                    .map_err(|err| problems.push((format!("{}.offset", key), err))),
+                None => Ok(Duration::zero()),
+            };
+            if let (Ok(base), Ok(offset)) = (base, offset) {
+                self.custom_events.insert(name, (base, offset));
+            }
+        }
+
+        problems
+    }
+
+    fn merge_json_input(mut self, json_input: JsonInput, source: ConfigSource) -> Result<Config> {
+        let invalid =
+            |msg: &str| HeliocronError::Config(ConfigErrorKind::InvalidInputJson(msg.to_string()));
+        self.coordinates = structs::Coordinates::from_signed_decimal_degrees(
+            json_input.latitude,
+            json_input.longitude,
+        )
+        .map_err(|_| {
+            invalid("'latitude' must be between -90 and 90, and 'longitude' between -180 and 180.")
+        })?;
+        self.coordinates_source = source.clone();
+
+        if let Some(ref time_zone) = json_input.time_zone {
+            parse_time_zone(time_zone)
+                .map_err(|_| invalid("'time_zone' must be a UTC offset, e.g. \"+01:00\"."))?;
+        }
+
+        // as with --date and --time-zone, a time zone on its own applies to today's date
+        if json_input.date.is_some() || json_input.time_zone.is_some() {
+            let date = match json_input.date {
+                Some(ref date) => date.clone(),
+                None => self.date.format("%Y-%m-%d").to_string(),
+            };
+            self.date = parsers::parse_date(&date, "%Y-%m-%d", json_input.time_zone.as_deref())
+                .map_err(|_| {
+                    invalid("'date' must be in the format YYYY-MM-DD, e.g. \"2020-06-21\".")
+                })?;
+        }
+        if json_input.date.is_some() {
+            self.date_source = source.clone();
+        }
+        if json_input.time_zone.is_some() {
+            self.time_zone = json_input.time_zone;
+            self.time_zone_source = source;
         }
         Ok(self)
     }
@@ -128,67 +1708,305 @@ impl Config {
     fn merge_cli_args(mut self, cli_args: Cli) -> Result<Config> {
         // merge in location if set. Structopt requires either both or neither of lat and long to be set
         if let (Some(latitude), Some(longitude)) = (cli_args.latitude, cli_args.longitude) {
-            self.coordinates = structs::Coordinates::from_decimal_degrees(&latitude, &longitude)?
+            self.coordinates = structs::Coordinates::from_decimal_degrees(&latitude, &longitude)?;
+            self.coordinates_source = ConfigSource::CommandLine;
+        }
+        if let Some(city) = cli_args.city {
+            self.coordinates = cities::find_city(&city)?.coordinates()?;
+            self.coordinates_source = ConfigSource::CommandLine;
+        }
+        if let Some(coords) = cli_args.coords {
+            self.coordinates = parsers::parse_coordinates(&coords)?;
+            self.coordinates_source = ConfigSource::CommandLine;
+        }
+        if let Some(bbox) = cli_args.bbox {
+            self.coordinates = parsers::parse_bounding_box(&bbox)?;
+            self.coordinates_source = ConfigSource::CommandLine;
+            self.from_bounding_box = true;
         }
 
-        // set the date
+        // set the date. A time zone on its own still applies, to today's date, and without one, any time zone
+        // from the JSON input still applies to the date
         let date_args = cli_args.date_args;
-        if let Some(date) = date_args.date {
+        let time_zone = date_args
+            .time_zone
+            .clone()
+            .or_else(|| self.time_zone.clone());
+        if date_args.date.is_some() || date_args.time_zone.is_some() {
+            let date = match date_args.date {
+                Some(ref date) => date.clone(),
+                None => self.date.format(&date_args.date_format).to_string(),
+            };
+            self.date = parsers::parse_date(&date, &date_args.date_format, time_zone.as_deref())?;
+        }
+        if let Some(ref datetime) = date_args.datetime {
+            self.date = parsers::parse_datetime(datetime, time_zone.as_deref())?;
+        }
+        if date_args.date_only {
             self.date = parsers::parse_date(
-                &date,
-                &date_args.date_format,
-                date_args.time_zone.as_deref(),
+                &self.date.format("%Y-%m-%d").to_string(),
+                "%Y-%m-%d",
+                time_zone.as_deref(),
             )?;
         }
+        if date_args.date.is_some() || date_args.datetime.is_some() {
+            self.date_source = ConfigSource::CommandLine;
+        }
+        if date_args.time_zone.is_some() {
+            self.time_zone_source = ConfigSource::CommandLine;
+        }
+
+        // set the date range, if any. Every day in the range is at noon in the chosen time zone
+        let date = self.date.naive_local().date();
+        let range = if let Some(end_date) = date_args.end_date {
+            let end_date =
+                parsers::parse_date(&end_date, &date_args.date_format, time_zone.as_deref())?;
+            Some((date, end_date.naive_local().date()))
+        } else if date_args.this_week {
+            let days_into_week = match date_args.week_start.as_str() {
+                "sunday" => date.weekday().num_days_from_sunday(),
+                _ => date.weekday().num_days_from_monday(),
+            };
+            let start = date - Duration::days(days_into_week as i64);
+            Some((start, start + Duration::days(6)))
+        } else if date_args.this_month {
+            let start = date.with_day(1).unwrap();
+            let next_month = if date.month() == 12 {
+                NaiveDate::from_ymd(date.year() + 1, 1, 1)
+            } else {
+                NaiveDate::from_ymd(date.year(), date.month() + 1, 1)
+            };
+            Some((start, next_month.pred()))
+        } else {
+            None
+        };
+
+        self.time_zone = time_zone;
+        self.fixed_offset = date_args.fixed_offset;
 
-        // set the subcommand to execute
-        self.subcommand = Some(cli_args.subcommand);
+        if let Some((start, end)) = range {
+            if end < start {
+                return Err(HeliocronError::Config(ConfigErrorKind::InvalidDateRange(
+                    "The end date must not be before the start date.",
+                )));
+            }
+            if (end - start).num_days() >= i64::from(date_args.max_range_days.get()) {
+                return Err(HeliocronError::Config(ConfigErrorKind::InvalidDateRange(
+                    "The range covers more days than allowed by --max-range-days.",
+                )));
+            }
+            // the range itself can be checked without a subcommand
+            let supports_range = match cli_args.subcommand {
+                Some(Subcommand::Report { .. })
+                | Some(Subcommand::Night { .. })
+                | Some(Subcommand::Darkness { .. }) => true,
+                _ => false,
+            };
+            if !cli_args.check_date && !supports_range {
+                return Err(HeliocronError::Config(ConfigErrorKind::InvalidDateRange(
+                    "Only the report, night and darkness subcommands support a range of dates.",
+                )));
+            }
+            self.date = self.date_at_noon(start)?;
+            self.end_date = Some(self.date_at_noon(end)?);
+            self.date_source = ConfigSource::CommandLine;
+        }
+
+        // refuse to silently give inaccurate results
+        let end_date = self.end_date.unwrap_or(self.date);
+        if !solar::is_supported_date(&self.date) || !solar::is_supported_date(&end_date) {
+            return Err(HeliocronError::Config(ConfigErrorKind::UnsupportedDate));
+        }
+
+        // either of the pressure and temperature may be given on its own, with the other at its standard value
+        let pressure = cli_args.pressure.transpose()?;
+        let temperature = cli_args.temperature.transpose()?;
+        if pressure.is_some() || temperature.is_some() {
+            self.twilight_angles = self.twilight_angles.with_atmosphere(
+                pressure.unwrap_or(structs::STANDARD_PRESSURE),
+                temperature.unwrap_or(structs::STANDARD_TEMPERATURE),
+            );
+            self.twilight_angles_source = ConfigSource::CommandLine;
+        }
+        self.pressure = pressure;
+        self.temperature = temperature;
+
+        if let Some(ref path) = cli_args.horizon_file {
+            let profile = fs::read_to_string(path)
+                .map_err(|_| HeliocronError::Config(ConfigErrorKind::UnreadableHorizonFile))?;
+            self.horizon = Some(parsers::parse_horizon_profile(&profile)?);
+        }
+        self.horizon_file = cli_args.horizon_file.clone();
+
+        if cli_args.tz_warning {
+            self.tz_warning = true;
+        } else if cli_args.no_tz_warning {
+            self.tz_warning = false;
+        }
+        self.warnings_json = cli_args.warnings_json;
+        self.verbose = cli_args.verbose;
+        self.hide_location = cli_args.no_location;
+        self.decimal_hours = cli_args.decimal_hours;
+        self.day_fraction = cli_args.day_fraction;
+        self.solar_time = cli_args.solar_time;
+        self.relative_to_noon = cli_args.relative_to_noon;
+        self.time_only = cli_args.time_only;
+        self.with_accuracy = cli_args.with_accuracy;
+        self.locale = cli_args.locale.transpose()?;
+        self.compact_json = cli_args.compact_json;
+
+        if cli_args.fail_if_dark || cli_args.fail_if_light {
+            self.guard = Some(structs::LightGuard {
+                require_light: cli_args.fail_if_dark,
+                twilight: cli_args.guard_twilight.transpose()?,
+            });
+        }
+
+        // set the subcommand to execute, which is required unless there is a guard to check, or only the
+        // defaults or the date are wanted
+        self.dump_defaults = cli_args.dump_defaults;
+        self.check_date = cli_args.check_date;
+        if cli_args.subcommand.is_none()
+            && self.guard.is_none()
+            && !self.dump_defaults
+            && !self.check_date
+        {
+            return Err(HeliocronError::Config(ConfigErrorKind::MissingSubcommand));
+        }
+        self.subcommand = cli_args.subcommand;
 
         Ok(self)
     }
 }
 
+fn resolve_now(now: Option<&str>) -> Result<DateTime<FixedOffset>> {
+    // --now, then HELIOCRON_NOW, and only then the system clock. An invalid value is an error rather than being
+    // ignored, so that a typo can't silently fall back to the real time
+    let (now, source) = match (now, env::var("HELIOCRON_NOW")) {
+        (Some(now), _) => (now.to_string(), "--now"),
+        (None, Ok(now)) if !now.trim().is_empty() => (now, "HELIOCRON_NOW"),
+        (None, Err(env::VarError::NotUnicode(_))) => {
+            return Err(HeliocronError::Config(ConfigErrorKind::InvalidNow(
+                "HELIOCRON_NOW",
+            )))
+        }
+        (None, _) => {
+            let now = Local::now();
+            return Ok(now.with_timezone(now.offset()));
+        }
+    };
+
+    DateTime::parse_from_rfc3339(now.trim())
+        .map_err(|_| HeliocronError::Config(ConfigErrorKind::InvalidNow(source)))
+}
+
 pub fn get_config() -> Result<Config> {
     // master function for collecting all config variables and returning a single runtime configuration
 
+    // the CLI arguments are needed up front, as they may point at a particular config file, and may set the
+    // current time which today's date is taken from
+    let cli_args = Cli::from_args();
+    let now = resolve_now(cli_args.now.as_deref())?;
+
     // 0. Set up default config
-    let default_config = Config {
-        coordinates: structs::Coordinates::from_decimal_degrees("51.4769N", "0.0005W")?,
-        date: Local::today()
-            .and_hms(12, 0, 0)
-            .with_timezone(&FixedOffset::from_offset(Local::today().offset())),
-        subcommand: None,
-        event: None,
+    let default_config = Config::defaults(now)?;
+
+    // 1. Overwrite defaults with config from TOML files if present. Paths passed with --config are always
+    // used, layered in order, otherwise look for heliocron.toml in the user's config directory, if there is
+    // such a thing on this platform. If we are running integration tests, we actually just want to use the
+    // default config
+    let explicit = !cli_args.config.is_empty();
+    let paths: Vec<PathBuf> = if explicit {
+        cli_args.config.clone()
+    } else if cfg!(feature = "integration-test") {
+        vec![]
+    } else {
+        dirs::config_dir()
+            .map(|dir| dir.join(Path::new("heliocron.toml")))
+            .into_iter()
+            .collect()
     };
 
-    // 1. Overwrite defaults with config from ~/.config/heliocron.toml if present
+    // only checking the config files means reporting every problem in them, rather than stopping at the first.
+    // A default config file which doesn't exist is skipped, as it would be otherwise
+    if cli_args.validate {
+        let mut validation = vec![];
+        for path in paths {
+            let problems = match fs::read_to_string(&path) {
+                Ok(contents) => validate_toml(&contents, &path, Config::defaults(now)?),
+                Err(ref err) if !explicit && err.kind() == io::ErrorKind::NotFound => continue,
+                Err(_) => vec!["the file could not be read".to_string()],
+            };
+            validation.push((path, problems));
+        }
+        return Ok(Config {
+            validation: Some(validation),
+            ..default_config
+        });
+    }
 
-    let config: Config = if cfg!(feature = "integration-test") {
-        default_config
-    } else {
-        let path = dirs::config_dir()
-            .unwrap() // this shouldn't ever really be None?
-            .join(Path::new("heliocron.toml"));
+    let strict = cli_args.strict;
 
-        let file = fs::read_to_string(path);
+    let mut config = default_config;
+    if explicit {
+        config.config_files = paths.clone();
+    }
+    let mut toml_location = false;
+    for path in paths {
+        let unreadable = || {
+            HeliocronError::Config(ConfigErrorKind::UnreadableConfigFile(
+                path.display().to_string(),
+            ))
+        };
+        let toml_config = match fs::read_to_string(&path) {
+            Ok(f) => TomlConfig::from_toml(toml::from_str(&f), &path, strict)?,
+            // a config file that was explicitly asked for must exist
+            Err(_) if explicit => return Err(unreadable()),
+            // as must one which exists, but can't be read, if we are being strict
+            Err(ref err) if strict && err.kind() != io::ErrorKind::NotFound => {
+                return Err(unreadable())
+            }
+            // any other problems opening the .toml file and we just continue on with the default configuration
+            _ => TomlConfig::new(),
+        };
 
-        let config: Config = match file {
-            Ok(f) => match default_config.merge_toml(TomlConfig::from_toml(toml::from_str(&f))) {
-                Ok(merged_config) => Ok(merged_config),
-                // any errors parsing the .toml raise an error
-                Err(_) => Err(HeliocronError::Config(ConfigErrorKind::InvalidTomlFile)),
-            },
-            // any problems opening the .toml file and we just continue on with the default configuration
-            Err(_) => Ok(default_config),
-        }?;
+        toml_location |= toml_config.latitude.is_some() && toml_config.longitude.is_some();
+        config = config.merge_toml(toml_config, ConfigSource::ConfigFile(path), strict)?;
+    }
 
-        config
-    };
-    // if we are running integration tests, we actually just want to use the default config
+    // 2. Overwrite with the JSON input, if given, which always includes a location
+    if let Some(ref path) = cli_args.input_json {
+        let json = if path == Path::new("-") {
+            let mut json = String::new();
+            io::Read::read_to_string(&mut io::stdin(), &mut json).map(|_| json)
+        } else {
+            fs::read_to_string(path)
+        }
+        .map_err(|_| {
+            HeliocronError::Config(ConfigErrorKind::InvalidInputJson(format!(
+                "could not read {}.",
+                path.display()
+            )))
+        })?;
+        config = config.merge_json_input(
+            JsonInput::from_json(&json)?,
+            ConfigSource::InputJson(path.clone()),
+        )?;
+    }
 
-    // 2. Overwrite any currently set config with CLI arguments
-    let cli_args = Cli::from_args();
+    // being strict means never falling back to the default location
+    let cli_location = cli_args.latitude.is_some()
+        || cli_args.city.is_some()
+        || cli_args.coords.is_some()
+        || cli_args.bbox.is_some()
+        || cli_args.input_json.is_some();
+    if strict && !toml_location && !cli_location {
+        return Err(HeliocronError::Config(ConfigErrorKind::Strict(
+            "no location was set, either in a config file or on the command line",
+        )));
+    }
 
+    // 3. Overwrite any currently set config with CLI arguments
     let config = config.merge_cli_args(cli_args)?;
 
     Ok(config)
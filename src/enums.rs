@@ -1,10 +1,25 @@
-use std::result;
+use std::{fmt, result};
+
+use chrono::{Datelike, Weekday};
+use serde::Serialize;
 
 use super::errors::{ConfigErrorKind, HeliocronError};
 
 type Result<T> = result::Result<T, HeliocronError>;
 
-#[derive(Debug, PartialEq)]
+pub const EVENT_NAMES: &[&str] = &[
+    "sunrise",
+    "sunset",
+    "civil_dawn",
+    "civil_dusk",
+    "nautical_dawn",
+    "nautical_dusk",
+    "astronomical_dawn",
+    "astronomical_dusk",
+];
+
+#[derive(Debug, PartialEq, Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
 pub enum Event {
     Sunrise,
     Sunset,
@@ -18,8 +33,8 @@ pub enum Event {
 
 impl Event {
     pub fn new(event: &str) -> Result<Event> {
-        let event = event.trim().to_lowercase();
-        match event.as_str() {
+        let name = event.trim().to_lowercase();
+        match name.as_str() {
             "sunrise" => Ok(Event::Sunrise),
             "sunset" => Ok(Event::Sunset),
             "civil_dawn" => Ok(Event::CivilDawn),
@@ -28,14 +43,621 @@ impl Event {
             "nautical_dusk" => Ok(Event::NauticalDusk),
             "astronomical_dawn" => Ok(Event::AstronomicalDawn),
             "astronomical_dusk" => Ok(Event::AstronomicalDusk),
-            _ => Err(HeliocronError::Config(ConfigErrorKind::InvalidEvent)),
+            _ => Err(HeliocronError::Config(ConfigErrorKind::UnknownEvent(
+                event.trim().to_string(),
+                Event::closest_name(&name),
+            ))),
+        }
+    }
+
+    fn closest_name(name: &str) -> Option<&'static str> {
+        // the most similar event name, as long as it is close enough to be a likely typo
+        EVENT_NAMES
+            .iter()
+            .map(|candidate| (strsim::levenshtein(name, candidate), *candidate))
+            .filter(|(distance, _)| *distance <= 3)
+            .min_by_key(|(distance, _)| *distance)
+            .map(|(_, candidate)| candidate)
+    }
+}
+
+impl Event {
+    pub fn all() -> [Event; 8] {
+        [
+            Event::Sunrise,
+            Event::Sunset,
+            Event::CivilDawn,
+            Event::CivilDusk,
+            Event::NauticalDawn,
+            Event::NauticalDusk,
+            Event::AstronomicalDawn,
+            Event::AstronomicalDusk,
+        ]
+    }
+
+    pub fn priority(&self) -> usize {
+        // a fixed order for events which happen at the same time, which is their order in all()
+        Event::all().iter().position(|event| event == self).unwrap()
+    }
+
+    pub fn description(&self) -> String {
+        // the name in a sentence, e.g. "Civil dawn"
+        let name = self.to_string().replace('_', " ");
+        let mut chars = name.chars();
+        match chars.next() {
+            Some(first) => first.to_uppercase().chain(chars).collect(),
+            None => name,
+        }
+    }
+}
+
+impl fmt::Display for Event {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Event::Sunrise => "sunrise",
+            Event::Sunset => "sunset",
+            Event::CivilDawn => "civil_dawn",
+            Event::CivilDusk => "civil_dusk",
+            Event::NauticalDawn => "nautical_dawn",
+            Event::NauticalDusk => "nautical_dusk",
+            Event::AstronomicalDawn => "astronomical_dawn",
+            Event::AstronomicalDusk => "astronomical_dusk",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+// a moment of the day which a wait can be clamped to, or fall back on: solar noon or solar midnight, which
+// happen every day, or an event
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum DayMark {
+    SolarNoon,
+    SolarMidnight,
+    Event(Event),
+}
+
+impl DayMark {
+    pub fn new(mark: &str) -> Result<DayMark> {
+        match mark.trim().to_lowercase().as_str() {
+            "solar_noon" => Ok(DayMark::SolarNoon),
+            "solar_midnight" => Ok(DayMark::SolarMidnight),
+            _ => Ok(DayMark::Event(Event::new(mark)?)),
+        }
+    }
+}
+
+impl fmt::Display for DayMark {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DayMark::SolarNoon => write!(f, "solar_noon"),
+            DayMark::SolarMidnight => write!(f, "solar_midnight"),
+            DayMark::Event(event) => write!(f, "{}", event),
+        }
+    }
+}
+
+// what an event asked for by name is based on: one of the standard events, or, for an event defined in the
+// config file, the centre of the Sun rising or setting through an altitude of its own, in degrees, which is
+// shown by the name it was defined with
+#[derive(Debug, PartialEq, Clone)]
+pub enum EventBase {
+    Standard(Event),
+    Elevation(String, f64, Direction),
+}
+
+impl EventBase {
+    pub fn priority(&self) -> usize {
+        // after the standard events, for events which happen at the same time
+        match self {
+            EventBase::Standard(event) => event.priority(),
+            EventBase::Elevation(..) => Event::all().len(),
+        }
+    }
+}
+
+impl fmt::Display for EventBase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EventBase::Standard(event) => write!(f, "{}", event),
+            EventBase::Elevation(name, ..) => write!(f, "{}", name),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Comparator {
+    Less,
+    LessOrEqual,
+    Equal,
+    NotEqual,
+    GreaterOrEqual,
+    Greater,
+}
+
+impl Comparator {
+    pub fn new(comparator: &str) -> Result<Comparator> {
+        match comparator.trim() {
+            "<" => Ok(Comparator::Less),
+            "<=" => Ok(Comparator::LessOrEqual),
+            "=" | "==" => Ok(Comparator::Equal),
+            "!=" => Ok(Comparator::NotEqual),
+            ">=" => Ok(Comparator::GreaterOrEqual),
+            ">" => Ok(Comparator::Greater),
+            _ => Err(HeliocronError::Config(ConfigErrorKind::InvalidFilter)),
+        }
+    }
+
+    pub fn compare<T: PartialOrd>(&self, left: T, right: T) -> bool {
+        match self {
+            Comparator::Less => left < right,
+            Comparator::LessOrEqual => left <= right,
+            Comparator::Equal => left == right,
+            Comparator::NotEqual => left != right,
+            Comparator::GreaterOrEqual => left >= right,
+            Comparator::Greater => left > right,
+        }
+    }
+}
+
+pub const OUTPUT_FORMAT_NAMES: &[&str] = &["plain", "json", "csv", "tsv", "ics"];
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum OutputFormat {
+    Plain,
+    Json,
+    Csv,
+    Tsv,
+    Ics,
+}
+
+impl OutputFormat {
+    pub fn new(format: &str) -> Result<OutputFormat> {
+        match format.trim().to_lowercase().as_str() {
+            "plain" => Ok(OutputFormat::Plain),
+            "json" => Ok(OutputFormat::Json),
+            "csv" => Ok(OutputFormat::Csv),
+            "tsv" => Ok(OutputFormat::Tsv),
+            "ics" => Ok(OutputFormat::Ics),
+            _ => Err(HeliocronError::Config(ConfigErrorKind::InvalidOutputFormat)),
+        }
+    }
+}
+
+impl fmt::Display for OutputFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            OutputFormat::Plain => "plain",
+            OutputFormat::Json => "json",
+            OutputFormat::Csv => "csv",
+            OutputFormat::Tsv => "tsv",
+            OutputFormat::Ics => "ics",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+pub const LOCALE_NAMES: &[&str] = &["en", "fr", "de", "es", "it", "nl", "pt"];
+
+const ENGLISH_WEEKDAYS: [&str; 7] = [
+    "Monday",
+    "Tuesday",
+    "Wednesday",
+    "Thursday",
+    "Friday",
+    "Saturday",
+    "Sunday",
+];
+const ENGLISH_MONTHS: [&str; 12] = [
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+const FRENCH_WEEKDAYS: [&str; 7] = [
+    "lundi", "mardi", "mercredi", "jeudi", "vendredi", "samedi", "dimanche",
+];
+const FRENCH_MONTHS: [&str; 12] = [
+    "janvier",
+    "février",
+    "mars",
+    "avril",
+    "mai",
+    "juin",
+    "juillet",
+    "août",
+    "septembre",
+    "octobre",
+    "novembre",
+    "décembre",
+];
+const GERMAN_WEEKDAYS: [&str; 7] = [
+    "Montag",
+    "Dienstag",
+    "Mittwoch",
+    "Donnerstag",
+    "Freitag",
+    "Samstag",
+    "Sonntag",
+];
+const GERMAN_MONTHS: [&str; 12] = [
+    "Januar",
+    "Februar",
+    "März",
+    "April",
+    "Mai",
+    "Juni",
+    "Juli",
+    "August",
+    "September",
+    "Oktober",
+    "November",
+    "Dezember",
+];
+const SPANISH_WEEKDAYS: [&str; 7] = [
+    "lunes",
+    "martes",
+    "miércoles",
+    "jueves",
+    "viernes",
+    "sábado",
+    "domingo",
+];
+const SPANISH_MONTHS: [&str; 12] = [
+    "enero",
+    "febrero",
+    "marzo",
+    "abril",
+    "mayo",
+    "junio",
+    "julio",
+    "agosto",
+    "septiembre",
+    "octubre",
+    "noviembre",
+    "diciembre",
+];
+const ITALIAN_WEEKDAYS: [&str; 7] = [
+    "lunedì",
+    "martedì",
+    "mercoledì",
+    "giovedì",
+    "venerdì",
+    "sabato",
+    "domenica",
+];
+const ITALIAN_MONTHS: [&str; 12] = [
+    "gennaio",
+    "febbraio",
+    "marzo",
+    "aprile",
+    "maggio",
+    "giugno",
+    "luglio",
+    "agosto",
+    "settembre",
+    "ottobre",
+    "novembre",
+    "dicembre",
+];
+const DUTCH_WEEKDAYS: [&str; 7] = [
+    "maandag",
+    "dinsdag",
+    "woensdag",
+    "donderdag",
+    "vrijdag",
+    "zaterdag",
+    "zondag",
+];
+const DUTCH_MONTHS: [&str; 12] = [
+    "januari",
+    "februari",
+    "maart",
+    "april",
+    "mei",
+    "juni",
+    "juli",
+    "augustus",
+    "september",
+    "oktober",
+    "november",
+    "december",
+];
+const PORTUGUESE_WEEKDAYS: [&str; 7] = [
+    "segunda-feira",
+    "terça-feira",
+    "quarta-feira",
+    "quinta-feira",
+    "sexta-feira",
+    "sábado",
+    "domingo",
+];
+const PORTUGUESE_MONTHS: [&str; 12] = [
+    "janeiro",
+    "fevereiro",
+    "março",
+    "abril",
+    "maio",
+    "junho",
+    "julho",
+    "agosto",
+    "setembro",
+    "outubro",
+    "novembro",
+    "dezembro",
+];
+
+// the language used for the names of weekdays and months in the plain report. chrono's own locale support needs
+// a newer version, so the names are kept here
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Locale {
+    English,
+    French,
+    German,
+    Spanish,
+    Italian,
+    Dutch,
+    Portuguese,
+}
+
+impl Locale {
+    // takes the language from a locale such as "fr", "fr_FR", "fr_FR.UTF-8" or "de-DE", ignoring the region and
+    // encoding, which don't change the names
+    pub fn new(locale: &str) -> Result<Locale> {
+        let locale = locale.trim().to_lowercase();
+        let language = locale
+            .split(|c| c == '_' || c == '-' || c == '.' || c == '@')
+            .next();
+        match language.unwrap_or("") {
+            "en" | "c" | "posix" => Ok(Locale::English),
+            "fr" => Ok(Locale::French),
+            "de" => Ok(Locale::German),
+            "es" => Ok(Locale::Spanish),
+            "it" => Ok(Locale::Italian),
+            "nl" => Ok(Locale::Dutch),
+            "pt" => Ok(Locale::Portuguese),
+            _ => Err(HeliocronError::Config(ConfigErrorKind::InvalidLocale)),
+        }
+    }
+
+    fn names(&self) -> (&'static [&'static str; 7], &'static [&'static str; 12]) {
+        match self {
+            Locale::English => (&ENGLISH_WEEKDAYS, &ENGLISH_MONTHS),
+            Locale::French => (&FRENCH_WEEKDAYS, &FRENCH_MONTHS),
+            Locale::German => (&GERMAN_WEEKDAYS, &GERMAN_MONTHS),
+            Locale::Spanish => (&SPANISH_WEEKDAYS, &SPANISH_MONTHS),
+            Locale::Italian => (&ITALIAN_WEEKDAYS, &ITALIAN_MONTHS),
+            Locale::Dutch => (&DUTCH_WEEKDAYS, &DUTCH_MONTHS),
+            Locale::Portuguese => (&PORTUGUESE_WEEKDAYS, &PORTUGUESE_MONTHS),
+        }
+    }
+
+    pub fn weekday(&self, weekday: Weekday) -> &'static str {
+        self.names().0[weekday.num_days_from_monday() as usize]
+    }
+
+    pub fn month(&self, month: u32) -> &'static str {
+        self.names().1[month as usize - 1]
+    }
+
+    // writes out the date in the order and with the punctuation usual for the language, e.g. "Sunday 8 March 2020"
+    // or "Sonntag, 8. März 2020"
+    pub fn format_date<D: Datelike>(&self, date: &D) -> String {
+        let weekday = self.weekday(date.weekday());
+        let month = self.month(date.month());
+        let (day, year) = (date.day(), date.year());
+        match self {
+            Locale::German => format!("{}, {}. {} {}", weekday, day, month, year),
+            Locale::Spanish | Locale::Portuguese => {
+                format!("{}, {} de {} de {}", weekday, day, month, year)
+            }
+            _ => format!("{} {} {} {}", weekday, day, month, year),
         }
     }
 }
 
-#[derive(Debug)]
+pub const TWILIGHT_NAMES: &[&str] = &["civil", "nautical", "astronomical"];
+
+#[derive(Debug, PartialEq, Clone, Copy)]
 pub enum TwilightType {
     Civil,
     Nautical,
     Astronomical,
 }
+
+impl TwilightType {
+    pub fn new(twilight_type: &str) -> Result<TwilightType> {
+        match twilight_type.trim().to_lowercase().as_str() {
+            "civil" => Ok(TwilightType::Civil),
+            "nautical" => Ok(TwilightType::Nautical),
+            "astronomical" => Ok(TwilightType::Astronomical),
+            _ => Err(HeliocronError::Config(ConfigErrorKind::InvalidTwilightType)),
+        }
+    }
+
+    pub fn dawn(&self) -> Event {
+        match self {
+            TwilightType::Civil => Event::CivilDawn,
+            TwilightType::Nautical => Event::NauticalDawn,
+            TwilightType::Astronomical => Event::AstronomicalDawn,
+        }
+    }
+
+    pub fn dusk(&self) -> Event {
+        match self {
+            TwilightType::Civil => Event::CivilDusk,
+            TwilightType::Nautical => Event::NauticalDusk,
+            TwilightType::Astronomical => Event::AstronomicalDusk,
+        }
+    }
+}
+
+impl fmt::Display for TwilightType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            TwilightType::Civil => "civil",
+            TwilightType::Nautical => "nautical",
+            TwilightType::Astronomical => "astronomical",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum DayPhase {
+    Day,
+    CivilTwilight,
+    NauticalTwilight,
+    AstronomicalTwilight,
+    Night,
+}
+
+impl DayPhase {
+    pub fn exit_code(&self) -> i32 {
+        // 1 is left for errors, so that a script can always tell a phase from a failure
+        match self {
+            DayPhase::Day => 0,
+            DayPhase::CivilTwilight => 2,
+            DayPhase::NauticalTwilight => 3,
+            DayPhase::AstronomicalTwilight => 4,
+            DayPhase::Night => 5,
+        }
+    }
+}
+
+impl fmt::Display for DayPhase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            DayPhase::Day => "day",
+            DayPhase::CivilTwilight => "civil_twilight",
+            DayPhase::NauticalTwilight => "nautical_twilight",
+            DayPhase::AstronomicalTwilight => "astronomical_twilight",
+            DayPhase::Night => "night",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+pub const DIRECTION_NAMES: &[&str] = &["rising", "setting"];
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Direction {
+    Rising,
+    Setting,
+}
+
+impl Direction {
+    pub fn new(direction: &str) -> Result<Direction> {
+        match direction.trim().to_lowercase().as_str() {
+            "rising" => Ok(Direction::Rising),
+            "setting" => Ok(Direction::Setting),
+            _ => Err(HeliocronError::Config(ConfigErrorKind::InvalidDirection)),
+        }
+    }
+}
+
+impl fmt::Display for Direction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Direction::Rising => "rising",
+            Direction::Setting => "setting",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+pub const ROUND_MODE_NAMES: &[&str] = &["up", "down", "nearest"];
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum RoundMode {
+    Up,
+    Down,
+    Nearest,
+}
+
+impl RoundMode {
+    pub fn new(round_mode: &str) -> Result<RoundMode> {
+        match round_mode.trim().to_lowercase().as_str() {
+            "up" => Ok(RoundMode::Up),
+            "down" => Ok(RoundMode::Down),
+            "nearest" => Ok(RoundMode::Nearest),
+            _ => Err(HeliocronError::Config(ConfigErrorKind::InvalidRoundMode)),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Season {
+    MarchEquinox,
+    JuneSolstice,
+    SeptemberEquinox,
+    DecemberSolstice,
+}
+
+impl Season {
+    pub fn all() -> [Season; 4] {
+        [
+            Season::MarchEquinox,
+            Season::JuneSolstice,
+            Season::SeptemberEquinox,
+            Season::DecemberSolstice,
+        ]
+    }
+
+    pub fn previous(&self) -> Season {
+        // the solstice or equinox before this one in the year
+        match self {
+            Season::MarchEquinox => Season::DecemberSolstice,
+            Season::JuneSolstice => Season::MarchEquinox,
+            Season::SeptemberEquinox => Season::JuneSolstice,
+            Season::DecemberSolstice => Season::SeptemberEquinox,
+        }
+    }
+
+    pub fn apparent_longitude(&self) -> f64 {
+        // the apparent ecliptic longitude of the Sun, in degrees, at which each season begins
+        match self {
+            Season::MarchEquinox => 0.0,
+            Season::JuneSolstice => 90.0,
+            Season::SeptemberEquinox => 180.0,
+            Season::DecemberSolstice => 270.0,
+        }
+    }
+}
+
+impl fmt::Display for Season {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Season::MarchEquinox => "March equinox",
+            Season::JuneSolstice => "June solstice",
+            Season::SeptemberEquinox => "September equinox",
+            Season::DecemberSolstice => "December solstice",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy, Serialize)]
+pub enum MoonPhase {
+    #[serde(rename = "new_moon")]
+    New,
+    #[serde(rename = "full_moon")]
+    Full,
+}
+
+impl fmt::Display for MoonPhase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            MoonPhase::New => "New moon",
+            MoonPhase::Full => "Full moon",
+        };
+        write!(f, "{}", name)
+    }
+}
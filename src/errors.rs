@@ -2,6 +2,8 @@ use std::error;
 
 use chrono;
 
+use super::{enums, report};
+
 #[derive(Debug)]
 pub enum HeliocronError {
     Config(ConfigErrorKind),
@@ -11,22 +13,138 @@ pub enum HeliocronError {
 #[derive(Debug)]
 pub enum ConfigErrorKind {
     InvalidCoordindates(&'static str),
-    InvalidTomlFile,
+    InvalidTomlFile(String),
+    UnknownSetting,
+    UnreadableConfigFile(String),
     ParseDate,
-    InvalidEvent,
+    ParseOffset,
+    UnknownEvent(String, Option<&'static str>),
+    Strict(&'static str),
+    InvalidDateRange(&'static str),
+    UnsupportedDate,
+    UnknownCity,
+    AmbiguousCity(String),
+    InvalidFilter,
+    InvalidOutputFormat,
+    InvalidLocale,
+    InvalidTwilightType,
+    UnsupportedCustomEvent(&'static str),
+    InvalidCustomEvent,
+    ReservedEventName(String),
+    InvalidTimeZone,
+    InvalidAltitude,
+    InvalidDepression,
+    InvalidPressure,
+    InvalidTemperature,
+    InvalidIlluminance,
+    InvalidDirection,
+    InvalidRoundMode,
+    UnreadableTemplateFile,
+    UnreadableOffsetFile,
+    InvalidOffsetFile,
+    UnreadableHorizonFile,
+    InvalidHorizonFile,
+    InvalidTemplate(&'static str),
+    UnknownPlaceholder(String),
+    InvalidNow(&'static str),
+    InvalidFlight(&'static str),
+    InvalidInputJson(String),
+    MissingSubcommand,
 }
 
 impl ConfigErrorKind {
     fn as_str(&self) -> &str {
         match *self {
             ConfigErrorKind::InvalidCoordindates(msg) => msg,
-            ConfigErrorKind::InvalidTomlFile => {
+            ConfigErrorKind::UnknownCity => {
+                "City not found. Try the name of a nearby major city, or set --latitude and --longitude instead."
+            }
+            ConfigErrorKind::AmbiguousCity(ref candidates) => candidates,
+            ConfigErrorKind::InvalidTomlFile(_) => {
                 "Error parsing TOML file. Ensure that it is of the correct format."
             }
+            ConfigErrorKind::UnknownSetting => {
+                "Unknown setting. Check its spelling against the settings in the README."
+            }
+            ConfigErrorKind::UnreadableConfigFile(_) => {
+                "Error reading config file. Ensure that it exists and is readable."
+            }
             ConfigErrorKind::ParseDate => {
                 "Error parsing date. Ensure the date and timezone formats are correct."
             }
-            ConfigErrorKind::InvalidEvent => "Error parsing event.",
+            ConfigErrorKind::ParseOffset => {
+                "Error parsing offset. Use [-]HH:MM[:SS], or hours, minutes and seconds such as '1h30m', optionally followed by 'before' or 'after', up to a day either way."
+            }
+            ConfigErrorKind::UnknownEvent(ref event, _) => event,
+            ConfigErrorKind::InvalidOutputFormat => {
+                "Error parsing output format. Choose from: plain, json, csv, tsv, ics."
+            }
+            ConfigErrorKind::InvalidLocale => {
+                "Error parsing locale. Choose a language from: en, fr, de, es, it, nl, pt, optionally followed by a region, e.g. 'fr_FR'."
+            }
+            ConfigErrorKind::InvalidFilter => {
+                "Error parsing filter. Use an event, a comparison (<, <=, =, !=, >= or >) and a time {HH:MM:SS | HH:MM}, e.g. 'sunset>=20:00'."
+            }
+            ConfigErrorKind::InvalidTwilightType => {
+                "Error parsing twilight. Choose from: civil, nautical, astronomical."
+            }
+            ConfigErrorKind::UnsupportedCustomEvent(usage) => usage,
+            ConfigErrorKind::InvalidCustomEvent => {
+                "Error parsing named event. Give either the standard event it is based on, or its elevation in degrees and a direction of rising or setting."
+            }
+            ConfigErrorKind::ReservedEventName(ref name) => name,
+            ConfigErrorKind::InvalidTimeZone => {
+                "Error parsing time zone. It should be a UTC offset, e.g. \"+01:00\"."
+            }
+            ConfigErrorKind::InvalidAltitude => {
+                "Error parsing altitude. It should be a number of degrees above the horizon, between -90 and 90."
+            }
+            ConfigErrorKind::InvalidDepression => {
+                "Error parsing depression. It should be a number of degrees below the horizon, between -90 and 90."
+            }
+            ConfigErrorKind::InvalidPressure => {
+                "Error parsing pressure. It should be a number of hectopascals (millibars), above 0 and up to 1100."
+            }
+            ConfigErrorKind::InvalidTemperature => {
+                "Error parsing temperature. It should be a number of degrees Celsius, between -90 and 60."
+            }
+            ConfigErrorKind::InvalidIlluminance => {
+                "Error parsing illuminance. It should be a number of lux between 0.0007 and 400, the range covered by the model of twilight."
+            }
+            ConfigErrorKind::InvalidDirection => {
+                "Error parsing direction. Choose from: rising, setting."
+            }
+            ConfigErrorKind::InvalidRoundMode => {
+                "Error parsing rounding mode. Choose from: up, down, nearest."
+            }
+            ConfigErrorKind::UnreadableTemplateFile => {
+                "Error reading template file. Ensure that it exists and is readable."
+            }
+            ConfigErrorKind::UnreadableOffsetFile => {
+                "Error reading offset file. Ensure that it exists and is readable."
+            }
+            ConfigErrorKind::UnreadableHorizonFile => {
+                "Error reading horizon file. Ensure that it exists and is readable."
+            }
+            ConfigErrorKind::InvalidHorizonFile => {
+                "Error parsing horizon file. Each line should be an azimuth, from 0 to 360, and the altitude of the skyline in that direction, both in degrees, e.g. 135 4.5."
+            }
+            ConfigErrorKind::InvalidOffsetFile => {
+                "Error parsing offset file. Each line should be an event and its offset, e.g. sunset = \"-30m\"."
+            }
+            ConfigErrorKind::InvalidTemplate(msg) => msg,
+            ConfigErrorKind::UnknownPlaceholder(ref placeholder) => placeholder,
+            ConfigErrorKind::Strict(msg) => msg,
+            ConfigErrorKind::InvalidDateRange(msg) => msg,
+            ConfigErrorKind::InvalidNow(source) => source,
+            ConfigErrorKind::InvalidFlight(msg) => msg,
+            ConfigErrorKind::InvalidInputJson(ref msg) => msg,
+            ConfigErrorKind::MissingSubcommand => {
+                "A subcommand is required, unless --fail-if-dark or --fail-if-light is given."
+            }
+            ConfigErrorKind::UnsupportedDate => {
+                "Dates must be between the years 1000 and 3000, outside of which the solar calculations are not accurate."
+            }
         }
     }
 }
@@ -34,16 +152,28 @@ impl ConfigErrorKind {
 #[derive(Debug)]
 pub enum RuntimeErrorKind {
     NonOccurringEvent,
+    NoOccurrenceWithinYear,
     PastEvent,
+    OutsideTolerance,
+    UnwritableFile(String),
 }
 
 impl RuntimeErrorKind {
     fn as_str(&self) -> &str {
         match *self {
             RuntimeErrorKind::NonOccurringEvent => "The chosen event does not occur on this day.",
+            RuntimeErrorKind::NoOccurrenceWithinYear => {
+                "The chosen event does not occur on any day of the next year, so there is nothing to schedule."
+            }
             RuntimeErrorKind::PastEvent => {
                 "The chosen event occurred in the past; cannot wait a negative amount of time."
             }
+            RuntimeErrorKind::OutsideTolerance => {
+                "At least one event differs from its expected time by more than the tolerance."
+            }
+            RuntimeErrorKind::UnwritableFile(_) => {
+                "Error writing file. Ensure that its directory exists and is writable."
+            }
         }
     }
 }
@@ -57,9 +187,82 @@ impl std::fmt::Display for HeliocronError {
                 match err {
                     ConfigErrorKind::InvalidCoordindates(msg) =>
                         format!("Invalid coordinates - {}", msg),
-                    ConfigErrorKind::InvalidTomlFile => err.as_str().to_string(),
+                    ConfigErrorKind::InvalidTomlFile(ref path) => format!(
+                        "Error parsing TOML file {}. Ensure that it is of the correct format.",
+                        path
+                    ),
+                    ConfigErrorKind::UnknownSetting => err.as_str().to_string(),
+                    ConfigErrorKind::UnreadableConfigFile(ref path) => format!(
+                        "Error reading config file {}. Ensure that it exists and is readable.",
+                        path
+                    ),
                     ConfigErrorKind::ParseDate => err.as_str().to_string(),
-                    ConfigErrorKind::InvalidEvent => err.as_str().to_string(),
+                    ConfigErrorKind::ParseOffset => err.as_str().to_string(),
+                    ConfigErrorKind::UnknownEvent(ref event, Some(suggestion)) => format!(
+                        "Unknown event '{}', did you mean '{}'?",
+                        event, suggestion
+                    ),
+                    ConfigErrorKind::UnknownEvent(ref event, None) => format!(
+                        "Unknown event '{}'. Choose from: {}.",
+                        event,
+                        enums::EVENT_NAMES.join(", ")
+                    ),
+                    ConfigErrorKind::InvalidFilter => err.as_str().to_string(),
+                    ConfigErrorKind::InvalidOutputFormat => err.as_str().to_string(),
+                    ConfigErrorKind::InvalidLocale => err.as_str().to_string(),
+                    ConfigErrorKind::InvalidTwilightType => err.as_str().to_string(),
+                    ConfigErrorKind::UnsupportedCustomEvent(usage) => format!(
+                        "Events defined in the config file can't be used {}. Choose one of the standard events.",
+                        usage
+                    ),
+                    ConfigErrorKind::InvalidCustomEvent => err.as_str().to_string(),
+                    ConfigErrorKind::ReservedEventName(ref name) => format!(
+                        "Error parsing named event - '{}' is the name of a standard event, so can't be used for a named one.",
+                        name
+                    ),
+                    ConfigErrorKind::InvalidTimeZone => err.as_str().to_string(),
+                    ConfigErrorKind::InvalidAltitude => err.as_str().to_string(),
+                    ConfigErrorKind::InvalidDepression => err.as_str().to_string(),
+                    ConfigErrorKind::InvalidPressure => err.as_str().to_string(),
+                    ConfigErrorKind::InvalidTemperature => err.as_str().to_string(),
+                    ConfigErrorKind::InvalidIlluminance => err.as_str().to_string(),
+                    ConfigErrorKind::InvalidDirection => err.as_str().to_string(),
+                    ConfigErrorKind::InvalidRoundMode => err.as_str().to_string(),
+                    ConfigErrorKind::UnreadableTemplateFile => err.as_str().to_string(),
+                    ConfigErrorKind::UnreadableOffsetFile => err.as_str().to_string(),
+                    ConfigErrorKind::InvalidOffsetFile => err.as_str().to_string(),
+                    ConfigErrorKind::UnreadableHorizonFile => err.as_str().to_string(),
+                    ConfigErrorKind::InvalidHorizonFile => err.as_str().to_string(),
+                    ConfigErrorKind::InvalidTemplate(msg) => format!("Invalid template - {}", msg),
+                    ConfigErrorKind::UnknownPlaceholder(ref placeholder) => format!(
+                        "Unknown placeholder '{{{}}}' in template. Choose from: {}.",
+                        placeholder,
+                        report::TEMPLATE_PLACEHOLDERS
+                            .iter()
+                            .chain(enums::EVENT_NAMES)
+                            .map(|name| format!("{{{}}}", name))
+                            .collect::<Vec<String>>()
+                            .join(", ")
+                    ),
+                    ConfigErrorKind::Strict(msg) => format!("Strict mode - {}", msg),
+                    ConfigErrorKind::UnsupportedDate => err.as_str().to_string(),
+                    ConfigErrorKind::UnknownCity => err.as_str().to_string(),
+                    ConfigErrorKind::AmbiguousCity(ref candidates) => format!(
+                        "Ambiguous city - choose one of the following by adding its country code, e.g. --city \"Springfield,US\": {}",
+                        candidates
+                    ),
+                    ConfigErrorKind::InvalidDateRange(msg) => {
+                        format!("Invalid date range - {}", msg)
+                    }
+                    ConfigErrorKind::InvalidNow(source) => format!(
+                        "Error parsing {} - expected an RFC 3339 date and time, e.g. 2020-06-21T12:00:00+01:00.",
+                        source
+                    ),
+                    ConfigErrorKind::InvalidFlight(msg) => format!("Invalid flight - {}", msg),
+                    ConfigErrorKind::InvalidInputJson(ref msg) => {
+                        format!("Invalid JSON input - {}", msg)
+                    }
+                    ConfigErrorKind::MissingSubcommand => err.as_str().to_string(),
                 }
             ),
             HeliocronError::Runtime(ref err) => write!(
@@ -67,7 +270,13 @@ impl std::fmt::Display for HeliocronError {
                 "Runtime error: {}",
                 match err {
                     RuntimeErrorKind::NonOccurringEvent => err.as_str().to_string(),
+                    RuntimeErrorKind::NoOccurrenceWithinYear => err.as_str().to_string(),
                     RuntimeErrorKind::PastEvent => err.as_str().to_string(),
+                    RuntimeErrorKind::OutsideTolerance => err.as_str().to_string(),
+                    RuntimeErrorKind::UnwritableFile(ref path) => format!(
+                        "Error writing file {}. Ensure that its directory exists and is writable.",
+                        path
+                    ),
                 }
             ),
         }
@@ -84,9 +293,7 @@ impl error::Error for HeliocronError {
 }
 
 impl From<chrono::ParseError> for HeliocronError {
-    fn from(err: chrono::ParseError) -> Self {
-        match err {
-            _err => HeliocronError::Config(ConfigErrorKind::ParseDate),
-        }
+    fn from(_err: chrono::ParseError) -> Self {
+        HeliocronError::Config(ConfigErrorKind::ParseDate)
     }
 }
@@ -1,8 +1,12 @@
+pub mod cities;
 pub mod config;
 pub mod enums;
 pub mod errors;
+pub mod lunar;
 pub mod parsers;
 pub mod report;
+pub mod solar;
 pub mod structs;
 pub mod subcommands;
+mod traits;
 pub mod utils;
@@ -0,0 +1,280 @@
+use chrono::{DateTime, Datelike, FixedOffset};
+use serde::Serialize;
+
+use super::{
+    enums::MoonPhase,
+    solar::{delta_t, julian_date_to_datetime, sidereal_time},
+    structs::Coordinates,
+    traits::DateTimeExt,
+};
+
+// the mean number of new moons per year, i.e. the number of days in a year divided by the mean synodic month
+const LUNATIONS_PER_YEAR: f64 = 12.3685;
+
+// the altitude of the centre of the Moon, in degrees, when its upper edge is on the horizon, allowing for
+// refraction and its semidiameter in the same way as for sunrise and sunset
+pub const MOONRISE_ALTITUDE: f64 = -0.833;
+
+#[derive(Debug, Serialize)]
+pub struct LunarPhase {
+    pub phase: MoonPhase,
+    pub time: DateTime<FixedOffset>,
+    // the second full moon in a calendar month
+    pub blue_moon: bool,
+}
+
+fn phase_julian_date(k: f64) -> f64 {
+    // the Julian date (UT) of a new moon, for whole numbers of lunations k since the new moon of 6th January
+    // 2000, or the full moon for k + 0.5. These are the series of Meeus, Astronomical Algorithms (ch. 49), which
+    // are good to within a minute or so
+    let t = k / 1236.85;
+    let mean = 2451550.09766 + 29.530588861 * k + 0.00015437 * t.powi(2) - 0.000000150 * t.powi(3)
+        + 0.00000000073 * t.powi(4);
+
+    // the eccentricity of the Earth's orbit, and the anomalies and arguments of the Sun and Moon in degrees
+    let e = 1.0 - 0.002516 * t - 0.0000074 * t.powi(2);
+    let m =
+        (2.5534 + 29.10535670 * k - 0.0000014 * t.powi(2) - 0.00000011 * t.powi(3)).to_radians();
+    let m_moon = (201.5643 + 385.81693528 * k + 0.0107582 * t.powi(2) + 0.00001238 * t.powi(3)
+        - 0.000000058 * t.powi(4))
+    .to_radians();
+    let f = (160.7108 + 390.67050284 * k - 0.0016118 * t.powi(2) - 0.00000227 * t.powi(3)
+        + 0.000000011 * t.powi(4))
+    .to_radians();
+    let omega =
+        (124.7746 - 1.56375588 * k + 0.0020672 * t.powi(2) + 0.00000215 * t.powi(3)).to_radians();
+
+    // the largest terms differ slightly between new and full moons
+    let new_moon = k.fract() == 0.0;
+    let largest = if new_moon {
+        [
+            0.40720, 0.17241, 0.01608, 0.01039, 0.00739, 0.00514, 0.00208,
+        ]
+    } else {
+        [
+            0.40614, 0.17302, 0.01614, 0.01043, 0.00734, 0.00515, 0.00209,
+        ]
+    };
+    let correction = -largest[0] * m_moon.sin()
+        + largest[1] * e * m.sin()
+        + largest[2] * (2.0 * m_moon).sin()
+        + largest[3] * (2.0 * f).sin()
+        + largest[4] * e * (m_moon - m).sin()
+        - largest[5] * e * (m_moon + m).sin()
+        + largest[6] * e.powi(2) * (2.0 * m).sin()
+        - 0.00111 * (m_moon - 2.0 * f).sin()
+        - 0.00057 * (m_moon + 2.0 * f).sin()
+        + 0.00056 * e * (2.0 * m_moon + m).sin()
+        - 0.00042 * (3.0 * m_moon).sin()
+        + 0.00042 * e * (m + 2.0 * f).sin()
+        + 0.00038 * e * (m - 2.0 * f).sin()
+        - 0.00024 * e * (2.0 * m_moon - m).sin()
+        - 0.00017 * omega.sin()
+        - 0.00007 * (m_moon + 2.0 * m).sin()
+        + 0.00004 * (2.0 * m_moon - 2.0 * f).sin()
+        + 0.00004 * (3.0 * m).sin()
+        + 0.00003 * (m_moon + m - 2.0 * f).sin()
+        + 0.00003 * (2.0 * m_moon + 2.0 * f).sin()
+        - 0.00003 * (m_moon + m + 2.0 * f).sin()
+        + 0.00003 * (m_moon - m + 2.0 * f).sin()
+        - 0.00002 * (m_moon - m - 2.0 * f).sin()
+        - 0.00002 * (3.0 * m_moon + m).sin()
+        + 0.00002 * (4.0 * m_moon).sin();
+
+    // and the perturbations by the planets
+    let planetary: f64 = [
+        (0.000325, 299.77 + 0.107408 * k - 0.009173 * t.powi(2)),
+        (0.000165, 251.88 + 0.016321 * k),
+        (0.000164, 251.83 + 26.651886 * k),
+        (0.000126, 349.42 + 36.412478 * k),
+        (0.000110, 84.66 + 18.206239 * k),
+        (0.000062, 141.74 + 53.303771 * k),
+        (0.000060, 207.14 + 2.453732 * k),
+        (0.000056, 154.84 + 7.306860 * k),
+        (0.000047, 34.52 + 27.261239 * k),
+        (0.000042, 207.19 + 0.121824 * k),
+        (0.000040, 291.34 + 1.844379 * k),
+        (0.000037, 161.72 + 24.198154 * k),
+        (0.000035, 239.56 + 25.513099 * k),
+        (0.000023, 331.55 + 3.592518 * k),
+    ]
+    .iter()
+    .map(|(coefficient, argument): &(f64, f64)| coefficient * argument.to_radians().sin())
+    .sum();
+
+    // the series give the time in TT, which is ahead of UT by ΔT
+    let julian_ephemeris_date = mean + correction + planetary;
+    julian_ephemeris_date - delta_t(julian_ephemeris_date) / 86400.0
+}
+
+pub fn moon_altitude(datetime: DateTime<FixedOffset>, coordinates: Coordinates) -> f64 {
+    // the altitude of the centre of the Moon above the horizon in degrees, as seen from the location rather than
+    // the centre of the Earth, but before refraction. This uses the low precision series of the Astronomical
+    // Almanac, which are good to a few tenths of a degree, and so to a couple of minutes for moonrise and moonset
+    let julian_date = datetime.to_julian_date();
+    let t = (julian_date + delta_t(julian_date) / 86400.0 - 2451545.0) / 36525.0;
+    let sin = |degrees: f64| degrees.to_radians().sin();
+    let cos = |degrees: f64| degrees.to_radians().cos();
+
+    // the Moon's ecliptic longitude and latitude, and its horizontal parallax, in degrees
+    let longitude = 218.32 + 481267.881 * t + 6.29 * sin(135.0 + 477198.87 * t)
+        - 1.27 * sin(259.3 - 413335.36 * t)
+        + 0.66 * sin(235.7 + 890534.22 * t)
+        + 0.21 * sin(269.9 + 954397.74 * t)
+        - 0.19 * sin(357.5 + 35999.05 * t)
+        - 0.11 * sin(186.5 + 966404.03 * t);
+    let latitude = 5.13 * sin(93.3 + 483202.02 * t) + 0.28 * sin(228.2 + 960400.89 * t)
+        - 0.28 * sin(318.3 + 6003.15 * t)
+        - 0.17 * sin(217.6 - 407332.21 * t);
+    let parallax = 0.9508
+        + 0.0518 * cos(135.0 + 477198.87 * t)
+        + 0.0095 * cos(259.3 - 413335.36 * t)
+        + 0.0078 * cos(235.7 + 890534.22 * t)
+        + 0.0028 * cos(269.9 + 954397.74 * t);
+    let obliquity = 23.439 - 0.013 * t;
+
+    // converted to right ascension and declination, and then to altitude from the local hour angle
+    let declination =
+        (sin(latitude) * cos(obliquity) + cos(latitude) * sin(obliquity) * sin(longitude)).asin();
+    let right_ascension = (sin(longitude) * cos(obliquity)
+        - latitude.to_radians().tan() * sin(obliquity))
+    .atan2(cos(longitude))
+    .to_degrees();
+    let hour_angle =
+        (sidereal_time(julian_date) + coordinates.longitude.value - right_ascension).to_radians();
+    let observer_latitude = coordinates.latitude.value.to_radians();
+    let altitude = (observer_latitude.sin() * declination.sin()
+        + observer_latitude.cos() * declination.cos() * hour_angle.cos())
+    .max(-1.0)
+    .min(1.0)
+    .asin()
+    .to_degrees();
+
+    // the Moon is close enough that it appears lower from the surface than from the centre of the Earth, by up
+    // to its parallax on the horizon
+    altitude - parallax * cos(altitude)
+}
+
+pub fn phases_in_year(year: i32, time_zone: &FixedOffset) -> Vec<LunarPhase> {
+    // every new and full moon in the calendar year, in the chosen time zone, from the lunations either side of
+    // an estimate of the first one
+    let first = ((year - 2000) as f64 * LUNATIONS_PER_YEAR).floor() - 1.0;
+    let mut phases: Vec<LunarPhase> = (0..15)
+        .flat_map(|i| {
+            let k = first + i as f64;
+            vec![(MoonPhase::New, k), (MoonPhase::Full, k + 0.5)]
+        })
+        .map(|(phase, k)| LunarPhase {
+            phase,
+            time: julian_date_to_datetime(phase_julian_date(k), time_zone),
+            blue_moon: false,
+        })
+        .filter(|phase| phase.time.year() == year)
+        .collect();
+    phases.sort_by_key(|phase| phase.time);
+
+    let mut previous_full_moon: Option<DateTime<FixedOffset>> = None;
+    for phase in phases
+        .iter_mut()
+        .filter(|phase| phase.phase == MoonPhase::Full)
+    {
+        phase.blue_moon =
+            previous_full_moon.map_or(false, |previous| previous.month() == phase.time.month());
+        previous_full_moon = Some(phase.time);
+    }
+
+    phases
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solar::SolarPosition;
+    use chrono::{TimeZone, Timelike};
+
+    #[test]
+    fn test_phase_julian_date() {
+        // Meeus' example 49.a: the new moon of 18th February 1977 at 03:37:42 TT, which was 03:36:54 UT
+        let new_moon = julian_date_to_datetime(phase_julian_date(-283.0), &FixedOffset::east(0));
+        let expected = FixedOffset::east(0).ymd(1977, 2, 18).and_hms(3, 36, 54);
+        assert!(
+            (new_moon - expected).num_seconds().abs() < 60,
+            "{}",
+            new_moon
+        );
+
+        // the full moon of 31st October 2020 at 14:49 UT
+        let full_moon = julian_date_to_datetime(phase_julian_date(257.5), &FixedOffset::east(0));
+        let expected = FixedOffset::east(0).ymd(2020, 10, 31).and_hms(14, 49, 0);
+        assert!(
+            (full_moon - expected).num_seconds().abs() < 120,
+            "{}",
+            full_moon
+        );
+    }
+
+    #[test]
+    fn test_moon_altitude() {
+        // at the greatest point of a solar eclipse, the Moon is in front of the Sun: the total eclipse of 21st
+        // August 2017 in Kentucky, and the annular eclipse of 21st June 2020 in northern India
+        let eclipses = [
+            ("2017-08-21T18:25:32+00:00", "36.97N", "87.67W"),
+            ("2020-06-21T06:40:04+00:00", "30.5N", "79.7E"),
+        ];
+        for (datetime, latitude, longitude) in eclipses.iter() {
+            let datetime = DateTime::parse_from_rfc3339(datetime).unwrap();
+            let coordinates = Coordinates::from_decimal_degrees(latitude, longitude).unwrap();
+            let sun_altitude = SolarPosition::geometric(datetime, coordinates).altitude;
+            let moon_altitude = moon_altitude(datetime, coordinates);
+            assert!(
+                (sun_altitude - moon_altitude).abs() < 0.5,
+                "{} {}",
+                sun_altitude,
+                moon_altitude
+            );
+        }
+
+        // a full moon is opposite the Sun, so in midwinter it is as high at midnight as the Sun is at midsummer
+        // noon, and below the horizon all day
+        let coordinates = Coordinates::from_decimal_degrees("51.4769N", "0.0005W").unwrap();
+        let midnight = DateTime::parse_from_rfc3339("2020-12-30T00:00:00+00:00").unwrap();
+        assert!(moon_altitude(midnight, coordinates) > 55.0);
+        let noon = DateTime::parse_from_rfc3339("2020-12-30T12:00:00+00:00").unwrap();
+        assert!(moon_altitude(noon, coordinates) < -10.0);
+    }
+
+    #[test]
+    fn test_phases_in_year() {
+        let phases = phases_in_year(2020, &FixedOffset::east(0));
+
+        // 2020 had 13 full moons and 12 new moons, alternating, with one blue moon on Halloween
+        let full_moons: Vec<&LunarPhase> = phases
+            .iter()
+            .filter(|phase| phase.phase == MoonPhase::Full)
+            .collect();
+        assert_eq!(13, full_moons.len());
+        assert_eq!(25, phases.len());
+        assert!(phases.windows(2).all(|pair| pair[0].phase != pair[1].phase));
+        assert!(phases.iter().all(|phase| phase.time.year() == 2020));
+
+        let blue_moons: Vec<&LunarPhase> = phases.iter().filter(|phase| phase.blue_moon).collect();
+        assert_eq!(1, blue_moons.len());
+        assert_eq!(
+            (10, 31),
+            (blue_moons[0].time.month(), blue_moons[0].time.day())
+        );
+        assert_eq!(14, blue_moons[0].time.hour());
+
+        // the calendar month depends on the time zone: the UK's blue moons of 31st January and 31st March 2018
+        // were on the 1st of the following months in New Zealand, which had its blue moon in April instead
+        let blue_moons = |time_zone: FixedOffset| -> Vec<(u32, u32)> {
+            phases_in_year(2018, &time_zone)
+                .iter()
+                .filter(|phase| phase.blue_moon)
+                .map(|phase| (phase.time.month(), phase.time.day()))
+                .collect()
+        };
+        assert_eq!(vec![(1, 31), (3, 31)], blue_moons(FixedOffset::east(0)));
+        assert_eq!(vec![(4, 30)], blue_moons(FixedOffset::east(13 * 3600)));
+    }
+}
@@ -1,10 +1,14 @@
-use std::result;
+use std::{collections::BTreeMap, result};
 
-use chrono::{DateTime, Duration, FixedOffset, Local, NaiveTime, TimeZone};
+use chrono::{
+    DateTime, Duration, FixedOffset, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone,
+};
 
 use super::{
-    enums::Event,
+    enums::{Comparator, DayMark, Direction, Event, Locale, OutputFormat, RoundMode, TwilightType},
     errors::{ConfigErrorKind, HeliocronError},
+    solar,
+    structs::{Coordinates, EventFilter, HorizonProfile, Location},
 };
 
 type Result<T> = result::Result<T, HeliocronError>;
@@ -39,28 +43,279 @@ pub fn parse_date(
     Ok(datetime)
 }
 
-pub fn parse_event(event: &str) -> Result<Event> {
-    Ok(Event::new(event)?)
+pub fn parse_datetime(datetime: &str, time_zone: Option<&str>) -> Result<DateTime<FixedOffset>> {
+    // a full date and time, either %Y-%m-%dT%H:%M:%S or %Y-%m-%dT%H:%M, rather than a date at midday
+    let datetime = ["%Y-%m-%dT%H:%M:%S", "%Y-%m-%dT%H:%M"]
+        .iter()
+        .find_map(|fmt| NaiveDateTime::parse_from_str(datetime, fmt).ok())
+        .ok_or(HeliocronError::Config(ConfigErrorKind::ParseDate))?;
+
+    // default time zone is the local time zone at the given moment
+    match time_zone {
+        Some(tz) => {
+            let datetimetz = format!("{}{}", datetime.format("%Y-%m-%dT%H:%M:%S"), tz);
+            Ok(DateTime::parse_from_str(
+                &datetimetz,
+                "%Y-%m-%dT%H:%M:%S%:z",
+            )?)
+        }
+        None => Local
+            .from_local_datetime(&datetime)
+            .earliest()
+            .map(|datetime| datetime.with_timezone(datetime.offset()))
+            .ok_or(HeliocronError::Config(ConfigErrorKind::ParseDate)),
+    }
 }
 
-pub fn parse_offset(offset: &str) -> Result<Duration> {
-    // offset should either be %H:%M:%S or %H:%M +/- a "-" if negative
-    let (positive, offset): (bool, &str) = match offset.chars().next() {
-        Some('-') => (false, &offset[1..]),
-        _ => (true, offset),
+pub fn parse_day(date: &str) -> Result<NaiveDate> {
+    // a date on its own, always in the format %Y-%m-%d, whose time zone is worked out later
+    NaiveDate::parse_from_str(date.trim(), "%Y-%m-%d")
+        .map_err(|_| HeliocronError::Config(ConfigErrorKind::ParseDate))
+}
+
+pub fn parse_coordinates(coordinates: &str) -> Result<Coordinates> {
+    // coordinates as copied from e.g. Google Maps: the latitude and then the longitude, in signed decimal
+    // degrees, separated by a comma
+    let invalid = || {
+        HeliocronError::Config(ConfigErrorKind::InvalidCoordindates(
+            "Coordinates must be a latitude and a longitude in signed decimal degrees, separated by a comma, e.g. \"51.4769, -0.0005\"",
+        ))
+    };
+
+    let values = coordinates
+        .split(',')
+        .map(|value| value.trim().parse::<f64>().map_err(|_| invalid()))
+        .collect::<Result<Vec<f64>>>()?;
+
+    match values.as_slice() {
+        [latitude, longitude] => Coordinates::from_signed_decimal_degrees(*latitude, *longitude),
+        _ => Err(invalid()),
+    }
+}
+
+pub fn parse_bounding_box(bounding_box: &str) -> Result<Coordinates> {
+    // a region as the corners of a box on the map, in signed decimal degrees in the order min latitude, min
+    // longitude, max latitude, max longitude, as given by most map tools, which is reduced to its centre. A box
+    // whose min longitude is east of its max longitude crosses the antimeridian
+    let invalid = || {
+        HeliocronError::Config(ConfigErrorKind::InvalidCoordindates(
+            "Bounding boxes must be a min latitude, min longitude, max latitude and max longitude in signed decimal degrees, separated by commas, e.g. \"51.28, -0.51, 51.69, 0.33\"",
+        ))
+    };
+
+    let values = bounding_box
+        .split(',')
+        .map(|value| value.trim().parse::<f64>().map_err(|_| invalid()))
+        .collect::<Result<Vec<f64>>>()?;
+
+    match values.as_slice() {
+        [south, west, north, east] if south <= north => {
+            Coordinates::from_signed_decimal_degrees(*south, *west)?;
+            Coordinates::from_signed_decimal_degrees(*north, *east)?;
+            let mut longitude = (west + east) / 2.0;
+            if west > east {
+                longitude += if longitude > 0.0 { -180.0 } else { 180.0 };
+            }
+            Coordinates::from_signed_decimal_degrees((south + north) / 2.0, longitude)
+        }
+        _ => Err(invalid()),
+    }
+}
+
+pub fn parse_location(location: &str) -> Result<Location> {
+    // coordinates in the same format as parse_coordinates, optionally followed by a third value for the UTC
+    // offset of the location's time zone, e.g. "40.6413, -73.7781, -04:00"
+    let invalid = || {
+        HeliocronError::Config(ConfigErrorKind::InvalidCoordindates(
+            "Locations must be a latitude and a longitude in signed decimal degrees, optionally followed by a UTC offset, separated by commas, e.g. \"40.6413, -73.7781, -04:00\"",
+        ))
     };
 
-    let offset = match offset {
-        offset if NaiveTime::parse_from_str(offset, "%H:%M:%S").is_ok() => {
-            Ok(NaiveTime::parse_from_str(offset, "%H:%M:%S")?)
+    let fields: Vec<&str> = location.split(',').map(|field| field.trim()).collect();
+    let (coordinates, time_zone) = match fields.as_slice() {
+        [latitude, longitude] => (format!("{},{}", latitude, longitude), None),
+        [latitude, longitude, time_zone] => {
+            (format!("{},{}", latitude, longitude), Some(time_zone))
         }
-        offset if NaiveTime::parse_from_str(offset, "%H:%M").is_ok() => {
-            Ok(NaiveTime::parse_from_str(offset, "%H:%M")?)
+        _ => return Err(invalid()),
+    };
+
+    let time_zone = match time_zone {
+        Some(time_zone) => Some(
+            *DateTime::parse_from_str(
+                &format!("2000-01-01T00:00:00{}", time_zone),
+                "%Y-%m-%dT%H:%M:%S%:z",
+            )
+            .map_err(|_| invalid())?
+            .offset(),
+        ),
+        None => None,
+    };
+
+    Ok(Location {
+        coordinates: parse_coordinates(&coordinates).map_err(|_| invalid())?,
+        time_zone,
+    })
+}
+
+pub fn parse_flight_time(datetime: &str) -> Result<DateTime<FixedOffset>> {
+    // a departure or arrival always has an explicit offset, as the two ends of a flight are usually in different
+    // time zones
+    DateTime::parse_from_rfc3339(datetime.trim()).map_err(|_| {
+        HeliocronError::Config(ConfigErrorKind::InvalidFlight(
+            "The departure and arrival must be in RFC 3339 format, e.g. 2020-06-21T18:00:00+01:00.",
+        ))
+    })
+}
+
+pub fn parse_event(event: &str) -> Result<Event> {
+    Event::new(event)
+}
+
+pub fn parse_day_mark(mark: &str) -> Result<DayMark> {
+    DayMark::new(mark)
+}
+
+pub fn parse_filter(filter: &str) -> Result<EventFilter> {
+    // a filter is an event, a comparison and a time of day e.g. "sunset>=20:00", optionally with spaces
+    // between them
+    let invalid = || HeliocronError::Config(ConfigErrorKind::InvalidFilter);
+    let is_comparator = |c: char| "<>=!".contains(c);
+
+    let start = filter.find(is_comparator).ok_or_else(invalid)?;
+    let end = filter[start..]
+        .find(|c: char| !is_comparator(c))
+        .map_or(filter.len(), |i| start + i);
+
+    Ok(EventFilter {
+        event: Event::new(&filter[..start])?,
+        comparator: Comparator::new(&filter[start..end])?,
+        time: parse_time(filter[end..].trim()).map_err(|_| invalid())?,
+    })
+}
+
+pub fn parse_output_format(format: &str) -> Result<OutputFormat> {
+    OutputFormat::new(format)
+}
+
+pub fn parse_locale(locale: &str) -> Result<Locale> {
+    Locale::new(locale)
+}
+
+pub fn parse_twilight_type(twilight_type: &str) -> Result<TwilightType> {
+    TwilightType::new(twilight_type)
+}
+
+pub fn parse_direction(direction: &str) -> Result<Direction> {
+    Direction::new(direction)
+}
+
+pub fn parse_round_mode(round_mode: &str) -> Result<RoundMode> {
+    RoundMode::new(round_mode)
+}
+
+pub fn parse_depression(depression: &str) -> Result<f64> {
+    // degrees below the horizon, as in the definitions of twilight. A negative depression is above the horizon
+    match depression.trim().parse::<f64>() {
+        Ok(depression) if (-90.0..=90.0).contains(&depression) => Ok(depression),
+        _ => Err(HeliocronError::Config(ConfigErrorKind::InvalidDepression)),
+    }
+}
+
+pub fn parse_pressure(pressure: &str) -> Result<f64> {
+    // in hPa, from the thin air of a high mountain to the highest pressures found at sea level
+    match pressure.trim().parse::<f64>() {
+        Ok(pressure) if pressure > 0.0 && pressure <= 1100.0 => Ok(pressure),
+        _ => Err(HeliocronError::Config(ConfigErrorKind::InvalidPressure)),
+    }
+}
+
+pub fn parse_temperature(temperature: &str) -> Result<f64> {
+    // in °C, within the extremes recorded on Earth
+    match temperature.trim().parse::<f64>() {
+        Ok(temperature) if (-90.0..=60.0).contains(&temperature) => Ok(temperature),
+        _ => Err(HeliocronError::Config(ConfigErrorKind::InvalidTemperature)),
+    }
+}
+
+pub fn parse_illuminance(illuminance: &str) -> Result<f64> {
+    // in lux, as the depression of the Sun at which twilight gives this much light, for as far as the model of
+    // twilight goes. The depression is rounded to a hundredth of a degree, which is within a few seconds and
+    // far finer than the model itself
+    illuminance
+        .trim()
+        .parse::<f64>()
+        .ok()
+        .and_then(solar::illuminance_altitude)
+        .map(|altitude| (-altitude * 100.0).round() / 100.0)
+        .ok_or(HeliocronError::Config(ConfigErrorKind::InvalidIlluminance))
+}
+
+pub fn parse_time(time: &str) -> Result<NaiveTime> {
+    // a time of day, either on the clock or as a fraction of the day, with a decimal point, e.g. 0.25 for 06:00
+    if time.contains('.') {
+        return parse_day_fraction(time);
+    }
+    parse_clock_time(time)
+}
+
+fn parse_day_fraction(fraction: &str) -> Result<NaiveTime> {
+    // from 0.0 up to but not including 1.0, to the nearest second. A fraction so close to 1.0 that it would
+    // round up to the next midnight is the last second of the day instead
+    match fraction.trim().parse::<f64>() {
+        Ok(fraction) if (0.0..1.0).contains(&fraction) => {
+            let seconds = std::cmp::min((fraction * 86400.0).round() as u32, 86399);
+            NaiveTime::from_num_seconds_from_midnight_opt(seconds, 0)
+                .ok_or(HeliocronError::Config(ConfigErrorKind::ParseDate))
         }
         _ => Err(HeliocronError::Config(ConfigErrorKind::ParseDate)),
-    }?;
+    }
+}
+
+fn parse_clock_time(time: &str) -> Result<NaiveTime> {
+    // time should either be %H:%M:%S or %H:%M
+    match time {
+        time if NaiveTime::parse_from_str(time, "%H:%M:%S").is_ok() => {
+            Ok(NaiveTime::parse_from_str(time, "%H:%M:%S")?)
+        }
+        time if NaiveTime::parse_from_str(time, "%H:%M").is_ok() => {
+            Ok(NaiveTime::parse_from_str(time, "%H:%M")?)
+        }
+        _ => Err(HeliocronError::Config(ConfigErrorKind::ParseDate)),
+    }
+}
+
+pub fn parse_offset(offset: &str) -> Result<Duration> {
+    // at most a day either way, as an offset of thousands of years would overflow the event time it's added to
+    match parse_signed_duration(offset)? {
+        offset if offset > Duration::days(1) || offset < -Duration::days(1) => {
+            Err(HeliocronError::Config(ConfigErrorKind::ParseOffset))
+        }
+        offset => Ok(offset),
+    }
+}
+
+fn parse_signed_duration(offset: &str) -> Result<Duration> {
+    // offset should either be %H:%M:%S or %H:%M, or a number of hours, minutes and/or seconds such as "1h30m".
+    // It is negative if it is prefixed with a "-" or followed by "before", and positive if it is prefixed with
+    // a "+", followed by "after" or neither
+    let offset = offset.trim();
+    let (positive, offset) = if offset.ends_with("before") {
+        (false, offset[..offset.len() - "before".len()].trim_end())
+    } else if offset.ends_with("after") {
+        (true, offset[..offset.len() - "after".len()].trim_end())
+    } else {
+        match offset.chars().next() {
+            Some('-') => (false, &offset[1..]),
+            Some('+') => (true, &offset[1..]),
+            _ => (true, offset),
+        }
+    };
 
-    let offset = offset.signed_duration_since(NaiveTime::from_hms(0, 0, 0));
+    let offset = match parse_clock_time(offset) {
+        Ok(time) => time.signed_duration_since(NaiveTime::from_hms(0, 0, 0)),
+        Err(_) => parse_duration_units(offset)?,
+    };
 
     if positive {
         Ok(offset)
@@ -69,6 +324,92 @@ pub fn parse_offset(offset: &str) -> Result<Duration> {
     }
 }
 
+pub fn parse_labelled_offset(offset: &str) -> Result<(String, Duration)> {
+    // an offset along with how it was given, e.g. "30m before", so that it can be shown back as it was written
+    Ok((offset.trim().to_string(), parse_offset(offset)?))
+}
+
+pub fn parse_offset_file(contents: &str) -> Result<Vec<(Event, Duration)>> {
+    // a TOML table of events and their offsets e.g. `sunset = "-30m"`, in the same formats as --offset
+    let table: BTreeMap<String, String> = toml::from_str(contents)
+        .map_err(|_| HeliocronError::Config(ConfigErrorKind::InvalidOffsetFile))?;
+
+    table
+        .iter()
+        .map(|(event, offset)| Ok((Event::new(event)?, parse_offset(offset)?)))
+        .collect()
+}
+
+pub fn parse_horizon_profile(contents: &str) -> Result<HorizonProfile> {
+    // one point per line, an azimuth and an altitude in degrees separated by whitespace or a comma. Blank lines
+    // and lines beginning with '#' are ignored
+    let points = contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| {
+            let fields: Vec<&str> = line
+                .split(|c: char| c == ',' || c.is_whitespace())
+                .filter(|field| !field.is_empty())
+                .collect();
+            match fields.as_slice() {
+                [azimuth, altitude] => match (azimuth.parse(), altitude.parse()) {
+                    (Ok(azimuth), Ok(altitude)) => Ok((azimuth, altitude)),
+                    _ => Err(HeliocronError::Config(ConfigErrorKind::InvalidHorizonFile)),
+                },
+                _ => Err(HeliocronError::Config(ConfigErrorKind::InvalidHorizonFile)),
+            }
+        })
+        .collect::<Result<Vec<(f64, f64)>>>()?;
+
+    HorizonProfile::new(points)
+}
+
+pub fn parse_duration(duration: &str) -> Result<Duration> {
+    // a length of time rather than an offset, so it can't be negative, but it can be longer than a day
+    match parse_signed_duration(duration)? {
+        duration if duration < Duration::zero() => {
+            Err(HeliocronError::Config(ConfigErrorKind::ParseOffset))
+        }
+        duration => Ok(duration),
+    }
+}
+
+fn parse_duration_units(duration: &str) -> Result<Duration> {
+    // e.g. "30m", "1h30m" or "1h 30m 15s"
+    let mut total = Duration::zero();
+    let mut number = String::new();
+    let mut units_seen = false;
+
+    for c in duration.chars().filter(|c| !c.is_whitespace()) {
+        match c {
+            '0'..='9' => number.push(c),
+            'h' | 'm' | 's' if !number.is_empty() => {
+                let n: u32 = number
+                    .parse()
+                    .map_err(|_| HeliocronError::Config(ConfigErrorKind::ParseOffset))?;
+                total = total
+                    .checked_add(&match c {
+                        'h' => Duration::hours(n as i64),
+                        'm' => Duration::minutes(n as i64),
+                        _ => Duration::seconds(n as i64),
+                    })
+                    .ok_or(HeliocronError::Config(ConfigErrorKind::ParseOffset))?;
+                number.clear();
+                units_seen = true;
+            }
+            _ => return Err(HeliocronError::Config(ConfigErrorKind::ParseOffset)),
+        }
+    }
+
+    // a trailing number without a unit is ambiguous
+    if units_seen && number.is_empty() {
+        Ok(total)
+    } else {
+        Err(HeliocronError::Config(ConfigErrorKind::ParseOffset))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -105,6 +446,389 @@ mod tests {
         let _result = parse_date("2020-03-25", "%Y-%m-%d", Some("00:00")).unwrap();
     }
 
+    #[test]
+    fn test_parse_datetime() {
+        let expected = DateTime::parse_from_rfc3339("2020-03-25T15:30:00+01:00").unwrap();
+        let result = parse_datetime("2020-03-25T15:30:00", Some("+01:00")).unwrap();
+        assert_eq!(expected, result);
+        assert_eq!(expected.offset(), result.offset());
+
+        let result = parse_datetime("2020-03-25T15:30", Some("+01:00")).unwrap();
+        assert_eq!(expected, result);
+
+        // a date on its own isn't enough
+        assert!(parse_datetime("2020-03-25", Some("+01:00")).is_err());
+        assert!(parse_datetime("2020-03-25T25:00", Some("+01:00")).is_err());
+    }
+
+    #[test]
+    fn test_parse_day() {
+        assert_eq!(
+            NaiveDate::from_ymd(2020, 3, 25),
+            parse_day("2020-03-25").unwrap()
+        );
+        assert!(parse_day("2020-02-30").is_err());
+        assert!(parse_day("25/03/2020").is_err());
+    }
+
+    #[test]
+    fn test_parse_time() {
+        let params = [
+            (NaiveTime::from_hms(3, 30, 0), "03:30"),
+            (NaiveTime::from_hms(3, 30, 0), "03:30:00"),
+            (NaiveTime::from_hms(23, 59, 59), "23:59:59"),
+        ];
+
+        for (expected, arg) in params.iter() {
+            assert_eq!(*expected, parse_time(arg).unwrap());
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_parse_time_fails() {
+        let _time = parse_time("25:00").unwrap();
+    }
+
+    #[test]
+    fn test_parse_day_fraction() {
+        let params = [
+            (NaiveTime::from_hms(0, 0, 0), "0.0"),
+            (NaiveTime::from_hms(6, 0, 0), "0.25"),
+            (NaiveTime::from_hms(18, 0, 0), ".75"),
+            (NaiveTime::from_hms(3, 36, 0), "0.15"),
+            (NaiveTime::from_hms(23, 59, 59), "0.9999999"),
+        ];
+        for (expected, arg) in params.iter() {
+            assert_eq!(*expected, parse_time(arg).unwrap());
+        }
+
+        for arg in ["1.0", "-0.25", "0.2.5", "1.5"].iter() {
+            assert!(parse_time(arg).is_err(), "{}", arg);
+        }
+        // offsets are lengths of time, not times of day
+        assert!(parse_offset("0.25").is_err());
+    }
+
+    #[test]
+    fn test_parse_offset() {
+        let params = [
+            (Duration::minutes(30), "00:30"),
+            (Duration::minutes(-30), "-00:30:00"),
+            (Duration::minutes(30), "30m"),
+            (Duration::minutes(-30), "-30m"),
+            (Duration::minutes(-30), "30m before"),
+            (Duration::hours(1), "1h after"),
+            (Duration::minutes(90), "1h30m after"),
+            (Duration::seconds(-5415), "1h 30m 15s before"),
+            (Duration::seconds(45), "45s"),
+            (Duration::minutes(-90), "01:30 before"),
+            (Duration::minutes(90), "90mafter"),
+        ];
+
+        for (expected, arg) in params.iter() {
+            assert_eq!(*expected, parse_offset(arg).unwrap(), "{}", arg);
+        }
+
+        // an offset can be up to a day either way, but no more
+        assert_eq!(Duration::hours(24), parse_offset("24h").unwrap());
+        assert_eq!(Duration::hours(-24), parse_offset("1440m before").unwrap());
+        for arg in ["24h1s", "-86401s", "4000000000h"].iter() {
+            match parse_offset(arg) {
+                Err(HeliocronError::Config(ConfigErrorKind::ParseOffset)) => (),
+                _ => panic!("{} should be out of range", arg),
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_offset_plus_sign() {
+        // a positive offset may be prefixed with a "+", but only once and not together with a "-"
+        assert_eq!(Duration::minutes(15), parse_offset("+15m").unwrap());
+        assert_eq!(Duration::minutes(15), parse_offset("+00:15").unwrap());
+        for arg in ["++15m", "+-15m", "+", "+15m before"].iter() {
+            assert!(parse_offset(arg).is_err(), "{}", arg);
+        }
+    }
+
+    #[test]
+    fn test_parse_labelled_offset() {
+        assert_eq!(
+            ("30m before".to_string(), Duration::minutes(-30)),
+            parse_labelled_offset(" 30m before").unwrap()
+        );
+        assert_eq!(
+            ("+00:15".to_string(), Duration::minutes(15)),
+            parse_labelled_offset("+00:15").unwrap()
+        );
+        assert!(parse_labelled_offset("soon").is_err());
+    }
+
+    #[test]
+    fn test_parse_offset_file() {
+        let offsets = parse_offset_file(
+            "# comments and blank lines are allowed\n\nsunrise = \"+15m\"\nsunset = \"30m before\"\n",
+        )
+        .unwrap();
+        assert_eq!(
+            vec![
+                (Event::Sunrise, Duration::minutes(15)),
+                (Event::Sunset, Duration::minutes(-30))
+            ],
+            offsets
+        );
+        assert!(parse_offset_file("").unwrap().is_empty());
+
+        match parse_offset_file("sunrse = \"15m\"") {
+            Err(HeliocronError::Config(ConfigErrorKind::UnknownEvent(event, Some("sunrise")))) => {
+                assert_eq!("sunrse", event)
+            }
+            _ => panic!("the event should be unknown"),
+        }
+        for contents in ["sunrise = \"15x\"", "sunrise = 15", "sunrise"].iter() {
+            assert!(parse_offset_file(contents).is_err(), "{}", contents);
+        }
+    }
+
+    #[test]
+    fn test_parse_horizon_profile() {
+        let profile =
+            parse_horizon_profile("# azimuth altitude\n\n0 2\n180, 6.0\n  270\t4 \n").unwrap();
+        assert_eq!(
+            HorizonProfile::new(vec![(0.0, 2.0), (180.0, 6.0), (270.0, 4.0)]).unwrap(),
+            profile
+        );
+
+        for contents in ["", "# nothing", "90", "90 2 3", "east 2", "400 2", "90 95"].iter() {
+            assert!(parse_horizon_profile(contents).is_err(), "{}", contents);
+        }
+    }
+
+    #[test]
+    fn test_parse_offset_fails() {
+        let params = [
+            "",
+            "30",
+            "30 before",
+            "before",
+            "-30m before",
+            "30x",
+            "m30",
+            "1.5h",
+            "30m ago",
+            "-",
+            "+",
+            "+30m after",
+            "+-30m",
+        ];
+
+        for arg in params.iter() {
+            assert!(parse_offset(arg).is_err(), "{}", arg);
+        }
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(Duration::hours(12), parse_duration("12:00").unwrap());
+        assert_eq!(Duration::hours(24), parse_duration("24h").unwrap());
+        assert_eq!(Duration::hours(48), parse_duration("48h").unwrap());
+        assert!(parse_duration("-12:00").is_err());
+        assert!(parse_duration("12h before").is_err());
+    }
+
+    #[test]
+    fn test_parse_coordinates() {
+        let params = [
+            ((51.4769, -0.0005), "51.4769, -0.0005"),
+            ((-33.8688, 151.2093), "-33.8688,151.2093"),
+            ((0.0, 0.0), " 0 , 0 "),
+        ];
+
+        for ((latitude, longitude), arg) in params.iter() {
+            let coordinates = parse_coordinates(arg).unwrap();
+            assert_eq!(*latitude, coordinates.latitude.value);
+            assert_eq!(*longitude, coordinates.longitude.value);
+        }
+    }
+
+    #[test]
+    fn test_parse_coordinates_fails() {
+        let params = [
+            "",
+            "51.4769",
+            "51.4769 -0.0005",
+            "51.4769, -0.0005, 10",
+            "51.4769N, 0.0005W",
+            "91.0, 0.0",
+            "0.0, 181.0",
+            "NaN, 0.0",
+        ];
+
+        for arg in params.iter() {
+            assert!(parse_coordinates(arg).is_err(), "{}", arg);
+        }
+    }
+
+    #[test]
+    fn test_parse_bounding_box() {
+        let params = [
+            ((51.485, -0.09), "51.28, -0.51, 51.69, 0.33"),
+            ((-33.5, 151.0), "-34,150,-33,152"),
+            // across the antimeridian, either side of it
+            ((-17.0, 179.0), "-18, 178, -16, -180"),
+            ((-17.0, -179.5), "-18, 179, -16, -178"),
+        ];
+
+        for ((latitude, longitude), arg) in params.iter() {
+            let coordinates = parse_bounding_box(arg).unwrap();
+            assert!(
+                (latitude - coordinates.latitude.value).abs() < 1e-9,
+                "{}",
+                arg
+            );
+            assert!(
+                (longitude - coordinates.longitude.value).abs() < 1e-9,
+                "{}",
+                arg
+            );
+        }
+
+        let params = [
+            "",
+            "51.28, -0.51",
+            "51.28, -0.51, 51.69",
+            "51.69, -0.51, 51.28, 0.33",
+            "51.28, -0.51, 91.0, 0.33",
+            "51.28, -181.0, 51.69, 0.33",
+            "51.28N, 0.51W, 51.69N, 0.33E",
+        ];
+
+        for arg in params.iter() {
+            assert!(parse_bounding_box(arg).is_err(), "{}", arg);
+        }
+    }
+
+    #[test]
+    fn test_parse_location() {
+        let location = parse_location("51.4769, -0.0005").unwrap();
+        assert_eq!(51.4769, location.coordinates.latitude.value);
+        assert!(location.time_zone.is_none());
+
+        let location = parse_location("40.6413,-73.7781,-04:00").unwrap();
+        assert_eq!(-73.7781, location.coordinates.longitude.value);
+        assert_eq!(-4 * 3600, location.time_zone.unwrap().local_minus_utc());
+
+        for arg in [
+            "",
+            "51.4769",
+            "51.4769, -0.0005, 10",
+            "51.4769, -0.0005, +01:00, x",
+            "91.0, 0.0, +01:00",
+        ]
+        .iter()
+        {
+            assert!(parse_location(arg).is_err(), "{}", arg);
+        }
+    }
+
+    #[test]
+    fn test_parse_flight_time() {
+        let departure = parse_flight_time("2020-06-21T18:00:00+01:00").unwrap();
+        assert_eq!(3600, departure.offset().local_minus_utc());
+        assert_eq!(
+            "2020-06-21T17:00:00+00:00",
+            departure.with_timezone(&FixedOffset::east(0)).to_rfc3339()
+        );
+
+        for arg in ["", "2020-06-21T18:00:00", "2020-06-21", "18:00+01:00"].iter() {
+            assert!(parse_flight_time(arg).is_err(), "{}", arg);
+        }
+    }
+
+    #[test]
+    fn test_parse_filter() {
+        let params = [
+            (
+                (Event::Sunset, Comparator::GreaterOrEqual, (20, 0, 0)),
+                "sunset>=20:00",
+            ),
+            (
+                (Event::Sunrise, Comparator::Less, (5, 30, 15)),
+                "sunrise < 05:30:15",
+            ),
+            (
+                (Event::CivilDusk, Comparator::Equal, (21, 0, 0)),
+                " civil_dusk == 21:00 ",
+            ),
+            (
+                (Event::NauticalDawn, Comparator::NotEqual, (4, 0, 0)),
+                "Nautical_Dawn!=04:00",
+            ),
+        ];
+
+        for ((event, comparator, (h, m, s)), arg) in params.iter() {
+            let filter = parse_filter(arg).unwrap();
+            assert_eq!(*event, filter.event);
+            assert_eq!(*comparator, filter.comparator);
+            assert_eq!(NaiveTime::from_hms(*h, *m, *s), filter.time);
+        }
+    }
+
+    #[test]
+    fn test_parse_filter_fails() {
+        let params = [
+            "",
+            "sunset",
+            "sunset 20:00",
+            ">=20:00",
+            "sunset>=",
+            "sunset=>20:00",
+            "sunset<>20:00",
+            "sunset>=25:00",
+            "sunsett>=20:00",
+            "sunset>=20:00>=21:00",
+        ];
+
+        for arg in params.iter() {
+            assert!(parse_filter(arg).is_err(), "{}", arg);
+        }
+    }
+
+    #[test]
+    fn test_parse_depression() {
+        assert_eq!(9.0, parse_depression("9").unwrap());
+        assert_eq!(-4.5, parse_depression(" -4.5 ").unwrap());
+        assert_eq!(90.0, parse_depression("90").unwrap());
+
+        for arg in ["91", "-90.5", "nine", "", "NaN"].iter() {
+            assert!(parse_depression(arg).is_err(), "{}", arg);
+        }
+    }
+
+    #[test]
+    fn test_parse_atmosphere() {
+        assert_eq!(1013.25, parse_pressure(" 1013.25").unwrap());
+        assert_eq!(-12.5, parse_temperature("-12.5").unwrap());
+
+        for arg in ["0", "-5", "1100.1", "high", "", "NaN"].iter() {
+            assert!(parse_pressure(arg).is_err(), "{}", arg);
+        }
+        for arg in ["-91", "61", "warm", "", "NaN"].iter() {
+            assert!(parse_temperature(arg).is_err(), "{}", arg);
+        }
+    }
+
+    #[test]
+    fn test_parse_illuminance() {
+        assert_eq!(6.0, parse_illuminance("3.4").unwrap());
+        assert_eq!(0.0, parse_illuminance(" 400 ").unwrap());
+        assert_eq!(7.21, parse_illuminance("1").unwrap());
+
+        for arg in ["401", "0", "-1", "bright", ""].iter() {
+            assert!(parse_illuminance(arg).is_err(), "{}", arg);
+        }
+    }
+
     #[test]
     fn test_parse_event() {
         let params = [
@@ -113,10 +837,30 @@ mod tests {
             (Event::Sunrise, "  sunrisE"),
             (Event::Sunset, "sunset"),
             (Event::Sunset, "sunSET  "),
+            (Event::Sunrise, "SUNRISE"),
+            (Event::CivilDusk, " Civil_Dusk\n"),
         ];
 
         for (expected, arg) in params.iter() {
-            assert_eq!(*expected, parse_event(*arg).unwrap());
+            assert_eq!(*expected, parse_event(arg).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_parse_event_display_round_trip() {
+        let events = [
+            Event::Sunrise,
+            Event::Sunset,
+            Event::CivilDawn,
+            Event::CivilDusk,
+            Event::NauticalDawn,
+            Event::NauticalDusk,
+            Event::AstronomicalDawn,
+            Event::AstronomicalDusk,
+        ];
+
+        for event in events.iter() {
+            assert_eq!(*event, parse_event(&event.to_string()).unwrap());
         }
     }
 
@@ -125,4 +869,31 @@ mod tests {
     fn test_parse_event_fails() {
         let _event = parse_event("sun rise").unwrap();
     }
+
+    #[test]
+    fn test_parse_event_suggestion() {
+        let params = [
+            (Some("sunrise"), "sunris"),
+            (Some("sunrise"), "Sun rise"),
+            (Some("civil_dusk"), "civil-dusk"),
+            (Some("astronomical_dawn"), "astronomicaldawn"),
+            (None, "midnight"),
+            (None, ""),
+        ];
+
+        for (expected, arg) in params.iter() {
+            match parse_event(arg) {
+                Err(HeliocronError::Config(ConfigErrorKind::UnknownEvent(event, suggestion))) => {
+                    assert_eq!(arg.trim(), event);
+                    assert_eq!(*expected, suggestion);
+                }
+                _ => panic!("{} should be an unknown event", arg),
+            }
+        }
+
+        assert_eq!(
+            "Config error: Unknown event 'sunris', did you mean 'sunrise'?",
+            parse_event("sunris").unwrap_err().to_string()
+        );
+    }
 }
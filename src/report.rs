@@ -1,24 +1,39 @@
-#[path = "./traits.rs"]
-mod traits;
+use std::{fmt, result};
 
-use std::fmt;
-
-use chrono::{DateTime, Duration, FixedOffset, Local, NaiveTime, Offset, TimeZone, Timelike};
+use chrono::{
+    DateTime, Duration, FixedOffset, Local, NaiveDate, NaiveDateTime, NaiveTime, Offset, TimeZone,
+    Utc,
+};
+use serde::{Serialize, Serializer};
+use serde_json::json;
 
 use super::{
-    enums, structs,
+    enums,
+    errors::{ConfigErrorKind, HeliocronError},
+    lunar,
+    solar::{
+        altitude_rate, delta_t, sidereal_time, solar_hour_angle, timing_uncertainty,
+        SolarParameters, SolarPosition,
+    },
+    structs,
     structs::{Coordinate, EventTime},
+    traits::DateTimeExt,
+    utils,
 };
-use traits::DateTimeExt;
 
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
 pub struct SolarReport {
     // required parameters
     pub date: DateTime<FixedOffset>,
+    // serialised by VersionedReport, so that it can be hidden
+    #[serde(skip)]
     pub coordinates: structs::Coordinates,
 
     // these attributes are always calculable
     pub solar_noon: DateTime<FixedOffset>,
+    // the apparent altitude of the centre of the Sun at solar noon, in degrees, which is its highest of the day
+    pub solar_noon_altitude: f64,
+    #[serde(serialize_with = "serialize_seconds")]
     pub day_length: Duration,
 
     // these attributes are sometimes not valid i.e. at high latitudes or
@@ -35,6 +50,95 @@ pub struct SolarReport {
 
     pub astronomical_dawn: EventTime,
     pub astronomical_dusk: EventTime,
+
+    // the definitions of twilight used for the dawns and dusks above
+    #[serde(skip)]
+    pub twilight_angles: structs::TwilightAngles,
+
+    // whether to leave the coordinates out of the output, which still uses them for the calculations
+    #[serde(skip)]
+    pub hide_location: bool,
+
+    // whether to show times as a decimal number of hours past midnight, rather than as the date and time
+    #[serde(skip)]
+    pub decimal_hours: bool,
+
+    // whether to show times as a fraction of the day past midnight, rather than as the date and time
+    #[serde(skip)]
+    pub day_fraction: bool,
+
+    // whether to show times in local apparent solar time, in which solar noon is at 12:00:00, rather than clock
+    // time
+    #[serde(skip)]
+    pub solar_time: bool,
+
+    // whether to show times as a signed offset from solar noon, e.g. -06:53:12 for sunrise
+    #[serde(skip)]
+    pub relative_to_noon: bool,
+
+    // whether to show only the time of day, e.g. 06:04:37, without the date and offset
+    #[serde(skip)]
+    pub time_only: bool,
+
+    // whether to follow each event time with an estimate of its uncertainty, e.g. (±2 min)
+    #[serde(skip)]
+    pub with_accuracy: bool,
+
+    // the language in which to write out the date below the numeric one in the plain report, from --locale
+    #[serde(skip)]
+    pub locale: Option<enums::Locale>,
+
+    // whether to add the astronomical data to the JSON output, as --astro does for the plain report
+    #[serde(skip)]
+    pub include_astro: bool,
+
+    // which pairs of events the plain report shows, from the config file
+    #[serde(skip)]
+    pub sections: structs::ReportSections,
+
+    // whether sunrise and sunset are over the local skyline, from --horizon-file, rather than a level horizon
+    #[serde(skip)]
+    pub local_horizon: bool,
+}
+
+// the version of the JSON output, which follows semantic versioning: the major version is bumped on any
+// change which could break an existing consumer, such as removing or renaming a field
+pub const SCHEMA_VERSION: &str = "1.3.0";
+
+#[derive(Serialize)]
+struct VersionedReport<'a> {
+    schema_version: &'static str,
+    coordinates: Option<&'a structs::Coordinates>,
+    #[serde(flatten)]
+    report: &'a SolarReport,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    astro: Option<AstroData>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AstroData {
+    // for the report's date and time. Angles are in degrees, sidereal times in hours, the equation of time in
+    // minutes, ΔT in seconds, the rates of change of the altitude in degrees per minute and the angular diameter
+    // of the Sun in arcminutes
+    pub julian_date: f64,
+    pub delta_t: f64,
+    pub declination: f64,
+    pub equation_of_time: f64,
+    pub hour_angle: f64,
+    pub greenwich_sidereal_time: f64,
+    pub local_sidereal_time: f64,
+    pub sunrise_altitude_rate: Option<f64>,
+    pub sunset_altitude_rate: Option<f64>,
+    pub angular_diameter: f64,
+}
+
+// the placeholders which can be used in a template (see SolarReport::render_template), along with the name of
+// each event
+pub const TEMPLATE_PLACEHOLDERS: &[&str] =
+    &["date", "latitude", "longitude", "solar_noon", "day_length"];
+
+fn serialize_seconds<S: Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_i64(duration.num_seconds())
 }
 
 impl Default for SolarReport {
@@ -45,6 +149,7 @@ impl Default for SolarReport {
         let default_day_length = Duration::seconds(0);
         SolarReport {
             solar_noon: default_datetime,
+            solar_noon_altitude: 0.0,
             sunrise: EventTime::from(None),
             sunset: EventTime::from(None),
             civil_dawn: EventTime::from(None),
@@ -56,6 +161,18 @@ impl Default for SolarReport {
             day_length: default_day_length,
             date: local_time.with_timezone(&FixedOffset::from_offset(local_time.offset())),
             coordinates: structs::Coordinates::from_decimal_degrees("0.0N", "0.0W").unwrap(),
+            twilight_angles: structs::TwilightAngles::default(),
+            hide_location: false,
+            decimal_hours: false,
+            day_fraction: false,
+            solar_time: false,
+            relative_to_noon: false,
+            time_only: false,
+            with_accuracy: false,
+            locale: None,
+            include_astro: false,
+            sections: structs::ReportSections::default(),
+            local_horizon: false,
         }
     }
 }
@@ -69,303 +186,3205 @@ impl fmt::Display for SolarReport {
 
 impl SolarReport {
     pub fn new(date: DateTime<FixedOffset>, coordinates: structs::Coordinates) -> SolarReport {
+        SolarReport::with_twilight_angles(date, coordinates, structs::TwilightAngles::default())
+    }
+
+    pub fn with_twilight_angles(
+        date: DateTime<FixedOffset>,
+        coordinates: structs::Coordinates,
+        twilight_angles: structs::TwilightAngles,
+    ) -> SolarReport {
+        let constants = LocationConstants::new(&coordinates, &twilight_angles);
+        SolarReport::with_constants(date, coordinates, twilight_angles, &constants)
+    }
+
+    fn with_constants(
+        date: DateTime<FixedOffset>,
+        coordinates: structs::Coordinates,
+        twilight_angles: structs::TwilightAngles,
+        constants: &LocationConstants,
+    ) -> SolarReport {
+        // every field is filled in here rather than from the default, which would look up the local time for
+        // each report only to replace it
         let mut report = SolarReport {
             date,
             coordinates,
-            ..Default::default()
+            solar_noon: date,
+            solar_noon_altitude: 0.0,
+            day_length: Duration::zero(),
+            sunrise: EventTime::from(None),
+            sunset: EventTime::from(None),
+            civil_dawn: EventTime::from(None),
+            civil_dusk: EventTime::from(None),
+            nautical_dawn: EventTime::from(None),
+            nautical_dusk: EventTime::from(None),
+            astronomical_dawn: EventTime::from(None),
+            astronomical_dusk: EventTime::from(None),
+            twilight_angles,
+            hide_location: false,
+            decimal_hours: false,
+            day_fraction: false,
+            solar_time: false,
+            relative_to_noon: false,
+            time_only: false,
+            with_accuracy: false,
+            locale: None,
+            include_astro: false,
+            sections: structs::ReportSections::default(),
+            local_horizon: false,
         };
 
-        report.run();
+        report.run(constants);
 
-        // make it immutable again by default
-        let report = report;
         report
     }
 
+    pub fn event_time(&self, event: &enums::Event) -> &EventTime {
+        match event {
+            enums::Event::Sunrise => &self.sunrise,
+            enums::Event::Sunset => &self.sunset,
+            enums::Event::CivilDawn => &self.civil_dawn,
+            enums::Event::CivilDusk => &self.civil_dusk,
+            enums::Event::NauticalDawn => &self.nautical_dawn,
+            enums::Event::NauticalDusk => &self.nautical_dusk,
+            enums::Event::AstronomicalDawn => &self.astronomical_dawn,
+            enums::Event::AstronomicalDusk => &self.astronomical_dusk,
+        }
+    }
+
+    pub fn base_time(&self, base: &enums::EventBase) -> EventTime {
+        // an altitude of the config file's own is crossed in the same way as the definitions of twilight
+        match base {
+            enums::EventBase::Standard(event) => EventTime::new(self.event_time(event).datetime),
+            enums::EventBase::Elevation(_, altitude, direction) => {
+                let (rising, setting) = self.crossings(-altitude);
+                match direction {
+                    enums::Direction::Rising => rising,
+                    enums::Direction::Setting => setting,
+                }
+            }
+        }
+    }
+
+    pub fn mark_time(&self, mark: &enums::DayMark) -> Option<DateTime<FixedOffset>> {
+        // solar midnight is the one after the day's solar noon, which is twelve hours later to within half a
+        // minute, as the equation of time changes so slowly
+        match mark {
+            enums::DayMark::SolarNoon => Some(self.solar_noon),
+            enums::DayMark::SolarMidnight => Some(self.solar_noon + Duration::hours(12)),
+            enums::DayMark::Event(event) => self.event_time(event).datetime,
+        }
+    }
+
+    pub fn time_or_fallback(
+        &self,
+        event: &enums::EventBase,
+        fallback: Option<enums::DayMark>,
+    ) -> (Option<DateTime<FixedOffset>>, Option<enums::DayMark>) {
+        // the time of the event, or, if it doesn't occur on the day, of the fallback, along with the fallback
+        // if it was used
+        match (self.base_time(event).datetime, fallback) {
+            (Some(time), _) => (Some(time), None),
+            (None, Some(fallback)) => match self.mark_time(&fallback) {
+                Some(time) => (Some(time), Some(fallback)),
+                None => (None, None),
+            },
+            (None, None) => (None, None),
+        }
+    }
+
+    pub fn to_json(&self, compact: bool) -> String {
+        utils::to_json(&self.versioned(), compact)
+    }
+
+    pub fn to_json_array(reports: &[SolarReport], compact: bool) -> String {
+        let reports: Vec<VersionedReport> = reports.iter().map(SolarReport::versioned).collect();
+        utils::to_json(&reports, compact)
+    }
+
+    pub fn to_delimited(reports: &[SolarReport], delimiter: char) -> String {
+        // one row per day, with the fields separated by commas for CSV or tabs for TSV. Times are in RFC 3339
+        // format (or decimal hours, see --decimal-hours, or offsets from solar noon, see --relative-to-noon), or
+        // empty if the event doesn't occur, and the day length is in seconds
+        let mut table = [
+            "date",
+            "solar_noon",
+            "day_length",
+            "sunrise",
+            "sunset",
+            "civil_dawn",
+            "civil_dusk",
+            "nautical_dawn",
+            "nautical_dusk",
+            "astronomical_dawn",
+            "astronomical_dusk",
+        ]
+        .join(&delimiter.to_string());
+        table.push('\n');
+        for report in reports {
+            let time = |datetime| report.machine_time(datetime);
+            let events = [
+                &report.sunrise,
+                &report.sunset,
+                &report.civil_dawn,
+                &report.civil_dusk,
+                &report.nautical_dawn,
+                &report.nautical_dusk,
+                &report.astronomical_dawn,
+                &report.astronomical_dusk,
+            ];
+
+            let mut row = vec![
+                report.date.format("%Y-%m-%d").to_string(),
+                time(Some(report.solar_noon)),
+                report.day_length.num_seconds().to_string(),
+            ];
+            row.extend(events.iter().map(|event| time(event.datetime)));
+            table.push_str(&row.join(&delimiter.to_string()));
+            table.push('\n');
+        }
+        table
+    }
+
+    pub fn to_chart_data(reports: &[SolarReport]) -> String {
+        // one row per day of sunrise and sunset in minutes past local midnight, to plot the curves of a year
+        // without parsing clock times, or empty where the Sun doesn't rise or set
+        let mut csv = String::from("date,sunrise_minutes,sunset_minutes\n");
+        for report in reports {
+            let minutes = |event_time: &EventTime| match event_time.datetime {
+                Some(datetime) => format!("{:.2}", report.hours_past_midnight(datetime) * 60.0),
+                None => String::new(),
+            };
+            csv.push_str(&format!(
+                "{},{},{}\n",
+                report.date.format("%Y-%m-%d"),
+                minutes(&report.sunrise),
+                minutes(&report.sunset)
+            ));
+        }
+        csv
+    }
+
+    pub fn to_shell(&self) -> String {
+        // variable assignments for a shell to eval, in the same formats as the CSV. Every value is single quoted,
+        // so that the shell takes it literally
+        let (latitude, longitude) = if self.hide_location {
+            (String::new(), String::new())
+        } else {
+            (
+                self.coordinates.latitude.value.to_string(),
+                self.coordinates.longitude.value.to_string(),
+            )
+        };
+        let mut variables = vec![
+            ("DATE".to_string(), self.date.format("%Y-%m-%d").to_string()),
+            ("LATITUDE".to_string(), latitude),
+            ("LONGITUDE".to_string(), longitude),
+            (
+                "SOLAR_NOON".to_string(),
+                self.machine_time(Some(self.solar_noon)),
+            ),
+            (
+                "DAY_LENGTH".to_string(),
+                self.day_length.num_seconds().to_string(),
+            ),
+        ];
+        for event in enums::Event::all().iter() {
+            variables.push((
+                event.to_string().to_uppercase(),
+                self.machine_time(self.event_time(event).datetime),
+            ));
+        }
+
+        variables
+            .iter()
+            .map(|(name, value)| format!("HELIOCRON_{}={}\n", name, utils::shell_quote(value)))
+            .collect()
+    }
+
+    fn machine_time(&self, datetime: Option<DateTime<FixedOffset>>) -> String {
+        // RFC 3339, unless decimal hours, fractions of the day, offsets from solar noon or times of day on their
+        // own were asked for, or empty if the event doesn't occur
+        match datetime {
+            Some(datetime) if self.is_numeric_time() || self.relative_to_noon || self.time_only => {
+                self.format_time(datetime)
+            }
+            Some(datetime) => datetime.to_rfc3339(),
+            None => String::new(),
+        }
+    }
+
+    pub fn format_comparison(reports: &[SolarReport]) -> String {
+        // a column for each location, with its times in its own time zone, e.g. to compare dawn where a
+        // flight leaves with dusk where it lands
+        let mut rows: Vec<(String, Vec<String>)> = vec![
+            (
+                "Location".to_string(),
+                reports
+                    .iter()
+                    .enumerate()
+                    .map(|(i, report)| {
+                        if report.hide_location {
+                            format!("Location {}", i + 1)
+                        } else {
+                            let (latitude, longitude) = report.coordinates.to_decimal_degrees();
+                            format!("{} {}", latitude, longitude)
+                        }
+                    })
+                    .collect(),
+            ),
+            (
+                "Time zone".to_string(),
+                reports
+                    .iter()
+                    .map(|report| report.date.offset().to_string())
+                    .collect(),
+            ),
+            (
+                "Solar noon".to_string(),
+                reports
+                    .iter()
+                    .map(|report| report.format_time(report.solar_noon))
+                    .collect(),
+            ),
+            (
+                "Day length".to_string(),
+                reports
+                    .iter()
+                    .map(|report| report.day_length_hms())
+                    .collect(),
+            ),
+        ];
+        for event in enums::Event::all().iter() {
+            rows.push((
+                event.description(),
+                reports
+                    .iter()
+                    .map(|report| report.format_event_time(report.event_time(event)))
+                    .collect(),
+            ));
+        }
+
+        // every column is as wide as its widest value, plus a gap before the next one
+        let label_width = rows
+            .iter()
+            .map(|(label, _)| label.len() + 1)
+            .max()
+            .unwrap_or(0)
+            + 2;
+        let widths: Vec<usize> = (0..reports.len())
+            .map(|i| {
+                rows.iter()
+                    .map(|(_, values)| values[i].chars().count())
+                    .max()
+                    .unwrap_or(0)
+                    + 2
+            })
+            .collect();
+
+        rows.iter()
+            .map(|(label, values)| {
+                let mut line = format!("{:width$}", format!("{}:", label), width = label_width);
+                for (value, width) in values.iter().zip(widths.iter()) {
+                    line.push_str(&format!("{:width$}", value, width = width));
+                }
+                line.trim_end().to_string()
+            })
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+
+    pub fn format_difference(&self, other: &SolarReport) -> String {
+        // how much later each event is on this day than on the other, by the clock, e.g. how much earlier
+        // sunrise is than a month ago. An event just past midnight is compared across midnight, rather than
+        // as the time of day on its own
+        let date = self.date.naive_local().date();
+        let other_date = other.date.naive_local().date();
+        let days_apart = Duration::days((date - other_date).num_days());
+        let difference = |time: DateTime<FixedOffset>, other_time: DateTime<FixedOffset>| {
+            utils::format_offset(time.naive_local() - other_time.naive_local() - days_apart)
+        };
+
+        let title = format!("CHANGE SINCE {}", other_date);
+        let mut lines = vec![
+            title.clone(),
+            "-".repeat(title.len()),
+            format!(
+                "{:<26}{}",
+                "Solar noon:",
+                difference(self.solar_noon, other.solar_noon)
+            ),
+            format!(
+                "{:<26}{}",
+                "Day length:",
+                utils::format_offset(self.day_length - other.day_length)
+            ),
+        ];
+        for event in enums::Event::all().iter() {
+            let change = match (
+                self.event_time(event).datetime,
+                other.event_time(event).datetime,
+            ) {
+                (Some(time), Some(other_time)) => difference(time, other_time),
+                (Some(_), None) => format!("Only on {}", date),
+                (None, Some(_)) => format!("Only on {}", other_date),
+                (None, None) => "Never".to_string(),
+            };
+            lines.push(format!(
+                "{:<26}{}",
+                format!("{}:", event.description()),
+                change
+            ));
+        }
+        lines.join("\n")
+    }
+
+    pub fn to_ics(
+        reports: &[SolarReport],
+        events: &[enums::Event],
+        created: DateTime<Utc>,
+    ) -> String {
+        // an iCalendar file (RFC 5545) with an event for each time, given in the offset the report was resolved
+        // to, so that it matches the other formats. Each offset other than UTC needs a time zone of its own,
+        // named after it, which has just the one fixed offset. Events which don't occur on a day are left out
+        let utc = |datetime: DateTime<FixedOffset>| {
+            datetime
+                .with_timezone(&Utc)
+                .format("%Y%m%dT%H%M%SZ")
+                .to_string()
+        };
+        let time_zone = |offset: &FixedOffset| format!("UTC{}", offset);
+        let start = |datetime: DateTime<FixedOffset>| {
+            if datetime.offset().local_minus_utc() == 0 {
+                format!("DTSTART:{}", utc(datetime))
+            } else {
+                format!(
+                    "DTSTART;TZID={}:{}",
+                    time_zone(datetime.offset()),
+                    datetime.format("%Y%m%dT%H%M%S")
+                )
+            }
+        };
+
+        let mut lines = vec![
+            "BEGIN:VCALENDAR".to_string(),
+            "VERSION:2.0".to_string(),
+            format!(
+                "PRODID:-//heliocron//heliocron {}//EN",
+                env!("CARGO_PKG_VERSION")
+            ),
+        ];
+        let mut offsets: Vec<FixedOffset> = vec![];
+        for report in reports {
+            for event in events {
+                if let Some(datetime) = report.event_time(event).datetime {
+                    let offset = *datetime.offset();
+                    if offset.local_minus_utc() != 0 && !offsets.contains(&offset) {
+                        offsets.push(offset);
+                    }
+                }
+            }
+        }
+        for offset in offsets.iter() {
+            let offset_value = offset.to_string().replace(":", "");
+            lines.push("BEGIN:VTIMEZONE".to_string());
+            lines.push(format!("TZID:{}", time_zone(offset)));
+            lines.push("BEGIN:STANDARD".to_string());
+            lines.push("DTSTART:19700101T000000".to_string());
+            lines.push(format!("TZOFFSETFROM:{}", offset_value));
+            lines.push(format!("TZOFFSETTO:{}", offset_value));
+            lines.push("END:STANDARD".to_string());
+            lines.push("END:VTIMEZONE".to_string());
+        }
+        for report in reports {
+            for event in events {
+                if let Some(datetime) = report.event_time(event).datetime {
+                    lines.push("BEGIN:VEVENT".to_string());
+                    lines.push(format!("UID:{}-{}@heliocron", event, utc(datetime)));
+                    lines.push(format!("DTSTAMP:{}", utc(created.into())));
+                    lines.push(start(datetime));
+                    lines.push(format!("SUMMARY:{}", event.description()));
+                    if !report.hide_location {
+                        lines.push(format!(
+                            "GEO:{};{}",
+                            report.coordinates.latitude.value, report.coordinates.longitude.value
+                        ));
+                    }
+                    lines.push("END:VEVENT".to_string());
+                }
+            }
+        }
+        lines.push("END:VCALENDAR".to_string());
+
+        // lines end with CRLF
+        lines.iter().map(|line| format!("{}\r\n", line)).collect()
+    }
+
+    fn versioned(&self) -> VersionedReport<'_> {
+        VersionedReport {
+            schema_version: SCHEMA_VERSION,
+            coordinates: if self.hide_location {
+                None
+            } else {
+                Some(&self.coordinates)
+            },
+            report: self,
+            astro: if self.include_astro {
+                Some(self.astro_data())
+            } else {
+                None
+            },
+        }
+    }
+
+    pub fn astro_data(&self) -> AstroData {
+        let julian_date = self.date.to_julian_date();
+        let parameters = SolarParameters::from_julian_date(julian_date);
+        let greenwich_sidereal_time = sidereal_time(julian_date) / 15.0;
+        let altitude_rate = |event_time: &EventTime| {
+            event_time
+                .datetime
+                .map(|datetime| altitude_rate(datetime, self.coordinates))
+        };
+
+        AstroData {
+            julian_date,
+            delta_t: delta_t(julian_date),
+            declination: parameters.declination,
+            equation_of_time: parameters.equation_of_time,
+            hour_angle: solar_hour_angle(self.date, self.coordinates),
+            greenwich_sidereal_time,
+            local_sidereal_time: (greenwich_sidereal_time
+                + self.coordinates.longitude.value / 15.0)
+                .rem_euclid(24.0),
+            sunrise_altitude_rate: altitude_rate(&self.sunrise),
+            sunset_altitude_rate: altitude_rate(&self.sunset),
+            angular_diameter: parameters.angular_diameter(),
+        }
+    }
+
+    pub fn hours_past_midnight(&self, datetime: DateTime<FixedOffset>) -> f64 {
+        // measured from the start of the report's day, so that times on the following day are over 24
+        let midnight = self.date.naive_local().date().and_hms(0, 0, 0);
+        let time = if self.solar_time {
+            self.to_solar_time(datetime)
+        } else {
+            datetime.naive_local()
+        };
+        (time - midnight).num_seconds() as f64 / 3600.0
+    }
+
+    pub fn to_solar_time(&self, datetime: DateTime<FixedOffset>) -> NaiveDateTime {
+        // local apparent solar time, measured from the Sun's transit at solar noon. The time of solar noon
+        // already includes the longitude and equation of time corrections, and the equation of time changes by
+        // well under a second over a day, so this is the same as applying them to each time separately
+        self.date.naive_local().date().and_hms(12, 0, 0) + (datetime - self.solar_noon)
+    }
+
+    pub fn is_numeric_time(&self) -> bool {
+        // whether times are shown as numbers, of hours or of fractions of the day
+        self.decimal_hours || self.day_fraction
+    }
+
+    pub fn format_time(&self, datetime: DateTime<FixedOffset>) -> String {
+        if self.relative_to_noon {
+            let offset = datetime - self.solar_noon;
+            if self.decimal_hours {
+                format!("{:+.4}", offset.num_seconds() as f64 / 3600.0)
+            } else if self.day_fraction {
+                format!("{:+.5}", offset.num_seconds() as f64 / 86400.0)
+            } else {
+                utils::format_offset(offset)
+            }
+        } else if self.decimal_hours {
+            format!("{:.4}", self.hours_past_midnight(datetime))
+        } else if self.day_fraction {
+            // five decimal places is to within a second
+            format!("{:.5}", self.hours_past_midnight(datetime) / 24.0)
+        } else if self.solar_time && self.time_only {
+            format!(
+                "{} solar time",
+                self.to_solar_time(datetime).format("%H:%M:%S")
+            )
+        } else if self.solar_time {
+            format!("{} solar time", self.to_solar_time(datetime))
+        } else if self.time_only {
+            datetime.format("%H:%M:%S").to_string()
+        } else {
+            datetime.to_string()
+        }
+    }
+
+    fn format_event_time(&self, event_time: &EventTime) -> String {
+        match event_time.datetime {
+            Some(datetime) => format!(
+                "{}{}",
+                self.format_time(datetime),
+                self.format_uncertainty(datetime)
+            ),
+            None => event_time.to_string(),
+        }
+    }
+
+    pub fn format_uncertainty(&self, event_time: DateTime<FixedOffset>) -> String {
+        // the estimated uncertainty of an event at this time, if it was asked for, in seconds up to a minute
+        // and in whole minutes, rounded up, beyond that
+        if !self.with_accuracy {
+            return String::new();
+        }
+        let seconds = timing_uncertainty(event_time, self.coordinates).num_seconds();
+        if seconds < 60 {
+            format!(" (±{}s)", seconds)
+        } else {
+            format!(" (±{} min)", (seconds + 59) / 60)
+        }
+    }
+
+    pub fn render_template(&self, template: &str) -> result::Result<String, HeliocronError> {
+        // placeholders are names in braces e.g. "{sunrise}", in the same formats as the plain report, whilst
+        // "{{" and "}}" are literal braces
+        let mut rendered = String::new();
+        let mut chars = template.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.as_str().starts_with('{') => {
+                    chars.next();
+                    rendered.push('{');
+                }
+                '}' if chars.as_str().starts_with('}') => {
+                    chars.next();
+                    rendered.push('}');
+                }
+                '{' => {
+                    let rest = chars.as_str();
+                    let end = rest.find('}').ok_or(HeliocronError::Config(
+                        ConfigErrorKind::InvalidTemplate(
+                            "a '{' is never closed, use '{{' for a literal brace.",
+                        ),
+                    ))?;
+                    rendered.push_str(&self.render_placeholder(&rest[..end])?);
+                    chars = rest[end + 1..].chars();
+                }
+                '}' => {
+                    return Err(HeliocronError::Config(ConfigErrorKind::InvalidTemplate(
+                        "a '}' is never opened, use '}}' for a literal brace.",
+                    )))
+                }
+                _ => rendered.push(c),
+            }
+        }
+        Ok(rendered)
+    }
+
+    fn render_placeholder(&self, placeholder: &str) -> result::Result<String, HeliocronError> {
+        // the coordinates without their "Latitude: " or "Longitude: " labels
+        let coordinate = |coordinate: String| match coordinate.splitn(2, ": ").nth(1) {
+            _ if self.hide_location => "Hidden".to_string(),
+            Some(value) => value.to_string(),
+            None => coordinate,
+        };
+        match placeholder.trim() {
+            "date" => Ok(self.date.to_string()),
+            "latitude" => Ok(coordinate(self.coordinates.latitude.to_string())),
+            "longitude" => Ok(coordinate(self.coordinates.longitude.to_string())),
+            "solar_noon" => Ok(self.format_time(self.solar_noon)),
+            "day_length" => Ok(self.day_length_hms()),
+            name => match enums::Event::new(name) {
+                Ok(event) => Ok(self.format_event_time(self.event_time(&event))),
+                Err(_) => Err(HeliocronError::Config(ConfigErrorKind::UnknownPlaceholder(
+                    name.to_string(),
+                ))),
+            },
+        }
+    }
+
     fn format_report(&self) -> String {
-        format!(
+        let mut report = self.format_events();
+        if !self.twilight_angles.is_standard() {
+            report.push_str(&format!(
+                "\nNote: non-standard twilight angles are in effect ({}).\n",
+                self.twilight_angles
+            ));
+        }
+        if self.local_horizon {
+            report.push_str(
+                "\nNote: sunrise and sunset are over the skyline from the horizon file.\n",
+            );
+        }
+        report
+    }
+
+    fn format_location(&self) -> String {
+        if self.hide_location {
+            "Hidden".to_string()
+        } else {
+            format!(
+                "{}\n{}",
+                self.coordinates.latitude, self.coordinates.longitude
+            )
+        }
+    }
+
+    fn format_date(&self) -> String {
+        match self.locale {
+            Some(locale) => format!("{}\n{}", self.date, locale.format_date(&self.date)),
+            None => self.date.to_string(),
+        }
+    }
+
+    fn format_events(&self) -> String {
+        let mut events = format!(
             "LOCATION\n\
         --------\n\
-        {}\n\
         {}\n\n\
         DATE\n\
         ----\n\
         {}\n\n\
         Solar noon is at:         {}\n\
-        The day length is:        {}\n\n\
-        Sunrise is at:            {}\n\
-        Sunset is at:             {}\n\n\
-        Civil dawn is at:         {}\n\
-        Civil dusk is at:         {}\n\n\
-        Nautical dawn is at:      {}\n\
-        Nautical dusk is at:      {}\n\n\
-        Astronomical dawn is at:  {}\n\
-        Astronomical dusk is at:  {}
-        ",
-            self.coordinates.latitude,
-            self.coordinates.longitude,
-            self.date,
-            self.solar_noon,
+        Solar noon altitude:      {:.2}°\n\
+        The day length is:        {}",
+            self.format_location(),
+            self.format_date(),
+            self.format_time(self.solar_noon),
+            self.solar_noon_altitude,
             self.day_length_hms(),
-            self.sunrise,
-            self.sunset,
-            self.civil_dawn,
-            self.civil_dusk,
-            self.nautical_dawn,
-            self.nautical_dusk,
-            self.astronomical_dawn,
-            self.astronomical_dusk
+        );
+
+        // each pair of events can be left out of the report in the config file
+        let sections = self.sections;
+        let pairs = [
+            (
+                sections.sunrise_sunset,
+                "Sunrise",
+                &self.sunrise,
+                "Sunset",
+                &self.sunset,
+            ),
+            (
+                sections.civil,
+                "Civil dawn",
+                &self.civil_dawn,
+                "Civil dusk",
+                &self.civil_dusk,
+            ),
+            (
+                sections.nautical,
+                "Nautical dawn",
+                &self.nautical_dawn,
+                "Nautical dusk",
+                &self.nautical_dusk,
+            ),
+            (
+                sections.astronomical,
+                "Astronomical dawn",
+                &self.astronomical_dawn,
+                "Astronomical dusk",
+                &self.astronomical_dusk,
+            ),
+        ];
+        for (shown, morning, morning_time, evening, evening_time) in pairs.iter() {
+            if *shown {
+                events.push_str(&format!(
+                    "\n\n{:<26}{}\n{:<26}{}",
+                    format!("{} is at:", morning),
+                    self.format_event_time(morning_time),
+                    format!("{} is at:", evening),
+                    self.format_event_time(evening_time),
+                ));
+            }
+        }
+        events.push_str("\n        ");
+        events
+    }
+
+    pub fn format_astro(&self) -> String {
+        let astro = self.astro_data();
+
+        format!(
+            "ASTRONOMICAL DATA\n\
+        -----------------\n\
+        Julian date (UT):         {:.5}\n\
+        ΔT (TT - UT):             {:.1}s\n\
+        Solar declination:        {:.4}°\n\
+        Equation of time:         {:.2} minutes\n\
+        Solar hour angle:         {:.4}°\n\
+        Sidereal time (GMST):     {:.5}h\n\
+        Sidereal time (local):    {:.5}h\n\
+        Altitude rate at sunrise: {}\n\
+        Altitude rate at sunset:  {}\n\
+        Angular diameter:         {:.2}'\n\n\
+        The values above are for {}.\n\
+        The position of the Sun is calculated in Terrestrial Time (TT = UT + ΔT). All times are\n\
+        given in UTC plus the chosen time zone offset; UTC is kept within 0.9s of UT by leap\n\
+        seconds, which are otherwise ignored. Times are truncated to the second.",
+            astro.julian_date,
+            astro.delta_t,
+            astro.declination,
+            astro.equation_of_time,
+            astro.hour_angle,
+            astro.greenwich_sidereal_time,
+            astro.local_sidereal_time,
+            format_altitude_rate(astro.sunrise_altitude_rate),
+            format_altitude_rate(astro.sunset_altitude_rate),
+            astro.angular_diameter,
+            self.date,
         )
     }
 
-    fn calculate_day_length(&self) -> Duration {
-        if self.sunrise.is_some() & self.sunset.is_some() {
-            self.sunset.datetime.unwrap() - self.sunrise.datetime.unwrap()
+    pub fn format_shadow(&self) -> String {
+        let position = SolarPosition::at(self.date, self.coordinates);
+
+        // shadows point directly away from the Sun, and an object of height h casts a shadow of length
+        // h / tan(altitude)
+        let (direction, length) = if position.altitude > 0.0 {
+            // round before wrapping, so that e.g. 359.999° is displayed as 0.00° rather than 360.00°
+            let direction = ((position.azimuth + 180.0) * 100.0).round() / 100.0 % 360.0;
+            (
+                format!("{:.2}°", direction),
+                format!(
+                    "{:.2} × object height",
+                    1.0 / position.altitude.to_radians().tan()
+                ),
+            )
         } else {
-            // 24 hours if sunrise and sunset don't occur
-            Duration::hours(24)
+            let none = "None (the Sun is below the horizon)".to_string();
+            (none.clone(), none)
+        };
+
+        format!(
+            "SHADOW\n\
+        ------\n\
+        At {}:\n\
+        Solar altitude:           {:.2}°\n\
+        Solar azimuth:            {:.2}°\n\
+        Sun is up:                {}\n\
+        Shadow direction:         {}\n\
+        Shadow length:            {}",
+            self.date,
+            position.altitude,
+            position.azimuth,
+            if position.altitude > 0.0 { "yes" } else { "no" },
+            direction,
+            length,
+        )
+    }
+
+    fn calculate_day_length(&self) -> Duration {
+        self.duration_above(&self.sunrise, &self.sunset, self.twilight_angles.sunrise)
+    }
+
+    fn duration_above(&self, start: &EventTime, end: &EventTime, altitude: f64) -> Duration {
+        // the time the Sun spends above the given altitude, between the two crossings of it. If there are no
+        // crossings, the Sun is either above it all day (e.g. the midnight Sun) or below it all day (e.g. the
+        // polar night), which can be told apart by whether it is above it at solar noon. The altitudes of the
+        // events already allow for refraction, so this is the geometric altitude, as in the events' calculation.
+        // With only one crossing on the day, the Sun is above it from that crossing to midnight, or from midnight
+        // to that crossing
+        let midnight = self.date.date().and_hms(0, 0, 0);
+        match (start.datetime, end.datetime) {
+            (Some(start), Some(end)) => end - start,
+            (Some(start), None) => midnight + Duration::days(1) - start,
+            (None, Some(end)) => end - midnight,
+            _ if SolarPosition::geometric(self.solar_noon, self.coordinates).altitude
+                > altitude =>
+            {
+                Duration::hours(24)
+            }
+            _ => Duration::zero(),
         }
     }
 
     fn day_length_hms(&self) -> String {
-        let day_length = self.day_length.num_seconds();
-        let hours = (day_length / 60) / 60;
-        let minutes = (day_length / 60) % 60;
-        let seconds = day_length % 60;
+        format_hms(self.day_length)
+    }
+
+    pub fn format_night(&self) -> String {
+        // for each definition of twilight, how long the Sun spends above and below its angle over the day
+        let angles = self.twilight_angles;
+        let twilights = [
+            ("Civil", &self.civil_dawn, &self.civil_dusk, angles.civil),
+            (
+                "Nautical",
+                &self.nautical_dawn,
+                &self.nautical_dusk,
+                angles.nautical,
+            ),
+            (
+                "Astronomical",
+                &self.astronomical_dawn,
+                &self.astronomical_dusk,
+                angles.astronomical,
+            ),
+        ];
+
+        let mut night = "NIGHT LENGTH\n\
+        ------------\n\
+        Twilight                  Sun above         Sun below (night)"
+            .to_string();
+        for (name, dawn, dusk, altitude) in twilights.iter() {
+            let above = self.duration_above(dawn, dusk, *altitude);
+            let note = if above == Duration::hours(24) {
+                " (no night)"
+            } else if above == Duration::zero() {
+                " (night all day)"
+            } else {
+                ""
+            };
+            night.push_str(&format!(
+                "\n{:<26}{:<18}{}{}",
+                format!("{} ({}°):", name, altitude),
+                format_hms(above),
+                format_hms(Duration::hours(24) - above),
+                note
+            ));
+        }
+        night
+    }
+
+    pub fn format_civil_day(&self) -> String {
+        // the usable light for outdoor work, from civil dawn to civil dusk. Without them, it is either light
+        // enough all day or never light enough
+        let length = self.duration_above(
+            &self.civil_dawn,
+            &self.civil_dusk,
+            self.twilight_angles.civil,
+        );
+        let note = if length == Duration::hours(24) {
+            " (all day)"
+        } else if length == Duration::zero() {
+            " (dark all day)"
+        } else {
+            ""
+        };
+        format!(
+            "CIVIL DAY\n\
+            ---------\n\
+            Date:                     {}\n\
+            Civil dawn is at:         {}\n\
+            Civil dusk is at:         {}\n\
+            The civil day length is:  {}{}",
+            self.date.format("%Y-%m-%d"),
+            self.format_event_time(&self.civil_dawn),
+            self.format_event_time(&self.civil_dusk),
+            format_hms(length),
+            note
+        )
+    }
+
+    pub fn format_photo(&self) -> String {
+        // the golden hour is when the geometric centre of the Sun is between 6° above and 4° below the horizon,
+        // and the blue hour when it is between 4° and 6° below. Each happens once in the morning and once in the
+        // evening, between the crossings of its two angles
+        type Crossings = (f64, (EventTime, EventTime));
+        let six_above = (6.0, self.crossings(-6.0));
+        let four_below = (-4.0, self.crossings(4.0));
+        let six_below = (-6.0, self.crossings(6.0));
+        let bands = [
+            ("golden", &six_above, &four_below),
+            ("blue", &four_below, &six_below),
+        ];
+
+        // the time the Sun spends within a band over the whole day, which may be all of it at high latitudes
+        let total = |(_, upper, lower): (&str, &Crossings, &Crossings)| {
+            let (lower_altitude, (rising, setting)) = lower;
+            let (upper_altitude, (upper_rising, upper_setting)) = upper;
+            self.duration_above(rising, setting, *lower_altitude)
+                - self.duration_above(upper_rising, upper_setting, *upper_altitude)
+        };
+
+        let mut photo = "GOLDEN AND BLUE HOURS\n\
+        ---------------------\n\
+        Window                    Starts                      Ends                        Length"
+            .to_string();
+        let windows = [
+            ("Morning blue hour:", bands[1], true),
+            ("Morning golden hour:", bands[0], true),
+            ("Evening golden hour:", bands[0], false),
+            ("Evening blue hour:", bands[1], false),
+        ];
+        for (name, band, morning) in windows.iter() {
+            let (kind, (_, upper), (_, lower)) = band;
+            // the Sun rises through the lower angle and then the upper one in the morning, and sets through them
+            // in the opposite order in the evening
+            let (start, end) = if *morning {
+                (&lower.0, &upper.0)
+            } else {
+                (&upper.1, &lower.1)
+            };
+            // without one of its crossings, a window runs into the day's other one, or on through the night
+            let length = match (start.datetime, end.datetime, morning) {
+                (Some(start), Some(end), _) => format_hms(end - start),
+                (None, None, _) if total(*band) == Duration::hours(24) => "All day".to_string(),
+                (None, None, _) => "None".to_string(),
+                (Some(_), None, true) => format!("Runs into the evening {} hour", kind),
+                (None, Some(_), false) => format!("Runs on from the morning {} hour", kind),
+                (None, Some(_), true) => "Runs on from the night before".to_string(),
+                (Some(_), None, false) => "Runs on through the night".to_string(),
+            };
+            photo.push_str(&format!(
+                "\n{:<26}{:<28}{:<28}{}",
+                name,
+                self.format_event_time(start),
+                self.format_event_time(end),
+                length
+            ));
+        }
+
+        photo.push('\n');
+        for band in bands.iter() {
+            let total = total(*band);
+            let note = if total == Duration::hours(24) {
+                " (all day)"
+            } else {
+                ""
+            };
+            photo.push_str(&format!(
+                "\n{:<26}{}{}",
+                format!("Total {} hour:", band.0),
+                format_hms(total),
+                note
+            ));
+        }
+        photo
+    }
+
+    fn day_fraction_to_datetime(&self, day_fraction: f64) -> DateTime<FixedOffset> {
+        // correct the date if the event rolls over to the next day, or happens on the previous day. The event
+        // still belongs to this day's report, e.g. the sunrise before this day's solar noon, even when that is
+        // late on the previous day
+        let days = day_fraction.floor();
+        let day_fraction = day_fraction - days;
+
+        let hour_fraction = day_fraction * 24.0;
+        let minute_fraction = hour_fraction.fract() * 60.0;
+        let second_fraction = minute_fraction.fract() * 60.0;
+
+        // counted in whole seconds from the day's midnight, which is much quicker than setting each field of
+        // the date in turn
+        let seconds = days as i64 * 86400
+            + hour_fraction.trunc() as i64 * 3600
+            + minute_fraction.trunc() as i64 * 60
+            + second_fraction.trunc() as i64;
+
+        self.date.date().and_hms(0, 0, 0) + Duration::seconds(seconds)
+    }
+
+    fn calculate_event_start_and_end(
+        &self,
+        constants: &LocationConstants,
+        cos_zenith: f64,
+        solar_noon: f64,
+        solar_declination: f64,
+    ) -> (EventTime, EventTime) {
+        let hour_angle = (cos_zenith
+            / (constants.cos_latitude * solar_declination.to_radians().cos())
+            - constants.tan_latitude * solar_declination.to_radians().tan())
+        .acos()
+        .to_degrees();
+
+        if hour_angle.is_nan() {
+            return (None.into(), None.into());
+        }
+
+        let start_fraction = solar_noon - (hour_angle * 4.0) / 1440.0;
+        let end_fraction = solar_noon + (hour_angle * 4.0) / 1440.0;
+
+        let start_time = self.day_fraction_to_datetime(start_fraction);
+        let end_time = self.day_fraction_to_datetime(end_fraction);
+
+        (Some(start_time).into(), Some(end_time).into())
+    }
+
+    pub fn apply_horizon(&mut self, profile: &structs::HorizonProfile) {
+        // recalculate sunrise, sunset and the day length over the skyline rather than a level horizon. The Sun
+        // counts as up when it is above the skyline in its direction by as much as it would be above a level
+        // horizon at sunrise, so a level profile gives the usual times. The skyline may hide the Sun more than
+        // once a day, so the Sun is checked every minute from midnight to midnight, each crossing is narrowed
+        // down to the second, and sunrise and sunset are the first time it appears and the last time it is hidden
+        let coordinates = self.coordinates;
+        let sunrise_altitude = self.twilight_angles.sunrise;
+        let sun_up = |datetime| {
+            let position = SolarPosition::geometric(datetime, coordinates);
+            position.altitude >= profile.altitude_at(position.azimuth) + sunrise_altitude
+        };
+        let crossing = |mut before: DateTime<FixedOffset>, mut after: DateTime<FixedOffset>| {
+            let up = sun_up(before);
+            while after - before > Duration::seconds(1) {
+                let middle = before + Duration::seconds((after - before).num_seconds() / 2);
+                if sun_up(middle) == up {
+                    before = middle;
+                } else {
+                    after = middle;
+                }
+            }
+            after
+        };
+
+        let midnight = self.date.date().and_hms(0, 0, 0);
+        let end = midnight + Duration::days(1);
+        let (mut sunrise, mut sunset) = (None, None);
+        let mut day_length = Duration::zero();
+        let mut up = sun_up(midnight);
+        let mut up_since = midnight;
+        let mut time = midnight;
+        while time < end {
+            let next = time + Duration::minutes(1);
+            if sun_up(next) != up {
+                let time = crossing(time, next);
+                up = !up;
+                if up {
+                    sunrise = sunrise.or(Some(time));
+                    up_since = time;
+                } else {
+                    sunset = Some(time);
+                    day_length = day_length + (time - up_since);
+                }
+            }
+            time = next;
+        }
+        if up {
+            day_length = day_length + (end - up_since);
+        }
+
+        self.sunrise = sunrise.into();
+        self.sunset = sunset.into();
+        self.day_length = day_length;
+        self.local_horizon = true;
+    }
+
+    pub fn crossings(&self, depression: f64) -> (EventTime, EventTime) {
+        // when the geometric centre of the Sun is this many degrees below the horizon, in the morning and in the
+        // evening, in the same way as the dawns and dusks of each kind of twilight
+        let constants = LocationConstants::new(&self.coordinates, &self.twilight_angles);
+        let (solar_noon, solar_declination) = self.solar_noon_and_declination();
+        self.calculate_event_start_and_end(
+            &constants,
+            (90.0 + depression).to_radians().cos(),
+            solar_noon,
+            solar_declination,
+        )
+    }
+
+    fn solar_noon_and_declination(&self) -> (f64, f64) {
+        // the time of solar noon, as a fraction of the day, and the Sun's declination in degrees
+        let time_zone = self.date.offset().fix().local_minus_utc() as f64 / 3600.0;
+
+        // the date may be any instant on the day, but the day's events are always calculated from midday
+        let julian_date: f64 = self.date.date().and_hms(12, 0, 0).to_julian_date();
+
+        let SolarParameters {
+            declination,
+            equation_of_time,
+            ..
+        } = SolarParameters::from_julian_date(julian_date);
+
+        let solar_noon = (720.0 - 4.0 * self.coordinates.longitude.value - equation_of_time
+            + time_zone * 60.0)
+            / 1440.0;
+
+        (solar_noon, declination)
+    }
+
+    fn run(&mut self, constants: &LocationConstants) {
+        let (solar_noon, solar_declination) = self.solar_noon_and_declination();
+
+        let [sunrise_zenith, civil_zenith, nautical_zenith, astronomical_zenith] =
+            constants.cos_zeniths;
+
+        // plain sunrise/sunset
+        let (sunrise, sunset) = self.calculate_event_start_and_end(
+            constants,
+            sunrise_zenith,
+            solar_noon,
+            solar_declination,
+        );
+
+        // civil twilight
+        let (civil_twilight_start, civil_twilight_end) = self.calculate_event_start_and_end(
+            constants,
+            civil_zenith,
+            solar_noon,
+            solar_declination,
+        );
+
+        // nautical twilight
+        let (nautical_twilight_start, nautical_twilight_end) = self.calculate_event_start_and_end(
+            constants,
+            nautical_zenith,
+            solar_noon,
+            solar_declination,
+        );
+
+        // astronomical twilight
+        let (astronomical_twilight_start, astronomical_twilight_end) = self
+            .calculate_event_start_and_end(
+                constants,
+                astronomical_zenith,
+                solar_noon,
+                solar_declination,
+            );
+
+        self.solar_noon = self.day_fraction_to_datetime(solar_noon);
+        self.solar_noon_altitude = SolarPosition::at(self.solar_noon, self.coordinates).altitude;
+
+        self.sunrise = sunrise;
+        self.sunset = sunset;
+
+        self.civil_dawn = civil_twilight_start;
+        self.civil_dusk = civil_twilight_end;
+
+        self.nautical_dawn = nautical_twilight_start;
+        self.nautical_dusk = nautical_twilight_end;
+
+        self.astronomical_dawn = astronomical_twilight_start;
+        self.astronomical_dusk = astronomical_twilight_end;
+
+        self.day_length = self.calculate_day_length();
+    }
+}
+
+// the parts of the sunrise equation which depend only on the location and the definitions of twilight, so
+// that they are worked out once for a whole range of days rather than for each day's report
+struct LocationConstants {
+    cos_latitude: f64,
+    tan_latitude: f64,
+    // the cosines of the zenith angles of the centre of the Sun at sunrise, and at the start of civil, nautical
+    // and astronomical twilight
+    cos_zeniths: [f64; 4],
+}
+
+impl LocationConstants {
+    fn new(
+        coordinates: &structs::Coordinates,
+        twilight_angles: &structs::TwilightAngles,
+    ) -> LocationConstants {
+        let cos_zenith = |zenith: f64| zenith.to_radians().cos();
+        LocationConstants {
+            cos_latitude: coordinates.latitude.to_radians().cos(),
+            tan_latitude: coordinates.latitude.to_radians().tan(),
+            cos_zeniths: [
+                cos_zenith(90.0 - twilight_angles.sunrise),
+                cos_zenith(90.0 - twilight_angles.civil),
+                cos_zenith(90.0 - twilight_angles.nautical),
+                cos_zenith(90.0 - twilight_angles.astronomical),
+            ],
+        }
+    }
+}
+
+pub fn solar_events_for_dates(
+    dates: &[DateTime<FixedOffset>],
+    coordinates: structs::Coordinates,
+    twilight_angles: structs::TwilightAngles,
+) -> Vec<SolarReport> {
+    // a report for each of the dates, sharing what can be worked out from the location alone
+    let constants = LocationConstants::new(&coordinates, &twilight_angles);
+    let mut reports = Vec::with_capacity(dates.len());
+    reports.extend(
+        dates.iter().map(|date| {
+            SolarReport::with_constants(*date, coordinates, twilight_angles, &constants)
+        }),
+    );
+    reports
+}
+
+pub fn solar_events_range(
+    coordinates: structs::Coordinates,
+    twilight_angles: structs::TwilightAngles,
+    start: DateTime<FixedOffset>,
+    end: DateTime<FixedOffset>,
+    step: Duration,
+) -> Vec<SolarReport> {
+    // a report every step from the start up to and including the end, which is empty unless the step moves
+    // forwards
+    if step <= Duration::zero() {
+        return vec![];
+    }
+    let mut dates = vec![];
+    let mut date = start;
+    while date <= end {
+        dates.push(date);
+        date = date + step;
+    }
+    solar_events_for_dates(&dates, coordinates, twilight_angles)
+}
+
+#[derive(Debug, Serialize)]
+pub struct NightReport {
+    // the date of the evening on which the night begins
+    pub date: NaiveDate,
+    pub twilight: String,
+    // the dusk on the evening of the date, and the dawn on the following morning
+    pub start: EventTime,
+    pub end: EventTime,
+    // in seconds, or null if the night doesn't both begin and end
+    #[serde(serialize_with = "serialize_optional_seconds")]
+    pub length: Option<Duration>,
+    // if the evening has no dusk, either there is no night at all or the Sun stays below the angle all day
+    #[serde(skip)]
+    pub no_night: bool,
+    #[serde(skip)]
+    pub night_all_day: bool,
+}
+
+fn serialize_optional_seconds<S: Serializer>(
+    duration: &Option<Duration>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    match duration {
+        Some(duration) => serializer.serialize_some(&duration.num_seconds()),
+        None => serializer.serialize_none(),
+    }
+}
+
+impl NightReport {
+    pub fn new(
+        evening: &SolarReport,
+        morning: &SolarReport,
+        twilight_type: enums::TwilightType,
+    ) -> NightReport {
+        // each event is taken from the report for its own day, so that the night spans midnight
+        let dusk = evening.event_time(&twilight_type.dusk());
+        let dawn = morning.event_time(&twilight_type.dawn());
+        let length = match (dusk.datetime, dawn.datetime) {
+            (Some(dusk), Some(dawn)) => Some(dawn - dusk),
+            _ => None,
+        };
+        let above = evening.duration_above(
+            evening.event_time(&twilight_type.dawn()),
+            dusk,
+            evening.twilight_angles.angle(&twilight_type),
+        );
+
+        NightReport {
+            date: evening.date.naive_local().date(),
+            twilight: twilight_type.to_string(),
+            start: EventTime::new(dusk.datetime),
+            end: EventTime::new(dawn.datetime),
+            length,
+            no_night: dusk.datetime.is_none() && above == Duration::hours(24),
+            night_all_day: dusk.datetime.is_none() && above == Duration::zero(),
+        }
+    }
+
+    fn format_length(&self) -> String {
+        match self.length {
+            Some(length) => format_hms(length),
+            None if self.no_night => format!("Never (no {} night)", self.twilight),
+            None if self.night_all_day => "Never (night all day)".to_string(),
+            None => "Never (the night doesn't end the next day)".to_string(),
+        }
+    }
+}
+
+impl fmt::Display for NightReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "NIGHT OF {}\n\
+            -------------------\n\
+            Twilight:                 {}\n\
+            Night begins at:          {}\n\
+            Night ends at:            {}\n\
+            The night length is:      {}",
+            self.date,
+            self.twilight,
+            self.start,
+            self.end,
+            self.format_length()
+        )
+    }
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct DarkWindow {
+    pub start: DateTime<FixedOffset>,
+    pub end: DateTime<FixedOffset>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DarknessReport {
+    // the astronomical night beginning on the evening of the date, and the parts of it when the Moon is down
+    pub night: NightReport,
+    // in seconds, or null if the night doesn't both begin and end
+    #[serde(serialize_with = "serialize_optional_seconds")]
+    pub moon_up: Option<Duration>,
+    #[serde(serialize_with = "serialize_optional_seconds")]
+    pub darkness: Option<Duration>,
+    pub windows: Vec<DarkWindow>,
+}
+
+impl DarknessReport {
+    pub fn new(evening: &SolarReport, morning: &SolarReport) -> DarknessReport {
+        let night = NightReport::new(evening, morning, enums::TwilightType::Astronomical);
+        let windows = match (night.start.datetime, night.end.datetime) {
+            (Some(dusk), Some(dawn)) => moonless_windows(dusk, dawn, evening.coordinates),
+            _ => vec![],
+        };
+        let darkness = night.length.map(|_| {
+            windows.iter().fold(Duration::zero(), |total, window| {
+                total + (window.end - window.start)
+            })
+        });
+
+        DarknessReport {
+            moon_up: match (night.length, darkness) {
+                (Some(length), Some(darkness)) => Some(length - darkness),
+                _ => None,
+            },
+            darkness,
+            night,
+            windows,
+        }
+    }
+}
+
+fn moonless_windows(
+    start: DateTime<FixedOffset>,
+    end: DateTime<FixedOffset>,
+    coordinates: structs::Coordinates,
+) -> Vec<DarkWindow> {
+    // the Moon is checked once a minute, and each moonrise or moonset is then narrowed down to the second
+    let moon_down =
+        |datetime| lunar::moon_altitude(datetime, coordinates) < lunar::MOONRISE_ALTITUDE;
+    let crossing = |mut before: DateTime<FixedOffset>, mut after: DateTime<FixedOffset>| {
+        let down = moon_down(before);
+        while after - before > Duration::seconds(1) {
+            let middle = before + Duration::seconds((after - before).num_seconds() / 2);
+            if moon_down(middle) == down {
+                before = middle;
+            } else {
+                after = middle;
+            }
+        }
+        after
+    };
+
+    let mut windows = vec![];
+    let mut window_start = if moon_down(start) { Some(start) } else { None };
+    let mut time = start;
+    while time < end {
+        let next = std::cmp::min(time + Duration::minutes(1), end);
+        let down = moon_down(next);
+        if down != window_start.is_some() {
+            let moonrise_or_set = crossing(time, next);
+            match window_start.take() {
+                Some(start) => windows.push(DarkWindow {
+                    start,
+                    end: moonrise_or_set,
+                }),
+                None => window_start = Some(moonrise_or_set),
+            }
+        }
+        time = next;
+    }
+    if let Some(start) = window_start {
+        windows.push(DarkWindow { start, end });
+    }
+    windows
+}
+
+impl fmt::Display for DarknessReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "DARKNESS ON THE NIGHT OF {}\n\
+            -----------------------------------\n\
+            Night begins at:          {}\n\
+            Night ends at:            {}\n\
+            The night length is:      {}",
+            self.night.date,
+            self.night.start,
+            self.night.end,
+            self.night.format_length()
+        )?;
+
+        let (moon_up, darkness) = match (self.moon_up, self.darkness) {
+            (Some(moon_up), Some(darkness)) => (format_hms(moon_up), format_hms(darkness)),
+            // without an astronomical night, there is no true darkness to speak of
+            _ => return Ok(()),
+        };
+        write!(
+            f,
+            "\nThe Moon is up for:       {}\n\
+            Moonless darkness:        {}",
+            moon_up, darkness
+        )?;
+
+        if self.windows.is_empty() {
+            return write!(f, "\nMoonless windows:         None");
+        }
+        for (i, window) in self.windows.iter().enumerate() {
+            write!(
+                f,
+                "\n{:<26}{} to {}",
+                if i == 0 { "Moonless windows:" } else { "" },
+                window.start,
+                window.end
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct DayLengthExtreme {
+    // every date on which the day length is the longest (or shortest), as it may be reached more than once
+    pub dates: Vec<NaiveDate>,
+    #[serde(serialize_with = "serialize_seconds")]
+    pub day_length: Duration,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct DatePeriod {
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+}
+
+impl DatePeriod {
+    fn days(&self) -> i64 {
+        (self.end - self.start).num_days() + 1
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct DayLengthExtremes {
+    pub longest: DayLengthExtreme,
+    pub shortest: DayLengthExtreme,
+    // the runs of days on which the Sun never sets, or never rises, which take the place of the longest and
+    // shortest days at high latitudes
+    pub polar_day: Vec<DatePeriod>,
+    pub polar_night: Vec<DatePeriod>,
+}
+
+impl DayLengthExtremes {
+    pub fn from_reports(reports: &[SolarReport]) -> DayLengthExtremes {
+        let extreme = |day_length: Duration| DayLengthExtreme {
+            dates: reports
+                .iter()
+                .filter(|report| report.day_length == day_length)
+                .map(|report| report.date.naive_local().date())
+                .collect(),
+            day_length,
+        };
+        let longest = reports.iter().map(|report| report.day_length).max();
+        let shortest = reports.iter().map(|report| report.day_length).min();
+
+        DayLengthExtremes {
+            longest: extreme(longest.unwrap_or_else(Duration::zero)),
+            shortest: extreme(shortest.unwrap_or_else(Duration::zero)),
+            polar_day: date_periods(reports, |report| report.day_length == Duration::hours(24)),
+            polar_night: date_periods(reports, |report| report.day_length == Duration::zero()),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct SeasonBounds {
+    pub year: i32,
+    // for each definition of twilight, the runs of days on which the Sun gets below its angle at all, i.e. there
+    // is some night of that kind
+    pub civil: Vec<DatePeriod>,
+    pub nautical: Vec<DatePeriod>,
+    pub astronomical: Vec<DatePeriod>,
+    #[serde(skip)]
+    days: usize,
+}
+
+impl SeasonBounds {
+    pub fn from_reports(year: i32, reports: &[SolarReport]) -> SeasonBounds {
+        let nights = |twilight_type: enums::TwilightType| {
+            date_periods(reports, |report| {
+                let above = report.duration_above(
+                    report.event_time(&twilight_type.dawn()),
+                    report.event_time(&twilight_type.dusk()),
+                    report.twilight_angles.angle(&twilight_type),
+                );
+                above < Duration::hours(24)
+            })
+        };
+
+        SeasonBounds {
+            year,
+            civil: nights(enums::TwilightType::Civil),
+            nautical: nights(enums::TwilightType::Nautical),
+            astronomical: nights(enums::TwilightType::Astronomical),
+            days: reports.len(),
+        }
+    }
+
+    fn format_nights(&self, nights: &[DatePeriod]) -> String {
+        match nights {
+            [] => "never".to_string(),
+            [night] if night.days() as usize == self.days => "every day".to_string(),
+            _ => format_periods(nights),
+        }
+    }
+}
+
+impl fmt::Display for SeasonBounds {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Civil night:              {}\n\
+            Nautical night:           {}\n\
+            Astronomical night:       {}",
+            self.format_nights(&self.civil),
+            self.format_nights(&self.nautical),
+            self.format_nights(&self.astronomical),
+        )
+    }
+}
+
+// how far ahead to look for the next occurrence of an event. Even at the poles, every event happens within a
+// year, unless the Sun never reaches its altitude at all
+pub const NEXT_OCCURRENCE_DAYS: u32 = 366;
+
+#[derive(Debug, Serialize)]
+pub struct NextOccurrence {
+    pub event: enums::Event,
+    pub from: NaiveDate,
+    pub time: Option<DateTime<FixedOffset>>,
+    // the number of days without it before the one it occurs on, i.e. zero if it occurs on the first date
+    pub days_without: Option<i64>,
+}
+
+impl NextOccurrence {
+    pub fn from_reports(reports: &[SolarReport], event: enums::Event) -> NextOccurrence {
+        // the first of the consecutive days' reports, starting with the chosen date, in which the event occurs
+        // at all, e.g. the first sunrise after the polar night
+        let from = reports[0].date.naive_local().date();
+        let found = reports.iter().find_map(|report| {
+            report
+                .event_time(&event)
+                .datetime
+                .map(|time| (report, time))
+        });
+
+        NextOccurrence {
+            event,
+            from,
+            time: found.map(|(_, time)| time),
+            days_without: found
+                .map(|(report, _)| (report.date.naive_local().date() - from).num_days()),
+        }
+    }
+}
+
+impl fmt::Display for NextOccurrence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Searching from:           {}", self.from)?;
+        let label = format!("Next {}:", self.event);
+        match (self.time, self.days_without) {
+            (Some(time), Some(days)) => write!(
+                f,
+                "{:<26}{}\nDays without one:         {}",
+                label, time, days
+            ),
+            _ => write!(f, "{:<26}None within {} days", label, NEXT_OCCURRENCE_DAYS),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct OffsetEventTime {
+    pub date: NaiveDate,
+    // the offset as it was given, e.g. "30m before", and as a number of seconds, negative before the event
+    pub offset: String,
+    pub offset_seconds: i64,
+    pub time: Option<DateTime<FixedOffset>>,
+    // the moment used instead of the event on a day without it (see --fallback-event), or null
+    pub fallback: Option<String>,
+}
+
+impl OffsetEventTime {
+    pub fn from_reports(
+        reports: &[SolarReport],
+        event: &enums::EventBase,
+        event_offset: Duration,
+        offsets: &[(String, Duration)],
+        fallback: Option<enums::DayMark>,
+    ) -> Vec<OffsetEventTime> {
+        // the event shifted by each offset on each day, in that order. An event defined in the config file is
+        // shifted by its own offset too, which isn't part of the one shown
+        reports
+            .iter()
+            .flat_map(|report| {
+                let (event_time, used) = report.time_or_fallback(event, fallback);
+                offsets.iter().map(move |(text, offset)| OffsetEventTime {
+                    date: report.date.naive_local().date(),
+                    offset: text.clone(),
+                    offset_seconds: offset.num_seconds(),
+                    time: event_time.map(|time| time + event_offset + *offset),
+                    fallback: used.map(|used| used.to_string()),
+                })
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug)]
+pub struct Schedule {
+    // the time of day a crontab line fires at, which is before the event [+ offset] on every day of the year,
+    // or None if it never happens
+    pub trigger: Option<NaiveTime>,
+    // whether the event [+ offset] is ever before the midnight which starts its day, which a line fired on the
+    // day can never be in time for
+    pub before_midnight: bool,
+}
+
+impl Schedule {
+    pub fn from_reports(
+        reports: &[SolarReport],
+        event: &enums::EventBase,
+        offset: Duration,
+        margin: Duration,
+    ) -> Schedule {
+        // the earliest the event [+ offset] happens, measured from the start of its day, less the margin and
+        // rounded down to cron's whole minutes. A line can only fire during the day, so no earlier than
+        // midnight and no later than a minute before the next one
+        let earliest = reports
+            .iter()
+            .filter_map(|report| {
+                let midnight = report.date.naive_local().date().and_hms(0, 0, 0);
+                report
+                    .base_time(event)
+                    .datetime
+                    .map(|time| (time + offset).naive_local() - midnight)
+            })
+            .min();
+
+        let last_minute = Duration::hours(24) - Duration::minutes(1);
+        Schedule {
+            trigger: earliest.map(|earliest| {
+                let trigger = (earliest - margin).max(Duration::zero()).min(last_minute);
+                NaiveTime::from_hms(0, 0, 0) + Duration::minutes(trigger.num_minutes())
+            }),
+            before_midnight: earliest.map_or(false, |earliest| earliest < Duration::zero()),
+        }
+    }
+}
+
+fn date_periods<F: Fn(&SolarReport) -> bool>(
+    reports: &[SolarReport],
+    condition: F,
+) -> Vec<DatePeriod> {
+    // the runs of consecutive reports which meet the condition
+    let mut periods: Vec<DatePeriod> = vec![];
+    for report in reports.iter().filter(|report| condition(report)) {
+        let date = report.date.naive_local().date();
+        match periods.last_mut() {
+            Some(period) if period.end.succ() == date => period.end = date,
+            _ => periods.push(DatePeriod {
+                start: date,
+                end: date,
+            }),
+        }
+    }
+    periods
+}
+
+fn format_periods(periods: &[DatePeriod]) -> String {
+    periods
+        .iter()
+        .map(|period| {
+            format!(
+                "from {} to {} ({} day{})",
+                period.start,
+                period.end,
+                period.days(),
+                if period.days() == 1 { "" } else { "s" }
+            )
+        })
+        .collect::<Vec<String>>()
+        .join(" and ")
+}
+
+fn format_dates(dates: &[NaiveDate]) -> String {
+    dates
+        .iter()
+        .map(|date| date.to_string())
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+impl fmt::Display for DayLengthExtremes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.polar_day.is_empty() {
+            writeln!(
+                f,
+                "The longest day is:       {} ({})",
+                format_dates(&self.longest.dates),
+                format_hms(self.longest.day_length)
+            )?;
+        } else {
+            writeln!(
+                f,
+                "The Sun doesn't set:      {}",
+                format_periods(&self.polar_day)
+            )?;
+        }
+
+        if self.polar_night.is_empty() {
+            write!(
+                f,
+                "The shortest day is:      {} ({})",
+                format_dates(&self.shortest.dates),
+                format_hms(self.shortest.day_length)
+            )
+        } else {
+            write!(
+                f,
+                "The Sun doesn't rise:     {}",
+                format_periods(&self.polar_night)
+            )
+        }
+    }
+}
+
+pub fn json_schema(compact: bool) -> String {
+    // the JSON Schema describing the output of report --json, which is either a single report or, for a
+    // range of dates, an array of them
+    let datetime = json!({"type": "string", "format": "date-time"});
+    let event = json!({
+        "type": ["string", "null"],
+        "format": "date-time",
+        "description": "null if the event doesn't occur on the day"
+    });
+    let rate = json!({
+        "type": ["number", "null"],
+        "description": "degrees per minute, or null if the event doesn't occur on the day"
+    });
+    let schema = json!({
+        "$schema": "http://json-schema.org/draft-07/schema#",
+        "title": "heliocron report",
+        "definitions": {
+            "report": {
+                "type": "object",
+                "required": [
+                    "schema_version", "date", "coordinates", "solar_noon", "solar_noon_altitude", "day_length",
+                    "sunrise", "sunset", "civil_dawn", "civil_dusk", "nautical_dawn", "nautical_dusk",
+                    "astronomical_dawn", "astronomical_dusk"
+                ],
+                "properties": {
+                    "schema_version": {"type": "string", "const": SCHEMA_VERSION},
+                    "date": datetime,
+                    "coordinates": {
+                        "type": ["object", "null"],
+                        "description": "null if the location was hidden with --no-location",
+                        "required": ["latitude", "longitude"],
+                        "properties": {
+                            "latitude": {
+                                "type": "number",
+                                "minimum": -90,
+                                "maximum": 90,
+                                "description": "decimal degrees, positive to the north"
+                            },
+                            "longitude": {
+                                "type": "number",
+                                "minimum": -180,
+                                "maximum": 180,
+                                "description": "decimal degrees, positive to the east"
+                            }
+                        }
+                    },
+                    "solar_noon": datetime,
+                    "solar_noon_altitude": {
+                        "type": "number",
+                        "minimum": -90,
+                        "maximum": 90,
+                        "description": "degrees, the Sun's highest apparent altitude of the day"
+                    },
+                    "day_length": {
+                        "type": "integer",
+                        "minimum": 0,
+                        "maximum": 86400,
+                        "description": "seconds"
+                    },
+                    "sunrise": event,
+                    "sunset": event,
+                    "civil_dawn": event,
+                    "civil_dusk": event,
+                    "nautical_dawn": event,
+                    "nautical_dusk": event,
+                    "astronomical_dawn": event,
+                    "astronomical_dusk": event,
+                    "astro": {
+                        "type": "object",
+                        "description": "only with --astro",
+                        "required": [
+                            "julian_date", "delta_t", "declination", "equation_of_time", "hour_angle",
+                            "greenwich_sidereal_time", "local_sidereal_time", "sunrise_altitude_rate",
+                            "sunset_altitude_rate", "angular_diameter"
+                        ],
+                        "properties": {
+                            "julian_date": {"type": "number", "description": "UT"},
+                            "delta_t": {"type": "number", "description": "seconds, TT - UT"},
+                            "declination": {"type": "number", "description": "degrees"},
+                            "equation_of_time": {"type": "number", "description": "minutes"},
+                            "hour_angle": {
+                                "type": "number",
+                                "minimum": -180,
+                                "maximum": 180,
+                                "description": "degrees, negative before solar noon"
+                            },
+                            "greenwich_sidereal_time": {
+                                "type": "number",
+                                "minimum": 0,
+                                "maximum": 24,
+                                "description": "hours"
+                            },
+                            "local_sidereal_time": {
+                                "type": "number",
+                                "minimum": 0,
+                                "maximum": 24,
+                                "description": "hours"
+                            },
+                            "sunrise_altitude_rate": rate,
+                            "sunset_altitude_rate": rate,
+                            "angular_diameter": {
+                                "type": "number",
+                                "exclusiveMinimum": 0,
+                                "description": "arcminutes, the apparent diameter of the Sun's disc"
+                            }
+                        }
+                    }
+                }
+            }
+        },
+        "oneOf": [
+            {"$ref": "#/definitions/report"},
+            {"type": "array", "items": {"$ref": "#/definitions/report"}}
+        ]
+    });
+    utils::to_json(&schema, compact)
+}
+
+#[derive(Debug, Serialize)]
+pub struct FlightEvent {
+    // sunrise or sunset, as seen from the aircraft
+    pub event: enums::Event,
+    pub time: DateTime<FixedOffset>,
+    pub coordinates: structs::Coordinates,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FlightReport {
+    pub departure: DateTime<FixedOffset>,
+    pub arrival: DateTime<FixedOffset>,
+    pub sun_up_at_departure: bool,
+    pub events: Vec<FlightEvent>,
+}
+
+impl FlightReport {
+    pub fn new(
+        from: structs::Coordinates,
+        to: structs::Coordinates,
+        departure: DateTime<FixedOffset>,
+        arrival: DateTime<FixedOffset>,
+        sunrise_altitude: f64,
+        step: Duration,
+    ) -> FlightReport {
+        // the aircraft is taken to fly along the great circle at a steady speed, so its position at any time is
+        // the same fraction of the way along the route as the time is of the flight. The Sun is checked at each
+        // step, and each sunrise or sunset is then narrowed down to the second, in the time zone of the departure
+        let flight_time = (arrival - departure).num_seconds() as f64;
+        let position = |datetime: DateTime<FixedOffset>| {
+            from.great_circle_point(
+                &to,
+                (datetime - departure).num_seconds() as f64 / flight_time,
+            )
+        };
+        let sun_up = |datetime| {
+            SolarPosition::geometric(datetime, position(datetime)).altitude >= sunrise_altitude
+        };
+        let crossing = |mut before: DateTime<FixedOffset>, mut after: DateTime<FixedOffset>| {
+            let up = sun_up(before);
+            while after - before > Duration::seconds(1) {
+                let middle = before + Duration::seconds((after - before).num_seconds() / 2);
+                if sun_up(middle) == up {
+                    before = middle;
+                } else {
+                    after = middle;
+                }
+            }
+            after
+        };
+
+        let arrival = arrival.with_timezone(departure.offset());
+        let sun_up_at_departure = sun_up(departure);
+        let mut events = vec![];
+        let mut up = sun_up_at_departure;
+        let mut time = departure;
+        while time < arrival {
+            let next = std::cmp::min(time + step, arrival);
+            if sun_up(next) != up {
+                let time = crossing(time, next);
+                up = !up;
+                events.push(FlightEvent {
+                    event: if up {
+                        enums::Event::Sunrise
+                    } else {
+                        enums::Event::Sunset
+                    },
+                    time,
+                    coordinates: position(time),
+                });
+            }
+            time = next;
+        }
+
+        FlightReport {
+            departure,
+            arrival,
+            sun_up_at_departure,
+            events,
+        }
+    }
+}
+
+impl fmt::Display for FlightReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "FLIGHT\n\
+            ------\n\
+            Departure:                {} (Sun {})\n\
+            Arrival:                  {}",
+            self.departure,
+            if self.sun_up_at_departure {
+                "up"
+            } else {
+                "down"
+            },
+            self.arrival,
+        )?;
+
+        if self.events.is_empty() {
+            return write!(f, "\n\nThe Sun neither rises nor sets en route.");
+        }
+        writeln!(f)?;
+        for event in self.events.iter() {
+            write!(
+                f,
+                "\n{:<26}{} at {:.4}, {:.4}",
+                format!("{} is at:", event.event.description()),
+                event.time,
+                event.coordinates.latitude.value,
+                event.coordinates.longitude.value
+            )?;
+        }
+        Ok(())
+    }
+}
+
+fn format_altitude_rate(rate: Option<f64>) -> String {
+    match rate {
+        Some(rate) => format!("{:+.4}° per minute", rate),
+        None => EventTime::new(None).to_string(),
+    }
+}
+
+pub fn format_hms(duration: Duration) -> String {
+    let duration = duration.num_seconds();
+    let hours = (duration / 60) / 60;
+    let minutes = (duration / 60) % 60;
+    let seconds = duration % 60;
+
+    format!("{}h {}m {}s", hours, minutes, seconds)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Datelike, Timelike};
+
+    #[test]
+    fn test_solar_report_new() {
+        // check that a 'new' method is defined and takes a coordinate and a date as parameters
+        let date = DateTime::parse_from_rfc3339("2020-03-25T12:00:00+00:00").unwrap();
+        let coordinates = structs::Coordinates {
+            latitude: structs::Latitude { value: 0.0 },
+            longitude: structs::Longitude { value: 0.0 },
+        };
+        // Default trait should handle the rest of the parameters
+        let _new_report = SolarReport::new(date, coordinates);
+    }
+
+    #[test]
+    fn test_solar_events_range() {
+        // the bulk calculation must agree exactly with the reports worked out one at a time
+        let start = DateTime::parse_from_rfc3339("2020-01-01T12:00:00+01:00").unwrap();
+        let end = DateTime::parse_from_rfc3339("2020-12-31T12:00:00+01:00").unwrap();
+        let coordinates =
+            structs::Coordinates::from_decimal_degrees("69.6492N", "18.9553E").unwrap();
+        let angles = structs::TwilightAngles::default();
+
+        let reports = solar_events_range(coordinates, angles, start, end, Duration::days(1));
+        assert_eq!(366, reports.len());
+        for (i, report) in reports.iter().enumerate() {
+            let expected = SolarReport::new(start + Duration::days(i as i64), coordinates);
+            assert_eq!(expected.date, report.date);
+            assert_eq!(expected.solar_noon, report.solar_noon);
+            assert_eq!(expected.day_length, report.day_length);
+            for event in enums::Event::all().iter() {
+                assert_eq!(
+                    expected.event_time(event).datetime,
+                    report.event_time(event).datetime
+                );
+            }
+        }
+
+        // both ends are included, however far the step goes
+        let weekly = solar_events_range(coordinates, angles, start, end, Duration::weeks(1));
+        assert_eq!(53, weekly.len());
+        assert_eq!(start + Duration::weeks(52), weekly[52].date);
+
+        assert!(solar_events_range(coordinates, angles, start, end, Duration::zero()).is_empty());
+        assert!(solar_events_range(coordinates, angles, end, start, Duration::days(1)).is_empty());
+
+        // and the twilight angles and refraction are those given, rather than the standard ones
+        let angles = structs::TwilightAngles {
+            civil: -4.0,
+            ..structs::TwilightAngles::default()
+        }
+        .with_atmosphere(950.0, -20.0);
+        let monthly = solar_events_range(coordinates, angles, start, end, Duration::days(30));
+        for report in monthly.iter() {
+            let expected = SolarReport::with_twilight_angles(report.date, coordinates, angles);
+            assert_eq!(expected.sunrise.datetime, report.sunrise.datetime);
+            assert_eq!(expected.civil_dusk.datetime, report.civil_dusk.datetime);
+        }
+        assert_ne!(
+            SolarReport::new(monthly[3].date, coordinates)
+                .civil_dusk
+                .datetime,
+            monthly[3].civil_dusk.datetime
+        );
+    }
+
+    #[test]
+    fn test_report_content() {
+        // check that the report contains all the correct metrics
+        let date = DateTime::parse_from_rfc3339("2020-03-25T12:00:00+00:00").unwrap();
+        let coordinates = structs::Coordinates {
+            latitude: structs::Latitude { value: 0.0 },
+            longitude: structs::Longitude { value: 0.0 },
+        };
+        let report = SolarReport::new(date, coordinates);
+        let report_str = report.format_report();
+
+        assert!(report_str.contains("LOCATION"));
+        assert!(report_str.contains("DATE"));
+        assert!(report_str.contains("Sunrise is at"));
+        assert!(report_str.contains("Solar noon is at"));
+        assert!(report_str.contains("Sunset is at"));
+        assert!(report_str.contains("The day length is"));
+
+        let sunrise_str = format!("{}", report.sunrise);
+        assert!(report_str.contains(&sunrise_str));
+
+        let solar_noon_str = format!("{}", report.solar_noon);
+        assert!(report_str.contains(&solar_noon_str));
+
+        let sunset_str = format!("{}", report.sunset);
+        assert!(report_str.contains(&sunset_str));
+
+        let day_length_str = report.day_length_hms().to_string();
+        assert!(report_str.contains(&day_length_str));
+    }
+
+    #[test]
+    fn test_report_solar_time() {
+        // in Edinburgh, well west of the Greenwich meridian, clock time is ahead of solar time
+        let date = DateTime::parse_from_rfc3339("2020-06-21T12:00:00+01:00").unwrap();
+        let coordinates =
+            structs::Coordinates::from_decimal_degrees("55.9533N", "3.1883W").unwrap();
+        let mut report = SolarReport::new(date, coordinates);
+        report.solar_time = true;
+
+        assert_eq!(
+            "2020-06-21 12:00:00 solar time",
+            report.format_time(report.solar_noon)
+        );
+        let clock_noon = DateTime::parse_from_rfc3339("2020-06-21T13:00:00+01:00").unwrap();
+        let solar_time = report.to_solar_time(clock_noon);
+        assert!(solar_time < NaiveDate::from_ymd(2020, 6, 21).and_hms(12, 0, 0));
+
+        // sunrise and sunset are equally far either side of noon
+        let noon = NaiveDate::from_ymd(2020, 6, 21).and_hms(12, 0, 0);
+        let morning = noon - report.to_solar_time(report.sunrise.datetime.unwrap());
+        let afternoon = report.to_solar_time(report.sunset.datetime.unwrap()) - noon;
+        assert!((morning - afternoon).num_seconds().abs() <= 60);
+
+        let report_str = report.format_report();
+        assert!(report_str.contains("Solar noon is at:         2020-06-21 12:00:00 solar time\n"));
+        // only the chosen date itself is still in clock time
+        assert_eq!(1, report_str.matches("+01:00").count());
+
+        // and combined with decimal hours
+        report.decimal_hours = true;
+        assert_eq!("12.0000", report.format_time(report.solar_noon));
+    }
+
+    #[test]
+    fn test_prime_meridian_and_equator() {
+        // at 0°N 0°E, clock noon in UTC is mean solar noon, so solar noon differs from it by the equation of
+        // time alone. This peaks at about 14 minutes late in February and 16½ minutes early in November
+        let coordinates = structs::Coordinates::from_decimal_degrees("0N", "0E").unwrap();
+        let start = DateTime::parse_from_rfc3339("2020-01-01T12:00:00+00:00").unwrap();
+        let end = DateTime::parse_from_rfc3339("2020-12-31T12:00:00+00:00").unwrap();
+        let angles = structs::TwilightAngles::default();
+        let reports = solar_events_range(coordinates, angles, start, end, Duration::days(1));
+
+        let noon_offset = |report: &SolarReport| (report.solar_noon - report.date).num_seconds();
+        let latest = reports
+            .iter()
+            .max_by_key(|report| noon_offset(report))
+            .unwrap();
+        let earliest = reports
+            .iter()
+            .min_by_key(|report| noon_offset(report))
+            .unwrap();
+        assert_eq!(
+            (2, 14 * 60 + 14),
+            (latest.date.month(), noon_offset(latest))
+        );
+        assert_eq!(
+            (11, -(16 * 60 + 30)),
+            (earliest.date.month(), noon_offset(earliest))
+        );
+
+        for report in reports.iter() {
+            // sunrise and sunset are either side of noon by the same amount
+            let morning = report.solar_noon - report.sunrise.datetime.unwrap();
+            let afternoon = report.sunset.datetime.unwrap() - report.solar_noon;
+            assert!((morning - afternoon).num_seconds().abs() <= 1);
+
+            // the day is a little over 12 hours all year round, as the Sun's upper edge and refraction make
+            // sunrise earlier and sunset later than the centre of the Sun crossing the horizon
+            assert!(report.day_length > Duration::seconds(12 * 3600 + 6 * 60));
+            assert!(report.day_length < Duration::seconds(12 * 3600 + 8 * 60));
+        }
+
+        // the default location of 0.0005°W puts solar noon about a tenth of a second later, so it rounds to
+        // the same second almost every day
+        let default = structs::Coordinates::from_decimal_degrees("51.4769N", "0.0005W").unwrap();
+        let greenwich = structs::Coordinates::from_decimal_degrees("51.4769N", "0E").unwrap();
+        for report in reports.iter() {
+            let noon = |coordinates| SolarReport::new(report.date, coordinates).solar_noon;
+            assert!((noon(default) - noon(greenwich)).num_seconds().abs() <= 1);
+        }
+
+        // and solar time is the same as UTC on the prime meridian, apart from the equation of time
+        let mut report = SolarReport::new(start, coordinates);
+        report.solar_time = true;
+        assert_eq!(
+            "2020-01-01 12:00:00 solar time",
+            report.format_time(report.solar_noon)
+        );
+    }
+
+    #[test]
+    fn test_report_relative_to_noon() {
+        let date = DateTime::parse_from_rfc3339("2020-06-21T12:00:00+01:00").unwrap();
+        let coordinates =
+            structs::Coordinates::from_decimal_degrees("55.9533N", "3.1883W").unwrap();
+        let mut report = SolarReport::new(date, coordinates);
+        report.relative_to_noon = true;
+
+        assert_eq!("+00:00:00", report.format_time(report.solar_noon));
+        let sunrise = report.format_time(report.sunrise.datetime.unwrap());
+        let sunset = report.format_time(report.sunset.datetime.unwrap());
+        assert!(sunrise.starts_with("-08:"), "{}", sunrise);
+        assert!(sunset.starts_with("+08:"), "{}", sunset);
+
+        // later on the following day, more than a day after noon
+        let next_day = report.solar_noon + Duration::hours(25) + Duration::seconds(5);
+        assert_eq!("+25:00:05", report.format_time(next_day));
+
+        let report_str = report.format_report();
+        assert!(report_str.contains("Solar noon is at:         +00:00:00\n"));
+
+        // and combined with decimal hours, a signed number of hours
+        report.decimal_hours = true;
+        assert_eq!("+0.0000", report.format_time(report.solar_noon));
+        assert_eq!(
+            "-1.5000",
+            report.format_time(report.solar_noon - Duration::minutes(90))
+        );
+    }
+
+    #[test]
+    fn test_report_decimal_hours() {
+        let date = DateTime::parse_from_rfc3339("2020-03-25T12:00:00+00:00").unwrap();
+        let coordinates =
+            structs::Coordinates::from_decimal_degrees("55.9533N", "3.1883W").unwrap();
+        let mut report = SolarReport::new(date, coordinates);
+        report.decimal_hours = true;
+
+        let sunrise = DateTime::parse_from_rfc3339("2020-03-25T06:04:37+00:00").unwrap();
+        assert_eq!("6.0769", report.format_time(sunrise));
+        let next_day = DateTime::parse_from_rfc3339("2020-03-26T00:30:00+00:00").unwrap();
+        assert_eq!("24.5000", report.format_time(next_day));
+
+        let report_str = report.format_report();
+        assert!(report_str.contains(&format!(
+            "Sunrise is at:            {:.4}\n",
+            report.hours_past_midnight(report.sunrise.datetime.unwrap())
+        )));
+        assert!(!report_str.contains("2020-03-25 06:"));
+
+        let mut polar_report = SolarReport::new(
+            date,
+            structs::Coordinates::from_decimal_degrees("89.0N", "0.0E").unwrap(),
+        );
+        polar_report.decimal_hours = true;
+        assert!(polar_report
+            .format_report()
+            .contains("Astronomical dusk is at:  Never"));
+    }
+
+    #[test]
+    fn test_report_day_fraction() {
+        let date = DateTime::parse_from_rfc3339("2020-03-25T12:00:00+00:00").unwrap();
+        let coordinates =
+            structs::Coordinates::from_decimal_degrees("55.9533N", "3.1883W").unwrap();
+        let mut report = SolarReport::new(date, coordinates);
+        report.day_fraction = true;
+
+        let time = DateTime::parse_from_rfc3339("2020-03-25T06:00:00+00:00").unwrap();
+        assert_eq!("0.25000", report.format_time(time));
+        let next_day = DateTime::parse_from_rfc3339("2020-03-26T00:30:00+00:00").unwrap();
+        assert_eq!("1.02083", report.format_time(next_day));
+        assert_eq!("0.25000", report.machine_time(Some(time)));
+
+        report.relative_to_noon = true;
+        let offset = report.solar_noon - Duration::hours(6);
+        assert_eq!("-0.25000", report.format_time(offset));
+    }
+
+    #[test]
+    fn test_report_time_only() {
+        let date = DateTime::parse_from_rfc3339("2020-03-25T12:00:00+01:00").unwrap();
+        let coordinates =
+            structs::Coordinates::from_decimal_degrees("55.9533N", "3.1883W").unwrap();
+        let mut report = SolarReport::new(date, coordinates);
+        report.time_only = true;
+
+        let time = DateTime::parse_from_rfc3339("2020-03-25T06:04:37+01:00").unwrap();
+        assert_eq!("06:04:37", report.format_time(time));
+        assert!(report.to_string().contains(&format!(
+            "Sunrise is at:            {}\n",
+            report.sunrise.datetime.unwrap().format("%H:%M:%S")
+        )));
+        assert_eq!("06:04:37", report.machine_time(Some(time)));
+
+        report.solar_time = true;
+        assert_eq!("12:00:00 solar time", report.format_time(report.solar_noon));
+    }
+
+    #[test]
+    fn test_report_with_accuracy() {
+        let date = DateTime::parse_from_rfc3339("2020-03-25T12:00:00+01:00").unwrap();
+        let coordinates =
+            structs::Coordinates::from_decimal_degrees("55.9533N", "3.1883W").unwrap();
+        let mut report = SolarReport::new(date, coordinates);
+        let sunrise = report.sunrise.datetime.unwrap();
+        assert_eq!("", report.format_uncertainty(sunrise));
+
+        report.with_accuracy = true;
+        assert_eq!(" (±2 min)", report.format_uncertainty(sunrise));
+        assert_eq!(
+            " (±39s)",
+            report.format_uncertainty(report.civil_dawn.datetime.unwrap())
+        );
+        assert!(report.to_string().contains(" (±2 min)\n"));
+    }
+
+    #[test]
+    fn test_report_with_locale() {
+        let date = DateTime::parse_from_rfc3339("2020-03-08T12:00:00+00:00").unwrap();
+        let coordinates =
+            structs::Coordinates::from_decimal_degrees("55.9533N", "3.1883W").unwrap();
+        let mut report = SolarReport::new(date, coordinates);
+        assert_eq!("2020-03-08 12:00:00 +00:00", report.format_date());
+
+        let params = [
+            ("en_GB.UTF-8", "Sunday 8 March 2020"),
+            ("fr", "dimanche 8 mars 2020"),
+            ("de-DE", "Sonntag, 8. März 2020"),
+            ("es_ES", "domingo, 8 de marzo de 2020"),
+            ("it", "domenica 8 marzo 2020"),
+            ("nl_NL", "zondag 8 maart 2020"),
+            ("pt_BR", "domingo, 8 de março de 2020"),
+        ];
+        for (locale, expected) in params.iter() {
+            report.locale = Some(enums::Locale::new(locale).unwrap());
+            assert_eq!(
+                format!("2020-03-08 12:00:00 +00:00\n{}", expected),
+                report.format_date()
+            );
+        }
+        assert!(report
+            .to_string()
+            .contains("DATE\n----\n2020-03-08 12:00:00 +00:00\ndomingo, 8 de março de 2020\n\n"));
+        assert!(enums::Locale::new("xx_XX").is_err());
+    }
+
+    #[test]
+    fn test_report_csv() {
+        let coordinates = structs::Coordinates::from_decimal_degrees("89.0N", "0.0E").unwrap();
+        let reports = [
+            SolarReport::new(
+                DateTime::parse_from_rfc3339("2020-03-25T12:00:00+00:00").unwrap(),
+                coordinates,
+            ),
+            SolarReport::new(
+                DateTime::parse_from_rfc3339("2020-03-26T12:00:00+00:00").unwrap(),
+                coordinates,
+            ),
+        ];
+        let csv = SolarReport::to_delimited(&reports, ',');
+        let lines: Vec<&str> = csv.lines().collect();
+
+        assert_eq!(3, lines.len());
+        assert_eq!(
+            "date,solar_noon,day_length,sunrise,sunset,civil_dawn,civil_dusk,nautical_dawn,nautical_dusk,\
+            astronomical_dawn,astronomical_dusk",
+            lines[0]
+        );
+        let fields: Vec<&str> = lines[1].split(',').collect();
+        assert_eq!(11, fields.len());
+        assert_eq!("2020-03-25", fields[0]);
+        assert_eq!(reports[0].solar_noon.to_rfc3339(), fields[1]);
+        assert_eq!(reports[0].day_length.num_seconds().to_string(), fields[2]);
+        // the Sun never sets near the pole, so there is no time for sunset
+        assert_eq!("", fields[4]);
+        assert!(lines[2].starts_with("2020-03-26,"));
+
+        // the same fields, separated by tabs
+        let tsv = SolarReport::to_delimited(&reports, '\t');
+        assert_eq!(csv.replace(',', "\t"), tsv);
+    }
+
+    #[test]
+    fn test_report_chart_data() {
+        let date = DateTime::parse_from_rfc3339("2020-06-21T12:00:00+01:00").unwrap();
+        let london = structs::Coordinates::from_decimal_degrees("51.4769N", "0.0005W").unwrap();
+        let pole = structs::Coordinates::from_decimal_degrees("89.0N", "0.0E").unwrap();
+        let reports = [SolarReport::new(date, london), SolarReport::new(date, pole)];
+
+        // sunrise at 04:42:54 and sunset at 21:20:53
+        assert_eq!(
+            "date,sunrise_minutes,sunset_minutes\n\
+            2020-06-21,282.90,1280.88\n\
+            2020-06-21,,\n",
+            SolarReport::to_chart_data(&reports)
+        );
+    }
+
+    #[test]
+    fn test_report_civil_day() {
+        let date = DateTime::parse_from_rfc3339("2020-06-21T12:00:00+01:00").unwrap();
+        let london = structs::Coordinates::from_decimal_degrees("51.4769N", "0.0005W").unwrap();
+        assert_eq!(
+            "CIVIL DAY\n\
+            ---------\n\
+            Date:                     2020-06-21\n\
+            Civil dawn is at:         2020-06-21 03:55:13 +01:00\n\
+            Civil dusk is at:         2020-06-21 22:08:35 +01:00\n\
+            The civil day length is:  18h 13m 22s",
+            SolarReport::new(date, london).format_civil_day()
+        );
+
+        // civil twilight never ends on a summer night in the far north
+        let north = structs::Coordinates::from_decimal_degrees("65.0N", "0.0E").unwrap();
+        assert!(SolarReport::new(date, north)
+            .format_civil_day()
+            .ends_with("Never\nThe civil day length is:  24h 0m 0s (all day)"));
+    }
+
+    #[test]
+    fn test_report_ics() {
+        let coordinates =
+            structs::Coordinates::from_decimal_degrees("55.9533N", "3.1883W").unwrap();
+        let mut report = SolarReport::new(
+            DateTime::parse_from_rfc3339("2020-06-21T12:00:00+01:00").unwrap(),
+            coordinates,
+        );
+        let created = Utc.ymd(2020, 1, 1).and_hms(0, 0, 0);
+        let events = [enums::Event::Sunrise, enums::Event::AstronomicalDawn];
+        let ics = SolarReport::to_ics(std::slice::from_ref(&report), &events, created);
+
+        let sunrise = report.sunrise.datetime.unwrap();
+        assert_eq!(
+            format!(
+                "BEGIN:VCALENDAR\r\n\
+                VERSION:2.0\r\n\
+                PRODID:-//heliocron//heliocron {}//EN\r\n\
+                BEGIN:VTIMEZONE\r\n\
+                TZID:UTC+01:00\r\n\
+                BEGIN:STANDARD\r\n\
+                DTSTART:19700101T000000\r\n\
+                TZOFFSETFROM:+0100\r\n\
+                TZOFFSETTO:+0100\r\n\
+                END:STANDARD\r\n\
+                END:VTIMEZONE\r\n\
+                BEGIN:VEVENT\r\n\
+                UID:sunrise-{}@heliocron\r\n\
+                DTSTAMP:20200101T000000Z\r\n\
+                DTSTART;TZID=UTC+01:00:{}\r\n\
+                SUMMARY:Sunrise\r\n\
+                GEO:55.9533;-3.1883\r\n\
+                END:VEVENT\r\n\
+                END:VCALENDAR\r\n",
+                env!("CARGO_PKG_VERSION"),
+                sunrise.with_timezone(&Utc).format("%Y%m%dT%H%M%SZ"),
+                sunrise.format("%Y%m%dT%H%M%S"),
+            ),
+            ics
+        );
+
+        report.hide_location = true;
+        assert!(
+            !SolarReport::to_ics(std::slice::from_ref(&report), &events, created).contains("GEO")
+        );
+    }
+
+    #[test]
+    fn test_render_template() {
+        let date = DateTime::parse_from_rfc3339("2020-03-25T12:00:00+00:00").unwrap();
+        let coordinates =
+            structs::Coordinates::from_decimal_degrees("55.9533N", "3.1883W").unwrap();
+        let mut report = SolarReport::new(date, coordinates);
+
+        assert_eq!(
+            "55.9533N, 3.1883W: sunrise at 2020-03-25 06:00:07 +00:00 {literal}\n",
+            report
+                .render_template("{latitude}, {longitude}: sunrise at { sunrise } {{literal}}\n")
+                .unwrap()
+        );
+
+        // the placeholders follow the display options
+        report.decimal_hours = true;
+        report.hide_location = true;
+        assert_eq!(
+            "Hidden 6.0019",
+            report.render_template("{latitude} {sunrise}").unwrap()
+        );
+
+        for template in ["{sunrse}", "{sunrise", "sunrise}", "{}"].iter() {
+            assert!(report.render_template(template).is_err(), "{}", template);
+        }
+        match report.render_template("{sunrse}") {
+            Err(HeliocronError::Config(ConfigErrorKind::UnknownPlaceholder(placeholder))) => {
+                assert_eq!("sunrse", placeholder)
+            }
+            _ => panic!("the placeholder should be unknown"),
+        }
+    }
+
+    #[test]
+    fn test_night_report() {
+        // the night begins with the dusk of the chosen date and ends with the dawn of the next
+        let coordinates =
+            structs::Coordinates::from_decimal_degrees("51.4769N", "0.0005W").unwrap();
+        let evening = SolarReport::new(
+            DateTime::parse_from_rfc3339("2020-12-21T12:00:00+00:00").unwrap(),
+            coordinates,
+        );
+        let morning = SolarReport::new(
+            DateTime::parse_from_rfc3339("2020-12-22T12:00:00+00:00").unwrap(),
+            coordinates,
+        );
+
+        let night = NightReport::new(&evening, &morning, enums::TwilightType::Astronomical);
+        assert_eq!(evening.astronomical_dusk.datetime, night.start.datetime);
+        assert_eq!(morning.astronomical_dawn.datetime, night.end.datetime);
+        assert_eq!(
+            Some(
+                morning.astronomical_dawn.datetime.unwrap()
+                    - evening.astronomical_dusk.datetime.unwrap()
+            ),
+            night.length
+        );
+        assert_eq!(
+            "NIGHT OF 2020-12-21\n\
+            -------------------\n\
+            Twilight:                 astronomical\n\
+            Night begins at:          2020-12-21 17:57:27 +00:00\n\
+            Night ends at:            2020-12-22 05:59:39 +00:00\n\
+            The night length is:      12h 2m 12s",
+            night.to_string()
+        );
+
+        // in the summer there is no astronomical night in London, but there is a nautical one
+        let evening = SolarReport::new(
+            DateTime::parse_from_rfc3339("2020-06-21T12:00:00+00:00").unwrap(),
+            coordinates,
+        );
+        let morning = SolarReport::new(
+            DateTime::parse_from_rfc3339("2020-06-22T12:00:00+00:00").unwrap(),
+            coordinates,
+        );
+        let night = NightReport::new(&evening, &morning, enums::TwilightType::Astronomical);
+        assert!(night.no_night && !night.night_all_day);
+        assert_eq!(None, night.length);
+        assert!(night
+            .to_string()
+            .contains("The night length is:      Never (no astronomical night)"));
+        assert!(
+            NightReport::new(&evening, &morning, enums::TwilightType::Nautical)
+                .length
+                .is_some()
+        );
+
+        // whilst in the polar night the Sun stays below the horizon all day
+        let coordinates = structs::Coordinates::from_decimal_degrees("78.0N", "15.0E").unwrap();
+        let evening = SolarReport::new(
+            DateTime::parse_from_rfc3339("2020-12-21T12:00:00+01:00").unwrap(),
+            coordinates,
+        );
+        let morning = SolarReport::new(
+            DateTime::parse_from_rfc3339("2020-12-22T12:00:00+01:00").unwrap(),
+            coordinates,
+        );
+        let night = NightReport::new(&evening, &morning, enums::TwilightType::Civil);
+        assert!(night.night_all_day && !night.no_night);
+        assert!(night
+            .to_string()
+            .contains("The night length is:      Never (night all day)"));
+    }
+
+    fn year_of_reports(year: i32, coordinates: structs::Coordinates) -> Vec<SolarReport> {
+        let mut date = FixedOffset::east(0).ymd(year, 1, 1).and_hms(12, 0, 0);
+        let mut reports = vec![];
+        while date.year() == year {
+            reports.push(SolarReport::new(date, coordinates));
+            date = date + Duration::days(1);
+        }
+        reports
+    }
+
+    #[test]
+    fn test_day_length_extremes() {
+        let coordinates =
+            structs::Coordinates::from_decimal_degrees("51.4769N", "0.0005W").unwrap();
+        let extremes = DayLengthExtremes::from_reports(&year_of_reports(2020, coordinates));
+
+        assert_eq!(
+            vec![
+                NaiveDate::from_ymd(2020, 6, 20),
+                NaiveDate::from_ymd(2020, 6, 21)
+            ],
+            extremes.longest.dates
+        );
+        assert_eq!(
+            vec![NaiveDate::from_ymd(2020, 12, 21)],
+            extremes.shortest.dates
+        );
+        assert!(extremes.polar_day.is_empty() && extremes.polar_night.is_empty());
+        assert_eq!(
+            format!(
+                "The longest day is:       2020-06-20, 2020-06-21 ({})\n\
+                The shortest day is:      2020-12-21 ({})",
+                format_hms(extremes.longest.day_length),
+                format_hms(extremes.shortest.day_length)
+            ),
+            extremes.to_string()
+        );
 
-        format!("{}h {}m {}s", hours, minutes, seconds)
-    }
+        // far enough north, the longest and shortest days give way to the polar day and night, and the polar
+        // night at the turn of the year is split in two
+        let coordinates = structs::Coordinates::from_decimal_degrees("78.0N", "15.0E").unwrap();
+        let extremes = DayLengthExtremes::from_reports(&year_of_reports(2020, coordinates));
 
-    fn day_fraction_to_datetime(&self, mut day_fraction: f64) -> DateTime<FixedOffset> {
-        let mut date = self.date;
+        assert_eq!(Duration::hours(24), extremes.longest.day_length);
+        assert_eq!(1, extremes.polar_day.len());
+        assert_eq!(2, extremes.polar_night.len());
+        assert_eq!(
+            NaiveDate::from_ymd(2020, 1, 1),
+            extremes.polar_night[0].start
+        );
+        assert_eq!(
+            NaiveDate::from_ymd(2020, 12, 31),
+            extremes.polar_night[1].end
+        );
 
-        // correct the date if the event rolls over to the next day, or happens on the previous day
-        if day_fraction < 0.0 {
-            date = date - Duration::days(1);
-            day_fraction = day_fraction.abs();
-        } else if day_fraction >= 1.0 {
-            date = date + Duration::days(1);
-            day_fraction -= 1.0;
-        }
+        let periods = [
+            &extremes.polar_night[0],
+            &extremes.polar_day[0],
+            &extremes.polar_night[1],
+        ];
+        assert!(periods.windows(2).all(|pair| pair[0].end < pair[1].start));
+        assert!(extremes.to_string().starts_with(&format!(
+            "The Sun doesn't set:      from {} to {} ({} days)\n\
+            The Sun doesn't rise:     from 2020-01-01 to ",
+            extremes.polar_day[0].start,
+            extremes.polar_day[0].end,
+            extremes.polar_day[0].days()
+        )));
+    }
 
-        let hour_fraction = day_fraction * 24.0;
-        let minute_fraction = hour_fraction.fract() * 60.0;
-        let second_fraction = minute_fraction.fract() * 60.0;
+    #[test]
+    fn test_season_bounds() {
+        // at 51.5N, astronomical night vanishes around the summer solstice but the other kinds last all year
+        let coordinates =
+            structs::Coordinates::from_decimal_degrees("51.4769N", "0.0005W").unwrap();
+        let bounds = SeasonBounds::from_reports(2021, &year_of_reports(2021, coordinates));
 
-        let time = NaiveTime::from_hms(
-            hour_fraction.trunc() as u32,
-            minute_fraction.trunc() as u32,
-            second_fraction.trunc() as u32,
+        assert_eq!(1, bounds.civil.len());
+        assert_eq!(1, bounds.nautical.len());
+        assert_eq!(2, bounds.astronomical.len());
+        assert_eq!(
+            NaiveDate::from_ymd(2021, 1, 1),
+            bounds.astronomical[0].start
         );
-
-        date.with_hour(time.hour())
-            .unwrap()
-            .with_minute(time.minute())
-            .unwrap()
-            .with_second(time.second())
-            .unwrap()
+        assert!(bounds.astronomical[0].end < NaiveDate::from_ymd(2021, 6, 21));
+        assert!(bounds.astronomical[1].start > NaiveDate::from_ymd(2021, 6, 21));
+        assert_eq!(
+            NaiveDate::from_ymd(2021, 12, 31),
+            bounds.astronomical[1].end
+        );
+        assert!(bounds.to_string().starts_with(
+            "Civil night:              every day\n\
+            Nautical night:           every day\n\
+            Astronomical night:       from 2021-01-01 to "
+        ));
+
+        // at the equator, there is every kind of night on every day, while near the pole there is none at all
+        // in the summer half of the year
+        let coordinates = structs::Coordinates::from_decimal_degrees("0.0N", "0.0E").unwrap();
+        let bounds = SeasonBounds::from_reports(2021, &year_of_reports(2021, coordinates));
+        assert!(bounds
+            .to_string()
+            .ends_with("Astronomical night:       every day"));
+
+        let coordinates = structs::Coordinates::from_decimal_degrees("89.0N", "0.0E").unwrap();
+        let bounds = SeasonBounds::from_reports(2021, &year_of_reports(2021, coordinates));
+        assert!(bounds
+            .civil
+            .iter()
+            .all(|night| night.end < NaiveDate::from_ymd(2021, 3, 20)
+                || night.start > NaiveDate::from_ymd(2021, 9, 23)));
     }
 
-    fn calculate_hour_angle(
-        &self,
-        event: Option<enums::TwilightType>,
-        solar_declination: f64,
-    ) -> f64 {
-        let event_angle: f64 = match event {
-            None => 90.833,
-            Some(enums::TwilightType::Civil) => 96.0,
-            Some(enums::TwilightType::Nautical) => 102.0,
-            Some(enums::TwilightType::Astronomical) => 108.0,
-        };
-
-        (((event_angle.to_radians().cos()
-            / (self.coordinates.latitude.to_radians().cos()
-                * solar_declination.to_radians().cos()))
-            - self.coordinates.latitude.to_radians().tan() * solar_declination.to_radians().tan())
-        .acos())
-        .to_degrees()
+    #[test]
+    fn test_next_occurrence() {
+        // the first sunrise after the polar night, and the first sunset, which comes the same day
+        let coordinates = structs::Coordinates::from_decimal_degrees("78.0N", "15.0E").unwrap();
+        let reports = year_of_reports(2020, coordinates);
+        let next = NextOccurrence::from_reports(&reports, enums::Event::Sunrise);
+        assert_eq!(NaiveDate::from_ymd(2020, 1, 1), next.from);
+        assert_eq!(Some(45), next.days_without);
+        assert_eq!(
+            "2020-02-15",
+            next.time.unwrap().format("%Y-%m-%d").to_string()
+        );
+        let next = NextOccurrence::from_reports(&reports, enums::Event::Sunset);
+        assert_eq!(Some(45), next.days_without);
+
+        // an event on the first day is found straight away
+        let next = NextOccurrence::from_reports(&reports[60..], enums::Event::Sunrise);
+        assert_eq!(Some(0), next.days_without);
+        assert!(next.to_string().ends_with("Days without one:         0"));
+
+        // or not at all, in the middle of the polar day
+        let next = NextOccurrence::from_reports(&reports[150..160], enums::Event::Sunset);
+        assert_eq!(None, next.days_without);
+        assert!(next
+            .to_string()
+            .ends_with("Next sunset:              None within 366 days"));
     }
 
-    fn calculate_event_start_and_end(
-        &self,
-        twilight_type: Option<enums::TwilightType>,
-        solar_noon: f64,
-        solar_declination: f64,
-    ) -> (EventTime, EventTime) {
-        let hour_angle = self.calculate_hour_angle(twilight_type, solar_declination);
+    #[test]
+    fn test_offset_event_times() {
+        let london = structs::Coordinates::from_decimal_degrees("51.4769N", "0.0005W").unwrap();
+        let pole = structs::Coordinates::from_decimal_degrees("89.0N", "0.0E").unwrap();
+        let reports = [
+            SolarReport::new(
+                DateTime::parse_from_rfc3339("2020-03-10T12:00:00+00:00").unwrap(),
+                london,
+            ),
+            SolarReport::new(
+                DateTime::parse_from_rfc3339("2020-06-21T12:00:00+00:00").unwrap(),
+                pole,
+            ),
+        ];
+        let sunset = enums::EventBase::Standard(enums::Event::Sunset);
+        let offsets = [
+            ("1h before".to_string(), Duration::hours(-1)),
+            ("+00:30".to_string(), Duration::minutes(30)),
+        ];
+        let times =
+            OffsetEventTime::from_reports(&reports, &sunset, Duration::minutes(5), &offsets, None);
 
-        if hour_angle.is_nan() {
-            return (None.into(), None.into());
-        }
+        // each day's times in the order of the offsets, which are kept as they were given
+        assert_eq!(4, times.len());
+        assert_eq!("1h before", times[0].offset);
+        assert_eq!(-3600, times[0].offset_seconds);
+        assert_eq!(
+            reports[0].sunset.datetime.unwrap() - Duration::minutes(55),
+            times[0].time.unwrap()
+        );
+        assert_eq!(1800, times[1].offset_seconds);
+        assert_eq!(NaiveDate::from_ymd(2020, 6, 21), times[2].date);
+        // the Sun doesn't set near the pole in summer, unless there is something to fall back on
+        assert_eq!(None, times[3].time);
+        assert_eq!(None, times[3].fallback);
+        let times = OffsetEventTime::from_reports(
+            &reports,
+            &sunset,
+            Duration::zero(),
+            &offsets,
+            Some(enums::DayMark::SolarMidnight),
+        );
+        assert_eq!(None, times[0].fallback);
+        assert_eq!(Some("solar_midnight".to_string()), times[3].fallback);
+        assert_eq!(
+            reports[1].solar_noon + Duration::minutes(12 * 60 + 30),
+            times[3].time.unwrap()
+        );
+    }
 
-        let start_fraction = solar_noon - (hour_angle * 4.0) / 1440.0;
-        let end_fraction = solar_noon + (hour_angle * 4.0) / 1440.0;
+    #[test]
+    fn test_time_or_fallback() {
+        let date = DateTime::parse_from_rfc3339("2020-06-21T12:00:00+01:00").unwrap();
+        let coordinates = structs::Coordinates::from_decimal_degrees("60.0N", "0.0E").unwrap();
+        let report = SolarReport::new(date, coordinates);
+        let sunset = enums::EventBase::Standard(enums::Event::Sunset);
+        let dusk = enums::EventBase::Standard(enums::Event::NauticalDusk);
 
-        let start_time = self.day_fraction_to_datetime(start_fraction);
-        let end_time = self.day_fraction_to_datetime(end_fraction);
+        // an event which occurs is used as it is, and one which doesn't falls back, if there is a fallback
+        assert_eq!(
+            (report.sunset.datetime, None),
+            report.time_or_fallback(&sunset, Some(enums::DayMark::SolarMidnight))
+        );
+        assert_eq!((None, None), report.time_or_fallback(&dusk, None));
+        assert_eq!(
+            (
+                Some(report.solar_noon + Duration::hours(12)),
+                Some(enums::DayMark::SolarMidnight)
+            ),
+            report.time_or_fallback(&dusk, Some(enums::DayMark::SolarMidnight))
+        );
 
-        (Some(start_time).into(), Some(end_time).into())
+        // unless the fallback doesn't occur either
+        assert_eq!(
+            (None, None),
+            report.time_or_fallback(
+                &dusk,
+                Some(enums::DayMark::Event(enums::Event::AstronomicalDusk))
+            )
+        );
     }
 
-    fn run(&mut self) {
-        let time_zone = self.date.offset().fix().local_minus_utc() as f64 / 3600.0;
-
-        let julian_date: f64 = self.date.to_julian_date();
-
-        let julian_century = (julian_date - 2451545.0) / 36525.0;
+    #[test]
+    fn test_schedule() {
+        // the earliest sunrise of the year, less the margin, rounded down to the minute
+        let coordinates =
+            structs::Coordinates::from_decimal_degrees("51.4769N", "0.0005W").unwrap();
+        let reports = year_of_reports(2020, coordinates);
+        let sunrise = enums::EventBase::Standard(enums::Event::Sunrise);
+        let earliest = reports
+            .iter()
+            .filter_map(|report| report.sunrise.datetime)
+            .map(|time| time.time())
+            .min()
+            .unwrap();
+        let schedule =
+            Schedule::from_reports(&reports, &sunrise, Duration::zero(), Duration::hours(1));
+        assert_eq!(
+            Some(NaiveTime::from_hms(
+                earliest.hour() - 1,
+                earliest.minute(),
+                0
+            )),
+            schedule.trigger
+        );
+        assert!(!schedule.before_midnight);
+
+        // it never fires before midnight, even when the event [+ offset] is before it
+        let schedule =
+            Schedule::from_reports(&reports, &sunrise, -Duration::hours(5), Duration::hours(1));
+        assert_eq!(Some(NaiveTime::from_hms(0, 0, 0)), schedule.trigger);
+        assert!(schedule.before_midnight);
+
+        // and has nothing to fire for if the event never happens
+        let overhead =
+            enums::EventBase::Elevation("overhead".to_string(), 80.0, enums::Direction::Rising);
+        let schedule =
+            Schedule::from_reports(&reports, &overhead, Duration::zero(), Duration::hours(1));
+        assert_eq!(None, schedule.trigger);
+    }
 
-        let geometric_solar_mean_longitude =
-            (280.46646 + julian_century * (36000.76983 + julian_century * 0.0003032)) % 360.0;
+    #[test]
+    fn test_polar_day_length() {
+        // a day without sunrise or sunset lasts all day in the midnight Sun, and not at all in the polar night
+        let coordinates = structs::Coordinates::from_decimal_degrees("78.22N", "15.635E").unwrap();
+        let summer = SolarReport::new(
+            DateTime::parse_from_rfc3339("2020-06-21T12:00:00+01:00").unwrap(),
+            coordinates,
+        );
+        assert_eq!(Duration::hours(24), summer.day_length);
 
-        let solar_mean_anomaly =
-            357.52911 + julian_century * (35999.05029 - 0.0001537 * julian_century);
+        let winter = SolarReport::new(
+            DateTime::parse_from_rfc3339("2020-12-21T12:00:00+01:00").unwrap(),
+            coordinates,
+        );
+        assert_eq!(Duration::zero(), winter.day_length);
+    }
 
-        let eccent_earth_orbit =
-            0.016708634 - julian_century * (0.000042037 + 0.0000001267 * julian_century);
+    #[test]
+    fn test_duration_above_one_crossing() {
+        // with only one crossing on the day, the time above the altitude runs to or from midnight
+        let report = SolarReport::new(
+            DateTime::parse_from_rfc3339("2020-06-21T12:00:00+01:00").unwrap(),
+            structs::Coordinates::from_decimal_degrees("51.4769N", "0.0005W").unwrap(),
+        );
+        let time = |rfc3339| EventTime::from(Some(DateTime::parse_from_rfc3339(rfc3339).unwrap()));
+        let never = EventTime::from(None);
 
-        let equation_of_the_center = solar_mean_anomaly.to_radians().sin()
-            * (1.914602 - julian_century * (0.004817 + 0.000014 * julian_century))
-            + (2.0 * solar_mean_anomaly).to_radians().sin()
-                * (0.019993 - 0.000101 * julian_century)
-            + (3.0 * solar_mean_anomaly).to_radians().sin() * 0.000289;
+        assert_eq!(
+            Duration::hours(2),
+            report.duration_above(&time("2020-06-21T22:00:00+01:00"), &never, -6.0)
+        );
+        assert_eq!(
+            Duration::hours(3),
+            report.duration_above(&never, &time("2020-06-21T03:00:00+01:00"), -6.0)
+        );
+    }
 
-        let solar_true_longitude = geometric_solar_mean_longitude + equation_of_the_center;
+    #[test]
+    fn test_polar_boundaries_day_length() {
+        // on the last day of the polar night, the Sun gets close enough to the horizon to be lifted above it by
+        // refraction without rising, which must not count as a day without sunset
+        let coordinates = structs::Coordinates::from_decimal_degrees("78.0N", "15.0E").unwrap();
+        let report = SolarReport::new(
+            DateTime::parse_from_rfc3339("2020-02-14T12:00:00+01:00").unwrap(),
+            coordinates,
+        );
+        assert!(!report.sunrise.is_some() && !report.sunset.is_some());
+        assert_eq!(Duration::zero(), report.day_length);
+    }
 
-        let solar_apparent_longitude = solar_true_longitude
-            - 0.00569
-            - 0.00478 * (125.04 - 1934.136 * julian_century).to_radians().sin();
+    #[test]
+    fn test_report_hidden_location() {
+        let date = DateTime::parse_from_rfc3339("2020-03-25T12:00:00+00:00").unwrap();
+        let coordinates =
+            structs::Coordinates::from_decimal_degrees("55.9533N", "3.1883W").unwrap();
+        let mut report = SolarReport::new(date, coordinates);
+        assert!(report.format_report().contains("Latitude: 55.9533N"));
 
-        let mean_oblique_ecliptic = 23.0
-            + (26.0
-                + (21.448
-                    - julian_century
-                        * (46.815 + julian_century * (0.00059 - julian_century * 0.001813)))
-                    / 60.0)
-                / 60.0;
+        report.hide_location = true;
+        let report_str = report.format_report();
+        assert!(report_str.contains("LOCATION\n--------\nHidden\n"));
+        assert!(!report_str.contains("55.9533"));
+        assert!(!report_str.contains("3.1883"));
+    }
 
-        let oblique_corrected = mean_oblique_ecliptic
-            + 0.00256 * (125.04 - 1934.136 * julian_century).to_radians().cos();
+    #[test]
+    fn test_report_json() {
+        let date = DateTime::parse_from_rfc3339("2020-06-21T12:00:00+01:00").unwrap();
+        let coordinates =
+            structs::Coordinates::from_decimal_degrees("55.9533N", "3.1883W").unwrap();
+        let report = SolarReport::new(date, coordinates);
 
-        let solar_declination = ((oblique_corrected.to_radians().sin()
-            * solar_apparent_longitude.to_radians().sin())
-        .asin())
-        .to_degrees();
+        let json: serde_json::Value = serde_json::from_str(&report.to_json(false)).unwrap();
 
-        let var_y = (oblique_corrected / 2.0).to_radians().tan().powi(2);
-
-        let equation_of_time = 4.0
-            * (var_y * (geometric_solar_mean_longitude.to_radians() * 2.0).sin()
-                - 2.0 * eccent_earth_orbit * solar_mean_anomaly.to_radians().sin()
-                + 4.0
-                    * eccent_earth_orbit
-                    * var_y
-                    * solar_mean_anomaly.to_radians().sin()
-                    * (geometric_solar_mean_longitude.to_radians() * 2.0).cos()
-                - 0.5 * var_y * var_y * (geometric_solar_mean_longitude.to_radians() * 4.0).sin()
-                - 1.25
-                    * eccent_earth_orbit
-                    * eccent_earth_orbit
-                    * (solar_mean_anomaly.to_radians() * 2.0).sin())
-            .to_degrees();
+        assert_eq!("2020-06-21T12:00:00+01:00", json["date"]);
+        assert_eq!(55.9533, json["coordinates"]["latitude"]);
+        assert_eq!(-3.1883, json["coordinates"]["longitude"]);
 
-        let solar_noon = (720.0 - 4.0 * self.coordinates.longitude.value - equation_of_time
-            + time_zone * 60.0)
-            / 1440.0;
+        // a hidden location is null, but still used in the calculations
+        let mut hidden_report = SolarReport::new(date, coordinates);
+        hidden_report.hide_location = true;
+        let hidden_json: serde_json::Value =
+            serde_json::from_str(&hidden_report.to_json(false)).unwrap();
+        assert!(hidden_json["coordinates"].is_null());
 
-        // plain sunrise/sunset
-        let (sunrise, sunset) =
-            self.calculate_event_start_and_end(None, solar_noon, solar_declination);
+        // compact JSON is the same, just on one line
+        let compact = report.to_json(true);
+        assert!(!compact.contains('\n'));
+        assert_eq!(
+            json,
+            serde_json::from_str::<serde_json::Value>(&compact).unwrap()
+        );
+        assert_eq!(json["sunrise"], hidden_json["sunrise"]);
+        assert_eq!("2020-06-21T04:26:26+01:00", json["sunrise"]);
+        assert_eq!("2020-06-21T13:14:39+01:00", json["solar_noon"]);
+        assert_eq!(report.day_length.num_seconds(), json["day_length"]);
+        // events which don't occur are null
+        assert!(json["astronomical_dusk"].is_null());
+        assert_eq!(SCHEMA_VERSION, json["schema_version"]);
+    }
 
-        // civil twilight
-        let (civil_twilight_start, civil_twilight_end) = self.calculate_event_start_and_end(
-            Some(enums::TwilightType::Civil),
-            solar_noon,
-            solar_declination,
+    #[test]
+    fn test_json_schema_matches_report() {
+        // every field of the JSON output must be described by the schema, and vice versa, with all but the
+        // astronomical data required
+        let date = DateTime::parse_from_rfc3339("2020-06-21T12:00:00+01:00").unwrap();
+        let coordinates =
+            structs::Coordinates::from_decimal_degrees("55.9533N", "3.1883W").unwrap();
+        let mut report = SolarReport::new(date, coordinates);
+        let json: serde_json::Value = serde_json::from_str(&report.to_json(false)).unwrap();
+        report.include_astro = true;
+        let astro_json: serde_json::Value = serde_json::from_str(&report.to_json(false)).unwrap();
+        let schema: serde_json::Value = serde_json::from_str(&json_schema(false)).unwrap();
+        let report_schema = &schema["definitions"]["report"];
+
+        let mut fields: Vec<&String> = json.as_object().unwrap().keys().collect();
+        let mut astro_fields: Vec<&String> = astro_json.as_object().unwrap().keys().collect();
+        let mut properties: Vec<&String> = report_schema["properties"]
+            .as_object()
+            .unwrap()
+            .keys()
+            .collect();
+        let mut required: Vec<&str> = report_schema["required"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|field| field.as_str().unwrap())
+            .collect();
+        fields.sort();
+        astro_fields.sort();
+        properties.sort();
+        required.sort();
+
+        assert_eq!(astro_fields, properties);
+        assert_eq!(
+            fields
+                .iter()
+                .map(|field| field.as_str())
+                .collect::<Vec<_>>(),
+            required
+        );
+        assert_eq!(
+            SCHEMA_VERSION,
+            report_schema["properties"]["schema_version"]["const"]
         );
 
-        // nautical twilight
-        let (nautical_twilight_start, nautical_twilight_end) = self.calculate_event_start_and_end(
-            Some(enums::TwilightType::Nautical),
-            solar_noon,
-            solar_declination,
+        let astro_schema = &report_schema["properties"]["astro"];
+        let mut astro_fields: Vec<&String> =
+            astro_json["astro"].as_object().unwrap().keys().collect();
+        let mut astro_properties: Vec<&String> = astro_schema["properties"]
+            .as_object()
+            .unwrap()
+            .keys()
+            .collect();
+        astro_fields.sort();
+        astro_properties.sort();
+        assert_eq!(astro_fields, astro_properties);
+        assert_eq!(
+            astro_fields.len(),
+            astro_schema["required"].as_array().unwrap().len()
         );
 
-        // astronomical twilight
-        let (astronomical_twilight_start, astronomical_twilight_end) = self
-            .calculate_event_start_and_end(
-                Some(enums::TwilightType::Astronomical),
-                solar_noon,
-                solar_declination,
-            );
+        // and a range of dates is an array of reports
+        let json: serde_json::Value =
+            serde_json::from_str(&SolarReport::to_json_array(&[report], false)).unwrap();
+        assert_eq!(SCHEMA_VERSION, json[0]["schema_version"]);
+    }
 
-        self.solar_noon = self.day_fraction_to_datetime(solar_noon);
+    #[test]
+    fn test_astro_content() {
+        let date = DateTime::parse_from_rfc3339("2020-03-25T12:00:00+00:00").unwrap();
+        let coordinates = structs::Coordinates::from_decimal_degrees("0.0N", "0.0W").unwrap();
+        let report = SolarReport::new(date, coordinates);
+        let astro_str = report.format_astro();
+
+        assert!(astro_str.contains("Julian date (UT):         2458934.00000"));
+        assert!(astro_str.contains("ΔT (TT - UT):             71.7s"));
+        assert!(astro_str.contains("Solar declination:        2.1043°"));
+        assert!(astro_str.contains("Solar hour angle:         -1.4504°"));
+        assert!(astro_str.contains("Sidereal time (GMST):     0.22727h"));
+        assert!(astro_str.contains("Altitude rate at sunrise: +0.2499° per minute"));
+        assert!(astro_str.contains("Altitude rate at sunset:  -0.2499° per minute"));
+        assert!(astro_str.contains("Angular diameter:         32.07'"));
+        assert!(astro_str.contains("Terrestrial Time"));
+    }
 
-        self.sunrise = sunrise;
-        self.sunset = sunset;
+    #[test]
+    fn test_shadow_content() {
+        // at solar noon on the equator at the equinox, the Sun is almost directly overhead
+        let date = DateTime::parse_from_rfc3339("2020-03-20T12:07:18+00:00").unwrap();
+        let coordinates = structs::Coordinates::from_decimal_degrees("0.0N", "0.0W").unwrap();
+        let report = SolarReport::new(date, coordinates);
+        let shadow_str = report.format_shadow();
 
-        self.civil_dawn = civil_twilight_start;
-        self.civil_dusk = civil_twilight_end;
+        assert!(shadow_str.contains("Solar altitude:           89.8"));
+        assert!(shadow_str.contains("Shadow length:            0.00 × object height"));
 
-        self.nautical_dawn = nautical_twilight_start;
-        self.nautical_dusk = nautical_twilight_end;
+        // whilst at 45° altitude, shadows are as long as the object is tall, and point away from the Sun
+        let date = DateTime::parse_from_rfc3339("2020-06-21T13:14:39+01:00").unwrap();
+        let coordinates = structs::Coordinates::from_decimal_degrees("68.4N", "3.1883W").unwrap();
+        let report = SolarReport::new(date, coordinates);
+        let shadow_str = report.format_shadow();
 
-        self.astronomical_dawn = astronomical_twilight_start;
-        self.astronomical_dusk = astronomical_twilight_end;
+        assert!(shadow_str.contains("Solar azimuth:            180.0"));
+        assert!(shadow_str.contains("Shadow direction:         0.00°"));
+        assert!(shadow_str.contains("Shadow length:            1.00 × object height"));
 
-        self.day_length = self.calculate_day_length();
+        // and at night, there are no shadows at all
+        let date = DateTime::parse_from_rfc3339("2020-03-20T00:00:00+00:00").unwrap();
+        let coordinates = structs::Coordinates::from_decimal_degrees("0.0N", "0.0W").unwrap();
+        let report = SolarReport::new(date, coordinates);
+        let shadow_str = report.format_shadow();
+
+        assert!(shadow_str.contains("Sun is up:                no"));
+        assert!(shadow_str.contains("Shadow direction:         None"));
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    #[test]
+    fn test_report_at_instant() {
+        // the day's events are the same at any time of day, whilst the position of the Sun is not
+        let coordinates =
+            structs::Coordinates::from_decimal_degrees("55.9533N", "3.1883W").unwrap();
+        let noon = DateTime::parse_from_rfc3339("2020-06-21T12:00:00+01:00").unwrap();
+        let instant = DateTime::parse_from_rfc3339("2020-06-21T21:30:00+01:00").unwrap();
+        let noon_report = SolarReport::new(noon, coordinates);
+        let instant_report = SolarReport::new(instant, coordinates);
+
+        assert_eq!(
+            noon_report.sunrise.datetime,
+            instant_report.sunrise.datetime
+        );
+        assert_eq!(noon_report.sunset.datetime, instant_report.sunset.datetime);
+        assert_eq!(noon_report.solar_noon, instant_report.solar_noon);
+        assert_eq!(instant, instant_report.date);
+
+        let shadow_str = instant_report.format_shadow();
+        assert!(shadow_str.contains("At 2020-06-21 21:30:00 +01:00:"));
+        assert!(shadow_str.contains("Sun is up:                yes"));
+    }
 
     #[test]
-    fn test_solar_report_new() {
-        // check that a 'new' method is defined and takes a coordinate and a date as parameters
+    fn test_custom_twilight_angles() {
         let date = DateTime::parse_from_rfc3339("2020-03-25T12:00:00+00:00").unwrap();
-        let coordinates = structs::Coordinates {
-            latitude: structs::Latitude { value: 0.0 },
-            longitude: structs::Longitude { value: 0.0 },
+        let coordinates =
+            structs::Coordinates::from_decimal_degrees("55.9533N", "3.1883W").unwrap();
+        let standard = SolarReport::new(date, coordinates);
+        assert!(!standard.to_string().contains("non-standard"));
+
+        // civil twilight at the nautical angle is just nautical twilight
+        let angles = structs::TwilightAngles {
+            civil: -12.0,
+            ..Default::default()
         };
-        // Default trait should handle the rest of the parameters
-        let _new_report = SolarReport::new(date, coordinates);
+        let custom = SolarReport::with_twilight_angles(date, coordinates, angles);
+
+        assert_eq!(standard.nautical_dawn.datetime, custom.civil_dawn.datetime);
+        assert_eq!(standard.nautical_dusk.datetime, custom.civil_dusk.datetime);
+        assert_eq!(standard.sunrise.datetime, custom.sunrise.datetime);
+        assert!(custom.to_string().contains(
+            "Note: non-standard twilight angles are in effect (civil -12.0°, nautical -12.0°, astronomical -18.0°)."
+        ));
+        assert!(custom.format_night().contains("Civil (-12°):"));
     }
 
     #[test]
-    fn test_report_content() {
-        // check that the report contains all the correct metrics
-        let date = DateTime::parse_from_rfc3339("2020-03-25T12:00:00+00:00").unwrap();
-        let coordinates = structs::Coordinates {
-            latitude: structs::Latitude { value: 0.0 },
-            longitude: structs::Longitude { value: 0.0 },
-        };
+    fn test_crossings() {
+        let date = DateTime::parse_from_rfc3339("2020-06-21T12:00:00+01:00").unwrap();
+        let coordinates =
+            structs::Coordinates::from_decimal_degrees("51.4769N", "0.0005W").unwrap();
         let report = SolarReport::new(date, coordinates);
-        let report_str = report.format_report();
 
-        assert!(report_str.contains("LOCATION"));
-        assert!(report_str.contains("DATE"));
-        assert!(report_str.contains("Sunrise is at"));
-        assert!(report_str.contains("Solar noon is at"));
-        assert!(report_str.contains("Sunset is at"));
-        assert!(report_str.contains("The day length is"));
+        // a depression of 6° is civil twilight
+        let (rising, setting) = report.crossings(6.0);
+        assert_eq!(report.civil_dawn.datetime, rising.datetime);
+        assert_eq!(report.civil_dusk.datetime, setting.datetime);
 
-        let sunrise_str = format!("{}", report.sunrise);
-        assert!(report_str.contains(&sunrise_str));
+        // the crossings move apart as the angle gets shallower
+        let (rising, setting) = report.crossings(1.0);
+        assert!(rising.datetime.unwrap() < report.sunrise.datetime.unwrap());
+        assert!(setting.datetime.unwrap() > report.sunset.datetime.unwrap());
+        assert!(rising.datetime.unwrap() > report.civil_dawn.datetime.unwrap());
 
-        let solar_noon_str = format!("{}", report.solar_noon);
-        assert!(report_str.contains(&solar_noon_str));
+        // and around midsummer in London, the Sun never gets 18° below the horizon
+        let (rising, setting) = report.crossings(18.0);
+        assert!(rising.datetime.is_none() && setting.datetime.is_none());
+    }
 
-        let sunset_str = format!("{}", report.sunset);
-        assert!(report_str.contains(&sunset_str));
+    #[test]
+    fn test_night_content() {
+        // around midsummer in London, it never gets astronomically dark
+        let date = DateTime::parse_from_rfc3339("2020-06-21T12:00:00+01:00").unwrap();
+        let coordinates =
+            structs::Coordinates::from_decimal_degrees("51.4769N", "0.0005W").unwrap();
+        let report = SolarReport::new(date, coordinates);
+        let night_str = report.format_night();
+
+        let nautical =
+            report.nautical_dusk.datetime.unwrap() - report.nautical_dawn.datetime.unwrap();
+        assert!(night_str.contains(&format!(
+            "Nautical (-12°):          {:<18}{}\n",
+            format_hms(nautical),
+            format_hms(Duration::hours(24) - nautical)
+        )));
+        assert!(
+            night_str.contains("Astronomical (-18°):      24h 0m 0s         0h 0m 0s (no night)")
+        );
 
-        let day_length_str = format!("{}", report.day_length_hms());
-        assert!(report_str.contains(&day_length_str));
+        // whilst during the polar night the Sun never rises, but still gets within 12° of the horizon
+        let date = DateTime::parse_from_rfc3339("2020-12-21T12:00:00+01:00").unwrap();
+        let coordinates = structs::Coordinates::from_decimal_degrees("78.22N", "15.635E").unwrap();
+        let report = SolarReport::new(date, coordinates);
+        let night_str = report.format_night();
+
+        assert_eq!("0h 0m 0s", report.day_length_hms());
+        assert!(night_str
+            .contains("Civil (-6°):              0h 0m 0s          24h 0m 0s (night all day)"));
+        assert!(!night_str.contains("Nautical (-12°):          0h 0m 0s"));
+    }
+
+    #[test]
+    fn test_photo_content() {
+        // at the equinox in London, the morning and evening windows mirror each other about solar noon
+        let date = DateTime::parse_from_rfc3339("2020-03-20T12:00:00+00:00").unwrap();
+        let coordinates =
+            structs::Coordinates::from_decimal_degrees("51.4769N", "0.0005W").unwrap();
+        let photo_str = SolarReport::new(date, coordinates).format_photo();
+        assert!(photo_str.contains(
+            "Morning golden hour:      2020-03-20 05:40:54 +00:00  2020-03-20 06:45:16 +00:00  1h 4m 22s\n"
+        ));
+        assert!(photo_str.contains(
+            "Evening blue hour:        2020-03-20 18:33:42 +00:00  2020-03-20 18:46:38 +00:00  0h 12m 56s\n"
+        ));
+        assert!(photo_str.contains("Total golden hour:        2h 8m 44s\n"));
+        assert!(photo_str.ends_with("Total blue hour:          0h 25m 52s"));
+
+        // in Tromsø at midsummer the Sun never gets as low as the blue hour, and the evening golden hour lasts
+        // through the night into the morning
+        let date = DateTime::parse_from_rfc3339("2020-06-21T12:00:00+01:00").unwrap();
+        let coordinates = structs::Coordinates::from_decimal_degrees("69.65N", "18.96E").unwrap();
+        let photo_str = SolarReport::new(date, coordinates).format_photo();
+        assert!(photo_str.contains("Runs on from the night before\n"));
+        assert!(photo_str.contains("Runs on through the night\n"));
+        assert!(photo_str.contains(
+            "Morning blue hour:        Never                       Never                       None\n"
+        ));
+
+        // whilst in the polar winter the Sun never gets high enough to end the golden hour before noon
+        let date = DateTime::parse_from_rfc3339("2020-12-21T12:00:00+01:00").unwrap();
+        let photo_str = SolarReport::new(date, coordinates).format_photo();
+        assert!(
+            photo_str.contains("Never                       Runs into the evening golden hour\n")
+        );
+        assert!(photo_str.contains("Total golden hour:        2h 25m 20s\n"));
+
+        // and near the pole at the equinox, it is the golden hour all day
+        let date = DateTime::parse_from_rfc3339("2020-03-25T12:00:00+00:00").unwrap();
+        let coordinates = structs::Coordinates::from_decimal_degrees("88N", "0E").unwrap();
+        let photo_str = SolarReport::new(date, coordinates).format_photo();
+        assert!(photo_str.contains("Total golden hour:        24h 0m 0s (all day)\n"));
+        assert_eq!(2, photo_str.matches("All day").count());
     }
 
     #[test]
     fn test_sunrise_sunset() {
-        // validated against NOAA calculations https://www.esrl.noaa.gov/gmd/grad/solcalc/calcdetails.html
+        // validated against NOAA calculations https://www.esrl.noaa.gov/gmd/grad/solcalc/calcdetails.html. NOAA
+        // don't distinguish between TT and UT, so a handful of these differ from their values by a second
         // ~Springtime
         let date = DateTime::parse_from_rfc3339("2020-03-25T12:00:00+00:00").unwrap();
         let coordinates =
@@ -374,6 +3393,7 @@ mod tests {
             date,
             coordinates,
             solar_noon: date,
+            solar_noon_altitude: 0.0,
             sunrise: EventTime::from(None),
             sunset: EventTime::from(None),
             civil_dawn: EventTime::from(None),
@@ -383,9 +3403,24 @@ mod tests {
             astronomical_dawn: EventTime::from(None),
             astronomical_dusk: EventTime::from(None),
             day_length: Duration::seconds(0),
+            twilight_angles: structs::TwilightAngles::default(),
+            hide_location: false,
+            decimal_hours: false,
+            day_fraction: false,
+            solar_time: false,
+            relative_to_noon: false,
+            time_only: false,
+            with_accuracy: false,
+            locale: None,
+            include_astro: false,
+            sections: structs::ReportSections::default(),
+            local_horizon: false,
         };
 
-        report.run();
+        report.run(&LocationConstants::new(
+            &report.coordinates,
+            &report.twilight_angles,
+        ));
         assert_eq!("06:00:07", report.sunrise.time().unwrap().to_string());
         assert_eq!("18:36:59", report.sunset.time().unwrap().to_string());
         assert_eq!("12:18:33", report.solar_noon.time().to_string());
@@ -410,6 +3445,7 @@ mod tests {
             date,
             coordinates,
             solar_noon: date,
+            solar_noon_altitude: 0.0,
             sunrise: EventTime::from(None),
             sunset: EventTime::from(None),
             civil_dawn: EventTime::from(None),
@@ -419,9 +3455,24 @@ mod tests {
             astronomical_dawn: EventTime::from(None),
             astronomical_dusk: EventTime::from(None),
             day_length: Duration::seconds(0),
+            twilight_angles: structs::TwilightAngles::default(),
+            hide_location: false,
+            decimal_hours: false,
+            day_fraction: false,
+            solar_time: false,
+            relative_to_noon: false,
+            time_only: false,
+            with_accuracy: false,
+            locale: None,
+            include_astro: false,
+            sections: structs::ReportSections::default(),
+            local_horizon: false,
         };
 
-        report.run();
+        report.run(&LocationConstants::new(
+            &report.coordinates,
+            &report.twilight_angles,
+        ));
         assert_eq!("04:26:26", report.sunrise.time().unwrap().to_string());
         assert_eq!("22:02:52", report.sunset.time().unwrap().to_string());
         assert_eq!("13:14:39", report.solar_noon.time().to_string());
@@ -444,6 +3495,7 @@ mod tests {
             date,
             coordinates,
             solar_noon: date,
+            solar_noon_altitude: 0.0,
             sunrise: EventTime::from(None),
             sunset: EventTime::from(None),
             civil_dawn: EventTime::from(None),
@@ -453,16 +3505,31 @@ mod tests {
             astronomical_dawn: EventTime::from(None),
             astronomical_dusk: EventTime::from(None),
             day_length: Duration::seconds(0),
+            twilight_angles: structs::TwilightAngles::default(),
+            hide_location: false,
+            decimal_hours: false,
+            day_fraction: false,
+            solar_time: false,
+            relative_to_noon: false,
+            time_only: false,
+            with_accuracy: false,
+            locale: None,
+            include_astro: false,
+            sections: structs::ReportSections::default(),
+            local_horizon: false,
         };
 
-        report.run();
+        report.run(&LocationConstants::new(
+            &report.coordinates,
+            &report.twilight_angles,
+        ));
         assert_eq!("06:47:03", report.sunrise.time().unwrap().to_string());
-        assert_eq!("19:47:03", report.sunset.time().unwrap().to_string());
+        assert_eq!("19:47:04", report.sunset.time().unwrap().to_string());
         assert_eq!("13:17:03", report.solar_noon.time().to_string());
         assert_eq!("06:09:13", report.civil_dawn.time().unwrap().to_string());
         assert_eq!("20:24:53", report.civil_dusk.time().unwrap().to_string());
         assert_eq!("05:23:09", report.nautical_dawn.time().unwrap().to_string());
-        assert_eq!("21:10:57", report.nautical_dusk.time().unwrap().to_string());
+        assert_eq!("21:10:58", report.nautical_dusk.time().unwrap().to_string());
         assert_eq!(
             "04:32:31",
             report.astronomical_dawn.time().unwrap().to_string()
@@ -479,6 +3546,7 @@ mod tests {
             date,
             coordinates,
             solar_noon: date,
+            solar_noon_altitude: 0.0,
             sunrise: EventTime::from(None),
             sunset: EventTime::from(None),
             civil_dawn: EventTime::from(None),
@@ -488,9 +3556,24 @@ mod tests {
             astronomical_dawn: EventTime::from(None),
             astronomical_dusk: EventTime::from(None),
             day_length: Duration::seconds(0),
+            twilight_angles: structs::TwilightAngles::default(),
+            hide_location: false,
+            decimal_hours: false,
+            day_fraction: false,
+            solar_time: false,
+            relative_to_noon: false,
+            time_only: false,
+            with_accuracy: false,
+            locale: None,
+            include_astro: false,
+            sections: structs::ReportSections::default(),
+            local_horizon: false,
         };
 
-        report.run();
+        report.run(&LocationConstants::new(
+            &report.coordinates,
+            &report.twilight_angles,
+        ));
         assert_eq!(
             "2020-03-25 17:23:21 +00:00",
             report.sunrise.datetime.unwrap().to_string()
@@ -509,7 +3592,7 @@ mod tests {
             report.civil_dusk.datetime.unwrap().to_string()
         );
         assert_eq!(
-            "2020-03-25 16:00:57 +00:00",
+            "2020-03-25 16:00:56 +00:00",
             report.nautical_dawn.datetime.unwrap().to_string()
         );
         assert_eq!(
@@ -517,7 +3600,7 @@ mod tests {
             report.nautical_dusk.datetime.unwrap().to_string()
         );
         assert_eq!(
-            "2020-03-25 15:12:24 +00:00",
+            "2020-03-25 15:12:23 +00:00",
             report.astronomical_dawn.datetime.unwrap().to_string()
         );
         assert_eq!(
@@ -532,6 +3615,7 @@ mod tests {
             date,
             coordinates,
             solar_noon: date,
+            solar_noon_altitude: 0.0,
             sunrise: EventTime::from(None),
             sunset: EventTime::from(None),
             civil_dawn: EventTime::from(None),
@@ -541,9 +3625,24 @@ mod tests {
             astronomical_dawn: EventTime::from(None),
             astronomical_dusk: EventTime::from(None),
             day_length: Duration::seconds(0),
+            twilight_angles: structs::TwilightAngles::default(),
+            hide_location: false,
+            decimal_hours: false,
+            day_fraction: false,
+            solar_time: false,
+            relative_to_noon: false,
+            time_only: false,
+            with_accuracy: false,
+            locale: None,
+            include_astro: false,
+            sections: structs::ReportSections::default(),
+            local_horizon: false,
         };
 
-        report.run();
+        report.run(&LocationConstants::new(
+            &report.coordinates,
+            &report.twilight_angles,
+        ));
         assert_eq!(None, report.sunrise.datetime);
         assert_eq!(None, report.sunset.datetime);
         assert_eq!("12:59:21", report.solar_noon.time().to_string());
@@ -568,6 +3667,10 @@ mod tests {
         let params = [
             ("2020-03-26 12:00:00 +00:00", 1.5),
             ("2020-03-24 12:00:00 +00:00", -0.5),
+            ("2020-03-24 18:00:00 +00:00", -0.25),
+            ("2020-03-24 23:59:59 +00:00", -0.00001),
+            ("2020-03-26 00:00:00 +00:00", 1.0),
+            ("2020-03-27 06:00:00 +00:00", 2.25),
         ];
 
         for (expected_time, arg) in params.iter() {
@@ -576,6 +3679,138 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_events_straddling_midnight() {
+        // far north, but with the time zone well behind the location's nominal one, the sunrise before solar
+        // noon is late on the previous day. It must still come before solar noon, and the day length must be
+        // the time between the two
+        let date = DateTime::parse_from_rfc3339("2020-05-10T12:00:00+00:00").unwrap();
+        let coordinates = structs::Coordinates::from_decimal_degrees("69.7N", "30.0E").unwrap();
+        let report = SolarReport::new(date, coordinates);
+
+        let sunrise = report.sunrise.datetime.unwrap();
+        let sunset = report.sunset.datetime.unwrap();
+        assert_eq!("2020-05-09", sunrise.format("%Y-%m-%d").to_string());
+        assert_eq!("2020-05-10", sunset.format("%Y-%m-%d").to_string());
+        assert!(sunrise < report.solar_noon && report.solar_noon < sunset);
+        assert_eq!(sunset - sunrise, report.day_length);
+        assert!(report.day_length < Duration::hours(24));
+
+        // and the same for a sunset early on the following day, with the time zone well ahead
+        let date = DateTime::parse_from_rfc3339("2020-05-10T12:00:00+05:00").unwrap();
+        let coordinates = structs::Coordinates::from_decimal_degrees("64.8N", "18.0W").unwrap();
+        let report = SolarReport::new(date, coordinates);
+
+        let sunrise = report.sunrise.datetime.unwrap();
+        let sunset = report.sunset.datetime.unwrap();
+        assert_eq!("2020-05-10", sunrise.format("%Y-%m-%d").to_string());
+        assert_eq!("2020-05-11", sunset.format("%Y-%m-%d").to_string());
+        assert!(sunrise < report.solar_noon && report.solar_noon < sunset);
+        assert_eq!(sunset - sunrise, report.day_length);
+
+        // the nearby days' reports follow on from each other
+        let next_report = SolarReport::new(date + Duration::days(1), coordinates);
+        assert!(next_report.sunrise.datetime.unwrap() > sunset);
+    }
+
+    #[test]
+    fn test_solar_noon_altitude() {
+        // at the June solstice, the Sun's highest altitude is roughly the complement of the latitude plus the
+        // 23.44° tilt of the Earth's axis
+        let coordinates =
+            structs::Coordinates::from_decimal_degrees("51.4769N", "0.0005W").unwrap();
+        let date = DateTime::parse_from_rfc3339("2020-06-21T12:00:00+00:00").unwrap();
+        let report = SolarReport::new(date, coordinates);
+        assert!((report.solar_noon_altitude - (90.0 - 51.4769 + 23.44)).abs() < 0.05);
+        assert!(report
+            .format_report()
+            .contains("Solar noon altitude:      61.97°\n"));
+
+        // it is the highest altitude of the day
+        for minutes in [-30, -1, 1, 30].iter() {
+            let time = report.solar_noon + Duration::minutes(*minutes);
+            assert!(SolarPosition::at(time, coordinates).altitude < report.solar_noon_altitude);
+        }
+
+        // and below the horizon in the polar night
+        let coordinates = structs::Coordinates::from_decimal_degrees("78.0N", "15.0E").unwrap();
+        let date = DateTime::parse_from_rfc3339("2020-12-21T12:00:00+01:00").unwrap();
+        let report = SolarReport::new(date, coordinates);
+        assert!(report.solar_noon_altitude < -10.0);
+    }
+
+    #[test]
+    fn test_base_time() {
+        let date = DateTime::parse_from_rfc3339("2020-03-25T12:00:00+00:00").unwrap();
+        let coordinates =
+            structs::Coordinates::from_decimal_degrees("51.4769N", "0.0005W").unwrap();
+        let report = SolarReport::new(date, coordinates);
+
+        let sunset = enums::EventBase::Standard(enums::Event::Sunset);
+        assert_eq!(report.sunset.datetime, report.base_time(&sunset).datetime);
+
+        // an altitude is crossed at the same time as the depression below the horizon
+        let (rising, setting) = report.crossings(6.0);
+        let dawn = enums::EventBase::Elevation("dawn".to_string(), -6.0, enums::Direction::Rising);
+        let dusk = enums::EventBase::Elevation("dusk".to_string(), -6.0, enums::Direction::Setting);
+        assert_eq!(rising.datetime, report.base_time(&dawn).datetime);
+        assert_eq!(setting.datetime, report.base_time(&dusk).datetime);
+        // and is shown by the name it was defined with
+        assert_eq!("dusk", dusk.to_string());
+
+        // and never if the Sun doesn't reach it
+        let overhead =
+            enums::EventBase::Elevation("overhead".to_string(), 60.0, enums::Direction::Rising);
+        assert!(!report.base_time(&overhead).is_some());
+    }
+
+    #[test]
+    fn test_apply_horizon() {
+        let date = DateTime::parse_from_rfc3339("2020-03-25T12:00:00+00:00").unwrap();
+        let coordinates = structs::Coordinates::from_decimal_degrees("51.5N", "0.1W").unwrap();
+        let level = SolarReport::new(date, coordinates);
+        let close = |a: &EventTime, b: &EventTime| {
+            (a.datetime.unwrap() - b.datetime.unwrap())
+                .num_seconds()
+                .abs()
+                < 60
+        };
+
+        // a level skyline gives the usual times, to within the accuracy of the usual calculation
+        let mut report = SolarReport::new(date, coordinates);
+        report.apply_horizon(&structs::HorizonProfile::new(vec![(0.0, 0.0)]).unwrap());
+        assert!(close(&level.sunrise, &report.sunrise));
+        assert!(close(&level.sunset, &report.sunset));
+        assert!(report.local_horizon);
+
+        // hills to the east delay sunrise, lower ones to the west bring sunset forward less
+        let mut report = SolarReport::new(date, coordinates);
+        let profile =
+            structs::HorizonProfile::new(vec![(0.0, 0.0), (90.0, 5.0), (180.0, 0.0), (270.0, 3.0)])
+                .unwrap();
+        report.apply_horizon(&profile);
+        assert_eq!(
+            "2020-03-25 06:22:16 +00:00",
+            report.sunrise.datetime.unwrap().to_string()
+        );
+        assert_eq!(
+            "2020-03-25 18:03:28 +00:00",
+            report.sunset.datetime.unwrap().to_string()
+        );
+        assert_eq!(
+            report.sunset.datetime.unwrap() - report.sunrise.datetime.unwrap(),
+            report.day_length
+        );
+        assert_eq!(level.civil_dawn.datetime, report.civil_dawn.datetime);
+
+        // a skyline too high for the Sun to clear hides it all day
+        let mut report = SolarReport::new(date, coordinates);
+        report.apply_horizon(&structs::HorizonProfile::new(vec![(0.0, 60.0)]).unwrap());
+        assert_eq!(None, report.sunrise.datetime);
+        assert_eq!(None, report.sunset.datetime);
+        assert_eq!(Duration::zero(), report.day_length);
+    }
+
     #[test]
     fn test_day_fraction_to_time() {
         let date = DateTime::parse_from_rfc3339("2020-03-25T12:00:00+00:00").unwrap();
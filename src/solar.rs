@@ -0,0 +1,776 @@
+use chrono::{DateTime, Datelike, Duration, FixedOffset, TimeZone, Timelike};
+use serde::Serialize;
+
+use super::{enums::Season, structs::Coordinates, traits::DateTimeExt};
+
+// the length of the tropical year in days, i.e. the time taken for the Sun's ecliptic longitude to advance by 360°
+const TROPICAL_YEAR: f64 = 365.242_189;
+
+// the Julian date of the Unix epoch, 1970-01-01T00:00:00 UTC
+const UNIX_EPOCH_JULIAN_DATE: f64 = 2_440_587.5;
+
+// the angular semi-diameter of the Sun, in arcseconds, as seen from a distance of 1 AU
+const SOLAR_SEMI_DIAMETER: f64 = 959.63;
+
+// the range of years for which results are trusted. The formulas for the position of the Sun lose accuracy
+// over the centuries either side of 2000, and so does ΔT, which is extrapolated beyond the present day and is
+// already uncertain by several minutes by the year 3000. Dates are in the proleptic Gregorian calendar
+pub const EARLIEST_YEAR: i32 = 1000;
+pub const LATEST_YEAR: i32 = 3000;
+
+pub fn is_supported_date<Tz: TimeZone>(datetime: &DateTime<Tz>) -> bool {
+    (EARLIEST_YEAR..=LATEST_YEAR).contains(&datetime.year())
+}
+
+pub fn delta_t(julian_date: f64) -> f64 {
+    // an estimate, in seconds, of ΔT = TT - UT, the difference between Terrestrial Time (the smooth time scale
+    // in which the Sun's motion is described) and Universal Time (which follows the Earth's irregular rotation).
+    // These are the polynomial fits of Espenak & Meeus, as used for NASA's eclipse predictions
+    let year = 2000.0 + (julian_date - 2451545.0) / 365.25;
+
+    match year {
+        y if y < -500.0 => long_term_delta_t(y),
+        y if y < 500.0 => {
+            let u = y / 100.0;
+            10583.6 - 1014.41 * u + 33.78311 * u.powi(2)
+                - 5.952053 * u.powi(3)
+                - 0.1798452 * u.powi(4)
+                + 0.022174192 * u.powi(5)
+                + 0.0090316521 * u.powi(6)
+        }
+        y if y < 1600.0 => {
+            let u = (y - 1000.0) / 100.0;
+            1574.2 - 556.01 * u + 71.23472 * u.powi(2) + 0.319781 * u.powi(3)
+                - 0.8503463 * u.powi(4)
+                - 0.005050998 * u.powi(5)
+                + 0.0083572073 * u.powi(6)
+        }
+        y if y < 1700.0 => {
+            let t = y - 1600.0;
+            120.0 - 0.9808 * t - 0.01532 * t.powi(2) + t.powi(3) / 7129.0
+        }
+        y if y < 1800.0 => {
+            let t = y - 1700.0;
+            8.83 + 0.1603 * t - 0.0059285 * t.powi(2) + 0.00013336 * t.powi(3)
+                - t.powi(4) / 1174000.0
+        }
+        y if y < 1860.0 => {
+            let t = y - 1800.0;
+            13.72 - 0.332447 * t + 0.0068612 * t.powi(2) + 0.0041116 * t.powi(3)
+                - 0.00037436 * t.powi(4)
+                + 0.0000121272 * t.powi(5)
+                - 0.0000001699 * t.powi(6)
+                + 0.000000000875 * t.powi(7)
+        }
+        y if y < 1900.0 => {
+            let t = y - 1860.0;
+            7.62 + 0.5737 * t - 0.251754 * t.powi(2) + 0.01680668 * t.powi(3)
+                - 0.0004473624 * t.powi(4)
+                + t.powi(5) / 233174.0
+        }
+        y if y < 1920.0 => {
+            let t = y - 1900.0;
+            -2.79 + 1.494119 * t - 0.0598939 * t.powi(2) + 0.0061966 * t.powi(3)
+                - 0.000197 * t.powi(4)
+        }
+        y if y < 1941.0 => {
+            let t = y - 1920.0;
+            21.20 + 0.84493 * t - 0.076100 * t.powi(2) + 0.0020936 * t.powi(3)
+        }
+        y if y < 1961.0 => {
+            let t = y - 1950.0;
+            29.07 + 0.407 * t - t.powi(2) / 233.0 + t.powi(3) / 2547.0
+        }
+        y if y < 1986.0 => {
+            let t = y - 1975.0;
+            45.45 + 1.067 * t - t.powi(2) / 260.0 - t.powi(3) / 718.0
+        }
+        y if y < 2005.0 => {
+            let t = y - 2000.0;
+            63.86 + 0.3345 * t - 0.060374 * t.powi(2)
+                + 0.0017275 * t.powi(3)
+                + 0.000651814 * t.powi(4)
+                + 0.00002373599 * t.powi(5)
+        }
+        y if y < 2050.0 => {
+            let t = y - 2000.0;
+            62.92 + 0.32217 * t + 0.005589 * t.powi(2)
+        }
+        y if y < 2150.0 => long_term_delta_t(y) - 0.5628 * (2150.0 - y),
+        y => long_term_delta_t(y),
+    }
+}
+
+fn long_term_delta_t(year: f64) -> f64 {
+    -20.0 + 32.0 * ((year - 1820.0) / 100.0).powi(2)
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct SolarParameters {
+    // all angles are in degrees, the equation of time is in minutes and the radius vector, the distance from
+    // the Earth to the Sun, is in AU
+    pub apparent_longitude: f64,
+    pub declination: f64,
+    pub equation_of_time: f64,
+    pub radius_vector: f64,
+}
+
+impl SolarParameters {
+    pub fn from_julian_date(julian_date: f64) -> SolarParameters {
+        // the Julian date is given in UT, but the Sun's position is a function of TT
+        let julian_ephemeris_date = julian_date + delta_t(julian_date) / 86400.0;
+        let julian_century = (julian_ephemeris_date - 2451545.0) / 36525.0;
+
+        let geometric_solar_mean_longitude =
+            (280.46646 + julian_century * (36000.76983 + julian_century * 0.0003032)) % 360.0;
+
+        let solar_mean_anomaly =
+            357.52911 + julian_century * (35999.05029 - 0.0001537 * julian_century);
+
+        let eccent_earth_orbit =
+            0.016708634 - julian_century * (0.000042037 + 0.0000001267 * julian_century);
+
+        let equation_of_the_center = solar_mean_anomaly.to_radians().sin()
+            * (1.914602 - julian_century * (0.004817 + 0.000014 * julian_century))
+            + (2.0 * solar_mean_anomaly).to_radians().sin()
+                * (0.019993 - 0.000101 * julian_century)
+            + (3.0 * solar_mean_anomaly).to_radians().sin() * 0.000289;
+
+        let solar_true_longitude = geometric_solar_mean_longitude + equation_of_the_center;
+
+        let solar_true_anomaly = solar_mean_anomaly + equation_of_the_center;
+
+        let radius_vector = (1.000001018 * (1.0 - eccent_earth_orbit * eccent_earth_orbit))
+            / (1.0 + eccent_earth_orbit * solar_true_anomaly.to_radians().cos());
+
+        let solar_apparent_longitude = solar_true_longitude
+            - 0.00569
+            - 0.00478 * (125.04 - 1934.136 * julian_century).to_radians().sin();
+
+        let mean_oblique_ecliptic = 23.0
+            + (26.0
+                + (21.448
+                    - julian_century
+                        * (46.815 + julian_century * (0.00059 - julian_century * 0.001813)))
+                    / 60.0)
+                / 60.0;
+
+        let oblique_corrected = mean_oblique_ecliptic
+            + 0.00256 * (125.04 - 1934.136 * julian_century).to_radians().cos();
+
+        let solar_declination = ((oblique_corrected.to_radians().sin()
+            * solar_apparent_longitude.to_radians().sin())
+        .asin())
+        .to_degrees();
+
+        let var_y = (oblique_corrected / 2.0).to_radians().tan().powi(2);
+
+        let equation_of_time = 4.0
+            * (var_y * (geometric_solar_mean_longitude.to_radians() * 2.0).sin()
+                - 2.0 * eccent_earth_orbit * solar_mean_anomaly.to_radians().sin()
+                + 4.0
+                    * eccent_earth_orbit
+                    * var_y
+                    * solar_mean_anomaly.to_radians().sin()
+                    * (geometric_solar_mean_longitude.to_radians() * 2.0).cos()
+                - 0.5 * var_y * var_y * (geometric_solar_mean_longitude.to_radians() * 4.0).sin()
+                - 1.25
+                    * eccent_earth_orbit
+                    * eccent_earth_orbit
+                    * (solar_mean_anomaly.to_radians() * 2.0).sin())
+            .to_degrees();
+
+        SolarParameters {
+            apparent_longitude: solar_apparent_longitude.rem_euclid(360.0),
+            declination: solar_declination,
+            equation_of_time,
+            radius_vector,
+        }
+    }
+
+    pub fn angular_diameter(&self) -> f64 {
+        // the apparent diameter of the Sun's disc in arcminutes, which is largest in early January, when the
+        // Earth is closest to the Sun, and smallest in early July
+        2.0 * SOLAR_SEMI_DIAMETER / 60.0 / self.radius_vector
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct SolarPosition {
+    // the apparent altitude (i.e. corrected for atmospheric refraction) above the horizon and the azimuth
+    // measured clockwise from north, both in degrees
+    pub altitude: f64,
+    pub azimuth: f64,
+}
+
+impl SolarPosition {
+    pub fn at(datetime: DateTime<FixedOffset>, coordinates: Coordinates) -> SolarPosition {
+        let position = SolarPosition::geometric(datetime, coordinates);
+        SolarPosition {
+            altitude: position.altitude + atmospheric_refraction(position.altitude),
+            ..position
+        }
+    }
+
+    pub fn geometric(datetime: DateTime<FixedOffset>, coordinates: Coordinates) -> SolarPosition {
+        // the position of the Sun, before any correction for atmospheric refraction
+        let parameters = SolarParameters::from_julian_date(datetime.to_julian_date());
+        let hour_angle = hour_angle(datetime, coordinates, &parameters);
+
+        let latitude = coordinates.latitude.value.to_radians();
+        let declination = parameters.declination.to_radians();
+
+        let zenith = (latitude.sin() * declination.sin()
+            + latitude.cos() * declination.cos() * hour_angle.to_radians().cos())
+        .max(-1.0)
+        .min(1.0)
+        .acos();
+
+        let azimuth = (((latitude.sin() * zenith.cos()) - declination.sin())
+            / (latitude.cos() * zenith.sin()))
+        .max(-1.0)
+        .min(1.0)
+        .acos()
+        .to_degrees();
+
+        let azimuth = if hour_angle > 0.0 {
+            (azimuth + 180.0) % 360.0
+        } else {
+            (540.0 - azimuth) % 360.0
+        };
+
+        SolarPosition {
+            altitude: 90.0 - zenith.to_degrees(),
+            azimuth,
+        }
+    }
+}
+
+fn hour_angle(
+    datetime: DateTime<FixedOffset>,
+    coordinates: Coordinates,
+    parameters: &SolarParameters,
+) -> f64 {
+    // the local hour angle of the Sun in degrees, from -180° to 180°, which is negative in the morning and zero
+    // at solar noon
+    let utc_datetime = datetime.naive_utc();
+    let minutes_past_midnight = utc_datetime.hour() as f64 * 60.0
+        + utc_datetime.minute() as f64
+        + utc_datetime.second() as f64 / 60.0;
+
+    let true_solar_time =
+        (minutes_past_midnight + parameters.equation_of_time + 4.0 * coordinates.longitude.value)
+            .rem_euclid(1440.0);
+
+    true_solar_time / 4.0 - 180.0
+}
+
+pub fn solar_hour_angle(datetime: DateTime<FixedOffset>, coordinates: Coordinates) -> f64 {
+    let parameters = SolarParameters::from_julian_date(datetime.to_julian_date());
+    hour_angle(datetime, coordinates, &parameters)
+}
+
+pub fn sidereal_time(julian_date: f64) -> f64 {
+    // the Greenwich mean sidereal time in degrees, from 0° to 360°, for a Julian date in UT (Meeus, ch. 12)
+    let days = julian_date - 2451545.0;
+    let julian_century = days / 36525.0;
+    (280.46061837 + 360.98564736629 * days + 0.000387933 * julian_century.powi(2)
+        - julian_century.powi(3) / 38710000.0)
+        .rem_euclid(360.0)
+}
+
+pub fn altitude_rate(datetime: DateTime<FixedOffset>, coordinates: Coordinates) -> f64 {
+    // how quickly the Sun's altitude is changing, in degrees per minute, from its position 30 seconds either
+    // side. Positive whilst the Sun is rising. This uses the geometric altitude, as the correction for
+    // refraction changes too abruptly near the horizon to differentiate
+    let before = SolarPosition::geometric(datetime - Duration::seconds(30), coordinates);
+    let after = SolarPosition::geometric(datetime + Duration::seconds(30), coordinates);
+    after.altitude - before.altitude
+}
+
+// how far off the Sun's calculated altitude may be, in degrees: the solar model itself is good to around 0.01°,
+// on top of the drift in the declination over the day (see timing_uncertainty), but near the horizon the
+// refraction depends on the weather, and varies from its standard value by 0.2° or so
+const MODEL_UNCERTAINTY: f64 = 0.01;
+const REFRACTION_UNCERTAINTY: f64 = 0.2;
+
+pub fn timing_uncertainty(datetime: DateTime<FixedOffset>, coordinates: Coordinates) -> Duration {
+    // an estimate of how far the real time of an event at this moment may be from the calculated one, which
+    // is the uncertainty in the altitude over the speed at which the Sun is crossing it. Far from the equator,
+    // or when the Sun only skims the altitude, it crosses slowly and the estimate grows. Refraction is only
+    // counted within a couple of degrees of the horizon, where it is large. The day's events are calculated with
+    // the Sun's declination at midday, so the further an event is from midday, the further the declination has
+    // drifted from the one used, by as much as a tenth of a degree around the equinoxes. How much of that drift
+    // shows in the altitude depends on where the Sun is in the sky, and never exceeds the drift itself
+    let parameters = SolarParameters::from_julian_date(datetime.to_julian_date());
+    let midday =
+        SolarParameters::from_julian_date(datetime.date().and_hms(12, 0, 0).to_julian_date());
+    let latitude = coordinates.latitude.value.to_radians();
+    let declination = parameters.declination.to_radians();
+    let hour_angle = hour_angle(datetime, coordinates, &parameters).to_radians();
+    let altitude = SolarPosition::geometric(datetime, coordinates)
+        .altitude
+        .to_radians();
+    let sensitivity = ((latitude.sin() * declination.cos()
+        - latitude.cos() * declination.sin() * hour_angle.cos())
+        / altitude.cos())
+    .abs()
+    .min(1.0);
+    let drift = (parameters.declination - midday.declination).abs() * sensitivity;
+
+    let mut uncertainty = MODEL_UNCERTAINTY + drift;
+    if SolarPosition::geometric(datetime, coordinates).altitude > -2.0 {
+        uncertainty += REFRACTION_UNCERTAINTY;
+    }
+    // a Sun hardly moving at all, e.g. at solar noon, is capped at a thousandth of a degree a minute, which is
+    // three and a half hours' uncertainty above the horizon, plus the drift
+    let rate = altitude_rate(datetime, coordinates).abs().max(0.001);
+    Duration::seconds((uncertainty / rate * 60.0).ceil() as i64)
+}
+
+fn atmospheric_refraction(altitude: f64) -> f64 {
+    // the approximate amount, in degrees, by which the atmosphere raises the apparent position of the Sun when
+    // it is at the given geometric altitude, as used by NOAA
+    let tan_altitude = altitude.to_radians().tan();
+
+    let arc_seconds = match altitude {
+        a if a > 85.0 => 0.0,
+        a if a > 5.0 => {
+            58.1 / tan_altitude - 0.07 / tan_altitude.powi(3) + 0.000086 / tan_altitude.powi(5)
+        }
+        a if a > -0.575 => 1735.0 + a * (-518.2 + a * (103.4 + a * (-12.79 + a * 0.711))),
+        _ => -20.772 / tan_altitude,
+    };
+
+    arc_seconds / 3600.0
+}
+
+// the approximate illuminance, in lux, of a horizontal surface lit by the sky alone, with the centre of the Sun
+// at each geometric altitude. These are the figures usually quoted for sunset and for the ends of civil,
+// nautical and astronomical twilight, which assume a clear sky, no Moon and no artificial light
+const TWILIGHT_ILLUMINANCE: [(f64, f64); 4] =
+    [(-18.0, 0.0007), (-12.0, 0.008), (-6.0, 3.4), (0.0, 400.0)];
+
+pub fn illuminance_altitude(illuminance: f64) -> Option<f64> {
+    // the altitude of the Sun at which twilight gives this illuminance, interpolating the logarithm of the
+    // illuminance linearly between the figures above. Clouds, haze and the Moon can each change the real
+    // illuminance by a factor of ten or more, so this is only a guide, and there is nothing to go on outside
+    // of twilight
+    TWILIGHT_ILLUMINANCE.windows(2).find_map(|pair| {
+        let ((low_altitude, low), (high_altitude, high)) = (pair[0], pair[1]);
+        if !(low..=high).contains(&illuminance) {
+            return None;
+        }
+        let fraction = (illuminance.log10() - low.log10()) / (high.log10() - low.log10());
+        Some(low_altitude + fraction * (high_altitude - low_altitude))
+    })
+}
+
+#[derive(Debug, Serialize)]
+pub struct SunPathSample {
+    pub time: DateTime<FixedOffset>,
+    #[serde(flatten)]
+    pub position: SolarPosition,
+}
+
+pub fn sun_path(
+    start: DateTime<FixedOffset>,
+    end: DateTime<FixedOffset>,
+    interval: Duration,
+    coordinates: Coordinates,
+) -> Vec<SunPathSample> {
+    // the position of the Sun every `interval` from start to end. The end itself is always included, so that
+    // e.g. a path from sunrise to sunset finishes at sunset
+    let mut samples = vec![];
+    let mut time = start;
+    while time < end {
+        samples.push(SunPathSample {
+            time,
+            position: SolarPosition::at(time, coordinates),
+        });
+        time = time + interval;
+    }
+    if start <= end {
+        samples.push(SunPathSample {
+            time: end,
+            position: SolarPosition::at(end, coordinates),
+        });
+    }
+    samples
+}
+
+pub fn julian_date_to_datetime(julian_date: f64, time_zone: &FixedOffset) -> DateTime<FixedOffset> {
+    let seconds = ((julian_date - UNIX_EPOCH_JULIAN_DATE) * 86400.0).round() as i64;
+    time_zone.timestamp(seconds, 0)
+}
+
+pub fn next_apparent_longitude(target: f64, after: DateTime<FixedOffset>) -> DateTime<FixedOffset> {
+    // find the first instant after the given one at which the Sun's apparent ecliptic longitude reaches the
+    // target value, using Newton's method with the Sun's mean angular speed as the derivative
+    let mut julian_date = after.to_julian_date();
+
+    let start = SolarParameters::from_julian_date(julian_date).apparent_longitude;
+    let mut distance = (target - start).rem_euclid(360.0);
+    if distance == 0.0 {
+        distance = 360.0;
+    }
+    julian_date += distance / 360.0 * TROPICAL_YEAR;
+
+    for _ in 0..20 {
+        let longitude = SolarParameters::from_julian_date(julian_date).apparent_longitude;
+        // wrap the error into [-180, 180) so that crossing 0° doesn't send us a year in the wrong direction
+        let error = (target - longitude + 180.0).rem_euclid(360.0) - 180.0;
+        julian_date += error / 360.0 * TROPICAL_YEAR;
+
+        // a millionth of a day is well under a second
+        if error.abs() / 360.0 * TROPICAL_YEAR < 1e-6 {
+            break;
+        }
+    }
+
+    julian_date_to_datetime(julian_date, after.offset())
+}
+
+pub fn next_season(after: DateTime<FixedOffset>) -> (Season, DateTime<FixedOffset>) {
+    Season::all()
+        .iter()
+        .map(|season| {
+            (
+                *season,
+                next_apparent_longitude(season.apparent_longitude(), after),
+            )
+        })
+        .min_by_key(|(_, datetime)| *datetime)
+        .unwrap()
+}
+
+pub fn previous_season(before: DateTime<FixedOffset>) -> (Season, DateTime<FixedOffset>) {
+    // the seasons are between 89 and 94 days apart, so searching from 100 days before the next one finds the
+    // one before it, and not the one before that
+    let (next, next_datetime) = next_season(before);
+    let previous = next.previous();
+    (
+        previous,
+        next_apparent_longitude(
+            previous.apparent_longitude(),
+            next_datetime - Duration::days(100),
+        ),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sun_path() {
+        let coordinates = Coordinates::from_decimal_degrees("55.9533N", "3.1883W").unwrap();
+        let start = DateTime::parse_from_rfc3339("2020-06-21T12:00:00+01:00").unwrap();
+        let end = DateTime::parse_from_rfc3339("2020-06-21T14:30:00+01:00").unwrap();
+        let path = sun_path(start, end, Duration::hours(1), coordinates);
+
+        let times: Vec<String> = path.iter().map(|sample| sample.time.to_string()).collect();
+        assert_eq!(
+            vec![
+                "2020-06-21 12:00:00 +01:00",
+                "2020-06-21 13:00:00 +01:00",
+                "2020-06-21 14:00:00 +01:00",
+                "2020-06-21 14:30:00 +01:00",
+            ],
+            times
+        );
+        // the Sun is highest at solar noon, 13:14:39
+        assert!(path[1].position.altitude > path[0].position.altitude);
+        assert!(path[1].position.altitude > path[2].position.altitude);
+
+        // an end which is on an interval isn't repeated
+        let path = sun_path(
+            start,
+            start + Duration::hours(2),
+            Duration::hours(1),
+            coordinates,
+        );
+        assert_eq!(3, path.len());
+    }
+
+    #[test]
+    fn test_is_supported_date() {
+        let params = [
+            ("0999-12-31T23:59:59+00:00", false),
+            ("1000-01-01T00:00:00+00:00", true),
+            ("2020-06-21T12:00:00+00:00", true),
+            ("3000-12-31T23:59:59+00:00", true),
+            ("3001-01-01T00:00:00+00:00", false),
+        ];
+
+        for (datetime, expected) in params.iter() {
+            let datetime = DateTime::parse_from_rfc3339(datetime).unwrap();
+            assert_eq!(*expected, is_supported_date(&datetime), "{}", datetime);
+        }
+    }
+
+    #[test]
+    fn test_next_season() {
+        let params = [
+            (Season::MarchEquinox, "2020-01-01T00:00:00+00:00"),
+            (Season::JuneSolstice, "2020-03-21T00:00:00+00:00"),
+            (Season::SeptemberEquinox, "2020-07-01T00:00:00+00:00"),
+            (Season::DecemberSolstice, "2020-12-01T00:00:00+00:00"),
+            (Season::MarchEquinox, "2020-12-25T00:00:00+00:00"),
+        ];
+
+        for (expected, after) in params.iter() {
+            let after = DateTime::parse_from_rfc3339(after).unwrap();
+            let (season, datetime) = next_season(after);
+            assert_eq!(*expected, season);
+            assert!(datetime > after);
+            // results are given in the same time zone as the starting point
+            assert_eq!(after.offset(), datetime.offset());
+        }
+    }
+
+    #[test]
+    fn test_previous_season() {
+        let params = [
+            (Season::DecemberSolstice, "2020-01-01T00:00:00+00:00"),
+            (Season::MarchEquinox, "2020-03-21T00:00:00+00:00"),
+            (Season::JuneSolstice, "2020-07-01T00:00:00+00:00"),
+            (Season::SeptemberEquinox, "2020-12-01T00:00:00+00:00"),
+            (Season::DecemberSolstice, "2020-12-25T00:00:00+00:00"),
+        ];
+
+        for (expected, before) in params.iter() {
+            let before = DateTime::parse_from_rfc3339(before).unwrap();
+            let (season, datetime) = previous_season(before);
+            assert_eq!(*expected, season);
+            assert!(datetime <= before && before - datetime < Duration::days(95));
+            assert_eq!(before.offset(), datetime.offset());
+        }
+    }
+
+    #[test]
+    fn test_angular_diameter() {
+        // the Earth is at perihelion in early January and aphelion in early July
+        let perihelion = SolarParameters::from_julian_date(2458852.5); // 2020-01-05
+        let aphelion = SolarParameters::from_julian_date(2459035.5); // 2020-07-06
+        assert!((perihelion.radius_vector - 0.98324).abs() < 0.0001);
+        assert!((aphelion.radius_vector - 1.01669).abs() < 0.0001);
+        assert_eq!("32.53", format!("{:.2}", perihelion.angular_diameter()));
+        assert_eq!("31.46", format!("{:.2}", aphelion.angular_diameter()));
+    }
+
+    #[test]
+    fn test_delta_t() {
+        // compared with the values published by the IERS, which are known to within a few seconds
+        let params = [
+            (57.0, "1990-01-01T00:00:00+00:00"),
+            (69.4, "2020-01-01T00:00:00+00:00"),
+        ];
+
+        for (expected, arg) in params.iter() {
+            let date = DateTime::parse_from_rfc3339(arg).unwrap();
+            assert!((expected - delta_t(date.to_julian_date())).abs() < 3.0);
+        }
+
+        // the estimate should never jump by more than a fraction of a second from one day to the next, even
+        // across the boundaries between the polynomial fits
+        // i.e. from 1000 BC to AD 3000
+        let mut julian_date = 1_355_800.5;
+        while julian_date < 2_817_000.5 {
+            assert!((delta_t(julian_date + 1.0) - delta_t(julian_date)).abs() < 0.5);
+            julian_date += 1.0;
+        }
+    }
+
+    #[test]
+    fn test_sidereal_time() {
+        // Meeus' example 12.a: 0h UT on 10th April 1987 was 13h 10m 46.3668s
+        assert!((197.693195 - sidereal_time(2446895.5)).abs() < 0.000001);
+        // and example 12.b: 19h 21m 00s UT on the same day was 8h 34m 57.0896s
+        assert!((128.737873 - sidereal_time(2446896.30625)).abs() < 0.000001);
+    }
+
+    #[test]
+    fn test_solar_hour_angle() {
+        // the hour angle is zero at solar noon, and grows by 15° an hour
+        let coordinates = Coordinates::from_decimal_degrees("55.9533N", "3.1883W").unwrap();
+        let noon = DateTime::parse_from_rfc3339("2020-06-21T13:14:39+01:00").unwrap();
+        assert!(solar_hour_angle(noon, coordinates).abs() < 0.01);
+        let afternoon = noon + Duration::hours(2);
+        assert!((30.0 - solar_hour_angle(afternoon, coordinates)).abs() < 0.01);
+        let morning = noon - Duration::hours(3);
+        assert!((-45.0 - solar_hour_angle(morning, coordinates)).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_altitude_rate() {
+        // on the equator at the equinox, the Sun rises and sets vertically, at 360° per day
+        let coordinates = Coordinates::from_decimal_degrees("0.0N", "0.0E").unwrap();
+        let sunrise = DateTime::parse_from_rfc3339("2020-03-20T06:07:00+00:00").unwrap();
+        assert!((0.25 - altitude_rate(sunrise, coordinates)).abs() < 0.001);
+        let sunset = DateTime::parse_from_rfc3339("2020-03-20T18:14:00+00:00").unwrap();
+        assert!((-0.25 - altitude_rate(sunset, coordinates)).abs() < 0.001);
+
+        // further from the equator the Sun rises at a shallower angle, and at noon it is hardly changing
+        let coordinates = Coordinates::from_decimal_degrees("55.9533N", "3.1883W").unwrap();
+        let sunrise = DateTime::parse_from_rfc3339("2020-06-21T04:26:00+01:00").unwrap();
+        let rate = altitude_rate(sunrise, coordinates);
+        assert!(rate > 0.08 && rate < 0.12, "{}", rate);
+        let noon = DateTime::parse_from_rfc3339("2020-06-21T13:14:39+01:00").unwrap();
+        assert!(altitude_rate(noon, coordinates).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_timing_uncertainty() {
+        // at the equator the Sun rises steeply, so sunrise is known to within a minute, and twilight, away from
+        // the refraction at the horizon, much better than that
+        let coordinates = Coordinates::from_decimal_degrees("0.0N", "0.0E").unwrap();
+        let sunrise = DateTime::parse_from_rfc3339("2020-03-20T06:07:00+00:00").unwrap();
+        assert_eq!(
+            Duration::seconds(51),
+            timing_uncertainty(sunrise, coordinates)
+        );
+        let dawn = DateTime::parse_from_rfc3339("2020-03-20T05:43:00+00:00").unwrap();
+        assert!(timing_uncertainty(dawn, coordinates) <= Duration::seconds(3));
+
+        // further north it rises at a shallower angle, and the uncertainty grows
+        let coordinates = Coordinates::from_decimal_degrees("55.9533N", "3.1883W").unwrap();
+        let sunrise = DateTime::parse_from_rfc3339("2020-06-21T04:26:00+01:00").unwrap();
+        let uncertainty = timing_uncertainty(sunrise, coordinates);
+        assert!(
+            uncertainty > Duration::minutes(1) && uncertainty < Duration::minutes(3),
+            "{}",
+            uncertainty
+        );
+
+        // and around an equinox, when the declination drifts quickly, so does the uncertainty of twilight, which
+        // is furthest from midday
+        let dawn = DateTime::parse_from_rfc3339("2020-03-25T05:22:43+00:00").unwrap();
+        let uncertainty = timing_uncertainty(dawn, coordinates);
+        assert!(
+            uncertainty > Duration::seconds(30) && uncertainty < Duration::minutes(1),
+            "{}",
+            uncertainty
+        );
+
+        // and it is capped when the Sun is hardly moving at all, at three and a half hours for the model and
+        // refraction, plus a few seconds for the drift since midday
+        let noon = DateTime::parse_from_rfc3339("2020-06-21T13:14:39+01:00").unwrap();
+        let uncertainty = timing_uncertainty(noon, coordinates);
+        assert!(
+            uncertainty >= Duration::minutes(210) && uncertainty < Duration::minutes(211),
+            "{}",
+            uncertainty
+        );
+    }
+
+    #[test]
+    fn test_solar_position() {
+        // at solar noon the Sun is due south (in the northern hemisphere) at an altitude of 90° - latitude +
+        // declination, plus a little refraction
+        let date = DateTime::parse_from_rfc3339("2020-06-21T13:14:39+01:00").unwrap();
+        let coordinates = Coordinates::from_decimal_degrees("55.9533N", "3.1883W").unwrap();
+        let position = SolarPosition::at(date, coordinates);
+        assert!((57.50 - position.altitude).abs() < 0.05);
+        assert!((180.0 - position.azimuth).abs() < 0.5);
+
+        // and due north in the southern hemisphere
+        let date = DateTime::parse_from_rfc3339("2020-06-21T11:56:58+10:00").unwrap();
+        let coordinates = Coordinates::from_decimal_degrees("33.8688S", "151.2093E").unwrap();
+        let position = SolarPosition::at(date, coordinates);
+        assert!((32.70 - position.altitude).abs() < 0.05);
+        assert!(position.azimuth < 0.5 || position.azimuth > 359.5);
+
+        // early in the morning, the Sun is in the east; in the evening, in the west
+        let date = DateTime::parse_from_rfc3339("2020-06-21T07:00:00+01:00").unwrap();
+        let position = SolarPosition::at(
+            date,
+            Coordinates::from_decimal_degrees("55.9533N", "3.1883W").unwrap(),
+        );
+        assert!(position.azimuth > 45.0 && position.azimuth < 135.0);
+
+        let date = DateTime::parse_from_rfc3339("2020-06-21T19:00:00+01:00").unwrap();
+        let position = SolarPosition::at(
+            date,
+            Coordinates::from_decimal_degrees("55.9533N", "3.1883W").unwrap(),
+        );
+        assert!(position.azimuth > 225.0 && position.azimuth < 315.0);
+
+        // and at midnight it is below the horizon
+        let date = DateTime::parse_from_rfc3339("2020-06-21T01:14:39+01:00").unwrap();
+        let position = SolarPosition::at(
+            date,
+            Coordinates::from_decimal_degrees("55.9533N", "3.1883W").unwrap(),
+        );
+        assert!(position.altitude < 0.0);
+    }
+
+    #[test]
+    fn test_julian_date_round_trip() {
+        let date = DateTime::parse_from_rfc3339("2020-03-15T13:09:01+01:00").unwrap();
+        let julian_date = date.to_julian_date();
+        assert_eq!(date, julian_date_to_datetime(julian_date, date.offset()));
+    }
+
+    #[test]
+    fn test_next_apparent_longitude() {
+        // validated against the times published by the US Naval Observatory. The solar model used is only
+        // accurate to around 0.01°, which is equivalent to several minutes of the Sun's motion along the ecliptic
+        let params = [
+            (
+                0.0,
+                "2020-01-01T00:00:00+00:00",
+                "2020-03-20T03:50:00+00:00",
+            ),
+            (
+                90.0,
+                "2020-01-01T00:00:00+00:00",
+                "2020-06-20T21:44:00+00:00",
+            ),
+            (
+                180.0,
+                "2020-01-01T00:00:00+00:00",
+                "2020-09-22T13:31:00+00:00",
+            ),
+            (
+                270.0,
+                "2020-01-01T00:00:00+00:00",
+                "2020-12-21T10:02:00+00:00",
+            ),
+            // the first crossing after the start, even if it is earlier in the calendar than the start
+            (
+                270.0,
+                "2020-12-22T00:00:00+00:00",
+                "2021-12-21T15:59:00+00:00",
+            ),
+        ];
+
+        for (target, after, expected) in params.iter() {
+            let after = DateTime::parse_from_rfc3339(after).unwrap();
+            let expected = DateTime::parse_from_rfc3339(expected).unwrap();
+            let result = next_apparent_longitude(*target, after);
+            assert!(
+                (result - expected).num_minutes().abs() < 10,
+                "expected {}, got {}",
+                expected,
+                result
+            );
+        }
+    }
+
+    #[test]
+    fn test_illuminance_altitude() {
+        // the figures themselves give their altitudes, and anything in between is in between
+        assert_eq!(Some(-6.0), illuminance_altitude(3.4));
+        assert_eq!(Some(0.0), illuminance_altitude(400.0));
+        assert_eq!(Some(-18.0), illuminance_altitude(0.0007));
+
+        let altitude = illuminance_altitude(1.0).unwrap();
+        assert!(-8.0 < altitude && altitude < -7.0, "{}", altitude);
+
+        // but there is nothing to go on in daylight or in the darkest night
+        assert_eq!(None, illuminance_altitude(1000.0));
+        assert_eq!(None, illuminance_altitude(0.0001));
+        assert_eq!(None, illuminance_altitude(std::f64::NAN));
+    }
+}
@@ -1,13 +1,17 @@
 use std::{fmt, result};
 
-use chrono::{DateTime, FixedOffset, NaiveTime};
-use serde::Deserialize;
+use chrono::{DateTime, Duration, FixedOffset, NaiveTime};
+use serde::{Deserialize, Serialize};
 
-use super::errors::{ConfigErrorKind, HeliocronError};
+use super::{
+    enums::{Comparator, DayMark, DayPhase, Event, RoundMode, TwilightType},
+    errors::{ConfigErrorKind, HeliocronError},
+};
 
 type Result<T> = result::Result<T, HeliocronError>;
 
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
+#[serde(transparent)]
 pub struct EventTime {
     pub datetime: Option<DateTime<FixedOffset>>,
 }
@@ -18,18 +22,27 @@ impl EventTime {
     }
 
     pub fn is_some(&self) -> bool {
-        if self.datetime.is_some() {
-            true
-        } else {
-            false
-        }
+        self.datetime.is_some()
     }
 
     pub fn time(&self) -> Option<NaiveTime> {
-        match self.datetime {
-            Some(datetime) => Some(datetime.time()),
-            None => None,
-        }
+        self.datetime.map(|datetime| datetime.time())
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct EventFilter {
+    pub event: Event,
+    pub comparator: Comparator,
+    pub time: NaiveTime,
+}
+
+impl EventFilter {
+    pub fn matches(&self, event_time: &EventTime) -> bool {
+        // an event which doesn't occur on the day can't satisfy any condition on its time
+        event_time
+            .time()
+            .map_or(false, |time| self.comparator.compare(time, self.time))
     }
 }
 
@@ -56,18 +69,20 @@ fn invalid_coordinates_error(msg: &'static str) -> HeliocronError {
     HeliocronError::Config(ConfigErrorKind::InvalidCoordindates(msg))
 }
 
-#[derive(Debug, Deserialize, Clone, Copy)]
+#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
 pub struct Coordinates {
     pub latitude: Latitude,
     pub longitude: Longitude,
 }
 
-#[derive(Debug, Deserialize, Clone, Copy)]
+#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
+#[serde(transparent)]
 pub struct Latitude {
     pub value: f64,
 }
 
-#[derive(Debug, Deserialize, Clone, Copy)]
+#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
+#[serde(transparent)]
 pub struct Longitude {
     pub value: f64,
 }
@@ -104,16 +119,29 @@ pub trait Coordinate: Sized {
     fn compass_correction(compass_direction: char) -> Result<f64>;
 }
 
+fn positive_zero(value: f64) -> f64 {
+    // a zero coordinate in the southern or western hemisphere would otherwise be -0.0, which compares equal
+    // to 0.0 but is displayed and serialised differently
+    if value == 0.0 {
+        0.0
+    } else {
+        value
+    }
+}
+
 impl Coordinate for Latitude {
     fn from_decimal_degrees(latitude: &str) -> Result<Latitude> {
-        // strictly, the latitude format must be a positive float or integer with an upper or lowercase 'N' or 'S'
+        // strictly, the latitude format must be a positive float or integer with an upper or lowercase 'N' or 'S'.
+        // The compass direction is always required, even for the equator, where "0N" and "0S" are the same. Any
+        // whitespace around it, as in a pasted "51.4769 N", is ignored
+        let latitude = latitude.trim();
 
         let compass_direction: char = Self::parse_compass_direction(latitude)?;
         let decimal_degrees: f64 = Self::parse_decimal_degrees(latitude)?;
         let compass_correction: f64 = Self::compass_correction(compass_direction)?;
 
         Ok(Latitude {
-            value: decimal_degrees * compass_correction,
+            value: positive_zero(decimal_degrees * compass_correction),
         })
     }
 
@@ -134,6 +162,7 @@ impl Coordinate for Latitude {
 
     fn parse_decimal_degrees(latitude: &str) -> Result<f64> {
         latitude[..latitude.len() - 1]
+            .trim_end()
             .parse()
             .map_err(|_| invalid_coordinates_error("Latitude must be a positive value followed by a compass direction ('N' or 'S')"))
             .and_then(|n: f64| match n {
@@ -162,14 +191,17 @@ impl Coordinate for Latitude {
 
 impl Coordinate for Longitude {
     fn from_decimal_degrees(longitude: &str) -> Result<Self> {
-        // strictly, the longitude format must be a positive float or integer with an upper or lowercase 'W' or 'E'
+        // strictly, the longitude format must be a positive float or integer with an upper or lowercase 'W' or 'E'.
+        // The compass direction is always required, even for the prime meridian, where "0W" and "0E" are the same.
+        // Any whitespace around it, as in a pasted "0.0005 W", is ignored
+        let longitude = longitude.trim();
 
         let compass_direction: char = Self::parse_compass_direction(longitude)?;
         let decimal_degrees: f64 = Self::parse_decimal_degrees(longitude)?;
         let compass_correction: f64 = Self::compass_correction(compass_direction)?;
 
         Ok(Longitude {
-            value: decimal_degrees * compass_correction,
+            value: positive_zero(decimal_degrees * compass_correction),
         })
     }
 
@@ -190,6 +222,7 @@ impl Coordinate for Longitude {
 
     fn parse_decimal_degrees(longitude: &str) -> Result<f64> {
         longitude[..longitude.len() - 1]
+            .trim_end()
             .parse()
             .map_err(|_| invalid_coordinates_error("Longitude must be a positive value followed by a compass direction ('W' or 'E')"))
             .and_then(|n: f64| match n {
@@ -216,6 +249,230 @@ impl Coordinate for Longitude {
     }
 }
 
+impl Longitude {
+    pub fn nominal_offset(&self) -> FixedOffset {
+        // the nautical time zone for this longitude, i.e. one hour for every 15 degrees east or west of the
+        // prime meridian. Political time zones frequently differ, so this is only ever an approximation
+        let hours = (self.value / 15.0).round() as i32;
+        FixedOffset::east(hours * 3600)
+    }
+}
+
+// the altitude of the centre of the Sun at sunrise and sunset, allowing for 16' of semi-diameter and 34' of
+// refraction, which is the refraction at the horizon at the standard pressure and temperature below
+pub const SUNRISE_ALTITUDE: f64 = -0.833;
+const HORIZON_REFRACTION: f64 = 34.0 / 60.0;
+pub const STANDARD_PRESSURE: f64 = 1010.0;
+pub const STANDARD_TEMPERATURE: f64 = 10.0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TwilightAngles {
+    // the altitudes, in degrees, of the centre of the Sun at sunrise and sunset, and at each kind of dawn and
+    // dusk
+    pub sunrise: f64,
+    pub civil: f64,
+    pub nautical: f64,
+    pub astronomical: f64,
+}
+
+impl Default for TwilightAngles {
+    fn default() -> TwilightAngles {
+        TwilightAngles {
+            sunrise: SUNRISE_ALTITUDE,
+            civil: -6.0,
+            nautical: -12.0,
+            astronomical: -18.0,
+        }
+    }
+}
+
+impl TwilightAngles {
+    pub fn angle(&self, twilight_type: &TwilightType) -> f64 {
+        match twilight_type {
+            TwilightType::Civil => self.civil,
+            TwilightType::Nautical => self.nautical,
+            TwilightType::Astronomical => self.astronomical,
+        }
+    }
+
+    pub fn with_atmosphere(self, pressure: f64, temperature: f64) -> TwilightAngles {
+        // the refraction at the horizon is proportional to the density of the air (Meeus, ch. 16), so sunrise
+        // and sunset move with the pressure, in hPa, and the temperature, in °C. The twilight angles are left
+        // as they are, as refraction makes little difference to the brightness of the sky once the Sun has set
+        let density = (pressure / STANDARD_PRESSURE)
+            * ((273.0 + STANDARD_TEMPERATURE) / (273.0 + temperature));
+        TwilightAngles {
+            sunrise: SUNRISE_ALTITUDE - HORIZON_REFRACTION * (density - 1.0),
+            ..self
+        }
+    }
+
+    pub fn is_standard(&self) -> bool {
+        *self == TwilightAngles::default()
+    }
+
+    pub fn day_phase(&self, altitude: f64) -> DayPhase {
+        // the phase of the day with the centre of the Sun at this geometric altitude. The day starts at the
+        // altitude of sunrise and sunset, which allows for refraction and the size of the Sun's disk
+        match altitude {
+            altitude if altitude >= self.sunrise => DayPhase::Day,
+            altitude if altitude >= self.civil => DayPhase::CivilTwilight,
+            altitude if altitude >= self.nautical => DayPhase::NauticalTwilight,
+            altitude if altitude >= self.astronomical => DayPhase::AstronomicalTwilight,
+            _ => DayPhase::Night,
+        }
+    }
+}
+
+// the extra sections of the plain report, from --astro, --shadow, --night and --photo, each of which is shown
+// after the report of every day
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ExtraSections {
+    pub astro: bool,
+    pub shadow: bool,
+    pub night: bool,
+    pub photo: bool,
+}
+
+// the moments which bound a wait for an event, from --clamp-after and --clamp-before, and the one waited for
+// instead if the event doesn't occur, from --fallback-event
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WaitBounds {
+    pub clamp_after: Option<DayMark>,
+    pub clamp_before: Option<DayMark>,
+    pub fallback: Option<DayMark>,
+}
+
+// how a wait is carried out once the moment it ends at is known
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SleepOptions {
+    pub grace: Duration,
+    pub align_minute: Option<RoundMode>,
+    pub poll_interval: std::time::Duration,
+    pub quiet: bool,
+}
+
+// a condition on the light for the program to go ahead, from --fail-if-dark or --fail-if-light. It is light
+// when the centre of the Sun is at or above the altitude of sunrise and sunset, or of the chosen twilight
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LightGuard {
+    pub require_light: bool,
+    pub twilight: Option<TwilightType>,
+}
+
+impl LightGuard {
+    pub fn passes(&self, altitude: f64, twilight_angles: &TwilightAngles) -> bool {
+        let threshold = match self.twilight {
+            Some(twilight) => twilight_angles.angle(&twilight),
+            None => twilight_angles.sunrise,
+        };
+        (altitude >= threshold) == self.require_light
+    }
+}
+
+// the altitude of the skyline around the observer, e.g. of hills or buildings, as points of azimuth (clockwise
+// from north) and altitude, in degrees. Between the points, the altitude is interpolated linearly, all the way
+// round, so a single point is a horizon which is level at that altitude
+#[derive(Debug, Clone, PartialEq)]
+pub struct HorizonProfile {
+    points: Vec<(f64, f64)>,
+}
+
+impl HorizonProfile {
+    pub fn new(mut points: Vec<(f64, f64)>) -> Result<HorizonProfile> {
+        let valid = |(azimuth, altitude): &(f64, f64)| {
+            (0.0..=360.0).contains(azimuth) && (-90.0..=90.0).contains(altitude)
+        };
+        if points.is_empty() || !points.iter().all(valid) {
+            return Err(HeliocronError::Config(ConfigErrorKind::InvalidHorizonFile));
+        }
+
+        // due north may be given as either 0 or 360, but only once
+        for (azimuth, _) in points.iter_mut() {
+            *azimuth = azimuth.rem_euclid(360.0);
+        }
+        points.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+        points.dedup_by(|a, b| a.0 == b.0);
+        Ok(HorizonProfile { points })
+    }
+
+    pub fn altitude_at(&self, azimuth: f64) -> f64 {
+        let azimuth = azimuth.rem_euclid(360.0);
+        let points = &self.points;
+        if points.len() == 1 {
+            return points[0].1;
+        }
+
+        // the points either side, wrapping round through north
+        let next = points
+            .iter()
+            .position(|(point, _)| *point > azimuth)
+            .unwrap_or(points.len());
+        let (before_azimuth, before) = match next {
+            0 => (
+                points[points.len() - 1].0 - 360.0,
+                points[points.len() - 1].1,
+            ),
+            _ => points[next - 1],
+        };
+        let (after_azimuth, after) = match points.get(next) {
+            Some(point) => *point,
+            None => (points[0].0 + 360.0, points[0].1),
+        };
+        before + (after - before) * (azimuth - before_azimuth) / (after_azimuth - before_azimuth)
+    }
+}
+
+// which pairs of events are shown in the plain report, which the config file can turn off one at a time to
+// leave only those of interest
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ReportSections {
+    pub sunrise_sunset: bool,
+    pub civil: bool,
+    pub nautical: bool,
+    pub astronomical: bool,
+}
+
+impl Default for ReportSections {
+    fn default() -> ReportSections {
+        ReportSections {
+            sunrise_sunset: true,
+            civil: true,
+            nautical: true,
+            astronomical: true,
+        }
+    }
+}
+
+// something which is worth pointing out but doesn't stop the program, printed to stderr either as text or, with
+// --warnings-json, as a JSON object. The code is fixed, for other programs to match on, whilst the message may
+// be reworded
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Warning {
+    pub code: &'static str,
+    pub message: String,
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Warning: {}", self.message)
+    }
+}
+
+impl fmt::Display for TwilightAngles {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // the altitude of sunrise only changes with the atmosphere, so is only worth a mention when it has
+        if self.sunrise != SUNRISE_ALTITUDE {
+            write!(f, "sunrise {:.3}°, ", self.sunrise)?;
+        }
+        write!(
+            f,
+            "civil {:.1}°, nautical {:.1}°, astronomical {:.1}°",
+            self.civil, self.nautical, self.astronomical
+        )
+    }
+}
+
 impl Coordinates {
     pub fn from_decimal_degrees(latitude: &str, longitude: &str) -> Result<Coordinates> {
         let latitude = Latitude::from_decimal_degrees(latitude)?;
@@ -225,6 +482,100 @@ impl Coordinates {
             longitude,
         })
     }
+
+    pub fn from_signed_decimal_degrees(latitude: f64, longitude: f64) -> Result<Coordinates> {
+        // positive for north and east, negative for south and west. These go via the usual parsing, so that
+        // they are held to the same rules as any other coordinates
+        let latitude = format!(
+            "{}{}",
+            latitude.abs(),
+            if latitude.is_sign_negative() {
+                'S'
+            } else {
+                'N'
+            }
+        );
+        let longitude = format!(
+            "{}{}",
+            longitude.abs(),
+            if longitude.is_sign_negative() {
+                'W'
+            } else {
+                'E'
+            }
+        );
+        Coordinates::from_decimal_degrees(&latitude, &longitude)
+    }
+
+    pub fn to_decimal_degrees(&self) -> (String, String) {
+        // the inverse of from_decimal_degrees, e.g. ("51.4769N", "0.0005W"), at full precision so that the
+        // coordinates survive being written out and read back in
+        let latitude = format!(
+            "{}{}",
+            self.latitude.value.abs(),
+            if self.latitude.value.is_sign_negative() {
+                'S'
+            } else {
+                'N'
+            }
+        );
+        let longitude = format!(
+            "{}{}",
+            self.longitude.value.abs(),
+            if self.longitude.value.is_sign_negative() {
+                'W'
+            } else {
+                'E'
+            }
+        );
+        (latitude, longitude)
+    }
+
+    pub fn great_circle_point(&self, to: &Coordinates, fraction: f64) -> Coordinates {
+        // the point this fraction of the way along the shortest route over the surface of the Earth, taken as a
+        // sphere, by interpolating between the two points as unit vectors. The route between two opposite
+        // points (or the same point), to within a few metres, is ambiguous, and this then stays at the start
+        let vector = |coordinates: &Coordinates| {
+            let (latitude, longitude) = (
+                coordinates.latitude.value.to_radians(),
+                coordinates.longitude.value.to_radians(),
+            );
+            [
+                latitude.cos() * longitude.cos(),
+                latitude.cos() * longitude.sin(),
+                latitude.sin(),
+            ]
+        };
+        let (start, end) = (vector(self), vector(to));
+        let dot: f64 = start.iter().zip(end.iter()).map(|(a, b)| a * b).sum();
+        let angle = dot.max(-1.0).min(1.0).acos();
+        if angle.sin().abs() < 1e-6 {
+            return *self;
+        }
+
+        let (a, b) = (
+            ((1.0 - fraction) * angle).sin() / angle.sin(),
+            (fraction * angle).sin() / angle.sin(),
+        );
+        let point = |i: usize| a * start[i] + b * end[i];
+        let (x, y, z) = (point(0), point(1), point(2));
+        Coordinates {
+            latitude: Latitude {
+                value: z.atan2((x * x + y * y).sqrt()).to_degrees(),
+            },
+            longitude: Longitude {
+                value: y.atan2(x).to_degrees(),
+            },
+        }
+    }
+}
+
+// a location to compare with others (see the compare subcommand), whose times are given in its own time zone
+// if it has one
+#[derive(Debug, Clone, Copy)]
+pub struct Location {
+    pub coordinates: Coordinates,
+    pub time_zone: Option<FixedOffset>,
 }
 
 #[cfg(test)]
@@ -241,12 +592,15 @@ mod tests {
             (-26.02, "26.020s"),
             (90.0, "90.0n"),
             (0.0, "0.0n"),
+            (51.4769, "51.4769 N"),
+            (-51.4769, " 51.4769s "),
+            (51.4769, "\t51.4769\tn\n"),
         ];
 
         for (expected, arg) in params.iter() {
             assert_eq!(
                 *expected,
-                Latitude::from_decimal_degrees(*arg).unwrap().value
+                Latitude::from_decimal_degrees(arg).unwrap().value
             )
         }
     }
@@ -260,12 +614,298 @@ mod tests {
             (-26.02, "26.020w"),
             (180.0, "180.0e"),
             (0.0, "0.0e"),
+            (-0.0005, "0.0005 W"),
+            (0.0005, "  0.0005e "),
+            (151.2093, "151.2093\tE"),
+        ];
+
+        for (expected, arg) in params.iter() {
+            assert_eq!(
+                *expected,
+                Longitude::from_decimal_degrees(arg).unwrap().value
+            )
+        }
+    }
+
+    #[test]
+    fn test_parse_zero_coordinates() {
+        // zero with either compass direction is the same, positive, zero
+        for arg in ["0N", "0.0n", "0S", "0.0s", "+0.0S"].iter() {
+            let latitude = Latitude::from_decimal_degrees(arg).unwrap().value;
+            assert_eq!(0.0, latitude);
+            assert!(latitude.is_sign_positive());
+        }
+        for arg in ["0E", "0.0e", "0W", "0.0w", "+0.0W"].iter() {
+            let longitude = Longitude::from_decimal_degrees(arg).unwrap().value;
+            assert_eq!(0.0, longitude);
+            assert!(longitude.is_sign_positive());
+        }
+    }
+
+    #[test]
+    fn test_parse_invalid_coordinates() {
+        // the compass direction is required, and signs other than '+' are not allowed, even for zero
+        let latitudes = [
+            "", "N", "0", "0.0", "50.0", "-0.0N", "-50.0N", "50.0E", "90.1N", "infN", "NaNN",
+            "5 0N", "+ 50N", "  ", " N ",
+        ];
+        for arg in latitudes.iter() {
+            assert!(Latitude::from_decimal_degrees(arg).is_err(), "{}", arg);
+        }
+
+        let longitudes = [
+            "", "E", "0", "0.0", "50.0", "-0.0W", "-50.0E", "50.0N", "180.1W", "infE", "NaNE",
+            "5 0E", "+ 50E", "  ", " E ",
+        ];
+        for arg in longitudes.iter() {
+            assert!(Longitude::from_decimal_degrees(arg).is_err(), "{}", arg);
+        }
+    }
+
+    #[test]
+    fn test_from_signed_decimal_degrees() {
+        let coordinates = Coordinates::from_signed_decimal_degrees(51.4769, -0.0005).unwrap();
+        assert_eq!(51.4769, coordinates.latitude.value);
+        assert_eq!(-0.0005, coordinates.longitude.value);
+
+        let coordinates = Coordinates::from_signed_decimal_degrees(-33.8688, 151.2093).unwrap();
+        assert_eq!("Latitude: 33.8688S", coordinates.latitude.to_string());
+        assert_eq!("Longitude: 151.2093E", coordinates.longitude.to_string());
+
+        let coordinates = Coordinates::from_signed_decimal_degrees(-0.0, -0.0).unwrap();
+        assert!(coordinates.latitude.value.is_sign_positive());
+        assert!(coordinates.longitude.value.is_sign_positive());
+
+        assert!(Coordinates::from_signed_decimal_degrees(90.1, 0.0).is_err());
+        assert!(Coordinates::from_signed_decimal_degrees(0.0, -180.1).is_err());
+        assert!(Coordinates::from_signed_decimal_degrees(std::f64::NAN, 0.0).is_err());
+    }
+
+    #[test]
+    fn test_to_decimal_degrees() {
+        let coordinates = Coordinates::from_decimal_degrees("51.4769N", "0.0005W").unwrap();
+        assert_eq!(
+            ("51.4769N".to_string(), "0.0005W".to_string()),
+            coordinates.to_decimal_degrees()
+        );
+
+        // and back again, without losing any precision
+        let coordinates = Coordinates::from_signed_decimal_degrees(-33.86881234, 151.2).unwrap();
+        let (latitude, longitude) = coordinates.to_decimal_degrees();
+        assert_eq!("33.86881234S", latitude);
+        let round_trip = Coordinates::from_decimal_degrees(&latitude, &longitude).unwrap();
+        assert_eq!(coordinates.latitude.value, round_trip.latitude.value);
+        assert_eq!(coordinates.longitude.value, round_trip.longitude.value);
+    }
+
+    #[test]
+    fn test_zero_coordinates_round_trip() {
+        // signed zeroes of either sign are the equator and prime meridian, written with the northern and
+        // eastern compass directions
+        for (latitude, longitude) in [(0.0, 0.0), (-0.0, -0.0)].iter() {
+            let coordinates =
+                Coordinates::from_signed_decimal_degrees(*latitude, *longitude).unwrap();
+            assert!(coordinates.latitude.value.is_sign_positive());
+            assert!(coordinates.longitude.value.is_sign_positive());
+            assert_eq!(
+                ("0N".to_string(), "0E".to_string()),
+                coordinates.to_decimal_degrees()
+            );
+            assert_eq!(0, coordinates.longitude.nominal_offset().local_minus_utc());
+        }
+    }
+
+    #[test]
+    fn test_great_circle_point() {
+        // the antimeridian is both 180° and -180°
+        let close = |coordinates: Coordinates, latitude: f64, longitude: f64| {
+            (coordinates.latitude.value - latitude).abs() < 1e-9
+                && ((coordinates.longitude.value - longitude).rem_euclid(360.0) + 1e-9) % 360.0
+                    < 2e-9
+        };
+
+        // along the equator, and over the pole between two points on the same parallel
+        let start = Coordinates::from_signed_decimal_degrees(0.0, 0.0).unwrap();
+        let end = Coordinates::from_signed_decimal_degrees(0.0, 90.0).unwrap();
+        assert!(close(start.great_circle_point(&end, 0.5), 0.0, 45.0));
+        assert!(close(start.great_circle_point(&end, 0.0), 0.0, 0.0));
+        assert!(close(start.great_circle_point(&end, 1.0), 0.0, 90.0));
+
+        let start = Coordinates::from_signed_decimal_degrees(45.0, 0.0).unwrap();
+        let end = Coordinates::from_signed_decimal_degrees(45.0, 180.0).unwrap();
+        assert!((start.great_circle_point(&end, 0.5).latitude.value - 90.0).abs() < 1e-9);
+
+        // across the antimeridian, rather than the long way round
+        let start = Coordinates::from_signed_decimal_degrees(0.0, 170.0).unwrap();
+        let end = Coordinates::from_signed_decimal_degrees(0.0, -170.0).unwrap();
+        assert!(close(start.great_circle_point(&end, 0.5), 0.0, 180.0));
+
+        // and there is no way to choose between the routes to the opposite side of the Earth
+        let end = Coordinates::from_signed_decimal_degrees(0.0, -10.0).unwrap();
+        assert!(close(start.great_circle_point(&end, 0.5), 0.0, 170.0));
+    }
+
+    #[test]
+    fn test_event_filter() {
+        let filter = EventFilter {
+            event: Event::Sunset,
+            comparator: Comparator::GreaterOrEqual,
+            time: NaiveTime::from_hms(20, 0, 0),
+        };
+
+        let sunset = |time: &str| {
+            EventTime::new(Some(
+                DateTime::parse_from_rfc3339(&format!("2020-06-21T{}+01:00", time)).unwrap(),
+            ))
+        };
+        assert!(filter.matches(&sunset("20:00:00")));
+        assert!(filter.matches(&sunset("21:30:00")));
+        assert!(!filter.matches(&sunset("19:59:59")));
+        assert!(!filter.matches(&EventTime::new(None)));
+
+        let filter = EventFilter {
+            comparator: Comparator::NotEqual,
+            ..filter
+        };
+        assert!(!filter.matches(&sunset("20:00:00")));
+        assert!(filter.matches(&sunset("20:00:01")));
+        assert!(!filter.matches(&EventTime::new(None)));
+    }
+
+    #[test]
+    fn test_twilight_angles() {
+        let standard = TwilightAngles::default();
+        assert!(standard.is_standard());
+        assert_eq!(-12.0, standard.angle(&TwilightType::Nautical));
+
+        let custom = TwilightAngles {
+            civil: -5.0,
+            ..standard
+        };
+        assert!(!custom.is_standard());
+        assert_eq!(-5.0, custom.angle(&TwilightType::Civil));
+        assert_eq!(
+            "civil -5.0°, nautical -12.0°, astronomical -18.0°",
+            custom.to_string()
+        );
+    }
+
+    #[test]
+    fn test_twilight_angles_with_atmosphere() {
+        // the standard atmosphere gives the standard sunrise, and denser air lowers it, as there is more
+        // refraction
+        let standard = TwilightAngles::default();
+        assert_eq!(
+            standard,
+            standard.with_atmosphere(STANDARD_PRESSURE, STANDARD_TEMPERATURE)
+        );
+
+        let cold = standard.with_atmosphere(1030.0, -20.0);
+        assert!((cold.sunrise - -0.913).abs() < 0.001, "{}", cold.sunrise);
+        assert_eq!(standard.civil, cold.civil);
+        assert!(!cold.is_standard());
+        assert_eq!(
+            "sunrise -0.913°, civil -6.0°, nautical -12.0°, astronomical -18.0°",
+            cold.to_string()
+        );
+
+        // half the pressure, as on a high mountain, halves the refraction
+        let thin = standard.with_atmosphere(505.0, STANDARD_TEMPERATURE);
+        assert!((thin.sunrise - (SUNRISE_ALTITUDE + HORIZON_REFRACTION / 2.0)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_horizon_profile() {
+        let profile = HorizonProfile::new(vec![(90.0, 10.0), (270.0, 2.0), (360.0, 0.0)]).unwrap();
+        assert_eq!(10.0, profile.altitude_at(90.0));
+        assert_eq!(6.0, profile.altitude_at(180.0));
+        assert_eq!(1.0, profile.altitude_at(315.0));
+        // wrapping round through north, given as 360
+        assert_eq!(5.0, profile.altitude_at(45.0));
+        assert_eq!(5.0, profile.altitude_at(405.0));
+
+        assert_eq!(
+            3.0,
+            HorizonProfile::new(vec![(10.0, 3.0)])
+                .unwrap()
+                .altitude_at(200.0)
+        );
+
+        assert!(HorizonProfile::new(vec![]).is_err());
+        assert!(HorizonProfile::new(vec![(361.0, 0.0)]).is_err());
+        assert!(HorizonProfile::new(vec![(0.0, 91.0)]).is_err());
+    }
+
+    #[test]
+    fn test_light_guard() {
+        let angles = TwilightAngles::default();
+        let light = LightGuard {
+            require_light: true,
+            twilight: None,
+        };
+        let dark = LightGuard {
+            require_light: false,
+            ..light
+        };
+        assert!(light.passes(10.0, &angles));
+        assert!(light.passes(SUNRISE_ALTITUDE, &angles));
+        assert!(!light.passes(-3.0, &angles));
+        assert!(dark.passes(-3.0, &angles));
+        assert!(!dark.passes(10.0, &angles));
+
+        // with a twilight, it stays light until the end of it
+        let civil = LightGuard {
+            twilight: Some(TwilightType::Civil),
+            ..light
+        };
+        assert!(civil.passes(-3.0, &angles));
+        assert!(!civil.passes(-7.0, &angles));
+    }
+
+    #[test]
+    fn test_day_phase() {
+        let standard = TwilightAngles::default();
+        let params = [
+            (DayPhase::Day, 45.0),
+            (DayPhase::Day, -0.833),
+            (DayPhase::CivilTwilight, -0.834),
+            (DayPhase::CivilTwilight, -6.0),
+            (DayPhase::NauticalTwilight, -6.1),
+            (DayPhase::AstronomicalTwilight, -17.9),
+            (DayPhase::Night, -18.1),
+            (DayPhase::Night, -90.0),
+        ];
+        for (expected, altitude) in params.iter() {
+            assert_eq!(*expected, standard.day_phase(*altitude), "{}", altitude);
+        }
+
+        // the bands follow any custom twilight angles
+        let custom = TwilightAngles {
+            civil: -8.0,
+            ..standard
+        };
+        assert_eq!(DayPhase::CivilTwilight, custom.day_phase(-7.0));
+    }
+
+    #[test]
+    fn test_longitude_nominal_offset() {
+        let params = [
+            (0, "0.0005W"),
+            (0, "7.4E"),
+            (3600, "7.6E"),
+            (9 * 3600, "139.6917E"),
+            (-5 * 3600, "74.006W"),
+            (12 * 3600, "180.0E"),
+            (-12 * 3600, "180.0W"),
         ];
 
         for (expected, arg) in params.iter() {
             assert_eq!(
                 *expected,
-                Longitude::from_decimal_degrees(*arg).unwrap().value
+                Longitude::from_decimal_degrees(arg)
+                    .unwrap()
+                    .nominal_offset()
+                    .local_minus_utc()
             )
         }
     }
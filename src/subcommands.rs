@@ -1,43 +1,793 @@
-use std::result;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    result,
+};
 
-use chrono::{Duration, FixedOffset, Local, TimeZone};
+use chrono::{
+    DateTime, Duration, FixedOffset, Local, LocalResult, NaiveTime, TimeZone, Timelike, Utc,
+};
 
 use super::{
-    enums,
-    errors::{HeliocronError, RuntimeErrorKind},
-    report, utils,
+    config, enums,
+    errors::{ConfigErrorKind, HeliocronError, RuntimeErrorKind},
+    lunar, parsers, report, solar, structs, utils,
 };
 
 type Result<T> = result::Result<T, HeliocronError>;
 
-pub fn display_report(report: report::SolarReport) {
-    println!("{}", report);
+pub fn display_report(
+    reports: &[report::SolarReport],
+    range: bool,
+    format: enums::OutputFormat,
+    compact_json: bool,
+    sections: structs::ExtraSections,
+) {
+    if format == enums::OutputFormat::Csv {
+        print!("{}", report::SolarReport::to_delimited(reports, ','));
+        return;
+    }
+    if format == enums::OutputFormat::Tsv {
+        print!("{}", report::SolarReport::to_delimited(reports, '\t'));
+        return;
+    }
+
+    if format == enums::OutputFormat::Json {
+        // a date range is shown as an array of reports, one for each day
+        if range {
+            println!(
+                "{}",
+                report::SolarReport::to_json_array(reports, compact_json)
+            );
+        } else {
+            for report in reports {
+                println!("{}", report.to_json(compact_json));
+            }
+        }
+        return;
+    }
+
+    for report in reports {
+        println!("{}", report);
+        if sections.astro {
+            println!("{}", report.format_astro());
+        }
+        if sections.shadow {
+            println!("{}", report.format_shadow());
+        }
+        if sections.night {
+            println!("{}", report.format_night());
+        }
+        if sections.photo {
+            println!("{}", report.format_photo());
+        }
+    }
+}
+
+pub fn display_ics(
+    reports: &[report::SolarReport],
+    events: &[enums::Event],
+    now: DateTime<FixedOffset>,
+) {
+    print!(
+        "{}",
+        report::SolarReport::to_ics(reports, events, now.with_timezone(&Utc))
+    );
+}
+
+pub fn display_chart_data(reports: &[report::SolarReport]) {
+    print!("{}", report::SolarReport::to_chart_data(reports));
+}
+
+pub fn display_civil_day(reports: &[report::SolarReport]) {
+    let days: Vec<String> = reports
+        .iter()
+        .map(|report| report.format_civil_day())
+        .collect();
+    println!("{}", days.join("\n\n"));
+}
+
+pub fn display_shell_export(reports: &[report::SolarReport]) -> Result<()> {
+    // the variables of one day would simply overwrite those of the day before
+    match reports {
+        [report] => {
+            print!("{}", report.to_shell());
+            Ok(())
+        }
+        _ => Err(HeliocronError::Config(ConfigErrorKind::InvalidDateRange(
+            "Only a single day can be output as shell variables.",
+        ))),
+    }
+}
+
+pub fn display_comparison(reports: &[report::SolarReport]) {
+    println!("{}", report::SolarReport::format_comparison(reports));
+}
+
+pub fn display_flight(flight: &report::FlightReport, json: bool, compact_json: bool) {
+    if json {
+        println!("{}", utils::to_json(flight, compact_json));
+    } else {
+        println!("{}", flight);
+    }
+}
+
+pub fn display_template(reports: &[report::SolarReport], path: &Path) -> Result<()> {
+    let template = fs::read_to_string(path)
+        .map_err(|_| HeliocronError::Config(ConfigErrorKind::UnreadableTemplateFile))?;
+
+    // render every report before showing any of them, so that a bad template doesn't give partial output
+    let rendered = reports
+        .iter()
+        .map(|report| report.render_template(&template))
+        .collect::<Result<Vec<String>>>()?;
+    for report in rendered {
+        if report.ends_with('\n') {
+            print!("{}", report);
+        } else {
+            println!("{}", report);
+        }
+    }
+    Ok(())
+}
+
+pub fn display_event_times(
+    reports: &[report::SolarReport],
+    event: enums::EventBase,
+    event_offset: Duration,
+    offsets: Vec<Duration>,
+    fallback: Option<enums::DayMark>,
+) {
+    // with no offsets, just show the event itself. An event defined in the config file moves every offset by
+    // its own
+    let offsets = if offsets.is_empty() {
+        vec![event_offset]
+    } else {
+        offsets
+            .into_iter()
+            .map(|offset| offset + event_offset)
+            .collect()
+    };
+
+    for report in reports {
+        let (event_time, used) = report.time_or_fallback(&event, fallback);
+
+        for offset in &offsets {
+            let time = match event_time {
+                // the offset moves the time, but not how well the event itself is known
+                Some(datetime) => format!(
+                    "{}{}{}",
+                    report.format_time(datetime + *offset),
+                    report.format_uncertainty(datetime),
+                    used.map_or_else(String::new, |used| format!(" ({} instead)", used))
+                ),
+                None => format!("Never ({})", report.date.format("%Y-%m-%d")),
+            };
+            println!(
+                "{} {} is at: {}",
+                event,
+                utils::format_offset(*offset),
+                time
+            );
+        }
+    }
 }
 
-pub fn wait(offset: Duration, report: report::SolarReport, event: enums::Event) -> Result<()> {
-    let event_time = match event {
-        enums::Event::Sunrise => report.sunrise,
-        enums::Event::Sunset => report.sunset,
-        enums::Event::CivilDawn => report.civil_dawn,
-        enums::Event::CivilDusk => report.civil_dusk,
-        enums::Event::NauticalDawn => report.nautical_dawn,
-        enums::Event::NauticalDusk => report.nautical_dusk,
-        enums::Event::AstronomicalDawn => report.astronomical_dawn,
-        enums::Event::AstronomicalDusk => report.astronomical_dusk,
+pub fn display_event_times_json(
+    reports: &[report::SolarReport],
+    event: enums::EventBase,
+    event_offset: Duration,
+    offsets: &[(String, Duration)],
+    fallback: Option<enums::DayMark>,
+    compact_json: bool,
+) {
+    // with no offsets, just the event itself
+    let offsets = if offsets.is_empty() {
+        vec![(utils::format_offset(Duration::zero()), Duration::zero())]
+    } else {
+        offsets.to_vec()
     };
+    let times =
+        report::OffsetEventTime::from_reports(reports, &event, event_offset, &offsets, fallback);
+    println!("{}", utils::to_json(&times, compact_json));
+}
+
+pub fn display_events(
+    reports: &[report::SolarReport],
+    mut events: Vec<(enums::EventBase, Duration)>,
+    between: Option<(NaiveTime, NaiveTime)>,
+) {
+    // each event once, whatever order or however often it was asked for, and then in order of time on each
+    // day, so that the output is stable. An event defined in the config file is what it is based on plus its
+    // offset, and is only the same as another if both match
+    events.sort_by_key(|(event, offset)| (event.priority(), *offset));
+    let mut unique: Vec<(enums::EventBase, Duration)> = vec![];
+    for event in events {
+        if !unique.contains(&event) {
+            unique.push(event);
+        }
+    }
 
-    // handle the case when the chosen event doesn't occur on this day
-    if event_time.to_string() == "Never" {
-        Err(HeliocronError::Runtime(RuntimeErrorKind::NonOccurringEvent))?;
+    for report in reports {
+        let mut times: Vec<(&enums::EventBase, Duration, Option<DateTime<FixedOffset>>)> = unique
+            .iter()
+            .map(|(event, offset)| {
+                let time = report.base_time(event).datetime.map(|time| time + *offset);
+                (event, *offset, time)
+            })
+            .collect();
+        times.sort_by_key(|(event, offset, time)| {
+            (time.is_none(), *time, event.priority(), *offset)
+        });
+        // only the events within the window of clock times, if there is one. Events which don't occur have no
+        // time, so are never within it
+        if let Some((start, end)) = between {
+            times.retain(|(_, _, time)| {
+                time.map_or(false, |time| {
+                    let time = time.time();
+                    if start <= end {
+                        start <= time && time <= end
+                    } else {
+                        start <= time || time <= end
+                    }
+                })
+            });
+        }
+
+        for (event, offset, time) in times {
+            let time = match time {
+                Some(time) => format!(
+                    "{}{}",
+                    report.format_time(time),
+                    report.format_uncertainty(time - offset)
+                ),
+                None => format!("Never ({})", report.date.format("%Y-%m-%d")),
+            };
+            println!("{} {} is at: {}", event, utils::format_offset(offset), time);
+        }
     }
+}
+
+pub fn display_crossings(
+    reports: &[report::SolarReport],
+    depression: f64,
+    direction: Option<enums::Direction>,
+) {
+    // both crossings of the depression angle, unless only one direction was asked for
+    for report in reports {
+        let (rising, setting) = report.crossings(depression);
+        let crossings = [
+            (enums::Direction::Rising, rising),
+            (enums::Direction::Setting, setting),
+        ];
 
-    let wait_until = event_time.datetime.unwrap() + offset;
+        for (crossing_direction, event_time) in crossings.iter() {
+            if direction.map_or(false, |direction| direction != *crossing_direction) {
+                continue;
+            }
+            let time = match event_time.datetime {
+                Some(datetime) => format!(
+                    "{}{}",
+                    report.format_time(datetime),
+                    report.format_uncertainty(datetime)
+                ),
+                None => format!("Never ({})", report.date.format("%Y-%m-%d")),
+            };
+            println!(
+                "Sun {} through {}° below the horizon is at: {}",
+                crossing_direction, depression, time
+            );
+        }
+    }
+}
+
+pub fn display_difference(report: &report::SolarReport, other: &report::SolarReport) {
+    println!("{}", report.format_difference(other));
+}
+
+pub fn display_around_now(reports: &[report::SolarReport], now: DateTime<FixedOffset>) {
+    // the latest event at or before now and the earliest one after it, from the reports of the days around now
+    let mut events = vec![];
+    for report in reports {
+        for event in enums::Event::all().iter() {
+            if let Some(datetime) = report.event_time(event).datetime {
+                events.push((report, *event, datetime));
+            }
+        }
+    }
+    let previous = events
+        .iter()
+        .filter(|(_, _, datetime)| *datetime <= now)
+        .max_by_key(|(_, event, datetime)| (*datetime, event.priority()));
+    let next = events
+        .iter()
+        .filter(|(_, _, datetime)| *datetime > now)
+        .min_by_key(|(_, event, datetime)| (*datetime, event.priority()));
 
-    let local_time = Local::now();
-    let local_time = local_time.with_timezone(&FixedOffset::from_offset(local_time.offset()));
+    match previous {
+        Some((report, event, datetime)) => println!(
+            "Previous event: {} at {}, {} ago",
+            event,
+            report.format_time(*datetime),
+            report::format_hms(now - *datetime)
+        ),
+        None => println!("Previous event: None within the past day"),
+    }
+    match next {
+        Some((report, event, datetime)) => println!(
+            "Next event: {} at {}, in {}",
+            event,
+            report.format_time(*datetime),
+            report::format_hms(*datetime - now)
+        ),
+        None => println!("Next event: None within the next day"),
+    }
+}
 
-    let duration_to_wait = wait_until - local_time;
+pub fn display_event_offsets(reports: &[report::SolarReport], path: &Path) -> Result<()> {
+    let offsets = fs::read_to_string(path)
+        .map_err(|_| HeliocronError::Config(ConfigErrorKind::UnreadableOffsetFile))?;
+    let offsets = parsers::parse_offset_file(&offsets)?;
 
-    utils::wait(duration_to_wait, wait_until)?;
+    for report in reports {
+        for event in enums::Event::all().iter() {
+            let offset = offsets
+                .iter()
+                .find(|(offset_event, _)| offset_event == event)
+                .map_or_else(Duration::zero, |(_, offset)| *offset);
+            let time = match report.event_time(event).datetime {
+                Some(datetime) => report.format_time(datetime + offset),
+                None => format!("Never ({})", report.date.format("%Y-%m-%d")),
+            };
+            println!("{} {} is at: {}", event, utils::format_offset(offset), time);
+        }
+    }
     Ok(())
 }
+
+pub fn display_count(reports: &[report::SolarReport]) {
+    println!("{}", reports.len());
+}
+
+pub fn display_occurrence(
+    reports: &[report::SolarReport],
+    event: enums::EventBase,
+    range: bool,
+) -> Result<()> {
+    let mut all_occur = true;
+    for report in reports {
+        let occurs = report.base_time(&event).is_some();
+        all_occur &= occurs;
+        if range {
+            println!("{} {}", report.date.format("%Y-%m-%d"), occurs);
+        } else {
+            println!("{}", occurs);
+        }
+    }
+
+    if all_occur {
+        Ok(())
+    } else {
+        Err(HeliocronError::Runtime(RuntimeErrorKind::NonOccurringEvent))
+    }
+}
+
+pub fn compare_expected_times(
+    reports: &[report::SolarReport],
+    expected_times: Vec<(enums::Event, NaiveTime)>,
+    tolerance: Duration,
+) -> Result<()> {
+    // compare the calculated times with expected ones, from an almanac say, on each of the days. A missing
+    // event is as far from its expected time as it's possible to be
+    let mut within_tolerance = true;
+
+    for report in reports {
+        for (event, expected_time) in &expected_times {
+            let expected = report.date.date().and_time(*expected_time).unwrap();
+            let (calculated, within) = match report.event_time(event).datetime {
+                Some(datetime) => {
+                    let difference = datetime - expected;
+                    let within = difference.num_seconds().abs() <= tolerance.num_seconds();
+                    (
+                        format!("{} ({})", datetime, utils::format_offset(difference)),
+                        within,
+                    )
+                }
+                None => ("Never".to_string(), false),
+            };
+            within_tolerance &= within;
+
+            println!(
+                "{} expected at: {} calculated at: {} {}",
+                event,
+                expected,
+                calculated,
+                if within { "OK" } else { "OUTSIDE TOLERANCE" }
+            );
+        }
+    }
+
+    if within_tolerance {
+        Ok(())
+    } else {
+        Err(HeliocronError::Runtime(RuntimeErrorKind::OutsideTolerance))
+    }
+}
+
+pub fn display_schema(compact_json: bool) {
+    println!("{}", report::json_schema(compact_json));
+}
+
+pub fn display_sun_path(
+    report: &report::SolarReport,
+    interval: Duration,
+    full_day: bool,
+    json: bool,
+    compact_json: bool,
+) {
+    // from sunrise to sunset, unless the Sun never rises or sets, in which case the whole day is either
+    // daylight or there is nothing to show
+    let midnight = report.date.date().and_hms(0, 0, 0);
+    let whole_day = Some((midnight, midnight + Duration::days(1)));
+    let period = match (report.sunrise.datetime, report.sunset.datetime) {
+        _ if full_day => whole_day,
+        (Some(sunrise), Some(sunset)) => Some((sunrise, sunset)),
+        _ if report.day_length > Duration::zero() => whole_day,
+        _ => None,
+    };
+    let samples = match period {
+        Some((start, end)) => solar::sun_path(start, end, interval, report.coordinates),
+        None => vec![],
+    };
+
+    if json {
+        println!("{}", utils::to_json(&samples, compact_json));
+        return;
+    }
+
+    println!("time,altitude,azimuth");
+    for sample in samples {
+        let time = if report.is_numeric_time() || report.relative_to_noon {
+            report.format_time(sample.time)
+        } else {
+            sample.time.to_rfc3339()
+        };
+        println!(
+            "{},{:.4},{:.4}",
+            time, sample.position.altitude, sample.position.azimuth
+        );
+    }
+}
+
+pub fn display_phase(
+    datetime: DateTime<FixedOffset>,
+    coordinates: structs::Coordinates,
+    twilight_angles: structs::TwilightAngles,
+) -> enums::DayPhase {
+    // the phase is judged by the geometric altitude, as are the times of the events which bound it
+    let altitude = solar::SolarPosition::geometric(datetime, coordinates).altitude;
+    let phase = twilight_angles.day_phase(altitude);
+    println!("{}", phase);
+    phase
+}
+
+pub fn check_guard(
+    guard: &structs::LightGuard,
+    datetime: DateTime<FixedOffset>,
+    coordinates: structs::Coordinates,
+    twilight_angles: &structs::TwilightAngles,
+) -> bool {
+    // by the geometric altitude, as for the phase of the day
+    let altitude = solar::SolarPosition::geometric(datetime, coordinates).altitude;
+    guard.passes(altitude, twilight_angles)
+}
+
+pub fn display_season(date: DateTime<FixedOffset>, day_number: bool) {
+    if day_number {
+        // the seasons either side of the chosen day, which counts as after one occurring on it, so that the
+        // day number runs from 0 on the day of the solstice or equinox itself
+        let following_day = date.date().succ().and_hms(0, 0, 0);
+        let (previous, previous_datetime) = solar::previous_season(following_day);
+        let (next, next_datetime) = solar::next_season(following_day);
+        let days =
+            |from: DateTime<FixedOffset>, to: DateTime<FixedOffset>| match (to.naive_local().date()
+                - from.naive_local().date())
+            .num_days()
+            {
+                1 => "1 day".to_string(),
+                days => format!("{} days", days),
+            };
+        println!(
+            "{} since the {} at {}",
+            days(previous_datetime, date),
+            previous,
+            previous_datetime
+        );
+        println!(
+            "{} until the {} at {}",
+            days(date, next_datetime),
+            next,
+            next_datetime
+        );
+        return;
+    }
+
+    // search from the start of the chosen day, so that a solstice or equinox occurring on that day is included
+    let (season, datetime) = solar::next_season(date.date().and_hms(0, 0, 0));
+    println!(
+        "The next solstice or equinox is the {} at {}",
+        season, datetime
+    );
+}
+
+pub fn display_day_extremes(reports: &[report::SolarReport], json: bool, compact_json: bool) {
+    let extremes = report::DayLengthExtremes::from_reports(reports);
+    if json {
+        println!("{}", utils::to_json(&extremes, compact_json));
+    } else {
+        println!("{}", extremes);
+    }
+}
+
+pub fn display_season_bounds(bounds: &report::SeasonBounds, json: bool, compact_json: bool) {
+    if json {
+        println!("{}", utils::to_json(bounds, compact_json));
+    } else {
+        println!("{}", bounds);
+    }
+}
+
+pub fn display_next_occurrence(next: &report::NextOccurrence, json: bool, compact_json: bool) {
+    if json {
+        println!("{}", utils::to_json(next, compact_json));
+    } else {
+        println!("{}", next);
+    }
+}
+
+pub fn display_schedule(
+    schedule: &report::Schedule,
+    wait_args: &[String],
+    run: &str,
+    warnings_json: bool,
+) -> Result<()> {
+    // a crontab line which fires every day at a time before the event, then waits for it, as in the example
+    // in the help
+    let trigger = schedule.trigger.ok_or(HeliocronError::Runtime(
+        RuntimeErrorKind::NoOccurrenceWithinYear,
+    ))?;
+    if schedule.before_midnight {
+        utils::warn(
+            &structs::Warning {
+                code: "schedule_before_midnight",
+                message: "the chosen event [+ offset] is sometimes before the midnight which starts its day, \
+                          which the crontab line can't fire before, so the wait will fail on those days."
+                    .to_string(),
+            },
+            warnings_json,
+        );
+    }
+
+    println!(
+        "{} {} * * * heliocron {} && {}",
+        trigger.minute(),
+        trigger.hour(),
+        wait_args.join(" "),
+        run
+    );
+    Ok(())
+}
+
+pub fn display_nights(nights: &[report::NightReport], json: bool, compact_json: bool) {
+    if json {
+        match nights {
+            [night] => println!("{}", utils::to_json(night, compact_json)),
+            _ => println!("{}", utils::to_json(&nights, compact_json)),
+        }
+        return;
+    }
+
+    let nights: Vec<String> = nights.iter().map(|night| night.to_string()).collect();
+    println!("{}", nights.join("\n\n"));
+}
+
+pub fn display_darkness(darkness: &[report::DarknessReport], json: bool, compact_json: bool) {
+    if json {
+        match darkness {
+            [night] => println!("{}", utils::to_json(night, compact_json)),
+            _ => println!("{}", utils::to_json(&darkness, compact_json)),
+        }
+        return;
+    }
+
+    let darkness: Vec<String> = darkness.iter().map(|night| night.to_string()).collect();
+    println!("{}", darkness.join("\n\n"));
+}
+
+pub fn display_moon_phases(
+    year: i32,
+    time_zone: &FixedOffset,
+    json: bool,
+    compact_json: bool,
+) -> Result<()> {
+    if !(solar::EARLIEST_YEAR..=solar::LATEST_YEAR).contains(&year) {
+        return Err(HeliocronError::Config(ConfigErrorKind::UnsupportedDate));
+    }
+
+    let phases = lunar::phases_in_year(year, time_zone);
+    if json {
+        println!("{}", utils::to_json(&phases, compact_json));
+        return Ok(());
+    }
+
+    for phase in phases {
+        println!(
+            "{}  {}{}",
+            phase.time,
+            phase.phase,
+            if phase.blue_moon { " (blue moon)" } else { "" }
+        );
+    }
+    Ok(())
+}
+
+pub fn wait(
+    offset: Duration,
+    report: report::SolarReport,
+    event: enums::EventBase,
+    bounds: structs::WaitBounds,
+    sleep: structs::SleepOptions,
+    warnings_json: bool,
+    now: DateTime<FixedOffset>,
+) -> Result<()> {
+    // handle the case when the chosen event doesn't occur on this day, and nor does any fallback
+    let event_time = match report.time_or_fallback(&event, bounds.fallback) {
+        (Some(time), used) => {
+            if let Some(used) = used {
+                utils::warn(&fallback_warning(&event, used, &report), warnings_json);
+            }
+            time
+        }
+        (None, _) => Err(HeliocronError::Runtime(RuntimeErrorKind::NonOccurringEvent))?,
+    };
+
+    // an offset which would take the wait past a clamp stops at it instead, e.g. when the events of a winter's
+    // day crowd together. A clamp which doesn't occur on the day can't be applied
+    let mut wait_until = event_time + offset;
+    if let Some(after) = bounds.clamp_after.and_then(|mark| report.mark_time(&mark)) {
+        wait_until = std::cmp::max(wait_until, after);
+    }
+    if let Some(before) = bounds.clamp_before.and_then(|mark| report.mark_time(&mark)) {
+        wait_until = std::cmp::min(wait_until, before);
+    }
+
+    sleep_until(wait_until, sleep, now)
+}
+
+fn fallback_warning(
+    event: &enums::EventBase,
+    fallback: enums::DayMark,
+    report: &report::SolarReport,
+) -> structs::Warning {
+    structs::Warning {
+        code: "fallback_event",
+        message: format!(
+            "{} does not occur on {}, so {} is used instead.",
+            event,
+            report.date.format("%Y-%m-%d"),
+            fallback
+        ),
+    }
+}
+
+pub fn wait_at(
+    offset: Duration,
+    time: NaiveTime,
+    time_zone: Option<FixedOffset>,
+    sleep: structs::SleepOptions,
+    now: DateTime<FixedOffset>,
+) -> Result<()> {
+    // a time zone given on the command line is fixed, otherwise the local time zone is followed, including
+    // any daylight saving transitions
+    let wait_until = match time_zone {
+        Some(time_zone) => {
+            utils::next_wall_clock_time(now.with_timezone(&time_zone), time, offset, |_| {
+                LocalResult::Single(time_zone)
+            })
+        }
+        None => {
+            let now = now.with_timezone(&Local);
+            utils::next_wall_clock_time(now.with_timezone(now.offset()), time, offset, |datetime| {
+                Local.offset_from_local_datetime(datetime)
+            })
+        }
+    };
+
+    sleep_until(wait_until, sleep, now)
+}
+
+fn sleep_until(
+    wait_until: DateTime<FixedOffset>,
+    sleep: structs::SleepOptions,
+    now: DateTime<FixedOffset>,
+) -> Result<()> {
+    let wait_until = match sleep.align_minute {
+        Some(round_mode) => utils::align_to_minute(wait_until, round_mode),
+        None => wait_until,
+    };
+
+    // the difference between two instants, whatever their offsets, so this is unaffected by daylight saving
+    let duration_to_wait = wait_until - now;
+
+    utils::wait(
+        duration_to_wait,
+        wait_until,
+        sleep.grace,
+        sleep.poll_interval,
+        sleep.quiet,
+    )?;
+    Ok(())
+}
+
+pub fn display_validation(validation: &[(PathBuf, Vec<String>)]) -> bool {
+    // each config file and its problems, if any, returning whether they were all valid
+    if validation.is_empty() {
+        println!("No config file found to validate.");
+    }
+    for (path, problems) in validation.iter() {
+        match problems.len() {
+            0 => println!("{}: OK", path.display()),
+            1 => println!("{}: 1 problem", path.display()),
+            n => println!("{}: {} problems", path.display(), n),
+        }
+        for problem in problems.iter() {
+            println!("  {}", problem);
+        }
+    }
+    validation.iter().all(|(_, problems)| problems.is_empty())
+}
+
+pub fn display_date_check(date: DateTime<FixedOffset>, end_date: Option<DateTime<FixedOffset>>) {
+    // the dates as they were parsed, before anything is calculated from them
+    println!("Date:            {}", date);
+    if let Some(end_date) = end_date {
+        println!("End date:        {}", end_date);
+    }
+}
+
+pub fn dump_defaults(defaults: &config::Config) {
+    // the date is today's, so the rule it comes from is given alongside it
+    println!(
+        "DEFAULTS\n\
+        --------\n\
+        Coordinates:     {}, {}\n\
+        Date:            midday today in the local time zone, currently {}\n\
+        Time zone:       the local time zone on the chosen date\n\
+        Output format:   {}\n\
+        Twilight angles: {}\n\
+        Wait offset:     {}, i.e. exactly at the event\n\
+        Wait event:      none, one must be chosen with --event, or a time with --at",
+        config::DEFAULT_LATITUDE,
+        config::DEFAULT_LONGITUDE,
+        defaults.date.format("%Y-%m-%d %H:%M:%S %:z"),
+        defaults.output_format,
+        defaults.twilight_angles,
+        config::DEFAULT_OFFSET,
+    );
+}
+
+pub fn dump_config(config: &config::Config, path: Option<&Path>) -> Result<()> {
+    let toml = format!(
+        "# written by heliocron dump-config, ready to be saved as heliocron.toml\n{}",
+        config.to_toml()
+    );
+    match path {
+        Some(path) => fs::write(path, toml).map_err(|_| {
+            HeliocronError::Runtime(RuntimeErrorKind::UnwritableFile(path.display().to_string()))
+        }),
+        None => {
+            print!("{}", toml);
+            Ok(())
+        }
+    }
+}
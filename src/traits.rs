@@ -7,16 +7,20 @@ pub trait DateTimeExt {
 impl<Tz: TimeZone> DateTimeExt for DateTime<Tz> {
     fn to_julian_date(&self) -> f64 {
         // takes a DateTime<Tz> and returns the number of days elapsed since noon 1/1/4713 BC
-        let (year, month, day): (i32, i32, i32) =
-            (self.year(), self.month() as i32, self.day() as i32);
+
+        // convert to UTC first, so that the calendar date and the time of day agree with each other
+        let utc_datetime = self.naive_utc();
+
+        let (year, month, day): (i32, i32, i32) = (
+            utc_datetime.year(),
+            utc_datetime.month() as i32,
+            utc_datetime.day() as i32,
+        );
 
         let julian_day =
             (367 * year - 7 * (year + (month + 9) / 12) / 4 + 275 * month / 9 + day + 1721014)
                 as f64;
 
-        // convert to UTC
-        let utc_datetime = self.naive_utc();
-
         // adjust for the epoch starting at 12:00 UTC
         let hour_part = if utc_datetime.hour() >= 12 {
             (utc_datetime.hour() - 12) as f64 / 24.0
@@ -45,8 +49,27 @@ mod tests {
         ];
 
         for (expected, arg) in params.iter() {
-            let date = DateTime::parse_from_rfc3339(*arg).unwrap();
+            let date = DateTime::parse_from_rfc3339(arg).unwrap();
             assert_eq!(*expected, format!("{:.5}", date.to_julian_date()));
         }
     }
+
+    #[test]
+    fn test_to_julian_date_uses_the_utc_date() {
+        // the same instant, where the local calendar date is a day either side of the UTC one
+        let utc = DateTime::parse_from_rfc3339("2020-03-15T10:09:01+00:00").unwrap();
+        for arg in ["2020-03-16T00:09:01+14:00", "2020-03-14T23:09:01-11:00"].iter() {
+            let date = DateTime::parse_from_rfc3339(arg).unwrap();
+            assert_eq!(utc.to_julian_date(), date.to_julian_date());
+        }
+    }
+
+    #[test]
+    fn test_to_julian_date_is_continuous_across_years() {
+        let before = DateTime::parse_from_rfc3339("2019-12-31T23:59:59+00:00").unwrap();
+        let after = DateTime::parse_from_rfc3339("2020-01-01T00:00:00+00:00").unwrap();
+
+        let difference = (after.to_julian_date() - before.to_julian_date()) * 86400.0;
+        assert_eq!(1, difference.round() as i64);
+    }
 }
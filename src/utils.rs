@@ -1,42 +1,512 @@
 use std::result;
 
-use chrono::{DateTime, Duration, FixedOffset};
+use chrono::{
+    DateTime, Duration, FixedOffset, Local, LocalResult, NaiveDateTime, NaiveTime, TimeZone,
+    Timelike,
+};
 
-use super::errors::{HeliocronError, RuntimeErrorKind};
+use serde::Serialize;
+
+use super::{
+    enums::RoundMode,
+    errors::{HeliocronError, RuntimeErrorKind},
+    structs::Warning,
+};
 
 type Result<T> = result::Result<T, HeliocronError>;
 
-fn sleep(dur: std::time::Duration) {
+fn sleep(
+    dur: std::time::Duration,
+    wait_until: DateTime<FixedOffset>,
+    poll_interval: std::time::Duration,
+) {
     if cfg!(feature = "integration-test") || cfg!(test) {
         println!("Fake sleep for {}s.", dur.as_secs());
     } else {
-        std::thread::sleep(dur);
+        // the clock runs on from the time that the wait was worked out from, which may have been set with --now
+        // rather than read from the system clock
+        let start = now();
+        let from = wait_until - Duration::from_std(dur).unwrap();
+        poll_until(
+            wait_until,
+            poll_interval,
+            || from + (now() - start),
+            std::thread::sleep,
+        );
     };
 }
 
-pub fn wait(duration: Duration, wait_until: DateTime<FixedOffset>) -> Result<()> {
+fn now() -> DateTime<FixedOffset> {
+    let now = Local::now();
+    now.with_timezone(&FixedOffset::from_offset(now.offset()))
+}
+
+fn poll_until<N, S>(
+    wait_until: DateTime<FixedOffset>,
+    poll_interval: std::time::Duration,
+    mut now: N,
+    mut sleep: S,
+) where
+    N: FnMut() -> DateTime<FixedOffset>,
+    S: FnMut(std::time::Duration),
+{
+    // sleep in steps of at most the poll interval, working out how long is left from the clock each time rather
+    // than trusting one long sleep to end at the right moment. Any change to the system clock, or time spent
+    // suspended, is then caught up with by the next step
+    while let Ok(remaining) = (wait_until - now()).to_std() {
+        if remaining == std::time::Duration::from_secs(0) {
+            break;
+        }
+        sleep(remaining.min(poll_interval));
+    }
+}
+
+pub fn to_json<T: Serialize>(value: &T, compact: bool) -> String {
+    // either on a single line, for other programs, or indented, for people. There is nothing in heliocron's
+    // output which can fail to serialise
+    if compact {
+        serde_json::to_string(value).unwrap()
+    } else {
+        serde_json::to_string_pretty(value).unwrap()
+    }
+}
+
+pub fn warn(warning: &Warning, json: bool) {
+    // every warning goes through here, so that --warnings-json applies to all of them. stdout is left for the
+    // output itself, and a JSON warning is always on a single line, so that each line of stderr can be parsed
+    if json {
+        eprintln!("{}", to_json(warning, true));
+    } else {
+        eprintln!("{}", warning);
+    }
+}
+
+pub fn wait(
+    duration: Duration,
+    wait_until: DateTime<FixedOffset>,
+    grace: Duration,
+    poll_interval: std::time::Duration,
+    quiet: bool,
+) -> Result<()> {
+    // an event which has only just passed, e.g. because cron ran a little late, still counts within the grace
+    // period
+    if duration < Duration::zero() && -duration <= grace {
+        if quiet {
+            return Ok(());
+        }
+        println!(
+            "The chosen time {} passed {} seconds ago, which is within the grace period. Continuing immediately.",
+            wait_until,
+            -duration.num_seconds()
+        );
+        return Ok(());
+    }
+
     let duration_to_wait = match duration.to_std() {
         Ok(dur) => Ok(dur),
         Err(_) => Err(HeliocronError::Runtime(RuntimeErrorKind::PastEvent)),
     }?;
 
-    println!(
-        "Thread going to sleep for {} seconds until {}. Press ctrl+C to cancel.",
-        duration_to_wait.as_secs(),
-        wait_until
-    );
-    sleep(duration_to_wait);
+    // errors are still reported when quiet, on stderr, and the exit code is the signal of success
+    if !quiet {
+        println!(
+            "Thread going to sleep for {} seconds until {}. Press ctrl+C to cancel.",
+            duration_to_wait.as_secs(),
+            wait_until
+        );
+    }
+    sleep(duration_to_wait, wait_until, poll_interval);
     Ok(())
 }
 
+pub fn format_offset(offset: Duration) -> String {
+    // e.g. -06:53:12, always with a sign, in a form which parsers::parse_offset reads back in
+    let sign = if offset < Duration::zero() { '-' } else { '+' };
+    let seconds = offset.num_seconds().abs();
+    format!(
+        "{}{:02}:{:02}:{:02}",
+        sign,
+        seconds / 3600,
+        (seconds / 60) % 60,
+        seconds % 60
+    )
+}
+
+pub fn shell_quote(value: &str) -> String {
+    // single quotes stop the shell interpreting anything inside them, leaving only single quotes themselves to
+    // be closed, escaped and reopened
+    format!("'{}'", value.replace('\'', "'\\''"))
+}
+
+pub fn align_to_minute(
+    datetime: DateTime<FixedOffset>,
+    round_mode: RoundMode,
+) -> DateTime<FixedOffset> {
+    // round to a whole minute, to match cron's granularity, leaving a whole minute as it is. Half a minute or
+    // more rounds up to the nearest minute
+    let past_the_minute = Duration::seconds(datetime.second() as i64)
+        + Duration::nanoseconds(datetime.nanosecond() as i64);
+    let start_of_minute = datetime - past_the_minute;
+    match round_mode {
+        _ if past_the_minute == Duration::zero() => datetime,
+        RoundMode::Down => start_of_minute,
+        RoundMode::Nearest if past_the_minute < Duration::seconds(30) => start_of_minute,
+        RoundMode::Up | RoundMode::Nearest => start_of_minute + Duration::minutes(1),
+    }
+}
+
+pub fn next_wall_clock_time<F>(
+    now: DateTime<FixedOffset>,
+    time: NaiveTime,
+    offset: Duration,
+    offset_at: F,
+) -> DateTime<FixedOffset>
+where
+    F: Fn(&NaiveDateTime) -> LocalResult<FixedOffset>,
+{
+    // the chosen time of day [+ offset] today, or tomorrow if it has already passed. `offset_at` gives the UTC
+    // offset in force at a given wall-clock time, and each day is resolved to an instant separately, so a
+    // daylight saving transition in between doesn't shift the time by an hour
+    let today = now.naive_local().date();
+    let wait_until = wall_clock_instant(today.and_time(time), &offset_at) + offset;
+    if wait_until > now {
+        wait_until
+    } else {
+        wall_clock_instant(today.succ().and_time(time), &offset_at) + offset
+    }
+}
+
+fn wall_clock_instant<F>(datetime: NaiveDateTime, offset_at: &F) -> DateTime<FixedOffset>
+where
+    F: Fn(&NaiveDateTime) -> LocalResult<FixedOffset>,
+{
+    // a time which happens twice when the clocks go back is taken to be the first one, and a time which is
+    // skipped when the clocks go forward is taken to be measured with the offset from before they went forward
+    let offset = offset_at(&datetime)
+        .earliest()
+        .or_else(|| offset_at(&(datetime - Duration::days(1))).earliest())
+        .unwrap_or_else(|| FixedOffset::east(0));
+    offset.from_local_datetime(&datetime).unwrap()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use chrono::{FixedOffset, TimeZone};
+
+    const POLL_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60);
+    use chrono::{FixedOffset, NaiveDate, TimeZone};
+
+    #[test]
+    fn test_format_offset() {
+        assert_eq!("+00:00:00", format_offset(Duration::zero()));
+        assert_eq!(
+            "-06:53:12",
+            format_offset(-Duration::seconds(6 * 3600 + 53 * 60 + 12))
+        );
+        assert_eq!("+25:00:05", format_offset(Duration::seconds(25 * 3600 + 5)));
+    }
+
+    #[test]
+    fn test_shell_quote() {
+        assert_eq!(
+            "'2020-03-08T06:28:26+00:00'",
+            shell_quote("2020-03-08T06:28:26+00:00")
+        );
+        assert_eq!("''", shell_quote(""));
+        assert_eq!("'$(rm -rf ~)'", shell_quote("$(rm -rf ~)"));
+        assert_eq!("'it'\\''s'", shell_quote("it's"));
+    }
+
     #[test]
     fn test_wait() {
         let duration_to_wait = Duration::seconds(5);
         let wait_until = FixedOffset::west(0).timestamp(9999999999, 0);
-        wait(duration_to_wait, wait_until).unwrap();
+        wait(
+            duration_to_wait,
+            wait_until,
+            Duration::zero(),
+            POLL_INTERVAL,
+            false,
+        )
+        .unwrap();
+        wait(
+            duration_to_wait,
+            wait_until,
+            Duration::zero(),
+            POLL_INTERVAL,
+            true,
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_poll_until() {
+        // a simulated clock, which only moves on when the thread sleeps or the clock is changed
+        let start = FixedOffset::east(0).ymd(2020, 3, 25).and_hms(6, 0, 0);
+        let wait_until = start + Duration::seconds(150);
+        let clock = std::cell::Cell::new(start);
+        let mut sleeps = vec![];
+        poll_until(
+            wait_until,
+            POLL_INTERVAL,
+            || clock.get(),
+            |dur| {
+                sleeps.push(dur.as_secs());
+                clock.set(clock.get() + Duration::from_std(dur).unwrap());
+            },
+        );
+        assert_eq!(vec![60, 60, 30], sleeps);
+        assert_eq!(wait_until, clock.get());
+
+        // the clock being put forward, e.g. by a suspend, cuts the wait short...
+        clock.set(start);
+        let mut sleeps = vec![];
+        poll_until(
+            wait_until,
+            POLL_INTERVAL,
+            || clock.get(),
+            |dur| {
+                sleeps.push(dur.as_secs());
+                clock.set(clock.get() + Duration::from_std(dur).unwrap() + Duration::seconds(90));
+            },
+        );
+        assert_eq!(vec![60], sleeps);
+
+        // ...and it being put back extends it
+        clock.set(start);
+        let mut sleeps = vec![];
+        poll_until(
+            wait_until,
+            POLL_INTERVAL,
+            || clock.get(),
+            |dur| {
+                sleeps.push(dur.as_secs());
+                let jump = if sleeps.len() == 1 { -60 } else { 0 };
+                clock.set(clock.get() + Duration::from_std(dur).unwrap() + Duration::seconds(jump));
+            },
+        );
+        assert_eq!(vec![60, 60, 60, 30], sleeps);
+
+        // and there is nothing to do for a time which has already passed
+        poll_until(
+            start,
+            POLL_INTERVAL,
+            || wait_until,
+            |_| panic!("shouldn't sleep"),
+        );
+        poll_until(
+            start,
+            POLL_INTERVAL,
+            || start,
+            |_| panic!("shouldn't sleep"),
+        );
+
+        // a longer interval sleeps for longer at a time, so the machine is woken less often
+        clock.set(start);
+        let mut sleeps = vec![];
+        poll_until(
+            wait_until,
+            std::time::Duration::from_secs(100),
+            || clock.get(),
+            |dur| {
+                sleeps.push(dur.as_secs());
+                clock.set(clock.get() + Duration::from_std(dur).unwrap());
+            },
+        );
+        assert_eq!(vec![100, 50], sleeps);
+    }
+
+    #[test]
+    fn test_wait_grace() {
+        let wait_until = FixedOffset::west(0).timestamp(0, 0);
+
+        // a time which has passed is fine within the grace period...
+        wait(
+            Duration::seconds(-30),
+            wait_until,
+            Duration::minutes(1),
+            POLL_INTERVAL,
+            false,
+        )
+        .unwrap();
+        wait(
+            Duration::seconds(-60),
+            wait_until,
+            Duration::minutes(1),
+            POLL_INTERVAL,
+            false,
+        )
+        .unwrap();
+
+        // ...but not outside of it
+        assert!(wait(
+            Duration::seconds(-61),
+            wait_until,
+            Duration::minutes(1),
+            POLL_INTERVAL,
+            false
+        )
+        .is_err());
+        assert!(wait(
+            Duration::seconds(-1),
+            wait_until,
+            Duration::zero(),
+            POLL_INTERVAL,
+            false
+        )
+        .is_err());
+
+        // and being quiet doesn't hide the error
+        assert!(wait(
+            Duration::seconds(-61),
+            wait_until,
+            Duration::minutes(1),
+            POLL_INTERVAL,
+            true
+        )
+        .is_err());
+    }
+
+    fn uk_offset(datetime: &NaiveDateTime) -> LocalResult<FixedOffset> {
+        // the UK's clocks went forward at 01:00 on 29th March 2020, and back at 02:00 on 25th October 2020
+        let gmt = FixedOffset::east(0);
+        let bst = FixedOffset::east(3600);
+        let spring = NaiveDate::from_ymd(2020, 3, 29).and_hms(1, 0, 0);
+        let autumn = NaiveDate::from_ymd(2020, 10, 25).and_hms(1, 0, 0);
+        if *datetime < spring {
+            LocalResult::Single(gmt)
+        } else if *datetime < spring + Duration::hours(1) {
+            LocalResult::None
+        } else if *datetime < autumn {
+            LocalResult::Single(bst)
+        } else if *datetime < autumn + Duration::hours(1) {
+            LocalResult::Ambiguous(bst, gmt)
+        } else {
+            LocalResult::Single(gmt)
+        }
+    }
+
+    #[test]
+    fn test_next_wall_clock_time() {
+        let params = [
+            // later the same day, on the day the clocks go forward
+            (
+                "2020-03-29T00:30:00+00:00",
+                "07:00:00",
+                0,
+                "2020-03-29T07:00:00+01:00",
+            ),
+            // tomorrow, across the clocks going forward: 22 hours away rather than 24
+            (
+                "2020-03-28T09:00:00+00:00",
+                "07:00:00",
+                0,
+                "2020-03-29T07:00:00+01:00",
+            ),
+            // tomorrow, across the clocks going back: 26 hours away
+            (
+                "2020-10-24T09:00:00+01:00",
+                "07:00:00",
+                0,
+                "2020-10-25T07:00:00+00:00",
+            ),
+            // a skipped time uses the offset from before the clocks went forward
+            (
+                "2020-03-29T00:30:00+00:00",
+                "01:30:00",
+                0,
+                "2020-03-29T01:30:00+00:00",
+            ),
+            // a repeated time is the first of the two
+            (
+                "2020-10-25T00:30:00+01:00",
+                "01:30:00",
+                0,
+                "2020-10-25T01:30:00+01:00",
+            ),
+            // the offset is added to the resolved time
+            (
+                "2020-03-29T00:30:00+00:00",
+                "07:00:00",
+                -30,
+                "2020-03-29T06:30:00+01:00",
+            ),
+        ];
+
+        for (now, time, offset, expected) in params.iter() {
+            let now = DateTime::parse_from_rfc3339(now).unwrap();
+            let time = NaiveTime::parse_from_str(time, "%H:%M:%S").unwrap();
+            let wait_until = next_wall_clock_time(now, time, Duration::minutes(*offset), uk_offset);
+            let expected = DateTime::parse_from_rfc3339(expected).unwrap();
+            assert_eq!(expected, wait_until);
+            assert_eq!(expected.offset(), wait_until.offset());
+        }
+
+        // a fixed time zone never changes
+        let fixed = FixedOffset::east(3600);
+        let now = DateTime::parse_from_rfc3339("2020-03-28T09:00:00+01:00").unwrap();
+        let wait_until =
+            next_wall_clock_time(now, NaiveTime::from_hms(7, 0, 0), Duration::zero(), |_| {
+                LocalResult::Single(fixed)
+            });
+        assert_eq!(Duration::hours(22), wait_until - now);
+    }
+
+    #[test]
+    fn test_align_to_minute() {
+        let params = [
+            ("2020-03-25T19:47:23+00:00", "2020-03-25T19:48:00+00:00"),
+            ("2020-03-25T19:47:00.5+01:00", "2020-03-25T19:48:00+01:00"),
+            ("2020-03-25T23:59:01-05:00", "2020-03-26T00:00:00-05:00"),
+            // a whole minute is already aligned
+            ("2020-03-25T19:48:00+00:00", "2020-03-25T19:48:00+00:00"),
+        ];
+
+        for (datetime, expected) in params.iter() {
+            let datetime = DateTime::parse_from_rfc3339(datetime).unwrap();
+            let expected = DateTime::parse_from_rfc3339(expected).unwrap();
+            assert_eq!(expected, align_to_minute(datetime, RoundMode::Up));
+            assert_eq!(
+                expected.offset(),
+                align_to_minute(datetime, RoundMode::Up).offset()
+            );
+        }
+    }
+
+    #[test]
+    fn test_align_to_minute_round_mode() {
+        let params = [
+            (
+                "2020-03-25T19:47:23+00:00",
+                RoundMode::Down,
+                "2020-03-25T19:47:00+00:00",
+            ),
+            (
+                "2020-03-25T19:47:23+00:00",
+                RoundMode::Nearest,
+                "2020-03-25T19:47:00+00:00",
+            ),
+            (
+                "2020-03-25T19:47:30+00:00",
+                RoundMode::Nearest,
+                "2020-03-25T19:48:00+00:00",
+            ),
+            (
+                "2020-03-25T23:59:59+01:00",
+                RoundMode::Nearest,
+                "2020-03-26T00:00:00+01:00",
+            ),
+            (
+                "2020-03-25T19:48:00+00:00",
+                RoundMode::Down,
+                "2020-03-25T19:48:00+00:00",
+            ),
+        ];
+
+        for (datetime, round_mode, expected) in params.iter() {
+            let datetime = DateTime::parse_from_rfc3339(datetime).unwrap();
+            let expected = DateTime::parse_from_rfc3339(expected).unwrap();
+            assert_eq!(expected, align_to_minute(datetime, *round_mode));
+        }
     }
 }
@@ -0,0 +1,50 @@
+use std::{
+    ffi::OsStr,
+    fs,
+    ops::Deref,
+    path::{Path, PathBuf},
+};
+
+// a path in the temp dir which is removed again when it goes out of scope. The name includes the process ID, so
+// that concurrent runs of the tests don't share files, and each test gives its files their own names
+pub struct TempFile(PathBuf);
+
+impl TempFile {
+    pub fn new(name: &str) -> TempFile {
+        let path = std::env::temp_dir().join(format!("heliocron-{}-{}", std::process::id(), name));
+        TempFile(path)
+    }
+
+    pub fn with_contents(name: &str, contents: &str) -> TempFile {
+        let file = TempFile::new(name);
+        fs::write(&file, contents).unwrap();
+        file
+    }
+}
+
+impl Deref for TempFile {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl AsRef<Path> for TempFile {
+    fn as_ref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl AsRef<OsStr> for TempFile {
+    fn as_ref(&self) -> &OsStr {
+        self.0.as_os_str()
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        // the file may never have been written, e.g. by a test of an error
+        let _ = fs::remove_file(&self.0);
+    }
+}
@@ -0,0 +1,62 @@
+use std::process::Command;
+
+use assert_cmd::prelude::*;
+
+#[test]
+fn test_compare() {
+    // assert that each location is shown in its own time zone, e.g. dawn at Heathrow and dusk at JFK
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&[
+        "-d",
+        "2020-06-21",
+        "compare",
+        "--location",
+        "51.4700,-0.4543,+01:00",
+        "--location",
+        "40.6413,-73.7781,-04:00",
+    ])
+    .assert()
+    .success()
+    .stdout(predicates::str::contains(
+        "Location:           51.47N 0.4543W              40.6413N 73.7781W\n",
+    ))
+    .stdout(predicates::str::contains(
+        "Civil dawn:         2020-06-21 03:57:05 +01:00  2020-06-21 04:51:08 -04:00\n",
+    ))
+    .stdout(predicates::str::contains(
+        "Civil dusk:         2020-06-21 22:10:20 +01:00  2020-06-21 21:02:58 -04:00\n",
+    ))
+    .stdout(predicates::str::contains(
+        "Astronomical dawn:  Never                       2020-06-21 03:18:20 -04:00\n",
+    ));
+
+    // a location without a time zone uses the chosen one
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&[
+        "-d",
+        "2020-06-21",
+        "-t",
+        "+09:00",
+        "compare",
+        "--location",
+        "35.5494,139.7798",
+    ])
+    .assert()
+    .success()
+    .stdout(predicates::str::contains("Time zone:          +09:00\n"));
+}
+
+#[test]
+fn test_compare_fails() {
+    for location in ["51.47,-0.4543,BST", "51.47N,0.4543W", "51.47"].iter() {
+        let mut cmd = Command::cargo_bin("heliocron").unwrap();
+        cmd.args(&["compare", "--location", location])
+            .assert()
+            .failure()
+            .stderr(predicates::str::contains("Locations must be"));
+    }
+
+    // at least one location is needed
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&["compare"]).assert().failure();
+}
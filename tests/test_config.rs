@@ -0,0 +1,1112 @@
+use std::process::Command;
+
+use assert_cmd::prelude::*;
+use predicates::prelude::*;
+
+mod common;
+use common::TempFile;
+
+fn write_config(name: &str, contents: &str) -> TempFile {
+    TempFile::with_contents(&format!("{}.toml", name), contents)
+}
+
+#[test]
+fn test_explicit_config() {
+    // assert that the location is taken from a config file given with --config
+    let path = write_config(
+        "explicit-config",
+        "latitude = \"55.9533N\"\nlongitude = \"3.1883W\"\n",
+    );
+
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    let report = cmd
+        .args(&["--config", path.to_str().unwrap(), "report"])
+        .assert();
+
+    report
+        .success()
+        .stdout(predicates::str::contains("Latitude: 55.9533N"))
+        .stdout(predicates::str::contains("Longitude: 3.1883W"));
+
+    // ...but arguments on the command line take precedence
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    let report = cmd
+        .args(&[
+            "--config",
+            path.to_str().unwrap(),
+            "-l",
+            "51.0N",
+            "-o",
+            "4.36E",
+            "report",
+        ])
+        .assert();
+
+    report
+        .success()
+        .stdout(predicates::str::contains("Latitude: 51.0000N"))
+        .stdout(predicates::str::contains("Longitude: 4.3600E"));
+}
+
+#[test]
+fn test_missing_explicit_config() {
+    // assert that a config file which was asked for, but doesn't exist, is an error
+    let path = std::env::temp_dir().join("heliocron-does-not-exist.toml");
+
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    let report = cmd
+        .args(&["--config", path.to_str().unwrap(), "report"])
+        .assert();
+
+    report
+        .failure()
+        .stderr(predicates::str::contains("Error reading config file"));
+}
+
+#[test]
+fn test_layered_configs() {
+    // assert that several config files are layered in order, later files overriding earlier ones
+    let base = write_config(
+        "layered-base",
+        "latitude = \"55.9533N\"\nlongitude = \"3.1883W\"\noutput_format = \"json\"\n",
+    );
+    let host = write_config(
+        "layered-host",
+        "latitude = \"51.0N\"\nlongitude = \"4.36E\"\n",
+    );
+
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&[
+        "--config",
+        base.to_str().unwrap(),
+        "--config",
+        host.to_str().unwrap(),
+        "report",
+    ])
+    .assert()
+    .success()
+    .stdout(predicates::str::contains("\"schema_version\""))
+    .stdout(predicates::str::contains("\"latitude\": 51.0,"))
+    .stdout(predicates::str::contains("\"longitude\": 4.36"));
+
+    // and that a bad file is reported by name
+    let invalid = write_config(
+        "layered-invalid",
+        "latitude = \"95.0N\"\nlongitude = \"3.1883W\"\n",
+    );
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&[
+        "--config",
+        base.to_str().unwrap(),
+        "--config",
+        invalid.to_str().unwrap(),
+        "report",
+    ])
+    .assert()
+    .failure()
+    .stderr(predicates::str::contains(format!(
+        "Error parsing TOML file {}",
+        invalid.display()
+    )));
+}
+
+#[test]
+fn test_custom_events() {
+    // assert that an event defined in the config file can be used in place of a standard event
+    let path = write_config(
+        "custom-events",
+        "[events.my_wakeup]\nevent = \"sunrise\"\noffset = \"00:20:00\"\n",
+    );
+
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&[
+        "--config",
+        path.to_str().unwrap(),
+        "-d",
+        "2020-03-08",
+        "-l",
+        "51.4769N",
+        "-o",
+        "0.0005W",
+        "report",
+        "--event",
+        "My_Wakeup",
+    ])
+    .assert()
+    .success()
+    .stdout(predicates::str::contains(
+        "sunrise +00:20:00 is at: 2020-03-08 06:48:26 +00:00",
+    ));
+
+    // and that its offset adds to any others
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&[
+        "--config",
+        path.to_str().unwrap(),
+        "-d",
+        "2020-03-08",
+        "-l",
+        "51.4769N",
+        "-o",
+        "0.0005W",
+        "report",
+        "--event",
+        "my_wakeup",
+        "--offsets",
+        "-00:20:00",
+    ])
+    .assert()
+    .success()
+    .stdout(predicates::str::contains(
+        "sunrise +00:00:00 is at: 2020-03-08 06:28:26 +00:00",
+    ));
+
+    // an undefined event is still an error
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&[
+        "--config",
+        path.to_str().unwrap(),
+        "report",
+        "--event",
+        "my_bedtime",
+    ])
+    .assert()
+    .failure();
+
+    // as is an event based on another custom event, or taking the name of a standard one
+    for (name, contents) in [
+        (
+            "custom-events-nested",
+            "[events.a]\nevent = \"sunrise\"\n[events.b]\nevent = \"a\"\n",
+        ),
+        (
+            "custom-events-builtin",
+            "[events.sunset]\nevent = \"sunrise\"\n",
+        ),
+        (
+            "custom-events-offset",
+            "[events.a]\nevent = \"sunrise\"\noffset = \"soon\"\n",
+        ),
+    ]
+    .iter()
+    {
+        let path = write_config(name, contents);
+        let mut cmd = Command::cargo_bin("heliocron").unwrap();
+        cmd.args(&["--config", path.to_str().unwrap(), "report"])
+            .assert()
+            .failure()
+            .stderr(predicates::str::contains("Error parsing TOML file"));
+    }
+}
+
+#[test]
+fn test_elevation_events() {
+    // assert that an event defined by an altitude and a direction can be used like any other event
+    let path = write_config(
+        "elevation-events",
+        "[events.dog_walk]\nelevation = -3.0\ndirection = \"setting\"\n",
+    );
+
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&[
+        "--config",
+        path.to_str().unwrap(),
+        "-d",
+        "2020-03-25",
+        "-t",
+        "+00:00",
+    ])
+    .args(&["report", "--event", "dog_walk", "--offsets", "-1h,0m"])
+    .assert()
+    .success()
+    .stdout(
+        "dog_walk -01:00:00 is at: 2020-03-25 17:35:44 +00:00\n\
+             dog_walk +00:00:00 is at: 2020-03-25 18:35:44 +00:00\n",
+    );
+
+    // in its place among the others, and at the same time as the crossing of that altitude
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&[
+        "--config",
+        path.to_str().unwrap(),
+        "-d",
+        "2020-03-25",
+        "-t",
+        "+00:00",
+    ])
+    .args(&["report", "--events", "civil_dusk,dog_walk,sunset"])
+    .assert()
+    .success()
+    .stdout(
+        "sunset +00:00:00 is at: 2020-03-25 18:21:44 +00:00\n\
+             dog_walk +00:00:00 is at: 2020-03-25 18:35:44 +00:00\n\
+             civil_dusk +00:00:00 is at: 2020-03-25 18:55:15 +00:00\n",
+    );
+
+    // the options which only take the standard events say so
+    for args in [
+        vec!["report", "--filter", "dog_walk>18:00"],
+        vec![
+            "report",
+            "--event",
+            "sunset",
+            "--fallback-event",
+            "dog_walk",
+        ],
+        vec!["wait", "-e", "sunset", "--clamp-after", "dog_walk"],
+        vec!["next-occurrence", "-e", "dog_walk"],
+    ]
+    .iter()
+    {
+        let mut cmd = Command::cargo_bin("heliocron").unwrap();
+        cmd.args(&["--config", path.to_str().unwrap()])
+            .args(args)
+            .assert()
+            .failure()
+            .stderr(predicates::str::contains(
+                "Events defined in the config file can't be used",
+            ));
+    }
+
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&["-d", "2020-03-25", "-t", "+00:00"])
+        .args(&["report", "--depression", "3", "--direction", "setting"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("2020-03-25 18:35:44 +00:00"));
+
+    // an event needs either a standard event or both an altitude and a direction
+    for (name, contents) in [
+        (
+            "elevation-events-direction",
+            "[events.a]\nelevation = -3.0\n",
+        ),
+        (
+            "elevation-events-both",
+            "[events.a]\nevent = \"sunset\"\nelevation = -3.0\ndirection = \"setting\"\n",
+        ),
+        (
+            "elevation-events-range",
+            "[events.a]\nelevation = -95.0\ndirection = \"setting\"\n",
+        ),
+        (
+            "elevation-events-unknown-direction",
+            "[events.a]\nelevation = -3.0\ndirection = \"sideways\"\n",
+        ),
+    ]
+    .iter()
+    {
+        let path = write_config(name, contents);
+        let mut cmd = Command::cargo_bin("heliocron").unwrap();
+        cmd.args(&["--config", path.to_str().unwrap(), "report"])
+            .assert()
+            .failure()
+            .stderr(predicates::str::contains("Error parsing TOML file"));
+    }
+}
+
+#[test]
+fn test_config_time_zone() {
+    // assert that the time zone is taken from a config file, applying to the chosen date...
+    let path = write_config(
+        "time-zone",
+        "latitude = \"35.6762N\"\nlongitude = \"139.6503E\"\ntime_zone = \"+09:00\"\n",
+    );
+    let path = path.to_str().unwrap();
+
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&["--config", path, "-d", "2020-06-21", "--verbose"])
+        .args(&["report", "--events", "sunrise"])
+        .assert()
+        .success()
+        .stdout("sunrise +00:00:00 is at: 2020-06-21 04:25:58 +09:00\n")
+        .stderr(predicates::str::contains(format!(
+            "Time zone: +09:00 (config file {})",
+            path
+        )));
+
+    // ...which the JSON input overrides...
+    let mut cmd = assert_cmd::Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&["--config", path, "--input-json", "-", "--no-tz-warning"])
+        .args(&["report", "--events", "sunrise"])
+        .write_stdin(r#"{"latitude": 35.6762, "longitude": 139.6503, "date": "2020-06-21", "time_zone": "+00:00"}"#)
+        .assert()
+        .success()
+        .stdout("sunrise +00:00:00 is at: 2020-06-20 19:26:03 +00:00\n");
+
+    // ...as does the command line, for a single run
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&["--config", path, "-d", "2020-06-21", "-t", "+00:00"])
+        .args(&["--no-tz-warning", "report", "--events", "sunrise"])
+        .assert()
+        .success()
+        .stdout("sunrise +00:00:00 is at: 2020-06-20 19:26:03 +00:00\n");
+
+    // it is written out by dump-config, and must be a UTC offset
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&["--config", path, "dump-config"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("time_zone = \"+09:00\"\n"));
+
+    let path = write_config("invalid-time-zone", "time_zone = \"JST\"\n");
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&["--config", path.to_str().unwrap(), "report"])
+        .assert()
+        .failure();
+
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&["--config", path.to_str().unwrap(), "--validate"])
+        .assert()
+        .failure()
+        .stdout(predicates::str::contains(
+            "time_zone: Error parsing time zone",
+        ));
+}
+
+#[test]
+fn test_invalid_explicit_config() {
+    let path = write_config(
+        "invalid-config",
+        "latitude = \"95.0N\"\nlongitude = \"3.1883W\"\n",
+    );
+
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    let report = cmd
+        .args(&["--config", path.to_str().unwrap(), "report"])
+        .assert();
+
+    report
+        .failure()
+        .stderr(predicates::str::contains("Error parsing TOML file"));
+}
+
+#[test]
+fn test_unknown_setting() {
+    // assert that a misspelt setting is ignored, and the rest of the file, including the location, still applies
+    let path = write_config(
+        "unknown-setting",
+        "latitude = \"40.0N\"\nlongitude = \"74.0W\"\nshow_nautcal = false\n\n[twilight]\ncivl = -5.0\n",
+    );
+
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    let report = cmd
+        .args(&["--config", path.to_str().unwrap(), "report"])
+        .assert();
+
+    report
+        .success()
+        .stdout(predicates::str::contains("Latitude: 40.0000N"))
+        .stdout(predicates::str::contains("Longitude: 74.0000W"))
+        .stdout(predicates::str::contains("Nautical dawn is at:"));
+}
+
+#[test]
+fn test_strict_location() {
+    // assert that strict mode refuses to use the default location
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&["--strict", "report"])
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("Strict mode"));
+
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&["--strict", "-l", "51.0N", "-o", "4.36E", "report"])
+        .assert()
+        .success();
+
+    let path = write_config(
+        "strict-location",
+        "latitude = \"55.9533N\"\nlongitude = \"3.1883W\"\n",
+    );
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&["--strict", "--config", path.to_str().unwrap(), "report"])
+        .assert()
+        .success();
+}
+
+#[test]
+fn test_strict_config_file() {
+    // assert that problems with the config file which are normally skipped over are errors in strict mode
+    let params = [
+        (
+            "strict-not-toml",
+            "this is not TOML",
+            "Error parsing TOML file",
+        ),
+        (
+            "strict-half-location",
+            "latitude = \"55.9533N\"\n",
+            "both latitude and longitude",
+        ),
+        (
+            "strict-unknown-setting",
+            "show_nautcal = false\n",
+            "Error parsing TOML file",
+        ),
+    ];
+
+    for (name, contents, error) in params.iter() {
+        let path = write_config(name, contents);
+
+        let mut cmd = Command::cargo_bin("heliocron").unwrap();
+        cmd.args(&[
+            "--config",
+            path.to_str().unwrap(),
+            "-l",
+            "51.0N",
+            "-o",
+            "4.36E",
+            "report",
+        ])
+        .assert()
+        .success();
+
+        let mut cmd = Command::cargo_bin("heliocron").unwrap();
+        cmd.args(&[
+            "--strict",
+            "--config",
+            path.to_str().unwrap(),
+            "-l",
+            "51.0N",
+            "-o",
+            "4.36E",
+            "report",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains(*error));
+    }
+}
+
+#[test]
+fn test_verbose() {
+    // assert that --verbose shows where each value came from
+    let path = write_config(
+        "verbose",
+        "latitude = \"55.9533N\"\nlongitude = \"3.1883W\"\n",
+    );
+
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&[
+        "--verbose",
+        "--config",
+        path.to_str().unwrap(),
+        "-d",
+        "2020-03-25",
+        "report",
+    ])
+    .assert()
+    .success()
+    .stderr(predicates::str::contains(format!(
+        "Latitude: 55.9533N, Longitude: 3.1883W (config file {})",
+        path.display()
+    )))
+    .stderr(predicates::str::contains(
+        "Date and time: 2020-03-25 12:00:00 (command line)",
+    ))
+    .stderr(predicates::str::contains("(default, the local time zone)"))
+    .stderr(predicates::str::contains("Subcommand: report"));
+
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&[
+        "--verbose",
+        "-l",
+        "51.0N",
+        "-o",
+        "4.36E",
+        "-t",
+        "+01:00",
+        "season",
+    ])
+    .assert()
+    .success()
+    .stderr(predicates::str::contains(
+        "Latitude: 51.0000N, Longitude: 4.3600E (command line)",
+    ))
+    .stderr(predicates::str::contains("(default)"))
+    .stderr(predicates::str::contains(
+        "Time zone: +01:00 (command line)",
+    ));
+}
+
+#[test]
+fn test_twilight_angles() {
+    // assert that the twilight angles can be changed in the config file
+    let path = write_config("twilight", "[twilight]\ncivil = -12.0\n");
+
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&[
+        "--config",
+        path.to_str().unwrap(),
+        "-d",
+        "2020-03-25",
+        "-t",
+        "+00:00",
+        "report",
+    ])
+    .assert()
+    .success()
+    .stdout(predicates::str::contains(
+        "Civil dawn is at:         2020-03-25 04:36:25 +00:00",
+    ))
+    .stdout(predicates::str::contains(
+        "Nautical dawn is at:      2020-03-25 04:36:25 +00:00",
+    ))
+    .stdout(predicates::str::contains(
+        "non-standard twilight angles are in effect (civil -12.0°, nautical -12.0°, astronomical -18.0°)",
+    ));
+
+    // but they must be altitudes
+    let path = write_config("twilight-invalid", "[twilight]\nastronomical = -100.0\n");
+
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&["--config", path.to_str().unwrap(), "report"])
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("Error parsing TOML file"));
+}
+
+#[test]
+fn test_city() {
+    // assert that a city sets the location
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&["--city", "edinburgh", "report"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Latitude: 55.9533N"))
+        .stdout(predicates::str::contains("Longitude: 3.1883W"));
+
+    // ambiguous cities list the candidates
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&["--city", "Perth", "report"])
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("Perth,AU"))
+        .stderr(predicates::str::contains("Perth,GB"));
+
+    // which can be told apart with a country code
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&["--city", "Perth,AU", "report"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Latitude: 31.9505S"));
+
+    // and a city can't be combined with coordinates
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&["--city", "Perth,AU", "-l", "51.0N", "-o", "4.36E", "report"])
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_coords() {
+    // assert that both coordinates can be set from one string
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&["--coords", "-33.8688, 151.2093", "report"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Latitude: 33.8688S"))
+        .stdout(predicates::str::contains("Longitude: 151.2093E"));
+
+    // malformed coordinates are an error
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&["--coords", "51.4769N, 0.0005W", "report"])
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("signed decimal degrees"));
+
+    // as is combining them with the separate options
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&[
+        "--coords",
+        "51.4769, -0.0005",
+        "-l",
+        "51.0N",
+        "-o",
+        "4.36E",
+        "report",
+    ])
+    .assert()
+    .failure();
+}
+
+#[test]
+fn test_bbox() {
+    // assert that the centre of the bounding box is used, with a warning that it is only approximate
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&["--bbox", "-34, 150, -33, 152", "--no-tz-warning", "report"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Latitude: 33.5000S"))
+        .stdout(predicates::str::contains("Longitude: 151.0000E"))
+        .stderr(predicates::str::contains(
+            "Warning: the times are for the centre of the bounding box (33.5S 151E)",
+        ));
+
+    // the warning can be given as JSON, like any other
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&[
+        "--bbox",
+        "-34,150,-33,152",
+        "--no-tz-warning",
+        "--warnings-json",
+    ])
+    .arg("report")
+    .assert()
+    .success()
+    .stderr(predicates::str::contains(
+        "{\"code\":\"bounding_box_centre\",",
+    ));
+
+    // a box must have its corners the right way up, and can't be combined with another location
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&["--bbox", "-33, 150, -34, 152", "report"])
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("Bounding boxes must be"));
+
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&["--bbox", "-34, 150, -33, 152", "--city", "Sydney", "report"])
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_no_location() {
+    // assert that the location is hidden from the output, but still used
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&[
+        "--no-location",
+        "--city",
+        "Sydney",
+        "-d",
+        "2020-06-21",
+        "-t",
+        "+10:00",
+        "report",
+    ])
+    .assert()
+    .success()
+    .stdout(predicates::str::contains("Hidden"))
+    .stdout(
+        predicates::str::contains("Sunrise is at:            2020-06-21 07:00:")
+            .and(predicates::str::contains("33.8688").not()),
+    );
+
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&["--no-location", "report", "--json"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("\"coordinates\": null"));
+}
+
+#[test]
+fn test_output_format() {
+    // assert that the config file sets the default format of the report
+    let path = write_config("output-format", "output_format = \"json\"\n");
+
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&["--config", path.to_str().unwrap(), "report"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("\"schema_version\""));
+
+    // which the command line overrides
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&[
+        "--config",
+        path.to_str().unwrap(),
+        "report",
+        "--output-format",
+        "csv",
+    ])
+    .assert()
+    .success()
+    .stdout(predicates::str::starts_with("date,solar_noon,day_length,"));
+
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&["--config", path.to_str().unwrap(), "report", "--astro"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("ASTRONOMICAL DATA"));
+
+    // unknown formats are an error
+    let path = write_config("output-format-invalid", "output_format = \"xml\"\n");
+
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&["--config", path.to_str().unwrap(), "report"])
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_tz_warning() {
+    // assert that the config file can turn the time zone warning on, and the command line back off
+    let path = write_config(
+        "tz-warning",
+        "latitude = \"35.6762N\"\nlongitude = \"139.6503E\"\ntz_warning = true\n",
+    );
+
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&["--config", path.to_str().unwrap(), "-t", "+00:00", "report"])
+        .assert()
+        .success()
+        .stderr(predicates::str::contains("time zone (+00:00) differs"));
+
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&[
+        "--config",
+        path.to_str().unwrap(),
+        "-t",
+        "+00:00",
+        "--no-tz-warning",
+        "report",
+    ])
+    .assert()
+    .success()
+    .stderr(predicates::str::is_empty());
+}
+
+#[test]
+fn test_dump_config() {
+    // assert that the config in effect is written out as a config file which can be read back in
+    let path = write_config(
+        "dump-config-input",
+        "output_format = \"csv\"\n[twilight]\ncivil = -7.5\n",
+    );
+    let output = TempFile::new("dump-config-output.toml");
+
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&[
+        "--config",
+        path.to_str().unwrap(),
+        "--coords",
+        "-33.8688, 151.2093",
+        "dump-config",
+    ])
+    .assert()
+    .success()
+    .stdout(predicates::str::contains("latitude = \"33.8688S\"\n"))
+    .stdout(predicates::str::contains("longitude = \"151.2093E\"\n"))
+    .stdout(predicates::str::contains("output_format = \"csv\"\n"))
+    .stdout(predicates::str::contains("civil = -7.5\n"));
+
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&[
+        "--config",
+        path.to_str().unwrap(),
+        "--coords",
+        "-33.8688, 151.2093",
+        "dump-config",
+        "--output",
+        output.to_str().unwrap(),
+    ])
+    .assert()
+    .success()
+    .stdout("");
+
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&[
+        "--strict",
+        "--verbose",
+        "--config",
+        output.to_str().unwrap(),
+        "report",
+    ])
+    .assert()
+    .success()
+    .stdout(predicates::str::starts_with("date,solar_noon,day_length,"))
+    .stderr(predicates::str::contains(
+        "Latitude: 33.8688S, Longitude: 151.2093E (config file",
+    ))
+    .stderr(predicates::str::contains("civil -7.5°"));
+}
+
+#[test]
+fn test_dump_defaults() {
+    // the built-in defaults are shown, whatever the command line sets, and no subcommand is needed
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&[
+        "--now",
+        "2020-06-21T08:00:00+00:00",
+        "--coords",
+        "-33.8688, 151.2093",
+        "--dump-defaults",
+    ])
+    .assert()
+    .success()
+    .stdout(predicates::str::contains(
+        "Coordinates:     51.4769N, 0.0005W\n",
+    ))
+    .stdout(predicates::str::contains(
+        "Twilight angles: civil -6.0°, nautical -12.0°, astronomical -18.0°\n",
+    ))
+    .stdout(predicates::str::contains("Wait offset:     00:00:00"));
+}
+
+#[test]
+fn test_check_date() {
+    // the date is parsed with the format and time zone and shown, and no subcommand is needed
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&[
+        "-d",
+        "21/06/2020",
+        "-f",
+        "%d/%m/%Y",
+        "-t",
+        "+02:00",
+        "--check-date",
+    ])
+    .assert()
+    .success()
+    .stdout("Date:            2020-06-21 12:00:00 +02:00\n");
+
+    // as is a date and time, or a range of dates, which no subcommand is needed for either
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&[
+        "-d",
+        "2020-06-01",
+        "--end-date",
+        "2020-06-30",
+        "-t",
+        "-05:00",
+        "--check-date",
+    ])
+    .assert()
+    .success()
+    .stdout(
+        "Date:            2020-06-01 12:00:00 -05:00\n\
+        End date:        2020-06-30 12:00:00 -05:00\n",
+    );
+
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&[
+        "--datetime",
+        "2020-06-21T23:55",
+        "-t",
+        "+01:00",
+        "--check-date",
+    ])
+    .assert()
+    .success()
+    .stdout("Date:            2020-06-21 23:55:00 +01:00\n");
+
+    // and a date which doesn't match the format is the usual error
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&["-d", "2020/06/21", "--check-date"])
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("Error parsing date"));
+}
+
+#[test]
+fn test_validate() {
+    // assert that every problem in a config file is listed, without running anything, and that the exit code
+    // says whether there were any
+    let good = write_config(
+        "validate-good",
+        "latitude = \"51.5N\"\nlongitude = \"0.1W\"\n",
+    );
+    let bad = write_config(
+        "validate-bad",
+        "latitude = \"95N\"\n\
+         longitude = \"0.1W\"\n\
+         [twilight]\n\
+         civil = -100.0\n\
+         [events.walk]\n\
+         event = \"sunsett\"\n\
+         offset = \"30 minutes\"\n",
+    );
+
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.arg("--config")
+        .arg(&good)
+        .arg("--validate")
+        .assert()
+        .success()
+        .stdout(format!("{}: OK\n", good.display()));
+
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.arg("--config")
+        .arg(&good)
+        .arg("--config")
+        .arg(&bad)
+        .arg("--validate")
+        .assert()
+        .code(1)
+        .stdout(predicates::str::contains(format!(
+            "{}: OK\n",
+            good.display()
+        )))
+        .stdout(predicates::str::contains(format!(
+            "{}: 4 problems\n",
+            bad.display()
+        )))
+        .stdout(predicates::str::contains(
+            "  latitude, longitude: Invalid coordinates",
+        ))
+        .stdout(predicates::str::contains(
+            "  twilight.civil: Error parsing altitude.",
+        ))
+        .stdout(predicates::str::contains(
+            "  events.walk: Unknown event 'sunsett', did you mean 'sunset'?",
+        ))
+        .stdout(predicates::str::contains(
+            "  events.walk.offset: Error parsing offset.",
+        ));
+
+    // a misspelt setting is listed along with the other problems, wherever it is in the file
+    let bad = write_config(
+        "validate-unknown",
+        "latitude = \"95.0N\"\nlongitude = \"3.1883W\"\ncolour = true\n\n[twilight]\ncivl = -5.0\n",
+    );
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.arg("--config")
+        .arg(&bad)
+        .arg("--validate")
+        .assert()
+        .code(1)
+        .stdout(predicates::str::contains(format!(
+            "{}: 3 problems\n",
+            bad.display()
+        )))
+        .stdout(predicates::str::contains(
+            "  colour: Unknown setting. Check its spelling against the settings in the README.",
+        ))
+        .stdout(predicates::str::contains(
+            "  twilight.civl: Unknown setting.",
+        ))
+        .stdout(predicates::str::contains(
+            "  latitude, longitude: Invalid coordinates",
+        ));
+
+    // whereas a file which isn't TOML at all stops the rest of the file from being read
+    let bad = write_config("validate-invalid", "latitude = [\n");
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.arg("--config")
+        .arg(&bad)
+        .arg("--validate")
+        .assert()
+        .code(1)
+        .stdout(predicates::str::contains("  not a valid config file"));
+}
+
+#[test]
+fn test_input_json() {
+    // assert that the location, date and time zone are read from the JSON input, whether a file or stdin
+    let path = TempFile::with_contents(
+        "input.json",
+        r#"{"latitude": 40.6413, "longitude": -73.7781, "date": "2020-06-21", "time_zone": "-04:00"}"#,
+    );
+
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&["--input-json", path.to_str().unwrap(), "--verbose"])
+        .args(&["report", "--events", "sunrise"])
+        .assert()
+        .success()
+        .stdout("sunrise +00:00:00 is at: 2020-06-21 05:24:30 -04:00\n")
+        .stderr(predicates::str::contains(
+            "Latitude: 40.6413N, Longitude: 73.7781W (JSON input",
+        ));
+
+    // the command line still wins, and the time zone from the input applies to the date given there
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&["--input-json", path.to_str().unwrap(), "-d", "2020-12-21"])
+        .args(&["report", "--events", "sunrise"])
+        .assert()
+        .success()
+        .stdout("sunrise +00:00:00 is at: 2020-12-21 08:15:45 -04:00\n");
+
+    assert_cmd::Command::cargo_bin("heliocron")
+        .unwrap()
+        .args(&["--input-json", "-", "report", "--events", "sunset"])
+        .write_stdin(r#"{"latitude": 51.4769, "longitude": -0.0005, "date": "2020-06-21", "time_zone": "+01:00"}"#)
+        .assert()
+        .success()
+        .stdout("sunset +00:00:00 is at: 2020-06-21 21:20:53 +01:00\n");
+}
+
+#[test]
+fn test_input_json_fails() {
+    // assert that each mistake is reported along with the field it is in
+    let params = [
+        (
+            r#"{"latitude": "40N", "longitude": 1}"#,
+            "'latitude' must be a number",
+        ),
+        (r#"{"latitude": 40}"#, "missing field 'longitude'"),
+        (
+            r#"{"latitude": 40, "longitude": 1, "lat": 1}"#,
+            "unknown field 'lat'",
+        ),
+        (
+            r#"{"latitude": 95, "longitude": 1}"#,
+            "'latitude' must be between -90 and 90",
+        ),
+        (
+            r#"{"latitude": 40, "longitude": 1, "date": "21/06/2020"}"#,
+            "'date' must be in the format YYYY-MM-DD",
+        ),
+        (
+            r#"{"latitude": 40, "longitude": 1, "time_zone": 1}"#,
+            "'time_zone' must be a string",
+        ),
+        (
+            r#"{"latitude": 40, "longitude": 1, "time_zone": "BST"}"#,
+            "'time_zone' must be a UTC offset",
+        ),
+        ("[51.4769, -0.0005]", "expected an object"),
+        ("{", "not valid JSON"),
+    ];
+
+    for (json, expected) in params.iter() {
+        assert_cmd::Command::cargo_bin("heliocron")
+            .unwrap()
+            .args(&["--input-json", "-", "report"])
+            .write_stdin(*json)
+            .assert()
+            .failure()
+            .stderr(predicates::str::contains(format!(
+                "Invalid JSON input - {}",
+                expected
+            )));
+    }
+}
+
+#[test]
+fn test_config_report_sections() {
+    // assert that pairs of events can be left out of the plain report, but are still given by --events
+    let path = write_config(
+        "sections",
+        "show_nautical = false\nshow_astronomical = false\n",
+    );
+
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&["--config", path.to_str().unwrap(), "-d", "2020-06-21"])
+        .args(&["-t", "+01:00", "report"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains(
+            "Civil dusk is at:         2020-06-21 22:08:35 +01:00\n",
+        ))
+        .stdout(predicates::str::contains("Nautical").not())
+        .stdout(predicates::str::contains("Astronomical").not());
+
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&["--config", path.to_str().unwrap(), "-d", "2020-06-21"])
+        .args(&["-t", "+01:00", "report", "--events", "nautical_dusk"])
+        .assert()
+        .success()
+        .stdout("nautical_dusk +00:00:00 is at: 2020-06-21 23:23:00 +01:00\n");
+
+    // and that only the hidden pairs are written out by dump-config
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&["--config", path.to_str().unwrap(), "dump-config"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains(
+            "show_nautical = false\nshow_astronomical = false\n",
+        ))
+        .stdout(predicates::str::contains("show_civil").not());
+}
@@ -0,0 +1,62 @@
+use std::process::Command;
+
+use assert_cmd::prelude::*;
+use predicates::prelude::*;
+
+#[test]
+fn test_darkness() {
+    // assert that the time the Moon is up is taken out of the astronomical night, e.g. a first quarter moon
+    // setting around midnight
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&["-d", "2020-12-21", "-t", "+00:00", "darkness"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains(
+            "The night length is:      12h 2m 12s\n",
+        ))
+        .stdout(predicates::str::contains(
+            "The Moon is up for:       5h 46m 10s\nMoonless darkness:        6h 16m 2s\n",
+        ))
+        .stdout(predicates::str::contains(
+            "Moonless windows:         2020-12-21 23:43:37 +00:00 to 2020-12-22 05:59:39 +00:00\n",
+        ));
+
+    // whilst a full moon is up all night
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&["-d", "2020-12-29", "-t", "+00:00", "darkness"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains(
+            "Moonless darkness:        0h 0m 0s\nMoonless windows:         None\n",
+        ));
+
+    // and there is no true darkness at all around midsummer in London
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&["-d", "2020-06-21", "-t", "+01:00", "darkness"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Never (no astronomical night)"))
+        .stdout(predicates::str::contains("Moonless").not());
+}
+
+#[test]
+fn test_darkness_json() {
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&[
+        "-d",
+        "2020-12-21",
+        "--end-date",
+        "2020-12-22",
+        "-t",
+        "+00:00",
+        "--compact-json",
+        "darkness",
+        "--json",
+    ])
+    .assert()
+    .success()
+    .stdout(predicates::str::starts_with("[{\"night\":{"))
+    .stdout(predicates::str::contains(
+        "\"moon_up\":20770,\"darkness\":22562,\"windows\":[{\"start\":\"2020-12-21T23:43:37+00:00\",\"end\":\"2020-12-22T05:59:39+00:00\"}]",
+    ));
+}
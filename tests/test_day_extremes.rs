@@ -0,0 +1,58 @@
+use std::process::Command;
+
+use assert_cmd::prelude::*;
+
+#[test]
+fn test_day_extremes() {
+    // assert that the longest and shortest days of the year are found, including any ties
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    let extremes = cmd
+        .args(&["-t", "+00:00", "day-extremes", "--year", "2020"])
+        .assert();
+
+    extremes.success().stdout(
+        "The longest day is:       2020-06-20, 2020-06-21 (16h 37m 58s)\n\
+        The shortest day is:      2020-12-21 (7h 49m 49s)\n",
+    );
+
+    // the year defaults to that of the chosen date
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    let extremes = cmd
+        .args(&["-d", "2020-03-01", "-t", "+00:00", "day-extremes", "--json"])
+        .assert();
+
+    extremes
+        .success()
+        .stdout(predicates::str::contains("\"2020-12-21\""))
+        .stdout(predicates::str::contains("\"polar_day\": []"));
+
+    // and must be one which is supported
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&["day-extremes", "--year", "300"])
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_day_extremes_polar() {
+    // in the Arctic, the polar day and night are given instead
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    let extremes = cmd
+        .args(&[
+            "-l",
+            "69.65N",
+            "-o",
+            "18.96E",
+            "-t",
+            "+01:00",
+            "day-extremes",
+            "--year",
+            "2021",
+        ])
+        .assert();
+
+    extremes.success().stdout(
+        "The Sun doesn't set:      from 2021-05-18 to 2021-07-25 (69 days)\n\
+        The Sun doesn't rise:     from 2021-01-01 to 2021-01-14 (14 days) and from 2021-11-27 to 2021-12-31 (35 days)\n",
+    );
+}
@@ -0,0 +1,80 @@
+use std::process::Command;
+
+use assert_cmd::prelude::*;
+
+#[test]
+fn test_flight_sunset() {
+    // an evening flight west from Heathrow to JFK chases the sunset, which is only caught up with near the end
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&[
+        "flight",
+        "--from",
+        "51.4700,-0.4543",
+        "--to",
+        "40.6413,-73.7781",
+        "--departure",
+        "2020-06-21T18:00:00+01:00",
+        "--arrival",
+        "2020-06-21T21:00:00-04:00",
+    ])
+    .assert()
+    .success()
+    .stdout(predicates::str::contains(
+        "Departure:                2020-06-21 18:00:00 +01:00 (Sun up)\n\
+        Arrival:                  2020-06-22 02:00:00 +01:00\n\
+        \n\
+        Sunset is at:             2020-06-22 01:20:57 +01:00 at 43.0933, -69.4426\n",
+    ));
+}
+
+#[test]
+fn test_flight_json() {
+    // an overnight flight east to Heathrow meets the sunrise over the Atlantic
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&[
+        "--compact-json",
+        "flight",
+        "--from",
+        "40.6413,-73.7781",
+        "--to",
+        "51.4700,-0.4543",
+        "--departure",
+        "2020-06-21T22:00:00-04:00",
+        "--arrival",
+        "2020-06-22T10:00:00+01:00",
+        "--json",
+    ])
+    .assert()
+    .success()
+    .stdout(predicates::str::contains(
+        r#""sun_up_at_departure":false,"events":[{"event":"sunrise","time":"2020-06-22T01:58:14-04:00","coordinates":{"latitude":52.94"#,
+    ));
+}
+
+#[test]
+fn test_flight_fails() {
+    // the arrival must come after the departure, and both need an explicit offset
+    for (departure, arrival) in [
+        ("2020-06-21T22:00:00-04:00", "2020-06-21T10:00:00+01:00"),
+        ("2020-06-21T22:00:00-04:00", "2020-06-24T10:00:00+01:00"),
+        ("2020-06-21T22:00:00", "2020-06-22T10:00:00+01:00"),
+    ]
+    .iter()
+    {
+        let mut cmd = Command::cargo_bin("heliocron").unwrap();
+        cmd.args(&[
+            "flight",
+            "--from",
+            "40.6413,-73.7781",
+            "--to",
+            "51.4700,-0.4543",
+            "--departure",
+            departure,
+            "--arrival",
+            arrival,
+        ])
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("Invalid flight"));
+    }
+}
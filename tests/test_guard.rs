@@ -0,0 +1,83 @@
+use std::process::Command;
+
+use assert_cmd::prelude::*;
+
+#[test]
+fn test_fail_if_dark() {
+    // assert that the guard passes silently when it is light, and fails with its own code when it is dark,
+    // with or without a twilight to judge by
+    let params = [
+        ("2020-06-21T12:00", &["--fail-if-dark"][..], Some(0)),
+        ("2020-06-21T21:30", &["--fail-if-dark"][..], Some(2)),
+        ("2020-06-21T21:30", &["--fail-if-light"][..], Some(0)),
+        (
+            "2020-06-21T21:30",
+            &["--fail-if-dark", "--guard-twilight", "civil"][..],
+            Some(0),
+        ),
+        (
+            "2020-06-21T21:30",
+            &["--fail-if-light", "--guard-twilight", "CIVIL"][..],
+            Some(2),
+        ),
+    ];
+    for (datetime, args, code) in params.iter() {
+        let mut cmd = Command::cargo_bin("heliocron").unwrap();
+        let assert = cmd
+            .args(&["--datetime", datetime, "-t", "+01:00"])
+            .args(*args)
+            .assert()
+            .stdout("");
+        assert_eq!(
+            *code,
+            assert.get_output().status.code(),
+            "{} {:?}",
+            datetime,
+            args
+        );
+    }
+}
+
+#[test]
+fn test_guard_with_subcommand() {
+    // assert that a subcommand only runs once the guard has passed
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&["--datetime", "2020-06-21T12:00", "-t", "+01:00"])
+        .args(&["--fail-if-dark", "phase"])
+        .assert()
+        .success()
+        .stdout("day\n");
+
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&["--datetime", "2020-06-21T12:00", "-t", "+01:00"])
+        .args(&["--fail-if-light", "phase"])
+        .assert()
+        .code(2)
+        .stdout("");
+
+    // the current time can be overridden as usual
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&["-t", "+00:00", "--fail-if-light"])
+        .env("HELIOCRON_NOW", "2020-12-21T23:00:00Z")
+        .assert()
+        .success();
+}
+
+#[test]
+fn test_guard_arguments() {
+    // assert that a subcommand is still needed without a guard, and that the guards are exclusive
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.assert().code(1).stderr(predicates::str::contains(
+        "Config error: A subcommand is required",
+    ));
+
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&["--fail-if-dark", "--fail-if-light"])
+        .assert()
+        .failure();
+
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&["--guard-twilight", "civil", "phase"])
+        .assert()
+        .failure();
+}
@@ -0,0 +1,38 @@
+use std::process::Command;
+
+use assert_cmd::prelude::*;
+
+#[test]
+fn test_moon_phases() {
+    // assert that the phases are listed, with the blue moon of October 2020 flagged
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    let phases = cmd
+        .args(&["-t", "+00:00", "moon-phases", "--year", "2020"])
+        .assert();
+
+    phases
+        .success()
+        .stdout(predicates::str::starts_with(
+            "2020-01-10 19:21:12 +00:00  Full moon\n2020-01-24 21:42:00 +00:00  New moon\n",
+        ))
+        .stdout(predicates::str::contains(
+            "2020-10-31 14:49:14 +00:00  Full moon (blue moon)\n",
+        ));
+
+    // the year defaults to that of the chosen date
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    let phases = cmd
+        .args(&["-d", "2018-06-01", "-t", "+00:00", "moon-phases", "--json"])
+        .assert();
+
+    phases
+        .success()
+        .stdout(predicates::str::contains("\"phase\": \"full_moon\""))
+        .stdout(predicates::str::contains("\"time\": \"2018-03-31T12:"));
+
+    // and must be one which is supported
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&["moon-phases", "--year", "999"])
+        .assert()
+        .failure();
+}
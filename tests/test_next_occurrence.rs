@@ -0,0 +1,58 @@
+use std::process::Command;
+
+use assert_cmd::prelude::*;
+
+#[test]
+fn test_next_occurrence() {
+    // assert that the first sunrise after the polar night is found
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    let next = cmd
+        .args(&[
+            "-l",
+            "69.65N",
+            "-o",
+            "18.96E",
+            "-t",
+            "+01:00",
+            "-d",
+            "2021-01-01",
+            "next-occurrence",
+        ])
+        .assert();
+
+    next.success().stdout(
+        "Searching from:           2021-01-01\n\
+        Next sunrise:             2021-01-15 11:24:45 +01:00\n\
+        Days without one:         14\n",
+    );
+
+    // and the first sunset after the polar day
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    let next = cmd
+        .args(&[
+            "-l",
+            "69.65N",
+            "-o",
+            "18.96E",
+            "-t",
+            "+01:00",
+            "-d",
+            "2021-05-20",
+            "next-occurrence",
+            "--event",
+            "sunset",
+            "--json",
+        ])
+        .assert();
+
+    next.success()
+        .stdout(predicates::str::contains(
+            "\"time\": \"2021-07-26T23:18:07+01:00\"",
+        ))
+        .stdout(predicates::str::contains("\"days_without\": 67"));
+
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&["next-occurrence", "--event", "moonrise"])
+        .assert()
+        .failure();
+}
@@ -0,0 +1,60 @@
+use std::process::Command;
+
+use assert_cmd::prelude::*;
+
+#[test]
+fn test_night() {
+    // assert that the night runs from the chosen evening's dusk to the following morning's dawn
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    let night = cmd
+        .args(&["-d", "2020-12-21", "-t", "+00:00", "night"])
+        .assert();
+
+    night.success().stdout(
+        "NIGHT OF 2020-12-21\n\
+        -------------------\n\
+        Twilight:                 astronomical\n\
+        Night begins at:          2020-12-21 17:57:27 +00:00\n\
+        Night ends at:            2020-12-22 05:59:39 +00:00\n\
+        The night length is:      12h 2m 12s\n",
+    );
+
+    // over a range of dates, with another kind of twilight
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    let nights = cmd
+        .args(&[
+            "-d",
+            "2020-12-21",
+            "--end-date",
+            "2020-12-22",
+            "-t",
+            "+00:00",
+            "--compact-json",
+            "night",
+            "--twilight",
+            "civil",
+            "--json",
+        ])
+        .assert();
+
+    nights
+        .success()
+        .stdout(predicates::str::starts_with(
+            "[{\"date\":\"2020-12-21\",\"twilight\":\"civil\",\"start\":\"2020-12-21T16:33:31+00:00",
+        ))
+        .stdout(predicates::str::contains("{\"date\":\"2020-12-22\""));
+}
+
+#[test]
+fn test_no_night() {
+    // there is no astronomical night in London around midsummer
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    let night = cmd
+        .args(&["-d", "2020-06-21", "-t", "+00:00", "night", "--json"])
+        .assert();
+
+    night
+        .success()
+        .stdout(predicates::str::contains("\"start\": null"))
+        .stdout(predicates::str::contains("\"length\": null"));
+}
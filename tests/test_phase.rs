@@ -0,0 +1,85 @@
+use std::process::Command;
+
+use assert_cmd::prelude::*;
+
+#[test]
+fn test_phase() {
+    // assert that the phase of the day is found from the altitude of the Sun at the chosen time
+    let params = [
+        ("2020-06-21T12:00", "day\n"),
+        ("2020-06-21T21:30", "civil_twilight\n"),
+        ("2020-06-21T23:00", "nautical_twilight\n"),
+    ];
+    for (datetime, expected) in params.iter() {
+        let mut cmd = Command::cargo_bin("heliocron").unwrap();
+        cmd.args(&["--datetime", datetime, "-t", "+01:00", "phase"])
+            .assert()
+            .success()
+            .stdout(*expected);
+    }
+
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&["--datetime", "2020-12-21T23:00", "-t", "+00:00", "phase"])
+        .assert()
+        .success()
+        .stdout("night\n");
+}
+
+#[test]
+fn test_phase_exit_code() {
+    // assert that each phase has its own exit code, if asked for
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&[
+        "--datetime",
+        "2020-06-21T12:00",
+        "-t",
+        "+01:00",
+        "phase",
+        "--exit-code",
+    ])
+    .assert()
+    .code(0);
+
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&[
+        "--datetime",
+        "2020-06-21T23:00",
+        "-t",
+        "+01:00",
+        "phase",
+        "--exit-code",
+    ])
+    .assert()
+    .code(3)
+    .stdout("nautical_twilight\n");
+
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&["--datetime", "2020-12-21T23:00", "-t", "+00:00", "phase"])
+        .assert()
+        .code(0);
+}
+
+#[test]
+fn test_phase_now() {
+    // assert that the current time can be set with --now or HELIOCRON_NOW, and that an invalid time is an
+    // error rather than falling back to the system clock
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&["--now", "2020-06-21T12:00:00+01:00", "phase"])
+        .assert()
+        .success()
+        .stdout("day\n");
+
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&["phase"])
+        .env("HELIOCRON_NOW", "2020-12-21T23:00:00Z")
+        .assert()
+        .success()
+        .stdout("night\n");
+
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&["phase"])
+        .env("HELIOCRON_NOW", "2020-12-21T23:00")
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("Error parsing HELIOCRON_NOW"));
+}
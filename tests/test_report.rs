@@ -1,7 +1,11 @@
-use std::process::Command;
+use std::{fs, process::Command};
 
 use assert_cmd::assert::Assert;
 use assert_cmd::prelude::*;
+use predicates::prelude::*;
+
+mod common;
+use common::TempFile;
 
 #[test]
 fn test_plain_bin() {
@@ -87,6 +91,308 @@ fn test_report_custom_timezone() {
     assert_report(report_short);
 }
 
+#[test]
+fn test_report_time_zone_without_date() {
+    // assert that a time zone given without a date still applies, to today's date
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&["-t", "+05:30", "report"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("+05:30\n"))
+        .stdout(predicates::str::contains("+00:00").not());
+}
+
+#[test]
+fn test_report_astro() {
+    // assert that the astronomical data and time scale note is added to the report on request
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    let report = cmd.args(&["report", "--astro"]).assert();
+
+    report
+        .success()
+        .stdout(predicates::str::contains("ASTRONOMICAL DATA"))
+        .stdout(predicates::str::contains("Julian date"))
+        .stdout(predicates::str::contains("Angular diameter"))
+        .stdout(predicates::str::contains("Terrestrial Time"));
+}
+
+#[test]
+fn test_report_shadow() {
+    // assert that the shadow section is added to the report on request
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    let report = cmd.args(&["report", "--shadow"]).assert();
+
+    report
+        .success()
+        .stdout(predicates::str::contains("SHADOW"))
+        .stdout(predicates::str::contains("Shadow direction"))
+        .stdout(predicates::str::contains("Shadow length"));
+}
+
+#[test]
+fn test_report_photo() {
+    // assert that the golden and blue hours are added to the report on request, but only to the full report
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&["-d", "2020-06-21", "-t", "+01:00", "report", "--photo"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains(
+            "Evening golden hour:      2020-06-21 20:26:41 +01:00  2020-06-21 21:49:06 +01:00  1h 22m 25s\n",
+        ));
+
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&["report", "--photo", "--offset-file", "offsets.toml"])
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("cannot be used with"));
+}
+
+#[test]
+fn test_report_json() {
+    // assert that the report can be output as JSON
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    let report = cmd
+        .args(&["-d", "2099-12-31", "-t", "+00:00", "report", "--json"])
+        .assert();
+
+    report
+        .success()
+        .stdout(predicates::str::contains(
+            "\"sunset\": \"2099-12-31T16:00:33+00:00\"",
+        ))
+        .stdout(predicates::str::contains("\"day_length\": "))
+        .stdout(predicates::str::contains("LOCATION").not());
+}
+
+#[test]
+fn test_report_event_offsets() {
+    // assert that a single event can be shown at several offsets
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    let report = cmd
+        .args(&[
+            "-d",
+            "2099-12-31",
+            "-t",
+            "+00:00",
+            "report",
+            "--event",
+            "sunset",
+            "--offsets",
+            "-00:30:00,00:00:00,00:30",
+        ])
+        .assert();
+
+    report
+        .success()
+        .stdout(predicates::str::contains(
+            "sunset -00:30:00 is at: 2099-12-31 15:30:33 +00:00",
+        ))
+        .stdout(predicates::str::contains(
+            "sunset +00:00:00 is at: 2099-12-31 16:00:33 +00:00",
+        ))
+        .stdout(predicates::str::contains(
+            "sunset +00:30:00 is at: 2099-12-31 16:30:33 +00:00",
+        ))
+        .stdout(predicates::str::contains("LOCATION").not());
+
+    // offsets make no sense without an event
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&["report", "--offsets", "00:30"])
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_report_event_offsets_json() {
+    // assert that the offsets are kept as they were given, in order, along with their signed seconds
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    let report = cmd
+        .args(&[
+            "-d",
+            "2099-12-31",
+            "-t",
+            "+00:00",
+            "--compact-json",
+            "report",
+            "--event",
+            "sunset",
+            "--offsets",
+            "30m before,+00:30",
+            "--json",
+        ])
+        .assert();
+
+    report.success().stdout(
+        "[{\"date\":\"2099-12-31\",\"offset\":\"30m before\",\"offset_seconds\":-1800,\
+        \"time\":\"2099-12-31T15:30:33+00:00\",\"fallback\":null},\
+        {\"date\":\"2099-12-31\",\"offset\":\"+00:30\",\"offset_seconds\":1800,\
+        \"time\":\"2099-12-31T16:30:33+00:00\",\"fallback\":null}]\n",
+    );
+
+    // and that an event which doesn't occur has no time
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    let report = cmd
+        .args(&[
+            "-l",
+            "78.0N",
+            "-o",
+            "15.0E",
+            "-d",
+            "2020-06-21",
+            "--compact-json",
+            "report",
+            "--event",
+            "sunset",
+            "--json",
+        ])
+        .assert();
+
+    report.success().stdout(predicates::str::contains(
+        "\"offset\":\"+00:00:00\",\"offset_seconds\":0,\"time\":null",
+    ));
+}
+
+#[test]
+fn test_report_fallback_event() {
+    // assert that a day without the event shows the fallback instead, marked as such
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    let report = cmd
+        .args(&[
+            "-l",
+            "60N",
+            "-o",
+            "0E",
+            "-d",
+            "2020-06-21",
+            "--end-date",
+            "2020-08-01",
+            "-t",
+            "+01:00",
+            "report",
+            "--event",
+            "nautical_dusk",
+            "--fallback-event",
+            "solar_midnight",
+        ])
+        .assert();
+
+    report
+        .success()
+        .stdout(predicates::str::contains(
+            "nautical_dusk +00:00:00 is at: 2020-06-22 01:01:53 +01:00 (solar_midnight instead)\n",
+        ))
+        .stdout(predicates::str::contains(
+            "nautical_dusk +00:00:00 is at: 2020-08-02 00:41:09 +01:00\n",
+        ))
+        .stdout(predicates::str::contains("Never").not());
+
+    // and in the JSON output, with each time saying whether it is the fallback
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    let report = cmd
+        .args(&[
+            "-l",
+            "60N",
+            "-o",
+            "0E",
+            "-d",
+            "2020-06-21",
+            "-t",
+            "+01:00",
+            "--compact-json",
+            "report",
+            "--event",
+            "sunset",
+            "--json",
+            "--fallback-event",
+            "solar_midnight",
+        ])
+        .assert();
+
+    report
+        .success()
+        .stdout(predicates::str::contains("\"fallback\":null"));
+}
+
+#[test]
+fn test_report_time_zone_warning() {
+    // assert that a mismatched time zone produces a warning on stderr when asked for, but still succeeds
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    let report = cmd
+        .args(&[
+            "-l",
+            "35.6762N",
+            "-o",
+            "139.6503E",
+            "-t",
+            "+00:00",
+            "--tz-warning",
+            "report",
+        ])
+        .assert();
+
+    report
+        .success()
+        .stderr(predicates::str::contains("Warning"))
+        .stderr(predicates::str::contains("+09:00"));
+
+    // and that there is no warning by default
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    let report = cmd
+        .args(&[
+            "-l",
+            "35.6762N",
+            "-o",
+            "139.6503E",
+            "-t",
+            "+00:00",
+            "report",
+        ])
+        .assert();
+
+    report.success().stderr(predicates::str::is_empty());
+
+    // a time zone within an hour of the nominal one is fine
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    let report = cmd
+        .args(&[
+            "-l",
+            "35.6762N",
+            "-o",
+            "139.6503E",
+            "-t",
+            "+10:00",
+            "--tz-warning",
+            "report",
+        ])
+        .assert();
+
+    report.success().stderr(predicates::str::is_empty());
+
+    // with --warnings-json, the warning is a JSON object on a single line, and stdout is untouched
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    let report = cmd
+        .args(&[
+            "-l",
+            "35.6762N",
+            "-o",
+            "139.6503E",
+            "-t",
+            "+00:00",
+            "--tz-warning",
+            "--warnings-json",
+            "report",
+        ])
+        .assert();
+
+    report
+        .success()
+        .stdout(predicates::str::contains("Warning").not())
+        .stderr(predicates::str::starts_with(
+            "{\"code\":\"time_zone_mismatch\",\"message\":\"the chosen time zone (+00:00) differs",
+        ))
+        .stderr(predicates::str::ends_with("by more than an hour.\"}\n"));
+}
+
 fn assert_report(report: Assert) {
     report
         .success()
@@ -96,3 +402,1745 @@ fn assert_report(report: Assert) {
         .stdout(predicates::str::contains("Solar noon"))
         .stdout(predicates::str::contains("Sunset"));
 }
+
+#[test]
+fn test_report_date_range() {
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    let report = cmd
+        .args(&[
+            "-d",
+            "2020-03-27",
+            "--end-date",
+            "2020-03-29",
+            "-t",
+            "+00:00",
+            "report",
+            "--event",
+            "sunset",
+        ])
+        .assert();
+
+    report
+        .success()
+        .stdout(predicates::str::contains("2020-03-26").not())
+        .stdout(predicates::str::contains("2020-03-27"))
+        .stdout(predicates::str::contains("2020-03-29"))
+        .stdout(predicates::str::contains("2020-03-30").not());
+
+    // an end date before the start date is an error
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    let report = cmd
+        .args(&["-d", "2020-03-27", "--end-date", "2020-03-26", "report"])
+        .assert();
+
+    report
+        .failure()
+        .stderr(predicates::str::contains("Invalid date range"));
+
+    // and so is a range with any other subcommand
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    let wait = cmd
+        .args(&["--this-week", "wait", "--event", "sunset"])
+        .assert();
+
+    wait.failure()
+        .stderr(predicates::str::contains("Invalid date range"));
+
+    // a range longer than --max-range-days is refused, unless the limit is raised
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&["-d", "2020-01-01", "--end-date", "3020-01-01", "report"])
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("--max-range-days"));
+
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&[
+        "-d",
+        "2020-03-27",
+        "--end-date",
+        "2020-03-29",
+        "--max-range-days",
+        "2",
+        "report",
+    ])
+    .assert()
+    .failure()
+    .stderr(predicates::str::contains("--max-range-days"));
+
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&[
+        "-d",
+        "2020-03-27",
+        "--end-date",
+        "2020-03-29",
+        "--max-range-days",
+        "3",
+        "report",
+        "--count-only",
+    ])
+    .assert()
+    .success();
+}
+
+#[test]
+fn test_report_this_week_and_month() {
+    // Wednesday 25th March 2020
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    let report = cmd
+        .args(&[
+            "-d",
+            "2020-03-25",
+            "-t",
+            "+00:00",
+            "--this-week",
+            "report",
+            "--json",
+        ])
+        .assert();
+
+    report
+        .success()
+        .stdout(predicates::str::starts_with("["))
+        .stdout(predicates::str::contains("2020-03-22T12:00:00").not())
+        .stdout(predicates::str::contains("2020-03-23T12:00:00"))
+        .stdout(predicates::str::contains("2020-03-29T12:00:00"));
+
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    let report = cmd
+        .args(&[
+            "-d",
+            "2020-03-25",
+            "-t",
+            "+00:00",
+            "--this-week",
+            "--week-start",
+            "sunday",
+            "report",
+            "--json",
+        ])
+        .assert();
+
+    report
+        .success()
+        .stdout(predicates::str::contains("2020-03-22T12:00:00"))
+        .stdout(predicates::str::contains("2020-03-28T12:00:00"))
+        .stdout(predicates::str::contains("2020-03-29T12:00:00").not());
+
+    // February in a leap year
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    let report = cmd
+        .args(&[
+            "-d",
+            "2020-02-15",
+            "-t",
+            "+00:00",
+            "--this-month",
+            "report",
+            "--event",
+            "sunrise",
+        ])
+        .assert();
+
+    report
+        .success()
+        .stdout(predicates::str::contains("2020-02-01"))
+        .stdout(predicates::str::contains("2020-02-29"))
+        .stdout(predicates::str::contains("2020-03-01").not());
+}
+
+#[test]
+fn test_report_expected_times() {
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    let report = cmd
+        .args(&[
+            "-d",
+            "2020-06-21",
+            "-t",
+            "+01:00",
+            "report",
+            "--expect-sunrise",
+            "04:43",
+            "--expect-sunset",
+            "21:21:30",
+        ])
+        .assert();
+
+    report
+        .success()
+        .stdout(predicates::str::contains(
+            "sunrise expected at: 2020-06-21 04:43:00 +01:00 calculated at: 2020-06-21 04:42:54 +01:00 (-00:00:06) OK",
+        ))
+        .stdout(predicates::str::contains(
+            "sunset expected at: 2020-06-21 21:21:30 +01:00 calculated at: 2020-06-21 21:20:53 +01:00 (-00:00:37) OK",
+        ));
+
+    // a difference larger than the tolerance is an error
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    let report = cmd
+        .args(&[
+            "-d",
+            "2020-06-21",
+            "-t",
+            "+01:00",
+            "report",
+            "--expect-sunset",
+            "21:21:30",
+            "--tolerance",
+            "30",
+        ])
+        .assert();
+
+    report
+        .failure()
+        .stdout(predicates::str::contains("OUTSIDE TOLERANCE"))
+        .stderr(predicates::str::contains("more than the tolerance"));
+
+    // as is an event which doesn't occur at all
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    let report = cmd
+        .args(&[
+            "-l",
+            "78.2232N",
+            "-o",
+            "15.6267E",
+            "-d",
+            "2020-06-21",
+            "-t",
+            "+02:00",
+            "report",
+            "--expect-sunrise",
+            "00:00",
+        ])
+        .assert();
+
+    report
+        .failure()
+        .stdout(predicates::str::contains("calculated at: Never"));
+}
+
+#[test]
+fn test_report_datetime() {
+    // the Sun's position is given at the chosen time, but the events are still those of the whole day
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    let report = cmd
+        .args(&[
+            "--datetime",
+            "2020-06-21T21:30",
+            "-t",
+            "+01:00",
+            "report",
+            "--shadow",
+        ])
+        .assert();
+
+    report
+        .success()
+        .stdout(predicates::str::contains(
+            "Sunset is at:             2020-06-21 21:20:53 +01:00",
+        ))
+        .stdout(predicates::str::contains("At 2020-06-21 21:30:00 +01:00:"))
+        .stdout(predicates::str::contains("Sun is up:                no"));
+
+    // and a date can't be given as well
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&[
+        "--datetime",
+        "2020-06-21T21:30",
+        "-d",
+        "2020-06-21",
+        "report",
+    ])
+    .assert()
+    .failure();
+}
+
+#[test]
+fn test_report_date_only() {
+    // the time given is discarded, leaving the date at midday, whenever in the day the time was
+    for datetime in ["2020-06-21T00:05", "2020-06-21T23:55"].iter() {
+        let mut cmd = Command::cargo_bin("heliocron").unwrap();
+        let report = cmd
+            .args(&[
+                "--datetime",
+                datetime,
+                "--date-only",
+                "-t",
+                "+01:00",
+                "report",
+                "--shadow",
+            ])
+            .assert();
+
+        report
+            .success()
+            .stdout(predicates::str::contains("\n2020-06-21 12:00:00 +01:00\n"))
+            .stdout(predicates::str::contains("At 2020-06-21 12:00:00 +01:00:"))
+            .stdout(predicates::str::contains("Sun is up:                yes"));
+    }
+}
+
+#[test]
+fn test_schema() {
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    let schema = cmd.args(&["schema"]).assert();
+
+    schema
+        .success()
+        .stdout(predicates::str::contains("json-schema.org"))
+        .stdout(predicates::str::contains("\"schema_version\""));
+}
+
+#[test]
+fn test_report_count_only() {
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    let report = cmd
+        .args(&[
+            "-d",
+            "2020-01-01",
+            "--end-date",
+            "2020-12-31",
+            "-t",
+            "+00:00",
+            "report",
+            "--min-day-length",
+            "12h",
+            "--count-only",
+        ])
+        .assert();
+
+    report.success().stdout("192\n");
+
+    // the conditions also filter the days which are shown
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    let report = cmd
+        .args(&[
+            "-d",
+            "2020-03-15",
+            "--end-date",
+            "2020-03-25",
+            "-t",
+            "+00:00",
+            "report",
+            "--max-day-length",
+            "12:00",
+            "--event",
+            "sunrise",
+        ])
+        .assert();
+
+    report
+        .success()
+        .stdout(predicates::str::contains("2020-03-16"))
+        .stdout(predicates::str::contains("2020-03-17").not());
+}
+
+#[test]
+fn test_report_filter() {
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    let report = cmd
+        .args(&[
+            "-l",
+            "51.5N",
+            "-o",
+            "0.1W",
+            "-t",
+            "+01:00",
+            "-d",
+            "2020-05-01",
+            "--end-date",
+            "2020-08-31",
+            "report",
+            "--filter",
+            "sunset>=21:15",
+            "--count-only",
+        ])
+        .assert();
+
+    report.success().stdout("32\n");
+
+    // every filter must match
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    let report = cmd
+        .args(&[
+            "-l",
+            "51.5N",
+            "-o",
+            "0.1W",
+            "-t",
+            "+01:00",
+            "-d",
+            "2020-05-01",
+            "--end-date",
+            "2020-08-31",
+            "report",
+            "--filter",
+            "sunset>=21:15",
+            "--filter",
+            "sunset < 21:16",
+            "--event",
+            "sunset",
+        ])
+        .assert();
+
+    report.success().stdout(
+        "sunset +00:00:00 is at: 2020-06-09 21:15:36 +01:00\n\
+        sunset +00:00:00 is at: 2020-07-10 21:15:41 +01:00\n",
+    );
+
+    // and malformed filters are refused
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&["report", "--filter", "sunset 20:00"])
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("Error parsing filter"));
+
+    // with a suggestion for misspelt events
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&["report", "--filter", "sunris>=05:00"])
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains(
+            "Unknown event 'sunris', did you mean 'sunrise'?",
+        ));
+}
+
+#[test]
+fn test_report_decimal_hours() {
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    let report = cmd
+        .args(&[
+            "-d",
+            "2020-03-25",
+            "-t",
+            "+00:00",
+            "--decimal-hours",
+            "report",
+            "--event",
+            "sunset",
+            "--offsets",
+            "-1h,6h",
+        ])
+        .assert();
+
+    report.success().stdout(
+        "sunset -01:00:00 is at: 17.3622\n\
+        sunset +06:00:00 is at: 24.3622\n",
+    );
+}
+
+#[test]
+fn test_report_time_only() {
+    // assert that only the time of day is shown, even once a shifted time has moved on to the next day
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    let report = cmd
+        .args(&[
+            "-d",
+            "2020-03-25",
+            "-t",
+            "+00:00",
+            "--time-only",
+            "report",
+            "--event",
+            "sunset",
+            "--offsets",
+            "-1h,6h",
+        ])
+        .assert();
+
+    report.success().stdout(
+        "sunset -01:00:00 is at: 17:21:44\n\
+        sunset +06:00:00 is at: 00:21:44\n",
+    );
+
+    // and that it can't be combined with a time which is a number
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&["--time-only", "--decimal-hours", "report"])
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_report_with_accuracy() {
+    // assert that each time is followed by its uncertainty, which is the event's own whatever the offset
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    let report = cmd
+        .args(&[
+            "-d",
+            "2020-03-25",
+            "-t",
+            "+00:00",
+            "--with-accuracy",
+            "report",
+            "--event",
+            "sunset",
+            "--offsets",
+            "-1h,6h",
+        ])
+        .assert();
+
+    report.success().stdout(
+        "sunset -01:00:00 is at: 2020-03-25 17:21:44 +00:00 (±2 min)\n\
+        sunset +06:00:00 is at: 2020-03-26 00:21:44 +00:00 (±2 min)\n",
+    );
+
+    // and that twilight, away from the refraction at the horizon, is known much better than sunrise
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    let report = cmd
+        .args(&[
+            "-d",
+            "2020-06-21",
+            "-t",
+            "+01:00",
+            "--with-accuracy",
+            "report",
+        ])
+        .assert();
+
+    report
+        .success()
+        .stdout(predicate::str::contains(
+            "Sunrise is at:            2020-06-21 04:42:54 +01:00 (±2 min)\n",
+        ))
+        .stdout(predicate::str::contains(
+            "Civil dawn is at:         2020-06-21 03:55:13 +01:00 (±7s)\n",
+        ));
+}
+
+#[test]
+fn test_report_with_locale() {
+    // assert that the date is also written out in the chosen language, which only needs its language part
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    let report = cmd
+        .args(&[
+            "-d",
+            "2020-06-21",
+            "-t",
+            "+01:00",
+            "--locale",
+            "de_DE.UTF-8",
+            "report",
+        ])
+        .assert();
+
+    report.success().stdout(predicate::str::contains(
+        "DATE\n----\n2020-06-21 12:00:00 +01:00\nSonntag, 21. Juni 2020\n\n",
+    ));
+
+    // and that a language without names to hand is an error
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    let report = cmd.args(&["--locale", "xx_XX", "report"]).assert();
+
+    report.failure().stderr(predicate::str::contains(
+        "Config error: Error parsing locale. Choose a language from: en, fr, de, es, it, nl, pt",
+    ));
+}
+
+#[test]
+fn test_report_day_fraction() {
+    // assert that times are shown as fractions of the day, and can be given that way too
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    let report = cmd
+        .args(&[
+            "-d",
+            "2020-03-25",
+            "-t",
+            "+00:00",
+            "--day-fraction",
+            "report",
+            "--event",
+            "sunset",
+            "--offsets",
+            "-1h,6h",
+        ])
+        .assert();
+
+    report.success().stdout(
+        "sunset -01:00:00 is at: 0.72343\n\
+        sunset +06:00:00 is at: 1.01509\n",
+    );
+
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&[
+        "-d",
+        "2020-03-25",
+        "-t",
+        "+00:00",
+        "report",
+        "--between",
+        "0.7",
+        "0.77",
+    ])
+    .assert()
+    .success()
+    .stdout("sunset +00:00:00 is at: 2020-03-25 18:21:44 +00:00\n");
+}
+
+#[test]
+fn test_report_compact_json() {
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    let report = cmd
+        .args(&[
+            "-d",
+            "2020-06-21",
+            "-t",
+            "+00:00",
+            "--compact-json",
+            "report",
+            "--json",
+        ])
+        .assert();
+
+    report
+        .success()
+        .stdout(predicates::str::starts_with(
+            "{\"schema_version\":\"1.3.0\",\"coordinates\":{",
+        ))
+        .stdout(predicates::str::contains(
+            "\"sunrise\":\"2020-06-21T03:42:55+00:00\"",
+        ))
+        .stdout(predicates::str::contains("\n").count(1));
+}
+
+#[test]
+fn test_report_csv() {
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    let report = cmd
+        .args(&[
+            "-d",
+            "2020-06-21",
+            "--end-date",
+            "2020-06-22",
+            "-t",
+            "+00:00",
+            "report",
+            "--output-format",
+            "csv",
+        ])
+        .assert();
+
+    report.success().stdout(
+        "date,solar_noon,day_length,sunrise,sunset,civil_dawn,civil_dusk,nautical_dawn,nautical_dusk,\
+        astronomical_dawn,astronomical_dusk\n\
+        2020-06-21,2020-06-21T12:01:54+00:00,59878,2020-06-21T03:42:55+00:00,2020-06-21T20:20:53+00:00,\
+        2020-06-21T02:55:13+00:00,2020-06-21T21:08:35+00:00,2020-06-21T01:40:48+00:00,2020-06-21T22:23:00+00:00,,\n\
+        2020-06-22,2020-06-22T12:02:07+00:00,59872,2020-06-22T03:43:11+00:00,2020-06-22T20:21:03+00:00,\
+        2020-06-22T02:55:30+00:00,2020-06-22T21:08:44+00:00,2020-06-22T01:41:08+00:00,2020-06-22T22:23:06+00:00,,\n",
+    );
+}
+
+#[test]
+fn test_report_tsv() {
+    // assert that the columns are the same as the CSV output's, separated by tabs, with either flag
+    for args in [&["--tsv"][..], &["--output-format", "tsv"][..]].iter() {
+        let mut cmd = Command::cargo_bin("heliocron").unwrap();
+        let report = cmd
+            .args(&["-d", "2020-06-21", "-t", "+00:00", "report"])
+            .args(*args)
+            .assert();
+
+        report.success().stdout(
+            "date\tsolar_noon\tday_length\tsunrise\tsunset\tcivil_dawn\tcivil_dusk\tnautical_dawn\t\
+            nautical_dusk\tastronomical_dawn\tastronomical_dusk\n\
+            2020-06-21\t2020-06-21T12:01:54+00:00\t59878\t2020-06-21T03:42:55+00:00\t2020-06-21T20:20:53+00:00\t\
+            2020-06-21T02:55:13+00:00\t2020-06-21T21:08:35+00:00\t2020-06-21T01:40:48+00:00\t\
+            2020-06-21T22:23:00+00:00\t\t\n",
+        );
+    }
+
+    // and that it can't be combined with another format
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&["report", "--tsv", "--json"]).assert().failure();
+}
+
+#[test]
+fn test_report_fixed_offset() {
+    // assert that the local time zone follows daylight saving over a range of dates...
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    let report = cmd
+        .env("TZ", "Europe/London")
+        .args(&[
+            "-d",
+            "2020-03-28",
+            "--end-date",
+            "2020-03-29",
+            "report",
+            "--event",
+            "sunrise",
+        ])
+        .assert();
+
+    report
+        .success()
+        .stdout(predicates::str::contains("2020-03-28 05:43:01 +00:00"))
+        .stdout(predicates::str::contains("2020-03-29 06:40:50 +01:00"));
+
+    // ...unless the offset is fixed to that of the chosen date
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    let report = cmd
+        .env("TZ", "Europe/London")
+        .args(&[
+            "-d",
+            "2020-03-28",
+            "--end-date",
+            "2020-03-29",
+            "--fixed-offset",
+            "report",
+            "--event",
+            "sunrise",
+        ])
+        .assert();
+
+    report
+        .success()
+        .stdout(predicates::str::contains("2020-03-28 05:43:01 +00:00"))
+        .stdout(predicates::str::contains("2020-03-29 05:40:44 +00:00"));
+}
+
+#[test]
+fn test_report_ics() {
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    let report = cmd
+        .args(&[
+            "-d",
+            "2020-06-21",
+            "--end-date",
+            "2020-06-22",
+            "-t",
+            "+00:00",
+            "report",
+            "--output-format",
+            "ics",
+            "--event",
+            "sunset",
+        ])
+        .assert();
+
+    report
+        .success()
+        .stdout(predicates::str::starts_with(
+            "BEGIN:VCALENDAR\r\nVERSION:2.0\r\n",
+        ))
+        .stdout(predicates::str::contains(
+            "DTSTART:20200621T202053Z\r\nSUMMARY:Sunset\r\n",
+        ))
+        .stdout(predicates::str::contains("DTSTART:20200622T202103Z\r\n"))
+        .stdout(predicates::str::contains("BEGIN:VEVENT").count(2))
+        .stdout(predicates::str::contains("VTIMEZONE").not())
+        .stdout(predicates::str::ends_with("END:VCALENDAR\r\n"));
+
+    // any other offset is kept, with a time zone defined for it once
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&[
+        "-d",
+        "2020-06-21",
+        "--end-date",
+        "2020-06-22",
+        "-t",
+        "+01:00",
+    ])
+    .args(&["report", "--output-format", "ics", "--event", "sunset"])
+    .assert()
+    .success()
+    .stdout(predicates::str::contains(
+        "BEGIN:VTIMEZONE\r\n\
+             TZID:UTC+01:00\r\n\
+             BEGIN:STANDARD\r\n\
+             DTSTART:19700101T000000\r\n\
+             TZOFFSETFROM:+0100\r\n\
+             TZOFFSETTO:+0100\r\n\
+             END:STANDARD\r\n\
+             END:VTIMEZONE\r\n",
+    ))
+    .stdout(predicates::str::contains("BEGIN:VTIMEZONE").count(1))
+    .stdout(predicates::str::contains(
+        "DTSTART;TZID=UTC+01:00:20200621T212053\r\n",
+    ))
+    .stdout(predicates::str::contains(
+        "UID:sunset-20200621T202053Z@heliocron\r\n",
+    ));
+}
+
+#[test]
+fn test_report_supported_dates() {
+    // assert that dates outside of the years 1000-3000 are refused, rather than silently inaccurate
+    for date in ["1000-01-01", "3000-12-31"].iter() {
+        let mut cmd = Command::cargo_bin("heliocron").unwrap();
+        cmd.args(&["-d", *date, "-t", "+00:00", "report"])
+            .assert()
+            .success();
+    }
+
+    for date in ["0999-12-31", "3001-01-01"].iter() {
+        let mut cmd = Command::cargo_bin("heliocron").unwrap();
+        cmd.args(&["-d", *date, "-t", "+00:00", "report"])
+            .assert()
+            .failure()
+            .stderr(predicates::str::contains(
+                "Dates must be between the years 1000 and 3000",
+            ));
+    }
+
+    // which includes the end of a date range
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&[
+        "-d",
+        "3000-12-31",
+        "--end-date",
+        "3001-01-01",
+        "-t",
+        "+00:00",
+        "report",
+    ])
+    .assert()
+    .failure();
+}
+
+#[test]
+fn test_sun_path() {
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    let sun_path = cmd
+        .args(&[
+            "-d",
+            "2020-06-21",
+            "-t",
+            "+01:00",
+            "sun-path",
+            "--interval",
+            "60",
+        ])
+        .assert();
+
+    // from sunrise to sunset
+    sun_path
+        .success()
+        .stdout(predicates::str::starts_with(
+            "time,altitude,azimuth\n2020-06-21T04:42:54+01:00,",
+        ))
+        .stdout(predicates::str::contains("\n2020-06-21T05:42:54+01:00,"))
+        .stdout(predicates::str::ends_with(
+            "\n2020-06-21T21:20:53+01:00,-0.4233,311.0452\n",
+        ));
+
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    let sun_path = cmd
+        .args(&[
+            "-d",
+            "2020-06-21",
+            "-t",
+            "+01:00",
+            "sun-path",
+            "--interval",
+            "720",
+            "--full-day",
+            "--json",
+        ])
+        .assert();
+
+    sun_path
+        .success()
+        .stdout(predicates::str::contains(
+            "\"time\": \"2020-06-21T00:00:00+01:00\"",
+        ))
+        .stdout(predicates::str::contains(
+            "\"time\": \"2020-06-21T12:00:00+01:00\"",
+        ))
+        .stdout(predicates::str::contains(
+            "\"time\": \"2020-06-22T00:00:00+01:00\"",
+        ))
+        .stdout(predicates::str::contains("\"altitude\": "));
+
+    // during the polar night, there is no daylight to sample
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    let sun_path = cmd
+        .args(&["-d", "2020-12-21", "--city", "Longyearbyen", "sun-path"])
+        .assert();
+
+    sun_path.success().stdout("time,altitude,azimuth\n");
+}
+
+#[test]
+fn test_report_sunrise_on_previous_day() {
+    // far north in summer, and many hours behind the location's nominal time zone, the day's sunrise is late
+    // the previous evening
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    let report = cmd
+        .args(&[
+            "-l",
+            "69.7N",
+            "-o",
+            "30.0E",
+            "-t",
+            "+00:00",
+            "-d",
+            "2020-05-10",
+            "report",
+        ])
+        .assert();
+
+    report
+        .success()
+        .stdout(predicates::str::contains(
+            "Sunrise is at:            2020-05-09 23:32:50 +00:00",
+        ))
+        .stdout(predicates::str::contains(
+            "Sunset is at:             2020-05-10 20:19:57 +00:00",
+        ))
+        .stdout(predicates::str::contains(
+            "The day length is:        20h 47m 7s",
+        ));
+}
+
+#[test]
+fn test_report_end_of_polar_night() {
+    // assert that on the last day of the polar night, when refraction alone would lift the Sun to the horizon
+    // at noon, the day has no length rather than lasting all day
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&[
+        "-l",
+        "70N",
+        "-o",
+        "0E",
+        "-t",
+        "+00:00",
+        "-d",
+        "2020-01-16",
+        "report",
+    ])
+    .assert()
+    .success()
+    .stdout(predicates::str::contains(
+        "Sunrise is at:            Never\n",
+    ))
+    .stdout(predicates::str::contains(
+        "The day length is:        0h 0m 0s\n",
+    ));
+}
+
+#[test]
+fn test_report_template_file() {
+    let path = TempFile::with_contents(
+        "template.txt",
+        "{date}\n  sunrise: {sunrise}\n  sunset:  {sunset}\n",
+    );
+
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    let report = cmd
+        .args(&[
+            "-d",
+            "2020-03-25",
+            "--end-date",
+            "2020-03-26",
+            "-l",
+            "51.4769N",
+            "-o",
+            "0.0005W",
+            "-t",
+            "+00:00",
+            "report",
+            "--template-file",
+        ])
+        .arg(&path)
+        .assert();
+
+    report.success().stdout(
+        "2020-03-25 12:00:00 +00:00\n  sunrise: 2020-03-25 05:49:51 +00:00\n  sunset:  2020-03-25 18:21:44 +00:00\n\
+         2020-03-26 12:00:00 +00:00\n  sunrise: 2020-03-26 05:47:34 +00:00\n  sunset:  2020-03-26 18:23:25 +00:00\n",
+    );
+
+    // unknown placeholders and unreadable files are errors
+    fs::write(&path, "{sunrise} {dusk}").unwrap();
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&["report", "--template-file"])
+        .arg(&path)
+        .assert()
+        .failure()
+        .stdout("")
+        .stderr(predicates::str::contains("Unknown placeholder '{dusk}'"));
+
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&["report", "--template-file"])
+        .arg(std::env::temp_dir().join("heliocron-does-not-exist.txt"))
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("Error reading template file"));
+
+    // the template replaces the whole report, so can't be combined with another format
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&["report", "--output-format", "csv", "--template-file"])
+        .arg(&path)
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("cannot be used with"));
+}
+
+#[test]
+fn test_report_occurs() {
+    // assert that whether the event occurs is shown, failing if it doesn't
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&["-d", "2020-06-21", "report", "-e", "sunrise", "--occurs"])
+        .assert()
+        .success()
+        .stdout("true\n");
+
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&[
+        "-d",
+        "2020-06-21",
+        "report",
+        "-e",
+        "astronomical_dawn",
+        "--occurs",
+    ])
+    .assert()
+    .failure()
+    .stdout("false\n")
+    .stderr(predicates::str::contains(
+        "The chosen event does not occur on this day.",
+    ));
+
+    // over a range of dates, every day is shown
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&[
+        "-d",
+        "2020-07-18",
+        "--end-date",
+        "2020-07-20",
+        "-t",
+        "+01:00",
+        "report",
+        "-e",
+        "astronomical_dusk",
+        "--occurs",
+    ])
+    .assert()
+    .failure()
+    .stdout("2020-07-18 false\n2020-07-19 false\n2020-07-20 true\n");
+
+    // whether the event occurs is only ever shown as plain text
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&[
+        "report",
+        "-e",
+        "sunrise",
+        "--occurs",
+        "--output-format",
+        "csv",
+    ])
+    .assert()
+    .failure()
+    .stderr(predicates::str::contains("cannot be used with"));
+}
+
+#[test]
+fn test_report_offset_file() {
+    let path = TempFile::with_contents("offsets.toml", "sunrise = \"+15m\"\nsunset = \"-30m\"\n");
+
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    let report = cmd
+        .args(&[
+            "-d",
+            "2020-06-21",
+            "-t",
+            "+01:00",
+            "report",
+            "--offset-file",
+        ])
+        .arg(&path)
+        .assert();
+
+    // every event is shown, with those missing from the file unshifted
+    report
+        .success()
+        .stdout(predicates::str::starts_with(
+            "sunrise +00:15:00 is at: 2020-06-21 04:57:54 +01:00\n\
+             sunset -00:30:00 is at: 2020-06-21 20:50:53 +01:00\n\
+             civil_dawn +00:00:00 is at: 2020-06-21 03:55:13 +01:00\n",
+        ))
+        .stdout(predicates::str::ends_with(
+            "astronomical_dusk +00:00:00 is at: Never (2020-06-21)\n",
+        ));
+
+    fs::write(&path, "sunset = \"30 minutes\"\n").unwrap();
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&["report", "--offset-file"])
+        .arg(&path)
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("Error parsing offset"));
+
+    // the shifted events replace the whole report, so can't be combined with a template
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&["report", "--template-file", "template.txt", "--offset-file"])
+        .arg(&path)
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("cannot be used with"));
+}
+
+#[test]
+fn test_report_horizon_file() {
+    let path = TempFile::with_contents(
+        "horizon.txt",
+        "# azimuth altitude\n0 0\n90 5\n180 0\n270 3\n",
+    );
+
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&[
+        "--latitude",
+        "51.5N",
+        "--longitude",
+        "0.1W",
+        "-d",
+        "2020-03-25",
+        "-t",
+        "+00:00",
+        "--horizon-file",
+    ])
+    .arg(&path)
+    .arg("report")
+    .assert()
+    .success()
+    .stdout(predicates::str::contains(
+        "Sunrise is at:            2020-03-25 06:22:16 +00:00",
+    ))
+    .stdout(predicates::str::contains(
+        "Sunset is at:             2020-03-25 18:03:28 +00:00",
+    ))
+    .stdout(predicates::str::contains(
+        "Note: sunrise and sunset are over the skyline from the horizon file.",
+    ));
+
+    fs::write(&path, "90 east\n").unwrap();
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.arg("--horizon-file")
+        .arg(&path)
+        .arg("report")
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("Error parsing horizon file"));
+}
+
+#[test]
+fn test_report_solar_noon_altitude() {
+    // assert that the altitude of the Sun at solar noon is shown alongside the time, and in the JSON
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&["-d", "2020-06-21", "-t", "+00:00", "report"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains(
+            "Solar noon is at:         2020-06-21 12:01:54 +00:00\n\
+             Solar noon altitude:      61.97°\n",
+        ));
+
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&["-d", "2020-06-21", "-t", "+00:00", "report", "--json"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("\"solar_noon_altitude\": 61.967"));
+}
+
+#[test]
+fn test_report_json_astro() {
+    // assert that the astronomical data is only added to the JSON on request, as numbers
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&["-d", "2020-03-25", "-t", "+00:00", "report", "--json"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("\"astro\"").not());
+
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    let report = cmd
+        .args(&[
+            "-d",
+            "2020-03-25",
+            "-l",
+            "0.0N",
+            "-o",
+            "0.0E",
+            "-t",
+            "+00:00",
+            "report",
+            "--json",
+            "--astro",
+        ])
+        .assert();
+
+    report
+        .success()
+        .stdout(predicates::str::contains("\"julian_date\": 2458934.0,"))
+        .stdout(predicates::str::contains("\"declination\": 2.104"))
+        .stdout(predicates::str::contains("\"hour_angle\": -1.450"))
+        .stdout(predicates::str::contains("\"local_sidereal_time\": 0.227"))
+        .stdout(predicates::str::contains(
+            "\"sunrise_altitude_rate\": 0.249",
+        ));
+}
+
+#[test]
+fn test_report_solar_time() {
+    // assert that solar noon is at 12:00:00 in solar time, with sunrise and sunset either side of it
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    let report = cmd
+        .args(&["-d", "2020-06-21", "-t", "+01:00", "--solar-time", "report"])
+        .assert();
+
+    report
+        .success()
+        .stdout(predicates::str::contains(
+            "Solar noon is at:         2020-06-21 12:00:00 solar time\n",
+        ))
+        .stdout(predicates::str::contains(
+            "Sunrise is at:            2020-06-21 03:41:00 solar time\n",
+        ))
+        .stdout(predicates::str::contains(
+            "Sunset is at:             2020-06-21 20:18:59 solar time\n",
+        ));
+}
+
+#[test]
+fn test_relative_to_noon() {
+    // assert that events are shown as signed offsets from solar noon
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    let report = cmd
+        .args(&[
+            "-d",
+            "2020-06-21",
+            "-t",
+            "+01:00",
+            "--relative-to-noon",
+            "report",
+        ])
+        .assert();
+
+    report
+        .success()
+        .stdout(predicates::str::contains(
+            "Solar noon is at:         +00:00:00\n",
+        ))
+        .stdout(predicates::str::contains(
+            "Sunrise is at:            -08:19:00\n",
+        ))
+        .stdout(predicates::str::contains(
+            "Sunset is at:             +08:18:59\n",
+        ));
+
+    // and in decimal hours in the CSV output
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&[
+        "-d",
+        "2020-06-21",
+        "--relative-to-noon",
+        "--decimal-hours",
+        "report",
+        "--output-format",
+        "csv",
+    ])
+    .assert()
+    .success()
+    .stdout(predicates::str::contains("2020-06-21,+0.0000,"));
+}
+
+#[test]
+fn test_depression() {
+    // assert that the crossings of a depression angle are shown, or just the one in the chosen direction
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&[
+        "-d",
+        "2020-06-21",
+        "-t",
+        "+01:00",
+        "report",
+        "--depression",
+        "9",
+    ])
+    .assert()
+    .success()
+    .stdout(predicates::str::contains(
+        "Sun rising through 9° below the horizon is at: 2020-06-21 03:22:15 +01:00\n",
+    ))
+    .stdout(predicates::str::contains(
+        "Sun setting through 9° below the horizon is at: 2020-06-21 22:41:32 +01:00\n",
+    ));
+
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&[
+        "-d",
+        "2020-06-21",
+        "-t",
+        "+01:00",
+        "report",
+        "--depression",
+        "18",
+        "--direction",
+        "setting",
+    ])
+    .assert()
+    .success()
+    .stdout("Sun setting through 18° below the horizon is at: Never (2020-06-21)\n");
+
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&["report", "--depression", "95"])
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("Error parsing depression"));
+
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&[
+        "report",
+        "--depression",
+        "9",
+        "--offset-file",
+        "offsets.toml",
+    ])
+    .assert()
+    .failure()
+    .stderr(predicates::str::contains("cannot be used with"));
+}
+
+#[test]
+fn test_illuminance() {
+    // assert that an illuminance is shown as the crossings of its depression angle, which can be limited to
+    // one direction in the same way
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&["-d", "2020-06-21", "-t", "+01:00", "report"])
+        .args(&["--illuminance", "3.4", "--direction", "rising"])
+        .assert()
+        .success()
+        .stdout("Sun rising through 6° below the horizon is at: 2020-06-21 03:55:13 +01:00\n");
+
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&["-d", "2020-06-21", "-t", "+01:00", "report"])
+        .args(&["--illuminance", "1"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains(
+            "Sun setting through 7.21° below the horizon is at: 2020-06-21 22:21:13 +01:00\n",
+        ));
+
+    // but not outside of twilight, or together with a depression
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&["report", "--illuminance", "10000"])
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("Error parsing illuminance"));
+
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&["report", "--illuminance", "1", "--depression", "6"])
+        .assert()
+        .failure();
+
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&["report", "--illuminance", "10", "--shell-export"])
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("cannot be used with"));
+}
+
+#[test]
+fn test_pasted_coordinates_and_event() {
+    // assert that spaces in the coordinates, and the case of the event, don't matter
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&[
+        "-d",
+        "2020-06-21",
+        "-t",
+        "+01:00",
+        "-l",
+        " 51.4769 N",
+        "-o",
+        "0.0005 w ",
+        "report",
+        "-e",
+        "SUNRISE",
+    ])
+    .assert()
+    .success()
+    .stdout("sunrise +00:00:00 is at: 2020-06-21 04:42:54 +01:00\n");
+}
+
+#[test]
+fn test_report_upcoming() {
+    // assert that the report moves on to the next day once sunset has passed, and not before
+    let upcoming = |datetime: &str| {
+        let mut cmd = Command::cargo_bin("heliocron").unwrap();
+        cmd.args(&[
+            "--datetime",
+            datetime,
+            "-t",
+            "+00:00",
+            "report",
+            "--event",
+            "sunset",
+            "--upcoming",
+        ])
+        .assert()
+        .success()
+    };
+    upcoming("2020-03-08T17:00").stdout("sunset +00:00:00 is at: 2020-03-08 17:52:50 +00:00\n");
+    upcoming("2020-03-08T19:00").stdout("sunset +00:00:00 is at: 2020-03-09 17:54:34 +00:00\n");
+
+    // a range of dates is left alone
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&[
+        "-d",
+        "2020-03-08",
+        "--end-date",
+        "2020-03-09",
+        "report",
+        "--upcoming",
+        "--count-only",
+    ])
+    .assert()
+    .success()
+    .stdout("2\n");
+}
+
+#[test]
+fn test_report_shell_export() {
+    // assert that the report is given as quoted shell variables, with empty values for missing events
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&[
+        "-d",
+        "2020-06-21",
+        "-t",
+        "+01:00",
+        "report",
+        "--shell-export",
+    ])
+    .assert()
+    .success()
+    .stdout(predicates::str::starts_with(
+        "HELIOCRON_DATE='2020-06-21'\n",
+    ))
+    .stdout(predicates::str::contains(
+        "HELIOCRON_SUNSET='2020-06-21T21:20:53+01:00'\n",
+    ))
+    .stdout(predicates::str::contains("HELIOCRON_DAY_LENGTH='59879'\n"))
+    .stdout(predicates::str::contains(
+        "HELIOCRON_ASTRONOMICAL_DAWN=''\n",
+    ));
+
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&[
+        "-d",
+        "2020-06-21",
+        "-t",
+        "+01:00",
+        "--decimal-hours",
+        "--no-location",
+        "report",
+        "--shell-export",
+    ])
+    .assert()
+    .success()
+    .stdout(predicates::str::contains("HELIOCRON_LATITUDE=''\n"))
+    .stdout(predicates::str::contains("HELIOCRON_SUNSET='21.3481'\n"));
+
+    // a range of dates would overwrite the variables
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&["--this-week", "report", "--shell-export"])
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("single day"));
+
+    // the assignments are the whole output, so can't be combined with a count of the days
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&["report", "--shell-export", "--count-only"])
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("cannot be used with"));
+}
+
+#[test]
+fn test_report_events() {
+    // assert that several events are each shown once, in order of time, with missing events last
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&[
+        "-d",
+        "2020-06-21",
+        "-t",
+        "+01:00",
+        "report",
+        "--events",
+        "sunset,astronomical_dawn,sunrise,SUNRISE,civil_dawn",
+    ])
+    .assert()
+    .success()
+    .stdout(
+        "civil_dawn +00:00:00 is at: 2020-06-21 03:55:13 +01:00\n\
+         sunrise +00:00:00 is at: 2020-06-21 04:42:54 +01:00\n\
+         sunset +00:00:00 is at: 2020-06-21 21:20:53 +01:00\n\
+         astronomical_dawn +00:00:00 is at: Never (2020-06-21)\n",
+    );
+
+    // and that any invalid event is an error
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&["report", "--events", "sunset,sundown"])
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("Unknown event 'sundown'"));
+
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&[
+        "report",
+        "--events",
+        "sunrise,sunset",
+        "--output-format",
+        "csv",
+    ])
+    .assert()
+    .failure()
+    .stderr(predicates::str::contains("cannot be used with"));
+}
+
+#[test]
+fn test_report_atmosphere() {
+    // assert that the standard atmosphere gives the standard times, and that cold, dense air refracts more,
+    // bringing sunrise earlier and sunset later
+    let args = ["-d", "2020-06-21", "-t", "+01:00"];
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&args)
+        .args(&["--pressure", "1010", "--temperature", "10", "report"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains(
+            "Sunrise is at:            2020-06-21 04:42:54 +01:00",
+        ))
+        .stdout(predicates::str::contains("Note:").not());
+
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&args)
+        .args(&["--pressure", "1030", "--temperature", "-20", "report"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains(
+            "Sunrise is at:            2020-06-21 04:42:14 +01:00",
+        ))
+        .stdout(predicates::str::contains(
+            "Sunset is at:             2020-06-21 21:21:34 +01:00",
+        ));
+
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&["--pressure", "0", "report"])
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("Error parsing pressure"));
+}
+
+#[test]
+fn test_report_around_now() {
+    // assert that the events either side of the chosen time are found, even on the day before
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&[
+        "--datetime",
+        "2020-06-21T02:00",
+        "-t",
+        "+01:00",
+        "report",
+        "--around-now",
+    ])
+    .assert()
+    .success()
+    .stdout(
+        "Previous event: nautical_dusk at 2020-06-20 23:22:47 +01:00, 2h 37m 13s ago\n\
+             Next event: nautical_dawn at 2020-06-21 02:40:47 +01:00, in 0h 40m 47s\n",
+    );
+
+    // or compared with the current time, which may be overridden
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&[
+        "--now",
+        "2020-06-21T23:00:00+01:00",
+        "-t",
+        "+01:00",
+        "report",
+        "--around-now",
+    ])
+    .assert()
+    .success()
+    .stdout(predicates::str::contains(
+        "Next event: nautical_dusk at 2020-06-21 23:23:00 +01:00, in 0h 23m 0s\n",
+    ));
+
+    // without anything to find in the polar summer
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&["--datetime", "2020-06-21T12:00", "-l", "80N", "-o", "0E"])
+        .args(&["report", "--around-now"])
+        .assert()
+        .success()
+        .stdout("Previous event: None within the past day\nNext event: None within the next day\n");
+
+    // and only around one moment
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&[
+        "-d",
+        "2020-06-21",
+        "--end-date",
+        "2020-06-23",
+        "report",
+        "--around-now",
+    ])
+    .assert()
+    .failure();
+
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&["report", "--around-now", "--count-only"])
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("cannot be used with"));
+}
+
+#[test]
+fn test_report_diff() {
+    // assert that each event is compared with the other date, by the clock, with a sign
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&[
+        "-l",
+        "51.48N",
+        "-o",
+        "0.0W",
+        "-t",
+        "+01:00",
+        "-d",
+        "2021-06-21",
+        "report",
+        "--diff",
+        "2021-05-21",
+    ])
+    .assert()
+    .success()
+    .stdout(
+        "CHANGE SINCE 2021-05-21\n\
+        -----------------------\n\
+        Solar noon:               +00:05:13\n\
+        Day length:               +00:43:47\n\
+        Sunrise:                  -00:16:40\n\
+        Sunset:                   +00:27:07\n\
+        Civil dawn:               -00:21:24\n\
+        Civil dusk:               +00:31:52\n\
+        Nautical dawn:            -00:35:35\n\
+        Nautical dusk:            +00:46:02\n\
+        Astronomical dawn:        Only on 2021-05-21\n\
+        Astronomical dusk:        Only on 2021-05-21\n",
+    );
+
+    // an event which occurs on neither date is shown as such
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&["-l", "69.65N", "-o", "18.96E", "-t", "+02:00"])
+        .args(&["-d", "2021-05-01", "report", "--diff", "2021-04-01"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains(
+            "Civil dawn:               Only on 2021-04-01\n",
+        ))
+        .stdout(predicates::str::contains(
+            "Astronomical dawn:        Never\n",
+        ));
+
+    // the other date must be valid, and only one day can be compared with it
+    for args in [
+        vec!["report", "--diff", "2021-02-30"],
+        vec!["report", "--diff", "0900-01-01"],
+        vec![
+            "-d",
+            "2021-06-21",
+            "--end-date",
+            "2021-06-23",
+            "report",
+            "--diff",
+            "2021-05-21",
+        ],
+    ]
+    .iter()
+    {
+        let mut cmd = Command::cargo_bin("heliocron").unwrap();
+        cmd.args(args).assert().failure();
+    }
+}
+
+#[test]
+fn test_report_chart_data() {
+    // assert that each day's sunrise and sunset are given in minutes past midnight, under a header
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&[
+        "-d",
+        "2020-06-20",
+        "--end-date",
+        "2020-06-21",
+        "-t",
+        "+01:00",
+    ])
+    .args(&["report", "--chart-data"])
+    .assert()
+    .success()
+    .stdout(
+        "date,sunrise_minutes,sunset_minutes\n\
+             2020-06-20,282.68,1280.67\n\
+             2020-06-21,282.90,1280.88\n",
+    );
+
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&["report", "--chart-data", "--around-now"])
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("cannot be used with"));
+}
+
+#[test]
+fn test_report_civil_day() {
+    // assert that each day has its own block of the civil dawn, dusk and day length
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&[
+        "-d",
+        "2020-06-20",
+        "--end-date",
+        "2020-06-21",
+        "-t",
+        "+01:00",
+    ])
+    .args(&["report", "--civil-day"])
+    .assert()
+    .success()
+    .stdout(predicates::str::contains(
+        "Civil dusk is at:         2020-06-20 22:08:22 +01:00\n\
+        The civil day length is:  18h 13m 23s\n\
+        \n\
+        CIVIL DAY\n",
+    ))
+    .stdout(predicates::str::ends_with(
+        "The civil day length is:  18h 13m 22s\n",
+    ));
+}
+
+#[test]
+fn test_report_between() {
+    // assert that only the events within the window are shown, in order of time
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&["-d", "2020-06-21", "-t", "+01:00"])
+        .args(&["report", "--between", "17:00", "23:30"])
+        .assert()
+        .success()
+        .stdout(
+            "sunset +00:00:00 is at: 2020-06-21 21:20:53 +01:00\n\
+            civil_dusk +00:00:00 is at: 2020-06-21 22:08:35 +01:00\n\
+            nautical_dusk +00:00:00 is at: 2020-06-21 23:23:00 +01:00\n",
+        );
+
+    // a window past midnight, picking from the chosen events only
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&["-d", "2020-06-21", "-t", "+01:00"])
+        .args(&[
+            "report",
+            "--between",
+            "22:00",
+            "04:00",
+            "--events",
+            "sunrise,sunset,civil_dawn,nautical_dusk",
+        ])
+        .assert()
+        .success()
+        .stdout(
+            "civil_dawn +00:00:00 is at: 2020-06-21 03:55:13 +01:00\n\
+            nautical_dusk +00:00:00 is at: 2020-06-21 23:23:00 +01:00\n",
+        );
+}
@@ -0,0 +1,121 @@
+use std::{fs, process::Command};
+
+use assert_cmd::prelude::*;
+use predicates::prelude::*;
+
+mod common;
+use common::TempFile;
+
+#[test]
+fn test_schedule() {
+    // assert that the line fires an hour before the earliest sunrise [+ offset] of the year, and waits for it
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    let schedule = cmd
+        .args(&[
+            "-l",
+            "51.4769N",
+            "-o",
+            "0.0005W",
+            "-d",
+            "2020-01-01",
+            "schedule",
+            "--event",
+            "sunrise",
+            "--offset",
+            "-00:30",
+            "--run",
+            "turn-on-lights.sh",
+        ])
+        .assert();
+
+    schedule.success().stdout(
+        "12 2 * * * heliocron --latitude 51.4769N --longitude 0.0005W wait --event sunrise \
+        --offset -00:30:00 && turn-on-lights.sh\n",
+    );
+
+    // and that a chosen time zone is passed on, with a different margin
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    let schedule = cmd
+        .args(&[
+            "-l",
+            "51.4769N",
+            "-o",
+            "0.0005W",
+            "-t",
+            "+01:00",
+            "-d",
+            "2020-01-01",
+            "schedule",
+            "--event",
+            "sunset",
+            "--margin",
+            "10m",
+            "--run",
+            "x.sh",
+        ])
+        .assert();
+
+    schedule.success().stdout(predicate::str::starts_with(
+        "40 16 * * * heliocron --latitude 51.4769N --longitude 0.0005W --time-zone +01:00 wait --event sunset && x.sh",
+    ));
+}
+
+#[test]
+fn test_schedule_before_midnight() {
+    // assert that an event [+ offset] before midnight fires at midnight, with a warning
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    let schedule = cmd
+        .args(&[
+            "-l",
+            "51.4769N",
+            "-o",
+            "0.0005W",
+            "-d",
+            "2020-01-01",
+            "--warnings-json",
+            "schedule",
+            "--event",
+            "sunrise",
+            "--offset",
+            "-8h",
+            "--run",
+            "x.sh",
+        ])
+        .assert();
+
+    schedule
+        .success()
+        .stdout(predicate::str::starts_with("0 0 * * * "))
+        .stderr(predicate::str::contains(
+            "\"code\":\"schedule_before_midnight\"",
+        ));
+}
+
+#[test]
+fn test_schedule_passes_options_on() {
+    // assert that the config files and the other options which change the time of the event are passed on to
+    // the wait, with the files by their full paths
+    let config = TempFile::with_contents(
+        "schedule-config.toml",
+        "[events.dusk_walk]\nelevation = -3.0\ndirection = \"setting\"\n",
+    );
+    let horizon = TempFile::with_contents("schedule-horizon.txt", "0 2\n180 2\n");
+    let full_path = |path: &TempFile| fs::canonicalize(path).unwrap().display().to_string();
+
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.arg("--config")
+        .arg(&config)
+        .arg("--horizon-file")
+        .arg(&horizon)
+        .args(&["--pressure", "1030", "--temperature", "-5"])
+        .args(&["-l", "51.4769N", "-o", "0.0005W", "-d", "2020-01-01"])
+        .args(&["schedule", "--event", "dusk_walk", "--run", "x.sh"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(format!(
+            "heliocron --latitude 51.4769N --longitude 0.0005W --config '{}' --pressure 1030 \
+             --temperature -5 --horizon-file '{}' wait --event dusk_walk && x.sh\n",
+            full_path(&config),
+            full_path(&horizon)
+        )));
+}
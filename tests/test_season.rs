@@ -0,0 +1,51 @@
+use std::process::Command;
+
+use assert_cmd::prelude::*;
+
+#[test]
+fn test_season() {
+    // assert that the next solstice or equinox is found, including when it falls on the given date
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    let season = cmd
+        .args(&["-d", "2020-06-20", "-t", "+01:00", "season"])
+        .assert();
+
+    season
+        .success()
+        .stdout(predicates::str::contains("June solstice"))
+        .stdout(predicates::str::contains("2020-06-20 22:4"));
+
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    let season = cmd
+        .args(&["-d", "2020-06-21", "-t", "+01:00", "season"])
+        .assert();
+
+    season
+        .success()
+        .stdout(predicates::str::contains("September equinox"))
+        .stdout(predicates::str::contains("2020-09-22"));
+}
+
+#[test]
+fn test_season_day_number() {
+    // assert that the days since the previous solstice or equinox and until the next are counted by date, from 0
+    // on the day of one
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&["-d", "2020-06-20", "-t", "+01:00", "season", "--day-number"])
+        .assert()
+        .success()
+        .stdout(
+            "0 days since the June solstice at 2020-06-20 22:42:15 +01:00\n\
+            94 days until the September equinox at 2020-09-22 14:28:14 +01:00\n",
+        );
+
+    // and across the turn of the year
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&["-d", "2020-12-31", "-t", "+01:00", "season", "--day-number"])
+        .assert()
+        .success()
+        .stdout(
+            "10 days since the December solstice at 2020-12-21 11:01:26 +01:00\n\
+            79 days until the March equinox at 2021-03-20 10:40:42 +01:00\n",
+        );
+}
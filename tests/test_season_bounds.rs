@@ -0,0 +1,52 @@
+use std::process::Command;
+
+use assert_cmd::prelude::*;
+
+#[test]
+fn test_season_bounds() {
+    // assert that the periods with each kind of night are found, and that those lasting all year are given so
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    let bounds = cmd
+        .args(&[
+            "-l",
+            "60.17N",
+            "-o",
+            "24.94E",
+            "-t",
+            "+02:00",
+            "season-bounds",
+            "--year",
+            "2021",
+        ])
+        .assert();
+
+    bounds.success().stdout(
+        "Civil night:              every day\n\
+        Nautical night:           from 2021-01-01 to 2021-05-10 (130 days) and from 2021-08-02 to 2021-12-31 (152 days)\n\
+        Astronomical night:       from 2021-01-01 to 2021-04-20 (110 days) and from 2021-08-22 to 2021-12-31 (132 days)\n",
+    );
+
+    // the year defaults to that of the chosen date
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    let bounds = cmd
+        .args(&[
+            "-d",
+            "2021-03-01",
+            "-t",
+            "+00:00",
+            "season-bounds",
+            "--json",
+        ])
+        .assert();
+
+    bounds
+        .success()
+        .stdout(predicates::str::contains("\"year\": 2021"))
+        .stdout(predicates::str::contains("\"end\": \"2021-05-22\""));
+
+    // and must be one which is supported
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&["season-bounds", "--year", "300"])
+        .assert()
+        .failure();
+}
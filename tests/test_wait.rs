@@ -1,6 +1,10 @@
 use std::process::Command;
 
 use assert_cmd::prelude::*;
+use predicates::prelude::*;
+
+mod common;
+use common::TempFile;
 
 // run these tests with `cargo test --test test_wait --features integration-test` in order to
 // override the default sleep function
@@ -54,7 +58,7 @@ fn test_wait_no_offset() {
     wait_short
         .success()
         .stdout(predicates::str::contains("going to sleep for"))
-        .stdout(predicates::str::contains("2091-10-05 06:07:54 +00:00"));
+        .stdout(predicates::str::contains("2091-10-05 06:07:55 +00:00"));
 }
 
 #[test]
@@ -98,5 +102,405 @@ fn test_wait_with_offset() {
     wait_short
         .success()
         .stdout(predicates::str::contains("going to sleep for"))
-        .stdout(predicates::str::contains("2091-10-04 17:37:02 +00:00"));
+        .stdout(predicates::str::contains("2091-10-04 17:37:03 +00:00"));
+}
+
+#[test]
+fn test_wait_at() {
+    // assert that heliocron will wait until a fixed time of day, rather than an event
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    let wait = cmd
+        .args(&["-t", "+00:00", "wait", "--at", "03:30"])
+        .assert();
+
+    wait.success()
+        .stdout(predicates::str::contains("going to sleep for"))
+        .stdout(predicates::str::contains("03:30:00 +00:00"));
+
+    // offsets still apply
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    let wait = cmd
+        .args(&["-t", "+00:00", "wait", "--at", "03:30", "-o", "-00:15"])
+        .assert();
+
+    wait.success()
+        .stdout(predicates::str::contains("03:15:00 +00:00"));
+
+    // but --at and --event are mutually exclusive, and one of them is required
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&["wait", "--at", "03:30", "--event", "sunrise"])
+        .assert()
+        .failure();
+
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&["wait"]).assert().failure();
+}
+
+#[test]
+fn test_wait_natural_offset() {
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    let wait = cmd
+        .args(&[
+            "-d",
+            "2099-03-25",
+            "-t",
+            "+00:00",
+            "wait",
+            "--event",
+            "sunrise",
+            "--offset",
+            "30m before",
+        ])
+        .assert();
+
+    wait.success()
+        .stdout(predicates::str::contains("2099-03-25 05:20:08 +00:00"));
+
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    let wait = cmd
+        .args(&["wait", "--event", "sunrise", "--offset", "30 before"])
+        .assert();
+
+    wait.failure()
+        .stderr(predicates::str::contains("Error parsing offset"));
+}
+
+#[test]
+fn test_wait_grace() {
+    // assert that an event which has already passed is an error...
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    let wait = cmd
+        .args(&["-d", "2020-03-25", "wait", "--event", "sunrise"])
+        .assert();
+
+    wait.failure()
+        .stderr(predicates::str::contains("occurred in the past"));
+
+    // ...unless it is within the grace period
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    let wait = cmd
+        .args(&[
+            "-d",
+            "2020-03-25",
+            "wait",
+            "--event",
+            "sunrise",
+            "--grace",
+            "100000h",
+        ])
+        .assert();
+
+    wait.success()
+        .stdout(predicates::str::contains("within the grace period"));
+}
+
+#[test]
+fn test_wait_align_minute() {
+    // assert that the time waited until is rounded up to the next whole minute
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    let wait = cmd
+        .args(&[
+            "-d",
+            "2099-12-31",
+            "-t",
+            "+00:00",
+            "wait",
+            "--event",
+            "sunset",
+            "--align-minute",
+        ])
+        .assert();
+
+    wait.success().stdout(predicates::str::contains(
+        "until 2099-12-31 16:01:00 +00:00",
+    ));
+}
+
+#[test]
+fn test_wait_round_mode() {
+    // assert that the rounding can go down or to the nearest minute instead, but only when aligning
+    let params = [
+        ("up", "03:31:00"),
+        ("down", "03:30:00"),
+        ("nearest", "03:30:00"),
+        ("NEAREST", "03:30:00"),
+    ];
+    for (round_mode, expected) in params.iter() {
+        let mut cmd = Command::cargo_bin("heliocron").unwrap();
+        cmd.args(&["--now", "2020-06-21T03:00:00+00:00", "-t", "+00:00"])
+            .args(&["wait", "--at", "03:30:20", "--align-minute"])
+            .args(&["--round-mode", round_mode])
+            .assert()
+            .success()
+            .stdout(predicates::str::contains(format!(
+                "until 2020-06-21 {} +00:00",
+                expected
+            )));
+    }
+
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&["wait", "--at", "03:30", "--round-mode", "down"])
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_wait_quiet() {
+    // assert that nothing is said about the wait, but errors are still reported
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    let wait = cmd
+        .args(&[
+            "-d",
+            "2099-12-31",
+            "-t",
+            "+00:00",
+            "wait",
+            "--event",
+            "sunset",
+            "--quiet",
+        ])
+        .assert();
+
+    wait.success()
+        .stdout(predicates::str::contains("going to sleep").not());
+
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    let wait = cmd
+        .args(&[
+            "-d",
+            "2000-01-01",
+            "-t",
+            "+00:00",
+            "wait",
+            "-e",
+            "sunset",
+            "-q",
+        ])
+        .assert();
+
+    wait.failure()
+        .stdout(predicates::str::is_empty())
+        .stderr(predicates::str::contains("Runtime error"));
+}
+
+#[test]
+fn test_wait_from_now() {
+    // assert that the wait is worked out from the time given by --now or HELIOCRON_NOW, the former first
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&["--now", "2020-06-21T03:00:00+00:00", "-t", "+00:00"])
+        .args(&["wait", "--at", "03:30"])
+        .env("HELIOCRON_NOW", "2020-06-21T01:00:00+00:00")
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Fake sleep for 1800s."));
+
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&["-t", "+00:00", "wait", "--event", "sunrise"])
+        .env("HELIOCRON_NOW", "2020-06-21T03:00:00+00:00")
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Fake sleep for 2575s."));
+}
+
+#[test]
+fn test_wait_elevation_event() {
+    // assert that a wait can be for an event from the config file defined by an altitude and a direction
+    let path = TempFile::with_contents(
+        "wait-elevation-event.toml",
+        "[events.dog_walk]\nelevation = -3.0\ndirection = \"setting\"\n",
+    );
+
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.arg("--config")
+        .arg(&path)
+        .args(&["--now", "2020-03-25T18:00:00+00:00", "-t", "+00:00"])
+        .args(&["wait", "--event", "dog_walk"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Fake sleep for 2144s."));
+}
+
+#[test]
+fn test_wait_fallback_event() {
+    // assert that a wait for an event which doesn't occur falls back on another moment of the day, still
+    // offset, with a warning
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&[
+        "-l",
+        "60N",
+        "-o",
+        "0E",
+        "-t",
+        "+01:00",
+        "--now",
+        "2020-06-21T12:00:00+01:00",
+        "--warnings-json",
+    ])
+    .args(&[
+        "wait",
+        "-e",
+        "nautical_dusk",
+        "-o",
+        "-30m",
+        "--fallback-event",
+        "solar_midnight",
+    ])
+    .assert()
+    .success()
+    .stdout(predicates::str::contains(
+        "until 2020-06-22 00:31:53 +01:00",
+    ))
+    .stderr(predicates::str::contains("\"code\":\"fallback_event\""))
+    .stderr(predicates::str::contains(
+        "nautical_dusk does not occur on 2020-06-21, so solar_midnight is used instead.",
+    ));
+
+    // and that an event which does occur is used as it is
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&["-l", "60N", "-o", "0E", "-t", "+01:00"])
+        .args(&["--now", "2020-06-21T12:00:00+01:00"])
+        .args(&["wait", "-e", "sunset", "--fallback-event", "solar_midnight"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains(
+            "until 2020-06-21 22:27:51 +01:00",
+        ))
+        .stderr(predicates::str::is_empty());
+
+    // whilst a fallback which doesn't occur either fails as before
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&["-l", "60N", "-o", "0E", "-t", "+01:00"])
+        .args(&["--now", "2020-06-21T12:00:00+01:00"])
+        .args(&[
+            "wait",
+            "-e",
+            "nautical_dusk",
+            "--fallback-event",
+            "astronomical_dusk",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains(
+            "The chosen event does not occur on this day.",
+        ));
+}
+
+#[test]
+fn test_wait_clamp() {
+    // assert that an offset which would end the wait before solar noon on a winter's day is clamped to it
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&["--now", "2020-12-21T08:00:00+00:00", "-t", "+00:00"])
+        .args(&[
+            "wait",
+            "-e",
+            "sunset",
+            "-o",
+            "-06:00",
+            "--clamp-after",
+            "solar_noon",
+        ])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains(
+            "until 2020-12-21 11:58:19 +00:00",
+        ));
+
+    // and that a wait which is already within the clamp is unchanged
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&["--now", "2020-12-21T08:00:00+00:00", "-t", "+00:00"])
+        .args(&[
+            "wait",
+            "-e",
+            "sunset",
+            "-o",
+            "-01:00",
+            "--clamp-after",
+            "solar_noon",
+        ])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains(
+            "until 2020-12-21 14:53:13 +00:00",
+        ));
+
+    // the other way, a wait is never later than civil dusk
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&["--now", "2020-12-21T08:00:00+00:00", "-t", "+00:00"])
+        .args(&[
+            "wait",
+            "-e",
+            "sunset",
+            "-o",
+            "02:00",
+            "--clamp-before",
+            "civil_dusk",
+        ])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains(
+            "until 2020-12-21 16:33:31 +00:00",
+        ));
+}
+
+#[test]
+fn test_wait_poll_interval() {
+    // assert that the poll interval doesn't change what is waited for, but must be a whole number of seconds
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&["--now", "2020-12-21T08:00:00+00:00", "-t", "+00:00"])
+        .args(&["wait", "-e", "sunset", "--poll-interval", "3600"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains(
+            "until 2020-12-21 15:53:13 +00:00",
+        ));
+
+    for interval in ["0", "-60", "1.5"].iter() {
+        let mut cmd = Command::cargo_bin("heliocron").unwrap();
+        cmd.args(&["wait", "-e", "sunset", "--poll-interval", interval])
+            .assert()
+            .failure();
+    }
+}
+
+#[test]
+fn test_wait_positional() {
+    // assert that the event and offset can be given on their own, even a negative offset after '--'
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&["--now", "2020-12-21T08:00:00+00:00", "-t", "+00:00"])
+        .args(&["wait", "sunset", "--", "-00:30:00"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains(
+            "until 2020-12-21 15:23:13 +00:00",
+        ));
+
+    // alongside the other flags, in any order
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&["--now", "2020-12-21T08:00:00+00:00", "-t", "+00:00"])
+        .args(&["wait", "--align-minute", "Sunset", "30m before", "--quiet"])
+        .assert()
+        .success()
+        .stdout("Fake sleep for 26640s.\n");
+
+    // but not as well as the flags they stand in for
+    for args in [
+        vec!["wait", "sunset", "--event", "sunrise"],
+        vec!["wait", "sunset", "--offset", "1h", "--", "-00:30:00"],
+        vec!["wait", "sunset", "--at", "03:30"],
+        vec!["wait", "sunsett"],
+    ]
+    .iter()
+    {
+        let mut cmd = Command::cargo_bin("heliocron").unwrap();
+        cmd.args(args).assert().failure();
+    }
+
+    // and a mistyped flag is still reported as one, rather than being taken as the offset
+    let mut cmd = Command::cargo_bin("heliocron").unwrap();
+    cmd.args(&["wait", "sunset", "--qiuet"])
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains(
+            "Found argument '--qiuet' which wasn't expected",
+        ));
 }